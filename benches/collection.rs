@@ -0,0 +1,92 @@
+// Collection path at 100, 1k and 10k simulated processes, run with `cargo bench`
+use criterion::{criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion};
+use rust_system_monitor::config::Config;
+use rust_system_monitor::mock::{MockSource, MockTick};
+use rust_system_monitor::models::{Process, SystemMonitor};
+
+const SIZES: [usize; 3] = [100, 1_000, 10_000];
+const GB: u64 = 1024 * 1024 * 1024;
+
+// processes in no particular order, so the memory sort has work to do
+fn processes(count: usize) -> Vec<Process> {
+    let mut seed: u64 = 0x2545_f491_4f6c_dd1d;
+    (0..count)
+        .map(|i| {
+            seed = seed
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            let share = (seed >> 33) as f64 / (1u64 << 31) as f64;
+            Process {
+                id: i as u32 + 1,
+                start_time: 1_700_000_000 + (seed >> 48),
+                name: format!("worker-{}", i).into(),
+                command: format!("/usr/bin/worker-{} --id {} --verbose", i, i).into(),
+                user: "bench".into(),
+                cpu_usage_percent: share * 100.,
+                cpu_time_secs: share * 3600.,
+                memory_usage_percent: share * 2.,
+                disk_io_rate: share * 1_000_000.,
+            }
+        })
+        .collect()
+}
+
+// one refresh: taking the sample, sorting the processes, stats, history and alert checks
+fn refresh(c: &mut Criterion) {
+    let mut group = c.benchmark_group("refresh");
+    for size in SIZES {
+        let tick = MockTick {
+            cpu_usage: 42.,
+            memory_usage: (6 * GB, 16 * GB),
+            swap_memory_usage: (0, 2 * GB),
+            processes: processes(size),
+        };
+        let mut monitor = SystemMonitor::with_mock(Config::default(), MockSource::default());
+
+        group.bench_with_input(BenchmarkId::from_parameter(size), &tick, |b, tick| {
+            b.iter_batched(
+                || tick.clone(),
+                |tick| {
+                    if let Some(source) = monitor.mock.as_mut() {
+                        source.push(tick);
+                    }
+                    monitor.refresh();
+                },
+                BatchSize::LargeInput,
+            )
+        });
+    }
+    group.finish();
+}
+
+// building the process table widget from the sorted list
+#[cfg(feature = "gui")]
+fn process_table(c: &mut Criterion) {
+    use rust_system_monitor::format::{NumberFormat, NumberLocale};
+    use rust_system_monitor::widgets::ProcessTable;
+
+    let mut group = c.benchmark_group("process_table");
+    for size in SIZES {
+        let processes = processes(size);
+        let visible: Vec<&Process> = processes.iter().collect();
+        let numbers = NumberFormat::new(2, NumberLocale::English);
+
+        group.bench_with_input(BenchmarkId::from_parameter(size), &visible, |b, visible| {
+            b.iter(|| {
+                std::hint::black_box(
+                    ProcessTable::<()>::new(visible, numbers)
+                        .show_command_line(true)
+                        .highlight_new(30)
+                        .view(),
+                )
+            })
+        });
+    }
+    group.finish();
+}
+
+#[cfg(feature = "gui")]
+criterion_group!(benches, refresh, process_table);
+#[cfg(not(feature = "gui"))]
+criterion_group!(benches, refresh);
+criterion_main!(benches);
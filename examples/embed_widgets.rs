@@ -0,0 +1,63 @@
+// Minimal iced app embedding the monitor's CPU chart and process table
+// run with `cargo run --example embed_widgets`
+use iced::widget::{column, scrollable, text};
+use iced::{executor, time, Application, Command, Element, Settings, Subscription, Theme};
+use rust_system_monitor::format::{NumberFormat, NumberLocale};
+use rust_system_monitor::models::{Process, SystemMonitor};
+use rust_system_monitor::widgets::{cpu_chart, ProcessTable};
+use std::time::Duration;
+
+struct Embedded {
+    monitor: SystemMonitor,
+}
+
+#[derive(Debug, Clone)]
+enum Message {
+    Tick,
+}
+
+impl Application for Embedded {
+    type Executor = executor::Default;
+    type Message = Message;
+    type Theme = Theme;
+    type Flags = ();
+
+    fn new(_flags: ()) -> (Self, Command<Message>) {
+        let monitor = SystemMonitor::init();
+        (Embedded { monitor }, Command::none())
+    }
+
+    fn title(&self) -> String {
+        String::from("Embedded monitor widgets")
+    }
+
+    fn update(&mut self, message: Message) -> Command<Message> {
+        match message {
+            Message::Tick => self.monitor.refresh(),
+        }
+        Command::none()
+    }
+
+    fn view(&self) -> Element<'_, Message> {
+        let numbers = NumberFormat::new(1, NumberLocale::Auto);
+        let top: Vec<&Process> = self.monitor.processes.iter().take(20).collect();
+
+        column![
+            text("CPU"),
+            cpu_chart(self.monitor.history.samples(), Vec::new(), Vec::new(), 120.),
+            text("Top processes by memory"),
+            scrollable(ProcessTable::new(&top, numbers).view()),
+        ]
+        .spacing(10)
+        .padding(10)
+        .into()
+    }
+
+    fn subscription(&self) -> Subscription<Message> {
+        time::every(Duration::from_secs(1)).map(|_| Message::Tick)
+    }
+}
+
+fn main() -> iced::Result {
+    Embedded::run(Settings::default())
+}
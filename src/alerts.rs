@@ -0,0 +1,615 @@
+use crate::format::{csv_field, usage_percent};
+use crate::models::{ExportFormat, SystemMonitor};
+use crate::sensors::SensorKind;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::io::Write;
+use std::process::{Command, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
+
+// what an alert message looks like unless the config sets `alert_template`
+pub const DEFAULT_ALERT_TEMPLATE: &str = "{time} {host} {rule}: {metric} = {value}";
+
+// Metrics that alert rules can watch
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum AlertMetric {
+    CpuUsage,
+    CpuSteal,
+    MemoryUsage,
+    SwapUsage,
+    DiskUsage,
+    DiskReadIops,
+    DiskWriteIops,
+    TcpCloseWait,
+    RespawnRate,
+    RunawayProcesses,
+    Temperature,
+    LogProblems,
+}
+
+impl AlertMetric {
+    pub const ALL: [AlertMetric; 12] = [
+        AlertMetric::CpuUsage,
+        AlertMetric::CpuSteal,
+        AlertMetric::MemoryUsage,
+        AlertMetric::SwapUsage,
+        AlertMetric::DiskUsage,
+        AlertMetric::DiskReadIops,
+        AlertMetric::DiskWriteIops,
+        AlertMetric::TcpCloseWait,
+        AlertMetric::RespawnRate,
+        AlertMetric::RunawayProcesses,
+        AlertMetric::Temperature,
+        AlertMetric::LogProblems,
+    ];
+
+    // short name used on the command line, e.g. `--check cpu>90`
+    pub fn from_name(name: &str) -> Option<AlertMetric> {
+        match name {
+            "cpu" => Some(AlertMetric::CpuUsage),
+            "steal" => Some(AlertMetric::CpuSteal),
+            "memory" | "mem" => Some(AlertMetric::MemoryUsage),
+            "swap" => Some(AlertMetric::SwapUsage),
+            "disk" => Some(AlertMetric::DiskUsage),
+            "read_iops" => Some(AlertMetric::DiskReadIops),
+            "write_iops" => Some(AlertMetric::DiskWriteIops),
+            "close_wait" => Some(AlertMetric::TcpCloseWait),
+            "temp" => Some(AlertMetric::Temperature),
+            _ => None,
+        }
+    }
+
+    // current value of the metric: a percentage, operations per second of the busiest disk,
+    // a number of sockets, restarts per minute of the process name that restarts most,
+    // the number of runaway processes, the hottest temperature sensor in °C, or the number of
+    // problems the last log integrity check found
+    pub fn value(&self, system_monitor: &SystemMonitor) -> f64 {
+        match self {
+            AlertMetric::CpuUsage => system_monitor.cpu_usage as f64,
+            AlertMetric::CpuSteal => system_monitor.cpu_steal_percent,
+            AlertMetric::MemoryUsage => usage_percent(system_monitor.memory_usage),
+            AlertMetric::SwapUsage => usage_percent(system_monitor.swap_memory_usage),
+            AlertMetric::DiskUsage => usage_percent(system_monitor.disk_usage),
+            AlertMetric::DiskReadIops => system_monitor
+                .disk_io
+                .iter()
+                .map(|disk| disk.read_iops)
+                .fold(0.0, f64::max),
+            AlertMetric::DiskWriteIops => system_monitor
+                .disk_io
+                .iter()
+                .map(|disk| disk.write_iops)
+                .fold(0.0, f64::max),
+            AlertMetric::TcpCloseWait => system_monitor
+                .tcp_states
+                .map_or(0.0, |states| states.close_wait as f64),
+            AlertMetric::RespawnRate => system_monitor.respawn_tracker.highest_rate() as f64,
+            AlertMetric::RunawayProcesses => system_monitor
+                .runaway_tracker
+                .runaways(system_monitor.config.runaway_duration(), Instant::now())
+                .len() as f64,
+            AlertMetric::Temperature => system_monitor
+                .sensors
+                .iter()
+                .filter(|sensor| sensor.id.kind == SensorKind::Temperature)
+                .map(|sensor| sensor.value)
+                .fold(0.0, f64::max),
+            AlertMetric::LogProblems => system_monitor.log_integrity.problems().len() as f64,
+        }
+    }
+}
+
+impl std::fmt::Display for AlertMetric {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AlertMetric::CpuUsage => write!(f, "CPU %"),
+            AlertMetric::CpuSteal => write!(f, "CPU Steal %"),
+            AlertMetric::MemoryUsage => write!(f, "Memory %"),
+            AlertMetric::SwapUsage => write!(f, "Swap %"),
+            AlertMetric::DiskUsage => write!(f, "Disk %"),
+            AlertMetric::DiskReadIops => write!(f, "Disk Read IOPS"),
+            AlertMetric::DiskWriteIops => write!(f, "Disk Write IOPS"),
+            AlertMetric::TcpCloseWait => write!(f, "TCP CLOSE_WAIT"),
+            AlertMetric::RespawnRate => write!(f, "Restarts / min"),
+            AlertMetric::RunawayProcesses => write!(f, "Runaway Processes"),
+            AlertMetric::Temperature => write!(f, "Temperature °C"),
+            AlertMetric::LogProblems => write!(f, "Log Problems"),
+        }
+    }
+}
+
+// What a rule does when it fires besides notifying, for a response that can't wait for
+// someone to notice, e.g. hibernating before a laptop overheats
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AlertAction {
+    // run through the shell with the alert in ALERT_RULE, ALERT_METRIC, ALERT_VALUE and
+    // ALERT_MESSAGE
+    Command(String),
+    LockSession,
+    Hibernate,
+}
+
+impl AlertAction {
+    // "command:<shell command>", "lock" or "hibernate" in rule CSV files, empty for no action
+    pub fn to_field(action: Option<&AlertAction>) -> String {
+        match action {
+            None => String::new(),
+            Some(AlertAction::Command(command)) => format!("command:{}", command),
+            Some(AlertAction::LockSession) => String::from("lock"),
+            Some(AlertAction::Hibernate) => String::from("hibernate"),
+        }
+    }
+
+    pub fn parse_field(field: &str) -> Result<Option<AlertAction>, String> {
+        match field.trim() {
+            "" => Ok(None),
+            "lock" => Ok(Some(AlertAction::LockSession)),
+            "hibernate" => Ok(Some(AlertAction::Hibernate)),
+            field => match field.strip_prefix("command:") {
+                Some(command) if !command.trim().is_empty() => {
+                    Ok(Some(AlertAction::Command(command.trim().to_string())))
+                }
+                _ => Err(format!(
+                    "unknown action {} (expected command:<command>, lock or hibernate)",
+                    field
+                )),
+            },
+        }
+    }
+}
+
+impl std::fmt::Display for AlertAction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AlertAction::Command(command) => write!(f, "run `{}`", command),
+            AlertAction::LockSession => write!(f, "lock the session"),
+            AlertAction::Hibernate => write!(f, "hibernate"),
+        }
+    }
+}
+
+// Kind of action picked in the rule form, the command is typed separately
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum AlertActionKind {
+    #[default]
+    None,
+    Command,
+    LockSession,
+    Hibernate,
+}
+
+impl AlertActionKind {
+    pub const ALL: [AlertActionKind; 4] = [
+        AlertActionKind::None,
+        AlertActionKind::Command,
+        AlertActionKind::LockSession,
+        AlertActionKind::Hibernate,
+    ];
+}
+
+impl std::fmt::Display for AlertActionKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AlertActionKind::None => write!(f, "No action"),
+            AlertActionKind::Command => write!(f, "Run command"),
+            AlertActionKind::LockSession => write!(f, "Lock session"),
+            AlertActionKind::Hibernate => write!(f, "Hibernate"),
+        }
+    }
+}
+
+// Fires when a metric stays above the threshold for the whole duration
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AlertRule {
+    pub name: String,
+    pub metric: AlertMetric,
+    pub threshold: f64,
+    pub duration_secs: u64,
+    pub enabled: bool,
+    // after notifying, further breaches within this many seconds only add to the suppressed
+    // count of that alert, so a flapping metric doesn't notify on every tick; 0 turns it off
+    #[serde(default)]
+    pub cooldown_secs: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub action: Option<AlertAction>,
+}
+
+impl AlertRule {
+    // built-in template: a noisy neighbour on a cloud VM shows up as sustained steal time
+    pub fn steal_template() -> AlertRule {
+        AlertRule {
+            name: String::from("Sustained CPU steal"),
+            metric: AlertMetric::CpuSteal,
+            threshold: 10.0,
+            duration_secs: 60,
+            enabled: true,
+            cooldown_secs: 0,
+            action: None,
+        }
+    }
+}
+
+const RULE_CSV_COLUMNS: [&str; 7] = [
+    "name",
+    "metric",
+    "threshold",
+    "duration_secs",
+    "enabled",
+    "cooldown_secs",
+    "action",
+];
+
+// rule files are CSV when the name ends in .csv and JSON otherwise
+pub fn rule_file_format(path: &str) -> ExportFormat {
+    if path.to_lowercase().ends_with(".csv") {
+        ExportFormat::Csv
+    } else {
+        ExportFormat::Json
+    }
+}
+
+// write a rule set to share with other machines; unlike the other exports the CSV keeps `,`
+// and `.` whatever the number locale, so it imports the same everywhere
+pub fn export_rules(rules: &[AlertRule], path: &str) -> Result<(), String> {
+    let contents = match rule_file_format(path) {
+        ExportFormat::Csv => {
+            let mut csv = RULE_CSV_COLUMNS.join(",");
+            csv.push('\n');
+            for rule in rules {
+                let fields = [
+                    csv_field(&rule.name, ','),
+                    metric_key(rule.metric),
+                    rule.threshold.to_string(),
+                    rule.duration_secs.to_string(),
+                    rule.enabled.to_string(),
+                    rule.cooldown_secs.to_string(),
+                    csv_field(&AlertAction::to_field(rule.action.as_ref()), ','),
+                ];
+                csv.push_str(&fields.join(","));
+                csv.push('\n');
+            }
+            csv
+        }
+        ExportFormat::Json => serde_json::to_string_pretty(rules).map_err(|e| e.to_string())?,
+    };
+    fs::write(path, contents).map_err(|e| format!("Failed to write {}: {}", path, e))
+}
+
+// read a rule set written by `export_rules` (or by hand), errors name the offending line
+pub fn import_rules(path: &str) -> Result<Vec<AlertRule>, String> {
+    let contents =
+        fs::read_to_string(path).map_err(|e| format!("Failed to read {}: {}", path, e))?;
+
+    match rule_file_format(path) {
+        ExportFormat::Json => serde_json::from_str(&contents)
+            .map_err(|e| format!("Invalid rule file {}: {}", path, e)),
+        ExportFormat::Csv => contents
+            .lines()
+            .enumerate()
+            .skip(1)
+            .filter(|(_, line)| !line.trim().is_empty())
+            .map(|(i, line)| {
+                parse_rule_csv(line).map_err(|e| format!("{} line {}: {}", path, i + 1, e))
+            })
+            .collect(),
+    }
+}
+
+// drop the actions of rules read from a file, which would otherwise run a shared rule set's
+// commands, or lock or hibernate the machine, as soon as a rule fires. Returns how many were
+// dropped, the actions can be added again in the rule editor
+pub fn strip_actions(rules: &mut [AlertRule]) -> usize {
+    let mut stripped = 0;
+    for rule in rules {
+        if rule.action.take().is_some() {
+            stripped += 1;
+        }
+    }
+    stripped
+}
+
+// told after an import, so the missing actions don't come as a surprise
+pub fn stripped_note(stripped: usize) -> String {
+    match stripped {
+        0 => String::new(),
+        _ => format!(
+            ", without the actions of {} rules (add them in the rule editor)",
+            stripped
+        ),
+    }
+}
+
+// add imported rules, replacing those of the same name; returns how many were new
+pub fn merge_rules(rules: &mut Vec<AlertRule>, imported: Vec<AlertRule>) -> usize {
+    let mut added = 0;
+    for rule in imported {
+        match rules.iter_mut().find(|existing| existing.name == rule.name) {
+            Some(existing) => *existing = rule,
+            None => {
+                rules.push(rule);
+                added += 1;
+            }
+        }
+    }
+    added
+}
+
+// metrics are written as in the config file, e.g. CpuUsage
+fn metric_key(metric: AlertMetric) -> String {
+    serde_json::to_value(metric)
+        .ok()
+        .and_then(|value| value.as_str().map(str::to_string))
+        .unwrap_or_default()
+}
+
+fn parse_rule_csv(line: &str) -> Result<AlertRule, String> {
+    let fields = split_csv_line(line);
+    // files written before cooldowns and actions existed don't have the last columns
+    let (fields, cooldown_secs, action) = match &fields[..] {
+        [fields @ .., cooldown_secs, action] if fields.len() == RULE_CSV_COLUMNS.len() - 2 => {
+            (fields, Some(cooldown_secs), Some(action))
+        }
+        [fields @ .., cooldown_secs] if fields.len() == RULE_CSV_COLUMNS.len() - 2 => {
+            (fields, Some(cooldown_secs), None)
+        }
+        fields => (fields, None, None),
+    };
+    let [name, metric, threshold, duration_secs, enabled] = fields else {
+        return Err(format!(
+            "expected {} fields ({})",
+            RULE_CSV_COLUMNS.len(),
+            RULE_CSV_COLUMNS.join(",")
+        ));
+    };
+
+    Ok(AlertRule {
+        name: name.clone(),
+        metric: serde_json::from_value(serde_json::Value::String(metric.trim().to_string()))
+            .map_err(|_| format!("unknown metric {}", metric))?,
+        threshold: threshold
+            .trim()
+            .parse()
+            .map_err(|_| format!("invalid threshold {}", threshold))?,
+        duration_secs: duration_secs
+            .trim()
+            .parse()
+            .map_err(|_| format!("invalid duration {}", duration_secs))?,
+        enabled: enabled
+            .trim()
+            .parse()
+            .map_err(|_| format!("enabled must be true or false, not {}", enabled))?,
+        cooldown_secs: match cooldown_secs {
+            Some(secs) => secs
+                .trim()
+                .parse()
+                .map_err(|_| format!("invalid cooldown {}", secs))?,
+            None => 0,
+        },
+        action: match action {
+            Some(action) => AlertAction::parse_field(action)?,
+            None => None,
+        },
+    })
+}
+
+// split on commas outside quotes, undoing the quoting of `csv_field`
+fn split_csv_line(line: &str) -> Vec<String> {
+    let mut fields = vec![String::new()];
+    let mut quoted = false;
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                chars.next();
+                fields.last_mut().unwrap().push('"');
+            }
+            '"' => quoted = !quoted,
+            ',' if !quoted => fields.push(String::new()),
+            _ => fields.last_mut().unwrap().push(c),
+        }
+    }
+    fields
+}
+
+// Inputs for a new rule in the alerts tab
+#[derive(Debug)]
+pub struct RuleForm {
+    pub name: String,
+    pub metric: AlertMetric,
+    pub threshold: String,
+    pub duration_secs: String,
+    // empty for no cooldown
+    pub cooldown_secs: String,
+    pub action: AlertActionKind,
+    pub action_command: String,
+    pub error: Option<String>,
+}
+
+impl Default for RuleForm {
+    fn default() -> Self {
+        RuleForm {
+            name: String::new(),
+            metric: AlertMetric::CpuUsage,
+            threshold: String::new(),
+            duration_secs: String::new(),
+            cooldown_secs: String::new(),
+            action: AlertActionKind::None,
+            action_command: String::new(),
+            error: None,
+        }
+    }
+}
+
+impl RuleForm {
+    pub fn to_rule(&self) -> Result<AlertRule, String> {
+        let name = self.name.trim();
+        if name.is_empty() {
+            return Err(String::from("Rule name is required"));
+        }
+        let threshold = self
+            .threshold
+            .trim()
+            .parse::<f64>()
+            .map_err(|_| String::from("Threshold must be a number"))?;
+        let duration_secs = self
+            .duration_secs
+            .trim()
+            .parse::<u64>()
+            .map_err(|_| String::from("Duration must be a whole number of seconds"))?;
+        let cooldown_secs = match self.cooldown_secs.trim() {
+            "" => 0,
+            secs => secs
+                .parse::<u64>()
+                .map_err(|_| String::from("Cooldown must be a whole number of seconds"))?,
+        };
+        let action = match self.action {
+            AlertActionKind::None => None,
+            AlertActionKind::Command => match self.action_command.trim() {
+                "" => return Err(String::from("Enter the command to run")),
+                command => Some(AlertAction::Command(command.to_string())),
+            },
+            AlertActionKind::LockSession => Some(AlertAction::LockSession),
+            AlertActionKind::Hibernate => Some(AlertAction::Hibernate),
+        };
+
+        Ok(AlertRule {
+            name: name.to_string(),
+            metric: self.metric,
+            threshold,
+            duration_secs,
+            enabled: true,
+            cooldown_secs,
+            action,
+        })
+    }
+}
+
+// An alert that fired
+#[derive(Debug, Clone)]
+pub struct AlertEvent {
+    pub timestamp: String,
+    pub rule_name: String,
+    pub metric: AlertMetric,
+    pub value: f64,
+    // later breaches of the same rule held back by its cooldown
+    pub suppressed: u64,
+}
+
+impl AlertEvent {
+    // fill in {rule}, {metric}, {value}, {host} and {time}; the value keeps a plain "1234.56"
+    // form whatever the number locale, so scripts reading the messages can parse it
+    pub fn message(&self, template: &str, host: &str) -> String {
+        template
+            .replace("{rule}", &self.rule_name)
+            .replace("{metric}", &self.metric.to_string())
+            .replace("{value}", &format!("{:.2}", self.value))
+            .replace("{host}", host)
+            .replace("{time}", &self.timestamp)
+    }
+}
+
+// POST the alert as JSON; "text" is what Slack and Mattermost show, the other fields are for
+// anything parsing it. Sent from its own thread so a slow endpoint doesn't hold up the refresh
+pub fn post_webhook(url: &str, event: &AlertEvent, message: &str, host: &str) {
+    let payload = serde_json::json!({
+        "text": message,
+        "rule": event.rule_name,
+        "metric": event.metric.to_string(),
+        "value": event.value,
+        "host": host,
+        "time": event.timestamp,
+    })
+    .to_string();
+    let url = url.to_string();
+
+    thread::spawn(move || {
+        let child = Command::new("curl")
+            .args(["--silent", "--show-error", "--fail", "--max-time", "10"])
+            .args(["--header", "Content-Type: application/json"])
+            .args(["--data-binary", "@-", &url])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .spawn();
+        let result = child.and_then(|mut child| {
+            if let Some(mut stdin) = child.stdin.take() {
+                stdin.write_all(payload.as_bytes())?;
+            }
+            child.wait()
+        });
+        match result {
+            Ok(status) if status.success() => {}
+            Ok(status) => eprintln!("Alert webhook {} failed: curl exited with {}", url, status),
+            Err(e) => eprintln!("Failed to run curl for the alert webhook: {}", e),
+        }
+    });
+}
+
+// Tracks how long each rule has been breached, keyed by rule name
+#[derive(Debug, Default)]
+pub struct AlertState {
+    breached_since: HashMap<String, Instant>,
+    firing: HashMap<String, bool>,
+    // when each rule last notified, for its cooldown
+    notified: HashMap<String, Instant>,
+    // rules that fired during their cooldown in the last evaluation
+    suppressed: Vec<String>,
+}
+
+impl AlertState {
+    // check the rules against the current metrics, returns the alerts that just fired
+    pub fn evaluate(
+        &mut self,
+        rules: &[AlertRule],
+        system_monitor: &SystemMonitor,
+        timestamp: &str,
+        now: Instant,
+    ) -> Vec<AlertEvent> {
+        let mut fired = Vec::new();
+        self.suppressed.clear();
+
+        for rule in rules.iter().filter(|rule| rule.enabled) {
+            let value = rule.metric.value(system_monitor);
+
+            if value <= rule.threshold {
+                // recovered, the rule can fire again next time
+                self.breached_since.remove(&rule.name);
+                self.firing.remove(&rule.name);
+                continue;
+            }
+
+            let since = *self.breached_since.entry(rule.name.clone()).or_insert(now);
+            let firing = self.firing.entry(rule.name.clone()).or_insert(false);
+            if !*firing && now.duration_since(since) >= Duration::from_secs(rule.duration_secs) {
+                *firing = true;
+                let cooling_down = self.notified.get(&rule.name).is_some_and(|notified| {
+                    now.duration_since(*notified) < Duration::from_secs(rule.cooldown_secs)
+                });
+                if cooling_down {
+                    self.suppressed.push(rule.name.clone());
+                    continue;
+                }
+                self.notified.insert(rule.name.clone(), now);
+                fired.push(AlertEvent {
+                    timestamp: timestamp.to_string(),
+                    rule_name: rule.name.clone(),
+                    metric: rule.metric,
+                    value,
+                    suppressed: 0,
+                });
+            }
+        }
+
+        fired
+    }
+
+    // names of the rules held back by their cooldown in the last evaluation
+    pub fn take_suppressed(&mut self) -> Vec<String> {
+        std::mem::take(&mut self.suppressed)
+    }
+
+    pub fn is_firing(&self, rule_name: &str) -> bool {
+        self.firing.get(rule_name).copied().unwrap_or(false)
+    }
+}
@@ -0,0 +1,75 @@
+// Actions alert rules run when they fire: a user command, locking the session or hibernating
+use crate::alerts::{AlertAction, AlertEvent};
+use std::process::Command;
+use std::thread;
+
+// started from its own thread, a command that takes a while (or a lock screen that only returns
+// once unlocked) must not hold up the refresh; failures end up on stderr with the alerts
+pub fn run_action(action: &AlertAction, event: &AlertEvent, message: &str) {
+    let mut command = match action {
+        AlertAction::Command(command) => shell(command),
+        AlertAction::LockSession => lock_command(),
+        AlertAction::Hibernate => hibernate_command(),
+    };
+    command
+        .env("ALERT_RULE", &event.rule_name)
+        .env("ALERT_METRIC", event.metric.to_string())
+        .env("ALERT_VALUE", format!("{:.2}", event.value))
+        .env("ALERT_MESSAGE", message);
+    let action = action.clone();
+
+    thread::spawn(move || match command.output() {
+        Ok(output) if output.status.success() => {}
+        Ok(output) => eprintln!(
+            "Alert action ({}) failed: {}",
+            action,
+            String::from_utf8_lossy(&output.stderr).trim()
+        ),
+        Err(e) => eprintln!("Failed to start alert action ({}): {}", action, e),
+    });
+}
+
+fn shell(command: &str) -> Command {
+    if cfg!(windows) {
+        let mut shell = Command::new("cmd");
+        shell.args(["/C", command]);
+        shell
+    } else {
+        let mut shell = Command::new("sh");
+        shell.args(["-c", command]);
+        shell
+    }
+}
+
+// loginctl locks every session of the user, which also works from the user's service;
+// macOS locks when the display sleeps if a password is required right after
+fn lock_command() -> Command {
+    if cfg!(windows) {
+        let mut lock = Command::new("rundll32.exe");
+        lock.arg("user32.dll,LockWorkStation");
+        lock
+    } else if cfg!(target_os = "macos") {
+        let mut lock = Command::new("pmset");
+        lock.arg("displaysleepnow");
+        lock
+    } else {
+        shell("loginctl lock-sessions || xdg-screensaver lock")
+    }
+}
+
+fn hibernate_command() -> Command {
+    if cfg!(windows) {
+        let mut hibernate = Command::new("shutdown");
+        hibernate.arg("/h");
+        hibernate
+    } else if cfg!(target_os = "macos") {
+        // sleeps or hibernates depending on the hibernatemode set with pmset
+        let mut hibernate = Command::new("pmset");
+        hibernate.arg("sleepnow");
+        hibernate
+    } else {
+        let mut hibernate = Command::new("systemctl");
+        hibernate.arg("hibernate");
+        hibernate
+    }
+}
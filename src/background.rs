@@ -0,0 +1,78 @@
+// Readings that run an external tool (nvidia-smi, chronyc, gdbus, ...) are taken on a thread of
+// their own: a tool can take seconds to answer, and neither a refresh nor a frame may wait for
+// it. The refresh starts a read when one is due and picks its result up on a later tick
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+#[derive(Debug)]
+pub struct BackgroundRead<T> {
+    // thread name, and what a failure to start it is reported as
+    name: &'static str,
+    interval: Duration,
+    started: Option<Instant>,
+    // Some while a read is running
+    pending: Option<mpsc::Receiver<T>>,
+}
+
+impl<T: Send + 'static> BackgroundRead<T> {
+    pub fn new(name: &'static str, interval: Duration) -> BackgroundRead<T> {
+        BackgroundRead {
+            name,
+            interval,
+            started: None,
+            pending: None,
+        }
+    }
+
+    // the result of a read that finished since the last call, if any; starts the next read once
+    // the previous one has finished and started `interval` ago, so a slow tool never runs twice
+    pub fn poll(&mut self, read: impl FnOnce() -> T + Send + 'static) -> Option<T> {
+        let result = match self.pending.as_ref().map(mpsc::Receiver::try_recv) {
+            Some(Ok(value)) => {
+                self.pending = None;
+                Some(value)
+            }
+            Some(Err(mpsc::TryRecvError::Disconnected)) => {
+                self.pending = None;
+                None
+            }
+            _ => None,
+        };
+
+        let due = self
+            .started
+            .is_none_or(|started| started.elapsed() >= self.interval);
+        if self.pending.is_none() && due {
+            self.started = Some(Instant::now());
+            let (sender, receiver) = mpsc::channel();
+            let spawned = thread::Builder::new()
+                .name(self.name.to_string())
+                .spawn(move || {
+                    let _ = sender.send(read());
+                });
+            match spawned {
+                Ok(_) => self.pending = Some(receiver),
+                Err(e) => eprintln!("Failed to start {}: {}", self.name, e),
+            }
+        }
+        result
+    }
+}
+
+// run a call that blocks (a polkit prompt, a slow file) on a thread of its own, as a future for
+// Command::perform so the window keeps drawing; None when the thread couldn't start or panicked
+#[cfg(feature = "gui")]
+pub async fn off_thread<T: Send + 'static>(
+    name: &'static str,
+    call: impl FnOnce() -> T + Send + 'static,
+) -> Option<T> {
+    let (sender, receiver) = iced::futures::channel::oneshot::channel();
+    thread::Builder::new()
+        .name(name.to_string())
+        .spawn(move || {
+            let _ = sender.send(call());
+        })
+        .ok()?;
+    receiver.await.ok()
+}
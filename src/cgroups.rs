@@ -0,0 +1,104 @@
+// CPU and memory limits for a single process on Linux, applied by moving it into a transient
+// systemd scope, a cgroup that systemd removes again once the process exits
+use std::fs;
+use std::process::Command;
+
+// Limits of the scope, None leaves that resource unlimited
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct ProcessQuota {
+    // percent of one core, 200 lets it use two full cores
+    pub cpu_percent: Option<f64>,
+    pub memory_mib: Option<u64>,
+}
+
+// cgroup v2 path of a process, e.g. /user.slice/user-1000.slice/session-2.scope
+pub fn read_cgroup(pid: u32) -> Option<String> {
+    let contents = fs::read_to_string(format!("/proc/{}/cgroup", pid)).ok()?;
+    contents
+        .lines()
+        .find_map(|line| line.strip_prefix("0::"))
+        .map(str::to_string)
+}
+
+// move the process into its own scope and set the limits on it, returns the scope's name;
+// the user's service manager can move the user's own processes out of app scopes, anything
+// else goes through the system manager, which needs root or a polkit prompt
+pub fn limit_process(pid: u32, quota: ProcessQuota) -> Result<String, String> {
+    if !cfg!(target_os = "linux") {
+        return Err(String::from(
+            "Limits need cgroups, they are only supported on Linux",
+        ));
+    }
+
+    let unit = format!("system-monitor-limit-{}.scope", pid);
+    let in_scope = read_cgroup(pid).is_some_and(|cgroup| cgroup.ends_with(&unit));
+    let manager = if in_scope {
+        // a scope from an earlier call can't be created again, only changed
+        ["--user", "--system"]
+            .into_iter()
+            .find(|manager| systemctl(manager, &["is-active", &unit]).is_ok())
+            .ok_or_else(|| format!("{} exists but no service manager knows it", unit))?
+    } else {
+        start_scope("--user", &unit, pid)
+            .map(|()| "--user")
+            .or_else(|_| start_scope("--system", &unit, pid).map(|()| "--system"))?
+    };
+
+    let cpu = match quota.cpu_percent {
+        Some(percent) => format!("CPUQuota={}%", percent),
+        None => String::from("CPUQuota="),
+    };
+    let memory = match quota.memory_mib {
+        Some(mib) => format!("MemoryMax={}M", mib),
+        None => String::from("MemoryMax=infinity"),
+    };
+    systemctl(
+        manager,
+        &["set-property", "--runtime", &unit, &cpu, &memory],
+    )?;
+    Ok(unit)
+}
+
+// StartTransientUnit with only the process in it, the limits are set afterwards the same way
+// for new and existing scopes
+fn start_scope(manager: &str, unit: &str, pid: u32) -> Result<(), String> {
+    let pid = pid.to_string();
+    let output = Command::new("busctl")
+        .args([
+            manager,
+            "call",
+            "org.freedesktop.systemd1",
+            "/org/freedesktop/systemd1",
+            "org.freedesktop.systemd1.Manager",
+            "StartTransientUnit",
+            "ssa(sv)a(sa(sv))",
+            unit,
+            "fail",
+            "1",
+            "PIDs",
+            "au",
+            "1",
+            &pid,
+            "0",
+        ])
+        .output()
+        .map_err(|e| format!("Failed to run busctl: {}", e))?;
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(String::from_utf8_lossy(&output.stderr).trim().to_string())
+    }
+}
+
+fn systemctl(manager: &str, args: &[&str]) -> Result<(), String> {
+    let output = Command::new("systemctl")
+        .arg(manager)
+        .args(args)
+        .output()
+        .map_err(|e| format!("Failed to run systemctl: {}", e))?;
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(String::from_utf8_lossy(&output.stderr).trim().to_string())
+    }
+}
@@ -0,0 +1,541 @@
+use crate::format::NumberFormat;
+use iced::mouse;
+use iced::widget::canvas::{
+    self, event, Event, Frame, Geometry, LineDash, Path, Program, Stroke, Text,
+};
+use iced::{Color, Length, Point, Rectangle, Renderer, Size, Theme};
+use std::sync::Arc;
+
+// Line chart for percentage series (0 - 100) drawn on a canvas
+pub struct LineChart<Message> {
+    pub series: Vec<(Color, Vec<f32>)>,
+    // faint lines behind the series, e.g. the same hours of an earlier day, broken where a
+    // value is missing
+    pub ghosts: Vec<(Color, Vec<Option<f32>>)>,
+    // stretches shaded grey as fractions of the width, e.g. while the user was idle
+    pub shaded: Vec<(f32, f32)>,
+    // selected part of the chart as fractions of its width
+    pub selection: Option<(f32, f32)>,
+    // annotation markers as (fraction of the width, label)
+    pub markers: Vec<(f32, String)>,
+    // message sent when a range is dragged out with the mouse, None disables selecting
+    pub on_select: Option<fn(f32, f32) -> Message>,
+    pub thresholds: Vec<Threshold>,
+}
+
+// Horizontal line at `from`, or band from `from` up to `to`, on the chart's 0 - 100 scale
+#[derive(Debug, Clone)]
+pub struct Threshold {
+    pub from: f32,
+    pub to: f32,
+    pub color: Color,
+    pub label: String,
+}
+
+#[derive(Default)]
+pub struct ChartState {
+    drag: Option<(f32, f32)>,
+}
+
+impl<Message> Program<Message> for LineChart<Message> {
+    type State = ChartState;
+
+    fn update(
+        &self,
+        state: &mut ChartState,
+        event: Event,
+        bounds: Rectangle,
+        cursor: mouse::Cursor,
+    ) -> (event::Status, Option<Message>) {
+        let Some(on_select) = self.on_select else {
+            return (event::Status::Ignored, None);
+        };
+        let Some(position) = cursor.position() else {
+            return (event::Status::Ignored, None);
+        };
+
+        // position of the cursor along the chart, kept inside the chart while dragging
+        let x = ((position.x - bounds.x) / bounds.width).clamp(0.0, 1.0);
+
+        match event {
+            Event::Mouse(mouse::Event::ButtonPressed(mouse::Button::Left))
+                if cursor.is_over(bounds) =>
+            {
+                state.drag = Some((x, x));
+                (event::Status::Captured, None)
+            }
+            Event::Mouse(mouse::Event::CursorMoved { .. }) => match state.drag.as_mut() {
+                Some(drag) => {
+                    drag.1 = x;
+                    (event::Status::Captured, None)
+                }
+                None => (event::Status::Ignored, None),
+            },
+            Event::Mouse(mouse::Event::ButtonReleased(mouse::Button::Left)) => {
+                match state.drag.take() {
+                    // ignore plain clicks, a range needs some width
+                    Some((start, end)) if (end - start).abs() > 0.005 => (
+                        event::Status::Captured,
+                        Some(on_select(start.min(end), start.max(end))),
+                    ),
+                    _ => (event::Status::Ignored, None),
+                }
+            }
+            _ => (event::Status::Ignored, None),
+        }
+    }
+
+    fn draw(
+        &self,
+        state: &ChartState,
+        renderer: &Renderer,
+        _theme: &Theme,
+        bounds: Rectangle,
+        _cursor: mouse::Cursor,
+    ) -> Vec<Geometry> {
+        let mut frame = Frame::new(renderer, bounds.size());
+        let (width, height) = (frame.width(), frame.height());
+
+        frame.fill_rectangle(Point::ORIGIN, frame.size(), Color::from_rgb(0.1, 0.1, 0.12));
+
+        for (start, end) in &self.shaded {
+            frame.fill_rectangle(
+                Point::new(start * width, 0.0),
+                Size::new((end - start) * width, height),
+                Color::from_rgba(0.5, 0.5, 0.5, 0.15),
+            );
+        }
+
+        // grid lines at 25%, 50% and 75%
+        for percent in [25.0, 50.0, 75.0] {
+            let y = height - height * percent / 100.0;
+            frame.stroke(
+                &Path::line(Point::new(0.0, y), Point::new(width, y)),
+                Stroke::default()
+                    .with_color(Color::from_rgb(0.25, 0.25, 0.3))
+                    .with_width(1.0),
+            );
+        }
+
+        // thresholds outside the chart are left out, bands are cut at its edges
+        for threshold in &self.thresholds {
+            let (from, to) = (threshold.from.max(0.0), threshold.to.min(100.0));
+            if from > to {
+                continue;
+            }
+            let y = |percent: f32| height - height * percent / 100.0;
+            if to > from {
+                frame.fill_rectangle(
+                    Point::new(0.0, y(to)),
+                    Size::new(width, y(from) - y(to)),
+                    Color {
+                        a: 0.15,
+                        ..threshold.color
+                    },
+                );
+            } else {
+                frame.stroke(
+                    &Path::line(Point::new(0.0, y(from)), Point::new(width, y(from))),
+                    Stroke {
+                        line_dash: LineDash {
+                            segments: &[6.0, 3.0],
+                            offset: 0,
+                        },
+                        ..Stroke::default()
+                            .with_color(threshold.color)
+                            .with_width(1.0)
+                    },
+                );
+            }
+            frame.fill_text(Text {
+                content: threshold.label.clone(),
+                position: Point::new(4.0, (y(to) - 14.0).max(0.0)),
+                color: threshold.color,
+                size: 12.0.into(),
+                ..Text::default()
+            });
+        }
+
+        // the range being dragged takes priority over the stored selection
+        let selection = state
+            .drag
+            .map(|(start, end)| (start.min(end), start.max(end)))
+            .or(self.selection);
+        if let Some((start, end)) = selection {
+            frame.fill_rectangle(
+                Point::new(start * width, 0.0),
+                Size::new((end - start) * width, height),
+                Color::from_rgba(1.0, 0.92, 0.0, 0.2),
+            );
+        }
+
+        for (color, values) in &self.ghosts {
+            if values.len() < 2 {
+                continue;
+            }
+
+            let step = width / (values.len() - 1) as f32;
+            let line = Path::new(|builder| {
+                let mut drawing = false;
+                for (i, value) in values.iter().enumerate() {
+                    let Some(value) = value else {
+                        drawing = false;
+                        continue;
+                    };
+                    let point = Point::new(
+                        i as f32 * step,
+                        height - height * value.clamp(0.0, 100.0) / 100.0,
+                    );
+                    if drawing {
+                        builder.line_to(point);
+                    } else {
+                        builder.move_to(point);
+                    }
+                    drawing = true;
+                }
+            });
+            frame.stroke(
+                &line,
+                Stroke::default()
+                    .with_color(Color { a: 0.35, ..*color })
+                    .with_width(1.5),
+            );
+        }
+
+        for (color, values) in &self.series {
+            if values.len() < 2 {
+                continue;
+            }
+
+            let step = width / (values.len() - 1) as f32;
+            let line = Path::new(|builder| {
+                for (i, value) in values.iter().enumerate() {
+                    let point = Point::new(
+                        i as f32 * step,
+                        height - height * value.clamp(0.0, 100.0) / 100.0,
+                    );
+                    if i == 0 {
+                        builder.move_to(point);
+                    } else {
+                        builder.line_to(point);
+                    }
+                }
+            });
+            frame.stroke(&line, Stroke::default().with_color(*color).with_width(2.0));
+        }
+
+        for (position, label) in &self.markers {
+            let x = position * width;
+            frame.stroke(
+                &Path::line(Point::new(x, 0.0), Point::new(x, height)),
+                Stroke {
+                    line_dash: LineDash {
+                        segments: &[4.0, 4.0],
+                        offset: 0,
+                    },
+                    ..Stroke::default()
+                        .with_color(Color::from_rgb(1.0, 0.92, 0.0))
+                        .with_width(1.0)
+                },
+            );
+            frame.fill_text(Text {
+                content: label.clone(),
+                position: Point::new(x + 4.0, 4.0),
+                color: Color::from_rgb(1.0, 0.92, 0.0),
+                size: 12.0.into(),
+                ..Text::default()
+            });
+        }
+
+        vec![frame.into_geometry()]
+    }
+}
+
+// chart widget with a fixed height that fills the available width
+pub fn view_chart<Message>(
+    chart: LineChart<Message>,
+    height: f32,
+) -> canvas::Canvas<LineChart<Message>, Message> {
+    canvas::Canvas::new(chart)
+        .width(Length::Fill)
+        .height(Length::Fixed(height))
+}
+
+// Percentages stacked on top of each other, first series at the bottom, with the area
+// under each one filled; the series of a sample are expected to add up to 100
+pub struct StackedAreaChart {
+    pub series: Vec<(Color, Vec<f32>)>,
+}
+
+impl StackedAreaChart {
+    pub fn view<Message>(self, height: f32) -> canvas::Canvas<StackedAreaChart, Message> {
+        canvas::Canvas::new(self)
+            .width(Length::Fill)
+            .height(Length::Fixed(height))
+    }
+}
+
+impl<Message> Program<Message> for StackedAreaChart {
+    type State = ();
+
+    fn draw(
+        &self,
+        _state: &(),
+        renderer: &Renderer,
+        _theme: &Theme,
+        bounds: Rectangle,
+        _cursor: mouse::Cursor,
+    ) -> Vec<Geometry> {
+        let mut frame = Frame::new(renderer, bounds.size());
+        let (width, height) = (frame.width(), frame.height());
+
+        frame.fill_rectangle(Point::ORIGIN, frame.size(), Color::from_rgb(0.1, 0.1, 0.12));
+
+        let samples = self
+            .series
+            .iter()
+            .map(|(_, values)| values.len())
+            .min()
+            .unwrap_or(0);
+        if samples >= 2 {
+            let step = width / (samples - 1) as f32;
+            let y = |percent: f32| height - height * percent.clamp(0.0, 100.0) / 100.0;
+            let mut below = vec![0.0; samples];
+
+            for (color, values) in &self.series {
+                let above: Vec<f32> = below.iter().zip(values).map(|(b, v)| b + v).collect();
+                let area = Path::new(|builder| {
+                    builder.move_to(Point::new(0.0, y(above[0])));
+                    for (i, value) in above.iter().enumerate().skip(1) {
+                        builder.line_to(Point::new(i as f32 * step, y(*value)));
+                    }
+                    for (i, value) in below.iter().enumerate().rev() {
+                        builder.line_to(Point::new(i as f32 * step, y(*value)));
+                    }
+                    builder.close();
+                });
+                frame.fill(&area, Color { a: 0.6, ..*color });
+                below = above;
+            }
+        }
+
+        // grid lines at 25%, 50% and 75%, over the areas so they stay visible
+        for percent in [25.0, 50.0, 75.0] {
+            let y = height - height * percent / 100.0;
+            frame.stroke(
+                &Path::line(Point::new(0.0, y), Point::new(width, y)),
+                Stroke::default()
+                    .with_color(Color::from_rgba(0.25, 0.25, 0.3, 0.6))
+                    .with_width(1.0),
+            );
+        }
+
+        vec![frame.into_geometry()]
+    }
+}
+
+// Grid of squares colored by the usage of each logical processor
+pub struct CoreHeatmap {
+    pub cores: Vec<(Arc<str>, f32, u64)>,
+    pub numbers: NumberFormat,
+    // whether a selected process may use each core and whether it ran there: the cores it may
+    // not use are faded, the ones it ran on outlined; empty draws every core plainly
+    pub affinity: Vec<(bool, bool)>,
+}
+
+const HEATMAP_COLUMNS: usize = 16;
+const HEATMAP_CELL: f32 = 20.0;
+const HEATMAP_GAP: f32 = 2.0;
+const HEATMAP_LABEL_HEIGHT: f32 = 20.0;
+
+impl CoreHeatmap {
+    pub fn view<Message>(self) -> canvas::Canvas<CoreHeatmap, Message> {
+        let rows = self.cores.len().div_ceil(HEATMAP_COLUMNS).max(1);
+        let columns = self.cores.len().clamp(1, HEATMAP_COLUMNS);
+
+        canvas::Canvas::new(self)
+            .width(Length::Fixed(columns as f32 * (HEATMAP_CELL + HEATMAP_GAP)))
+            .height(Length::Fixed(
+                rows as f32 * (HEATMAP_CELL + HEATMAP_GAP) + HEATMAP_LABEL_HEIGHT,
+            ))
+    }
+
+    fn cell_at(&self, point: Point) -> Option<usize> {
+        let column = (point.x / (HEATMAP_CELL + HEATMAP_GAP)) as usize;
+        let row = (point.y / (HEATMAP_CELL + HEATMAP_GAP)) as usize;
+        let index = row * HEATMAP_COLUMNS + column;
+
+        (column < HEATMAP_COLUMNS && index < self.cores.len()).then_some(index)
+    }
+}
+
+// green at idle through yellow to red at full load
+fn usage_color(usage: f32) -> Color {
+    let usage = usage.clamp(0.0, 100.0) / 100.0;
+    if usage < 0.5 {
+        Color::from_rgb(usage * 2.0, 0.8, 0.2)
+    } else {
+        Color::from_rgb(1.0, 0.8 * (1.0 - usage) * 2.0, 0.2)
+    }
+}
+
+impl<Message> Program<Message> for CoreHeatmap {
+    type State = ();
+
+    fn draw(
+        &self,
+        _state: &(),
+        renderer: &Renderer,
+        _theme: &Theme,
+        bounds: Rectangle,
+        cursor: mouse::Cursor,
+    ) -> Vec<Geometry> {
+        let mut frame = Frame::new(renderer, bounds.size());
+
+        for (i, (_, usage, _)) in self.cores.iter().enumerate() {
+            let column = (i % HEATMAP_COLUMNS) as f32;
+            let row = (i / HEATMAP_COLUMNS) as f32;
+            let corner = Point::new(
+                column * (HEATMAP_CELL + HEATMAP_GAP),
+                row * (HEATMAP_CELL + HEATMAP_GAP),
+            );
+            let (allowed, ran) = self.affinity.get(i).copied().unwrap_or((true, false));
+            let color = usage_color(*usage);
+            frame.fill_rectangle(
+                corner,
+                Size::new(HEATMAP_CELL, HEATMAP_CELL),
+                if allowed {
+                    color
+                } else {
+                    Color { a: 0.2, ..color }
+                },
+            );
+            if ran {
+                frame.stroke(
+                    &Path::rectangle(
+                        Point::new(corner.x + 1.0, corner.y + 1.0),
+                        Size::new(HEATMAP_CELL - 2.0, HEATMAP_CELL - 2.0),
+                    ),
+                    Stroke::default()
+                        .with_color(Color::from_rgb(0.2, 0.6, 1.0))
+                        .with_width(2.0),
+                );
+            }
+        }
+
+        // details of the hovered core below the grid
+        let hovered = cursor
+            .position_in(bounds)
+            .and_then(|point| self.cell_at(point));
+        if let Some((name, usage, frequency)) = hovered.map(|i| &self.cores[i]) {
+            frame.fill_text(Text {
+                content: format!(
+                    "{}: {} @ {} MHz",
+                    name,
+                    self.numbers.percent(*usage as f64),
+                    self.numbers.integer(*frequency)
+                ),
+                position: Point::new(0.0, frame.height() - HEATMAP_LABEL_HEIGHT + 4.0),
+                color: Color::from_rgb(0.1, 0.8, 0.2),
+                size: 14.0.into(),
+                ..Text::default()
+            });
+        }
+
+        vec![frame.into_geometry()]
+    }
+}
+
+// One cell per core side by side, stretched over the available width, for the CPU strip
+pub struct CoreStrip {
+    pub usages: Vec<f32>,
+}
+
+impl CoreStrip {
+    pub fn view<Message>(self, height: f32) -> canvas::Canvas<CoreStrip, Message> {
+        canvas::Canvas::new(self)
+            .width(Length::Fill)
+            .height(Length::Fixed(height))
+    }
+}
+
+impl<Message> Program<Message> for CoreStrip {
+    type State = ();
+
+    fn draw(
+        &self,
+        _state: &(),
+        renderer: &Renderer,
+        _theme: &Theme,
+        bounds: Rectangle,
+        _cursor: mouse::Cursor,
+    ) -> Vec<Geometry> {
+        let mut frame = Frame::new(renderer, bounds.size());
+        let cell = bounds.width / self.usages.len().max(1) as f32;
+
+        for (i, usage) in self.usages.iter().enumerate() {
+            frame.fill_rectangle(
+                Point::new(i as f32 * cell, 0.0),
+                Size::new((cell - HEATMAP_GAP).max(1.0), bounds.height),
+                usage_color(*usage),
+            );
+        }
+
+        vec![frame.into_geometry()]
+    }
+}
+
+const TIMELINE_ROW: f32 = 18.0;
+
+// One bar per process from when it was first seen to when it was last seen, green to red by its
+// peak CPU use, labelled with its name
+pub struct ProcessTimeline {
+    // (label, start, end, peak CPU %) with start and end as fractions of the width
+    pub bars: Vec<(String, f32, f32, f32)>,
+}
+
+impl ProcessTimeline {
+    pub fn view<Message>(self) -> canvas::Canvas<ProcessTimeline, Message> {
+        let height = self.bars.len().max(1) as f32 * TIMELINE_ROW;
+        canvas::Canvas::new(self)
+            .width(Length::Fill)
+            .height(Length::Fixed(height))
+    }
+}
+
+impl<Message> Program<Message> for ProcessTimeline {
+    type State = ();
+
+    fn draw(
+        &self,
+        _state: &(),
+        renderer: &Renderer,
+        _theme: &Theme,
+        bounds: Rectangle,
+        _cursor: mouse::Cursor,
+    ) -> Vec<Geometry> {
+        let mut frame = Frame::new(renderer, bounds.size());
+        let width = frame.width();
+        frame.fill_rectangle(Point::ORIGIN, frame.size(), Color::from_rgb(0.1, 0.1, 0.12));
+
+        for (i, (label, start, end, cpu)) in self.bars.iter().enumerate() {
+            let y = i as f32 * TIMELINE_ROW;
+            // a process seen in a single refresh still gets a visible sliver
+            frame.fill_rectangle(
+                Point::new(start * width, y + 2.0),
+                Size::new(((end - start) * width).max(3.0), TIMELINE_ROW - 4.0),
+                usage_color(*cpu),
+            );
+            // labels of processes near the right edge are moved in to stay readable
+            frame.fill_text(Text {
+                content: label.clone(),
+                position: Point::new((start * width + 4.0).min(width - 240.0).max(0.0), y + 2.0),
+                color: Color::WHITE,
+                size: 12.0.into(),
+                ..Text::default()
+            });
+        }
+
+        vec![frame.into_geometry()]
+    }
+}
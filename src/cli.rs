@@ -0,0 +1,189 @@
+use crate::alerts::AlertMetric;
+use crate::config::Preset;
+
+pub const USAGE: &str = "Usage: rust-system-monitor [OPTIONS]
+
+Without options the graphical dashboard is started.
+
+Options:
+    --stdout             Run headless and write one JSON sample per interval to stdout
+    --daemon             Run headless and append one sample per interval to system_log.json
+    --interval <SECS>    Seconds between samples in headless mode
+                         (default: interval_secs from monitor_config.json, or 1)
+    --preset agent       Run headless as a fleet agent: no panels, no log file, only the
+                         readings alert rules need; metrics are rewritten in the Prometheus
+                         text format to metrics_textfile (default system_monitor.prom) and
+                         alerts are posted to alert_webhook when the config sets one
+    --once               Print a single JSON sample and exit
+    --metrics            Print the current metrics in the Prometheus text format and exit
+    --check <EXPR>       Assert on the current metrics and set the exit code, e.g. `cpu>90`
+                         Metrics: cpu, steal, memory, swap, disk (percent),
+                         read_iops, write_iops (busiest disk, per second, Linux),
+                         close_wait (TCP sockets, Linux), temp (hottest sensor, °C)
+                         Operators: >, >=, <, <=. Can be given more than once.
+                         Exit code: 0 when all checks pass, 2 when any fails, 3 on errors
+    --install-service    Register --daemon (with --interval) to start with the system,
+                         using the current directory for the config and log file
+    --uninstall-service  Stop and remove the registered service
+    --import-rules <FILE>
+                         Add the alert rules of a .json or .csv rule file to
+                         monitor_config.json, replacing rules of the same name
+    --export-rules <FILE>
+                         Write the configured alert rules to a .json or .csv file
+    --replay <FILE>      Open the dashboard on a session recorded with \"Record Session\"
+                         (session_<date>-<time>.rec.zst), one refresh per second
+    -h, --help           Print this help";
+
+// Comparison used by a --check assertion
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Comparison {
+    Greater,
+    GreaterOrEqual,
+    Less,
+    LessOrEqual,
+}
+
+impl Comparison {
+    pub fn holds(&self, value: f64, limit: f64) -> bool {
+        match self {
+            Comparison::Greater => value > limit,
+            Comparison::GreaterOrEqual => value >= limit,
+            Comparison::Less => value < limit,
+            Comparison::LessOrEqual => value <= limit,
+        }
+    }
+}
+
+impl std::fmt::Display for Comparison {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Comparison::Greater => write!(f, ">"),
+            Comparison::GreaterOrEqual => write!(f, ">="),
+            Comparison::Less => write!(f, "<"),
+            Comparison::LessOrEqual => write!(f, "<="),
+        }
+    }
+}
+
+// A --check assertion, fails when the comparison holds (like an alert threshold)
+#[derive(Debug, Clone)]
+pub struct Check {
+    pub expression: String,
+    pub metric: AlertMetric,
+    pub comparison: Comparison,
+    pub limit: f64,
+}
+
+impl Check {
+    pub fn parse(expression: &str) -> Result<Check, String> {
+        // two-character operators have to be tried first
+        let operators = [
+            (">=", Comparison::GreaterOrEqual),
+            ("<=", Comparison::LessOrEqual),
+            (">", Comparison::Greater),
+            ("<", Comparison::Less),
+        ];
+        let (name, comparison, limit) = operators
+            .iter()
+            .find_map(|(operator, comparison)| {
+                expression
+                    .split_once(operator)
+                    .map(|(name, limit)| (name, *comparison, limit))
+            })
+            .ok_or_else(|| format!("Invalid check (expected e.g. cpu>90): {}", expression))?;
+
+        let metric = AlertMetric::from_name(name.trim())
+            .ok_or_else(|| format!("Unknown metric in check: {}", name.trim()))?;
+        let limit = limit
+            .trim()
+            .parse::<f64>()
+            .map_err(|_| format!("Invalid number in check: {}", limit.trim()))?;
+
+        Ok(Check {
+            expression: expression.to_string(),
+            metric,
+            comparison,
+            limit,
+        })
+    }
+}
+
+// Options given on the command line
+#[derive(Debug, Default)]
+pub struct CliOptions {
+    pub help: bool,
+    pub stdout: bool,
+    pub daemon: bool,
+    pub once: bool,
+    pub metrics: bool,
+    pub checks: Vec<Check>,
+    pub interval_secs: Option<u64>,
+    pub install_service: bool,
+    pub uninstall_service: bool,
+    // recorded session to play back in the GUI
+    pub replay: Option<String>,
+    // alert rule files to merge into or write from the config
+    pub import_rules: Option<String>,
+    pub export_rules: Option<String>,
+    pub preset: Option<Preset>,
+}
+
+pub fn parse_args(mut args: impl Iterator<Item = String>) -> Result<CliOptions, String> {
+    let mut options = CliOptions::default();
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-h" | "--help" => options.help = true,
+            "--stdout" => options.stdout = true,
+            "--daemon" => options.daemon = true,
+            "--once" => options.once = true,
+            "--metrics" => options.metrics = true,
+            "--install-service" => options.install_service = true,
+            "--uninstall-service" => options.uninstall_service = true,
+            "--check" => {
+                let expression = args
+                    .next()
+                    .ok_or_else(|| String::from("--check needs an expression"))?;
+                options.checks.push(Check::parse(&expression)?);
+            }
+            "--replay" => {
+                let path = args
+                    .next()
+                    .ok_or_else(|| String::from("--replay needs a recording"))?;
+                options.replay = Some(path);
+            }
+            "--import-rules" => {
+                let path = args
+                    .next()
+                    .ok_or_else(|| String::from("--import-rules needs a rule file"))?;
+                options.import_rules = Some(path);
+            }
+            "--export-rules" => {
+                let path = args
+                    .next()
+                    .ok_or_else(|| String::from("--export-rules needs a file name"))?;
+                options.export_rules = Some(path);
+            }
+            "--preset" => {
+                let name = args
+                    .next()
+                    .ok_or_else(|| String::from("--preset needs a name"))?;
+                options.preset = Some(
+                    Preset::from_name(&name).ok_or_else(|| format!("Unknown preset: {}", name))?,
+                );
+            }
+            "--interval" => {
+                let value = args
+                    .next()
+                    .ok_or_else(|| String::from("--interval needs a value"))?;
+                options.interval_secs = match value.parse::<u64>() {
+                    Ok(secs) if secs > 0 => Some(secs),
+                    _ => return Err(format!("Invalid interval: {}", value)),
+                };
+            }
+            _ => return Err(format!("Unknown option: {}", arg)),
+        }
+    }
+
+    Ok(options)
+}
@@ -0,0 +1,1190 @@
+use crate::alerts::{post_webhook, AlertEvent, AlertMetric, AlertState, RuleForm};
+use crate::automation::run_action;
+use crate::background::BackgroundRead;
+use crate::config::{Config, Preset, CONFIG_FILE};
+use crate::disk_rates::DiskRateHistory;
+use crate::disks::DiskTopology;
+use crate::emergency::{write_post_mortem, PressureWatch};
+use crate::filter::ProcessFilter;
+use crate::format::usage_percent;
+use crate::gpu::{read_gpu_status, GpuStatus, GPU_REFRESH_INTERVAL};
+use crate::heartbeat::{notify_watchdog, write_heartbeat};
+use crate::history::History;
+use crate::identity::read_host_identity;
+use crate::idle::{read_idle_time, IDLE_POLL_INTERVAL};
+use crate::integrity::{self, IntegrityMonitor, LogProblem};
+use crate::intern::NameCache;
+use crate::lifetimes::LifetimeTracker;
+use crate::logging::LogWriter;
+use crate::mock::{MockSource, MockTick};
+use crate::models::{
+    InterruptInfo, Process, ProcessLimit, ProcessOrder, ProtocolRates, SystemBaseInfo, SystemData,
+    SystemMonitor,
+};
+use crate::pacing::TickPacer;
+use crate::pi::{read_pi_status, PI_REFRESH_INTERVAL};
+use crate::power::{read_battery, read_on_battery};
+use crate::procfs::{
+    link_states, offline_cpus, read_cpu_times, read_diskstats, read_interrupts,
+    read_process_cpu_time, read_protocol_counters, read_tcp_states, read_top_ports,
+};
+use crate::prometheus::write_textfile;
+use crate::recording::{RecordingHeader, Replay, TickSnapshot};
+use crate::respawn::RespawnTracker;
+use crate::runaway::RunawayTracker;
+use crate::sensors::platform_backend;
+use crate::space_scan::ScanGrouping;
+use crate::stats::SessionStats;
+use crate::timesync::read_time_sync;
+use crate::updates::read_update_status;
+use crate::utils::{
+    calculate_disk_usage, collect_system_data, command_line, current_timestamp, disks_info,
+    local_timestamp, memory_cache, process_user,
+};
+use crate::wear::{WearLedger, WEAR_FILE};
+use std::collections::{HashMap, VecDeque};
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+use sysinfo::{Disks, Networks, ProcessRefreshKind, ProcessesToUpdate, System, UpdateKind, Users};
+#[cfg(feature = "gui")]
+use {
+    crate::delta::{DeltaTracker, SnapshotDelta},
+    crate::rows::PanelRows,
+    crate::utils::user_totals,
+    std::collections::HashSet,
+};
+
+const ALERT_HISTORY_LIMIT: usize = 100;
+// time services and update status are queried with external commands, once a minute is
+// plenty; the SSD write counts are saved as often
+const TIME_SYNC_INTERVAL: Duration = Duration::from_secs(60);
+const WEAR_SAVE_INTERVAL: Duration = Duration::from_secs(60);
+
+// plugging in or unplugging shows up on the timeline within this long
+const POWER_POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+// the protocol chart in the network panel covers two minutes, like the TCP socket chart
+const PROTOCOL_RATE_SAMPLES: usize = 120;
+
+// ports listed under the protocol chart
+const TOP_PORTS: usize = 5;
+
+// ticks arrive a little early or late, so a process refresh due within this is done now
+const PROCESS_REFRESH_SLACK: Duration = Duration::from_millis(500);
+
+impl SystemMonitor {
+    // gather the initial system information
+    pub fn init() -> SystemMonitor {
+        let mut system = System::new_all();
+        let disks = Disks::new_with_refreshed_list();
+        let networks = Networks::new_with_refreshed_list();
+        let users = Users::new_with_refreshed_list();
+        system.refresh_all();
+
+        let total_memory = system.total_memory();
+        let used_memory = system.used_memory();
+        let cached_memory = memory_cache(&system);
+        let total_swap_memory = system.total_swap();
+        let used_swap_memory = system.used_swap();
+
+        let (used_disk, total_disk) = calculate_disk_usage(&disks);
+        let config = Config::load();
+
+        let mut disk_topology = DiskTopology::default();
+        let disks_info = disks_info(&disks, &mut disk_topology);
+
+        let network_sent = networks.iter().fold(0, |acc, (_interface, network)| {
+            acc + network.total_transmitted()
+        });
+
+        let network_received = networks.iter().fold(0, |acc, (_interface, network)| {
+            acc + network.total_received()
+        });
+
+        let no_of_processes: u32 = system.processes().len() as u32;
+        let cpu_usage = system.global_cpu_usage();
+        let physical_cores: u32 = match system.physical_core_count() {
+            Some(count) => count as u32,
+            None => {
+                eprintln!("Failed to retrieve physical core count. Using default value of 0.");
+                0
+            }
+        };
+        let logical_processors = system.cpus().len() as u32;
+
+        let system_base_info = SystemBaseInfo {
+            system_name: System::name().unwrap_or_default(),
+            kernal_version: System::kernel_version().unwrap_or_default(),
+            os_version: System::os_version().unwrap_or_default(),
+            host_name: System::host_name().unwrap_or_default(),
+        };
+
+        let mut names = NameCache::default();
+        let processors_info: Vec<_> = system
+            .cpus()
+            .iter()
+            .enumerate()
+            .map(|(i, cpu)| (names.core(i, cpu.name()), cpu.cpu_usage(), cpu.frequency()))
+            .collect();
+
+        // capture initial data to calculate CPU percentages accurately
+        let initial_cpu_time = system.global_cpu_usage();
+
+        // Optional delay for measuring changes over time
+        thread::sleep(Duration::from_secs(1));
+
+        // Refresh system data after sleep for updated values
+        system.refresh_cpu_all();
+        system.refresh_memory();
+
+        // Calculate the change in CPU usage over time
+        let cpu_time_diff = system.global_cpu_usage() - initial_cpu_time;
+
+        let mut processes: Vec<Process> = Vec::new();
+
+        // Iterate through each process
+        for (pid, process) in system.processes() {
+            // Calculate CPU usage percent for the process
+            let cpu_usage_percent = if cpu_time_diff > 0.0 {
+                process.cpu_usage()
+            } else {
+                0.0
+            };
+
+            // Calculate memory usage percent relative to total system memory
+            let memory_usage_percent =
+                (process.memory() as f64 / system.total_memory() as f64) * 100.0;
+
+            if memory_usage_percent >= 0.01 {
+                let names = names.process(
+                    (pid.as_u32(), process.start_time()),
+                    &process.name().to_string_lossy(),
+                    || (command_line(process), process_user(process, &users)),
+                );
+                processes.push(Process {
+                    id: pid.as_u32(),
+                    start_time: process.start_time(),
+                    name: names.name,
+                    command: names.command,
+                    user: names.user,
+                    cpu_usage_percent: cpu_usage_percent as f64,
+                    cpu_time_secs: read_process_cpu_time(pid.as_u32()).unwrap_or_default(),
+                    memory_usage_percent,
+                    // the first refresh reports the I/O since each process started
+                    disk_io_rate: 0.,
+                });
+            }
+        }
+        names.finish_refresh();
+
+        let mut sensor_backend = platform_backend();
+        let sensors = sensor_backend.read();
+
+        SystemMonitor {
+            system,
+            users,
+            disks,
+            disks_info,
+            disk_topology,
+            networks,
+            system_base_info,
+            host_identity: Arc::new(read_host_identity()),
+            on_battery: read_on_battery(),
+            cpu_usage,
+            cpu_times: read_cpu_times(),
+            no_of_processes,
+            smoothed_cores: processors_info.clone(),
+            processors_info,
+            names,
+            offline_cpus: offline_cpus(),
+            link_states: link_states(),
+            interrupt_counts: read_interrupts(),
+            physical_cores,
+            logical_processors,
+            memory_usage: (used_memory, total_memory),
+            memory_cache: cached_memory,
+            swap_memory_usage: (used_swap_memory, total_swap_memory),
+            disk_usage: (used_disk, total_disk),
+            disk_counters: read_diskstats(),
+            disk_wear: WearLedger::load(),
+            network_sent,
+            network_received,
+            tcp_states: read_tcp_states(),
+            protocol_counters: read_protocol_counters(),
+            protocol_rates: VecDeque::new(),
+            top_ports: Vec::new(),
+            sensor_backend,
+            sensors,
+            processes,
+            ..SystemMonitor::blank(config)
+        }
+    }
+
+    // monitor fed from a scripted source instead of the real system, e.g. for tests
+    pub fn with_mock(config: Config, source: MockSource) -> SystemMonitor {
+        SystemMonitor {
+            mock: Some(source),
+            ..SystemMonitor::blank(config)
+        }
+    }
+
+    // monitor that plays back a recorded session, monitoring from the start
+    pub fn with_replay(config: Config, header: RecordingHeader, replay: Replay) -> SystemMonitor {
+        SystemMonitor {
+            system_base_info: header.system_base_info,
+            host_identity: Arc::new(header.host),
+            replay: Some(replay),
+            is_monitoring: true,
+            ..SystemMonitor::blank(config)
+        }
+    }
+
+    pub fn process_order(&self) -> ProcessOrder {
+        ProcessOrder {
+            sort: self.process_sort,
+            reversed: self.process_sort_reversed,
+        }
+    }
+
+    // time of the current refresh: the recorded one while replaying a session
+    pub fn sample_timestamp(&self) -> String {
+        self.replay
+            .as_ref()
+            .and_then(Replay::timestamp)
+            .map_or_else(current_timestamp, str::to_string)
+    }
+
+    // monitor without any measurements yet
+    fn blank(config: Config) -> SystemMonitor {
+        let (active_tab, maximized_panel) = config.startup_view();
+        let log_directory = config.log_directory();
+        SystemMonitor {
+            system: System::new(),
+            users: Users::new(),
+            disks: Disks::new(),
+            disks_info: Vec::new(),
+            networks: Networks::new(),
+            system_base_info: SystemBaseInfo {
+                system_name: String::new(),
+                kernal_version: String::new(),
+                os_version: String::new(),
+                host_name: String::new(),
+            },
+            host_identity: Arc::default(),
+            time_sync: None,
+            time_sync_read: BackgroundRead::new("time-sync-read", TIME_SYNC_INTERVAL),
+            wear_saved: Instant::now(),
+            update_status: None,
+            update_read: BackgroundRead::new("update-status-read", TIME_SYNC_INTERVAL),
+            on_battery: None,
+            battery: None,
+            power_polled: None,
+            power_status: String::new(),
+            #[cfg(feature = "gui")]
+            dismissed_hints: HashSet::new(),
+            cpu_usage: 0.0,
+            cpu_steal_percent: 0.0,
+            cpu_times: None,
+            no_of_processes: 0,
+            processors_info: Vec::new(),
+            smoothed_cores: Vec::new(),
+            offline_cpus: Vec::new(),
+            link_states: HashMap::new(),
+            interrupt_counts: None,
+            interrupt_info: InterruptInfo::default(),
+            physical_cores: 0,
+            logical_processors: 0,
+            memory_usage: (0, 0),
+            memory_cache: 0,
+            swap_memory_usage: (0, 0),
+            disk_usage: (0, 0),
+            disk_counters: None,
+            disk_io: Vec::new(),
+            disk_rates: DiskRateHistory::default(),
+            disk_wear: WearLedger::default(),
+            disk_topology: DiskTopology::default(),
+            alias_device: None,
+            space_scan: None,
+            space_scan_mount: None,
+            // file owners are only known on Unix
+            space_scan_grouping: if cfg!(unix) {
+                ScanGrouping::Owner
+            } else {
+                ScanGrouping::Directory
+            },
+            alias_input: String::new(),
+            network_sent: 0,
+            network_received: 0,
+            network_sent_rate: 0.0,
+            network_received_rate: 0.0,
+            tcp_states: None,
+            protocol_counters: None,
+            protocol_rates: VecDeque::new(),
+            new_protocol_rates: None,
+            top_ports: Vec::new(),
+            interface_action: None,
+            wake_mac_input: String::new(),
+            interface_action_status: String::new(),
+            link_down_pending: false,
+            sensor_backend: platform_backend(),
+            sensors: Vec::new(),
+            gpu: GpuStatus::default(),
+            gpu_read: BackgroundRead::new("gpu-read", GPU_REFRESH_INTERVAL),
+            gpu_action_status: String::new(),
+            pi: None,
+            pi_read: BackgroundRead::new("pi-read", PI_REFRESH_INTERVAL),
+            user_idle: None,
+            idle_read: BackgroundRead::new("idle-read", IDLE_POLL_INTERVAL),
+            processes: Vec::new(),
+            process_limit: ProcessLimit::Top50,
+            process_page: 0,
+            process_filter: ProcessFilter::default(),
+            process_sort: config.startup_process_order().sort,
+            process_sort_reversed: config.startup_process_order().reversed,
+            view_name_input: String::new(),
+            highlight_secs_input: config.new_process_highlight_secs().to_string(),
+            process_refresh_input: config.process_refresh_secs().to_string(),
+            selected_processes: HashMap::new(),
+            nice_value: String::new(),
+            process_action_status: String::new(),
+            process_connections: None,
+            process_affinity: None,
+            process_cgroup: None,
+            quota_cpu_input: String::new(),
+            quota_memory_input: String::new(),
+            explained_process: None,
+            #[cfg(feature = "gui")]
+            user_totals: Vec::new(),
+            names: NameCache::default(),
+            alert_state: AlertState::default(),
+            respawn_tracker: RespawnTracker::default(),
+            process_lifetimes: LifetimeTracker::default(),
+            runaway_tracker: RunawayTracker::default(),
+            alert_history: Vec::new(),
+            rule_form: RuleForm::default(),
+            rule_file_input: String::new(),
+            rule_file_status: String::new(),
+            pressure_watch: PressureWatch::default(),
+            session_stats: SessionStats::default(),
+            last_refresh: Instant::now(),
+            processes_refreshed: None,
+            tick_pacer: TickPacer::default(),
+            sampler: None,
+            #[cfg(feature = "gui")]
+            delta_tracker: DeltaTracker::default(),
+            #[cfg(feature = "gui")]
+            delta: SnapshotDelta::default(),
+            #[cfg(feature = "gui")]
+            panel_rows: PanelRows::default(),
+            history: History::default(),
+            history_selection: None,
+            history_comparison: None,
+            comparison_loading: None,
+            comparison_status: String::new(),
+            export_status: String::new(),
+            annotation_input: String::new(),
+            interval_in_secs: config
+                .interval_secs
+                .map(|secs| secs.to_string())
+                .unwrap_or_default(),
+            save_to_file: config.save_to_file,
+            config,
+            config_modified: Config::modified(),
+            preset: None,
+            #[cfg(feature = "gui")]
+            setup: None,
+            active_tab,
+            #[cfg(feature = "gui")]
+            detached_panels: HashMap::new(),
+            paused_panels: HashMap::new(),
+            maximized_panel,
+            #[cfg(feature = "gui")]
+            mini_mode: None,
+            brightness: None,
+            volume: None,
+            quick_control_status: String::new(),
+            #[cfg(feature = "gui")]
+            cpu_strip: None,
+            #[cfg(feature = "gui")]
+            away_mark: None,
+            #[cfg(feature = "gui")]
+            digest: None,
+            diagnosis_capture: None,
+            diagnosis: None,
+            screenshot_status: String::new(),
+            metrics_status: String::new(),
+            is_monitoring: false,
+            log_writer: LogWriter::in_directory(log_directory),
+            log_integrity: IntegrityMonitor::default(),
+            mock: None,
+            replay: None,
+            recorder: None,
+            recording_status: String::new(),
+        }
+    }
+
+    pub fn set_preset(&mut self, preset: Preset) {
+        preset.apply(&mut self.config);
+        self.save_to_file = self.config.save_to_file;
+        self.preset = Some(preset);
+    }
+
+    // read the config file again even if it looks unchanged, e.g. on SIGHUP
+    pub fn force_reload_config(&mut self) -> bool {
+        self.config_modified = None;
+        self.reload_config()
+    }
+
+    // pick up edits made to the config file while running, returns whether it was reloaded
+    pub fn reload_config(&mut self) -> bool {
+        let modified = Config::modified();
+        if modified.is_none() || modified == self.config_modified {
+            return false;
+        }
+        self.config_modified = modified;
+
+        let mut config = match Config::read() {
+            Ok(config) => config,
+            Err(e) => {
+                eprintln!(
+                    "Invalid config file {}: {}\nKeeping the current settings.",
+                    CONFIG_FILE, e
+                );
+                return false;
+            }
+        };
+
+        // finish the current log file before switching to another format
+        if config.log_format != self.config.log_format
+            || config.log_compression != self.config.log_compression
+        {
+            if let Err(e) = self.log_writer.flush() {
+                eprintln!("Failed to write log file: {}", e);
+            }
+        }
+
+        // the fallback directory can be cleared by hand, e.g. once the disk was cleaned up
+        self.log_writer.set_directory(config.log_directory());
+
+        if let Some(preset) = self.preset {
+            preset.apply(&mut config);
+        }
+        self.config = config;
+        true
+    }
+
+    // scripted runs on mock data leave the config file alone
+    pub fn save_config(&mut self) {
+        if self.mock.is_some() {
+            return;
+        }
+        if let Err(e) = self.config.save() {
+            eprintln!("Failed to save config: {}", e);
+        }
+        // our own write is not an external edit
+        self.config_modified = Config::modified();
+    }
+
+    // refresh every metric, done on each tick while monitoring
+    pub fn refresh(&mut self) {
+        if let Some(replay) = self.replay.as_mut() {
+            if let Some(frame) = replay.next_frame() {
+                frame.apply(self);
+            }
+        } else {
+            match self.mock.as_mut().map(MockSource::next_tick) {
+                Some(tick) => self.apply_mock_tick(tick),
+                None => self.refresh_system(),
+            }
+        }
+        // replays are smoothed the same way as live readings
+        self.smoothed_cores = smooth_cores(
+            &self.smoothed_cores,
+            &self.processors_info,
+            self.config.core_smoothing(),
+        );
+        self.disk_rates.push(&self.disk_io);
+        self.record_tick();
+
+        let order = self.process_order();
+        self.processes.sort_by(|a, b| order.compare(a, b));
+
+        // the panels get what changed and only rebuild those rows
+        #[cfg(feature = "gui")]
+        {
+            self.delta = self.delta_tracker.update(&self.processes, &self.disks_info);
+            self.panel_rows.apply(
+                &self.delta,
+                &self.processes,
+                &self.disks_info,
+                self.config.number_format(),
+            );
+            if !self.delta.processes.is_empty() {
+                self.user_totals = user_totals(&self.processes);
+            }
+        }
+
+        self.session_stats.record(
+            self.cpu_usage as f64,
+            usage_percent(self.memory_usage),
+            self.network_sent_rate,
+            self.network_received_rate,
+        );
+        self.push_history();
+        self.evaluate_alerts();
+        self.update_diagnosis();
+        // a replayed session is not this machine running out of memory or staying alive
+        if self.replay.is_none() {
+            self.capture_memory_pressure();
+            self.send_heartbeat();
+            self.export_metrics();
+        }
+    }
+
+    // with a background sampler the history gets its readings, at the collect interval, with
+    // the values it doesn't sample taken from this refresh; otherwise one sample per refresh
+    fn push_history(&mut self) {
+        let Some(sampler) = &self.sampler else {
+            self.history.push(collect_system_data(self));
+            return;
+        };
+        for sample in sampler.take() {
+            let data = SystemData {
+                local_timestamp: local_timestamp(&sample.timestamp),
+                timestamp: sample.timestamp,
+                cpu_usage_percent: sample.cpu_usage,
+                memory_usage_byte: sample.memory_usage,
+                memory_cache_byte: sample.memory_cache,
+                swap_memory_usage_byte: sample.swap_memory_usage,
+                ..collect_system_data(self)
+            };
+            self.history.push(data);
+        }
+    }
+
+    // feed a running "find what is slow" capture, and diagnose once it has seen enough
+    fn update_diagnosis(&mut self) {
+        let Some(mut capture) = self.diagnosis_capture.take() else {
+            return;
+        };
+        capture.record(self);
+        if capture.finished() {
+            self.diagnosis = Some(capture.diagnose(self, self.config.number_format()));
+        } else {
+            self.diagnosis_capture = Some(capture);
+        }
+    }
+
+    // the raw values of this refresh, before anything is derived from them
+    fn record_tick(&mut self) {
+        let Some(mut recorder) = self.recorder.take() else {
+            return;
+        };
+        let snapshot = TickSnapshot::capture(self);
+        if let Err(stopped) = recorder.record(snapshot) {
+            // the thread's own failure says more than that it stopped
+            let e = recorder.finish().err().unwrap_or(stopped);
+            self.recording_status = format!("Recording stopped: {}", e);
+        } else {
+            self.recorder = Some(recorder);
+        }
+    }
+
+    fn apply_mock_tick(&mut self, tick: Option<MockTick>) {
+        if let Some(tick) = tick {
+            self.cpu_usage = tick.cpu_usage;
+            self.memory_usage = tick.memory_usage;
+            self.swap_memory_usage = tick.swap_memory_usage;
+            self.no_of_processes = tick.processes.len() as u32;
+            self.processes = tick.processes;
+        }
+        self.last_refresh = Instant::now();
+    }
+
+    fn refresh_system(&mut self) {
+        // enumerating processes is the most expensive part of a refresh, so it has its own cadence
+        let refresh_processes = self.processes_refreshed.is_none_or(|refreshed| {
+            refreshed.elapsed() + PROCESS_REFRESH_SLACK
+                >= Duration::from_secs(self.config.process_refresh_secs())
+        });
+        self.system.refresh_cpu_all();
+        self.system.refresh_memory();
+
+        // pick up disks and network interfaces that were added or removed
+        let previous_disks = self.disk_mounts();
+        let previous_interfaces = self.interface_names();
+        self.disks.refresh_list();
+        self.networks.refresh_list();
+        self.annotate_device_changes(&previous_disks, &previous_interfaces);
+        let link_states = link_states();
+        self.annotate_link_changes(&link_states);
+        self.link_states = link_states;
+
+        // update cpu info
+        self.cpu_usage = self.system.global_cpu_usage();
+        let names = &mut self.names;
+        self.processors_info = self
+            .system
+            .cpus()
+            .iter()
+            .enumerate()
+            .map(|(i, cpu)| (names.core(i, cpu.name()), cpu.cpu_usage(), cpu.frequency()))
+            .collect();
+
+        self.physical_cores = self.system.physical_core_count().unwrap_or(0) as u32;
+        self.logical_processors = self.system.cpus().len() as u32;
+
+        // steal share of the cpu time since the previous refresh
+        let cpu_times = read_cpu_times();
+        if let (Some((total, steal)), Some((previous_total, previous_steal))) =
+            (cpu_times, self.cpu_times)
+        {
+            let total = total.saturating_sub(previous_total);
+            if total > 0 {
+                self.cpu_steal_percent =
+                    steal.saturating_sub(previous_steal) as f64 / total as f64 * 100.;
+            }
+        }
+        self.cpu_times = cpu_times;
+
+        // readings only the dashboard panels show
+        let extras = !self.config.minimal_collection;
+
+        if extras {
+            let interrupt_counts = read_interrupts();
+            if let (Some(current), Some(previous)) = (&interrupt_counts, &self.interrupt_counts) {
+                self.interrupt_info = current.since(previous);
+            }
+            self.interrupt_counts = interrupt_counts;
+        }
+
+        if extras
+            && self
+                .power_polled
+                .is_none_or(|polled| polled.elapsed() >= POWER_POLL_INTERVAL)
+        {
+            let on_battery = read_on_battery();
+            self.annotate_power_source(on_battery);
+            self.on_battery = on_battery;
+            self.battery = read_battery();
+            self.power_polled = Some(Instant::now());
+        }
+
+        let offline_cpus = offline_cpus();
+        self.annotate_cpu_changes(&offline_cpus);
+        self.offline_cpus = offline_cpus;
+
+        // chronyc, timedatectl and w32tm can take a while to answer
+        if let Some(time_sync) = self.time_sync_read.poll(read_time_sync) {
+            self.time_sync = time_sync;
+        }
+        // so can needs-restarting and reg query
+        if extras {
+            if let Some(update_status) = self.update_read.poll(read_update_status) {
+                self.update_status = update_status;
+            }
+        }
+        if self.wear_saved.elapsed() >= WEAR_SAVE_INTERVAL {
+            if let Err(e) = self.disk_wear.save() {
+                eprintln!("Failed to write {}: {}", WEAR_FILE, e);
+            }
+            self.wear_saved = Instant::now();
+        }
+
+        // update memory info
+        self.memory_usage = (self.system.used_memory(), self.system.total_memory());
+        self.memory_cache = memory_cache(&self.system);
+        self.swap_memory_usage = (self.system.used_swap(), self.system.total_swap());
+
+        // update disk info
+        self.disk_usage = calculate_disk_usage(&self.disks);
+
+        self.disks_info = disks_info(&self.disks, &mut self.disk_topology);
+
+        // update network info
+        let network_sent = self.networks.iter().fold(0, |acc, (_interface, network)| {
+            acc + network.total_transmitted()
+        });
+        let network_received = self.networks.iter().fold(0, |acc, (_interface, network)| {
+            acc + network.total_received()
+        });
+
+        // totals can drop when an interface goes away, treat that as no traffic
+        let elapsed = self.last_refresh.elapsed().as_secs_f64().max(0.001);
+        self.network_sent_rate = network_sent.saturating_sub(self.network_sent) as f64 / elapsed;
+        self.network_received_rate =
+            network_received.saturating_sub(self.network_received) as f64 / elapsed;
+        self.network_sent = network_sent;
+        self.network_received = network_received;
+        self.tcp_states = read_tcp_states();
+
+        self.new_protocol_rates = None;
+        if extras {
+            let protocol_counters = read_protocol_counters();
+            if let (Some(current), Some(previous)) = (&protocol_counters, &self.protocol_counters) {
+                self.push_protocol_rates(current.since(previous, elapsed));
+            }
+            self.protocol_counters = protocol_counters;
+            self.top_ports = read_top_ports(TOP_PORTS);
+        }
+
+        let disk_counters = read_diskstats();
+        if let (Some(current), Some(previous)) = (&disk_counters, &self.disk_counters) {
+            self.disk_io = current.since(previous, elapsed);
+            self.disk_wear.add_disk_writes(current, previous);
+        }
+        self.disk_counters = disk_counters;
+        self.last_refresh = Instant::now();
+        // temperature rules need the sensors even when collecting the minimum
+        let temperature_rule = self
+            .config
+            .alert_rules
+            .iter()
+            .any(|rule| rule.enabled && rule.metric == AlertMetric::Temperature);
+        if extras || temperature_rule {
+            self.sensors = self.sensor_backend.read();
+        }
+
+        if extras {
+            if let Some(gpu) = self.gpu_read.poll(read_gpu_status) {
+                self.gpu = gpu;
+            }
+        }
+
+        if extras {
+            if let Some(pi) = self.pi_read.poll(read_pi_status) {
+                self.pi = pi;
+            }
+        }
+
+        let idle_after = self.config.idle_after();
+        if idle_after.is_zero() {
+            self.user_idle = None;
+        } else if let Some(idle) = self.idle_read.poll(read_idle_time) {
+            self.user_idle = idle.map(|idle| idle >= idle_after);
+        }
+
+        if refresh_processes {
+            self.refresh_processes();
+        }
+    }
+
+    fn refresh_processes(&mut self) {
+        // sysinfo reads a command line and user once per process unless asked again; the accounts
+        // are read again too, for a user added since
+        let mut refresh_kind = ProcessRefreshKind::everything();
+        if self.names.start_refresh() {
+            self.users.refresh_list();
+            refresh_kind = refresh_kind
+                .with_cmd(UpdateKind::Always)
+                .with_user(UpdateKind::Always);
+        }
+        self.system
+            .refresh_processes_specifics(ProcessesToUpdate::All, refresh_kind);
+        let elapsed = self
+            .processes_refreshed
+            .map_or(0., |refreshed| refreshed.elapsed().as_secs_f64());
+        // the first refresh reports everything written since each process started
+        if self.processes_refreshed.is_some() {
+            let written = self.system.processes().values();
+            self.disk_wear
+                .add_process_writes(written.map(|p| p.disk_usage().written_bytes).sum());
+        }
+        self.processes_refreshed = Some(Instant::now());
+        self.no_of_processes = self.system.processes().len() as u32;
+
+        // without /proc the CPU time is summed from the usage seen since the monitor started
+        let previous_cpu_times: HashMap<(u32, u64), f64> = self
+            .processes
+            .iter()
+            .map(|p| ((p.id, p.start_time), p.cpu_time_secs))
+            .collect();
+
+        self.processes.clear();
+        for (pid, process) in self.system.processes() {
+            // Calculate memory usage percent relative to total system memory
+            let memory_usage_percent =
+                (process.memory() as f64 / self.system.total_memory() as f64) * 100.0;
+
+            let users = &self.users;
+            let names = self.names.process(
+                (pid.as_u32(), process.start_time()),
+                &process.name().to_string_lossy(),
+                || (command_line(process), process_user(process, users)),
+            );
+            self.processes.push(Process {
+                id: pid.as_u32(),
+                start_time: process.start_time(),
+                name: names.name,
+                command: names.command,
+                user: names.user,
+                cpu_usage_percent: process.cpu_usage() as f64,
+                cpu_time_secs: read_process_cpu_time(pid.as_u32()).unwrap_or_else(|| {
+                    let key = (pid.as_u32(), process.start_time());
+                    previous_cpu_times.get(&key).copied().unwrap_or_default()
+                        + process.cpu_usage() as f64 / 100. * elapsed
+                }),
+                memory_usage_percent,
+                disk_io_rate: if elapsed > 0. {
+                    let io = process.disk_usage();
+                    (io.read_bytes + io.written_bytes) as f64 / elapsed
+                } else {
+                    0.
+                },
+            });
+        }
+        self.names.finish_refresh();
+    }
+
+    // tell supervisors this refresh went through
+    fn send_heartbeat(&self) {
+        if let Some(path) = &self.config.heartbeat_file {
+            if let Err(e) = write_heartbeat(path) {
+                eprintln!("Failed to write heartbeat file {}: {}", path, e);
+            }
+        }
+        if self.config.systemd_watchdog {
+            if let Err(e) = notify_watchdog() {
+                eprintln!("Failed to notify the systemd watchdog: {}", e);
+            }
+        }
+    }
+
+    fn export_metrics(&self) {
+        if let Some(path) = &self.config.metrics_textfile {
+            if let Err(e) = write_textfile(self, path) {
+                eprintln!("Failed to write metrics file {}: {}", path, e);
+            }
+        }
+    }
+
+    fn disk_mounts(&self) -> Vec<String> {
+        self.disks
+            .iter()
+            .map(|disk| disk.mount_point().to_string_lossy().to_string())
+            .collect()
+    }
+
+    fn interface_names(&self) -> Vec<String> {
+        self.networks.keys().cloned().collect()
+    }
+
+    fn evaluate_alerts(&mut self) {
+        let timestamp = self.sample_timestamp();
+        let mut alert_state = std::mem::take(&mut self.alert_state);
+        let fired =
+            alert_state.evaluate(&self.config.alert_rules, self, &timestamp, Instant::now());
+        let suppressed = alert_state.take_suppressed();
+        self.alert_state = alert_state;
+
+        // a suppressed breach counts against the alert it repeats
+        for rule_name in suppressed {
+            if let Some(event) = self
+                .alert_history
+                .iter_mut()
+                .rev()
+                .find(|event| event.rule_name == rule_name)
+            {
+                event.suppressed += 1;
+            }
+        }
+
+        self.process_lifetimes
+            .update(&self.processes, Instant::now());
+        let looping = self.respawn_tracker.update(
+            &self.processes,
+            Instant::now(),
+            self.config.respawn_alert_per_minute(),
+        );
+        let respawns = looping.into_iter().map(|(name, restarts)| AlertEvent {
+            timestamp: timestamp.clone(),
+            rule_name: format!("Respawn loop: {}", name),
+            metric: AlertMetric::RespawnRate,
+            value: restarts as f64,
+            suppressed: 0,
+        });
+
+        let runaway_duration = self.config.runaway_duration();
+        let runaways = if runaway_duration.is_zero() {
+            self.runaway_tracker = RunawayTracker::default();
+            Vec::new()
+        } else {
+            self.runaway_tracker.update(
+                &self.processes,
+                self.config.runaway_cpu_percent(),
+                runaway_duration,
+                Instant::now(),
+            )
+        };
+        let runaway_count = AlertMetric::RunawayProcesses.value(self);
+        let runaways = runaways.into_iter().filter_map(|(pid, start_time)| {
+            let process = self
+                .processes
+                .iter()
+                .find(|p| p.id == pid && p.start_time == start_time)?;
+            Some(AlertEvent {
+                timestamp: timestamp.clone(),
+                rule_name: format!("Runaway process: {} ({})", process.name, pid),
+                metric: AlertMetric::RunawayProcesses,
+                value: runaway_count,
+                suppressed: 0,
+            })
+        });
+        let events: Vec<AlertEvent> = fired.into_iter().chain(respawns).chain(runaways).collect();
+        self.raise_alerts(events, &timestamp);
+    }
+
+    // notify and act on alerts that just fired, then keep them in the alert history
+    fn raise_alerts(&mut self, events: Vec<AlertEvent>, timestamp: &str) {
+        for event in events {
+            let message = event.message(
+                self.config.alert_template(),
+                &self.system_base_info.host_name,
+            );
+            if let Some(url) = &self.config.alert_webhook {
+                post_webhook(url, &event, &message, &self.system_base_info.host_name);
+            }
+            // stderr ends up in the journal / service log, where scripts can pick the messages up
+            eprintln!("{}", message);
+            self.history
+                .add_annotation(timestamp.to_string(), format!("Alert: {}", event.rule_name));
+
+            // a replayed session only shows what fired, it doesn't act on this machine
+            let action = self
+                .config
+                .alert_rules
+                .iter()
+                .find(|rule| rule.name == event.rule_name)
+                .and_then(|rule| rule.action.as_ref())
+                .filter(|_| self.replay.is_none());
+            if let Some(action) = action {
+                run_action(action, &event, &message);
+                self.history
+                    .add_annotation(timestamp.to_string(), format!("Alert action: {}", action));
+            }
+            self.alert_history.push(event);
+        }
+
+        // only keep the most recent alerts
+        let excess = self.alert_history.len().saturating_sub(ALERT_HISTORY_LIMIT);
+        self.alert_history.drain(..excess);
+    }
+
+    // every few minutes while logging: alert on a log that stopped being written, and move it to
+    // the fallback directory when its own can't take it any more
+    pub fn check_log_integrity(&mut self) {
+        let now = Instant::now();
+        if self.replay.is_some() || !self.log_integrity.due(now) {
+            return;
+        }
+
+        // back to the working directory as soon as it takes the log again
+        let timestamp = self.sample_timestamp();
+        let home = PathBuf::new();
+        if self.log_writer.directory() != home && integrity::usable(&home, &self.disks) {
+            self.move_log(
+                home,
+                &timestamp,
+                String::from("Log moved back to the working directory"),
+            );
+        }
+
+        let problems = self.log_integrity.check(
+            self.log_writer.directory(),
+            self.log_writer.failures(),
+            &self.history,
+            &self.disks,
+            now,
+        );
+        if problems.is_empty() {
+            return;
+        }
+
+        let fallback = self.config.log_fallback_dir();
+        if problems.iter().any(LogProblem::moves_log) && self.log_writer.directory() != fallback {
+            match fs::create_dir_all(&fallback) {
+                Ok(()) => {
+                    let note = format!("Log moved to {}", fallback.display());
+                    self.move_log(fallback, &timestamp, note);
+                }
+                Err(e) => eprintln!(
+                    "Failed to create fallback log directory {}: {}",
+                    fallback.display(),
+                    e
+                ),
+            }
+        }
+
+        let count = AlertMetric::LogProblems.value(self);
+        let events = problems
+            .into_iter()
+            .map(|problem| AlertEvent {
+                timestamp: timestamp.clone(),
+                rule_name: format!("Log integrity: {}", problem),
+                metric: AlertMetric::LogProblems,
+                value: count,
+                suppressed: 0,
+            })
+            .collect();
+        self.raise_alerts(events, &timestamp);
+    }
+
+    // write the log to `directory` from the next record on, marked in the history and kept in the
+    // config so the next start (and anything reading the log) finds it there
+    fn move_log(&mut self, directory: PathBuf, timestamp: &str, note: String) {
+        eprintln!("{}", note);
+        self.history.add_annotation(timestamp.to_string(), note);
+        self.config.log_active_dir =
+            (!directory.as_os_str().is_empty()).then(|| directory.to_string_lossy().into_owned());
+        self.log_writer.set_directory(directory);
+        self.save_config();
+    }
+
+    // save a post-mortem snapshot when memory or swap has just become critical
+    fn capture_memory_pressure(&mut self) {
+        let reason = self.pressure_watch.update(
+            self.memory_usage,
+            self.swap_memory_usage,
+            self.config.emergency_memory_percent(),
+            self.config.emergency_swap_percent(),
+        );
+        let Some(reason) = reason else {
+            return;
+        };
+
+        match write_post_mortem(self, &reason) {
+            Ok(path) => self.history.add_annotation(
+                current_timestamp(),
+                format!("Post-mortem snapshot ({}): {}", reason, path),
+            ),
+            Err(e) => eprintln!("Failed to write post-mortem snapshot: {}", e),
+        }
+    }
+
+    // the chart keeps the last PROTOCOL_RATE_SAMPLES refreshes
+    pub fn push_protocol_rates(&mut self, rates: ProtocolRates) {
+        self.protocol_rates.push_back(rates);
+        if self.protocol_rates.len() > PROTOCOL_RATE_SAMPLES {
+            self.protocol_rates.pop_front();
+        }
+        self.new_protocol_rates = Some(rates);
+    }
+
+    // timeline marker when the laptop is plugged in or unplugged
+    fn annotate_power_source(&mut self, on_battery: Option<bool>) {
+        let label = match (self.on_battery, on_battery) {
+            (Some(false), Some(true)) => "Unplugged from AC",
+            (Some(true), Some(false)) => "Plugged into AC",
+            _ => return,
+        };
+        self.history
+            .add_annotation(current_timestamp(), label.to_string());
+    }
+
+    // add timeline markers for logical CPUs that were hot-plugged or taken offline
+    fn annotate_cpu_changes(&mut self, offline_cpus: &[u32]) {
+        let timestamp = current_timestamp();
+
+        for cpu in offline_cpus
+            .iter()
+            .filter(|cpu| !self.offline_cpus.contains(cpu))
+        {
+            self.history
+                .add_annotation(timestamp.clone(), format!("CPU {} offline", cpu));
+        }
+        for cpu in self
+            .offline_cpus
+            .iter()
+            .filter(|cpu| !offline_cpus.contains(cpu))
+        {
+            self.history
+                .add_annotation(timestamp.clone(), format!("CPU {} online", cpu));
+        }
+    }
+
+    // add timeline markers for disks and network interfaces that came or went
+    fn annotate_device_changes(
+        &mut self,
+        previous_disks: &[String],
+        previous_interfaces: &[String],
+    ) {
+        let disks = self.disk_mounts();
+        let interfaces = self.interface_names();
+        let timestamp = current_timestamp();
+
+        let mut events = Vec::new();
+        events.extend(
+            disks
+                .iter()
+                .filter(|mount| !previous_disks.contains(mount))
+                .map(|mount| format!("Disk appeared: {}", self.config.display_name(mount))),
+        );
+        events.extend(
+            previous_disks
+                .iter()
+                .filter(|mount| !disks.contains(mount))
+                .map(|mount| format!("Disk removed: {}", self.config.display_name(mount))),
+        );
+        events.extend(
+            interfaces
+                .iter()
+                .filter(|name| !previous_interfaces.contains(name))
+                .map(|name| format!("Interface added: {}", self.config.display_name(name))),
+        );
+        events.extend(
+            previous_interfaces
+                .iter()
+                .filter(|name| !interfaces.contains(name))
+                .map(|name| format!("Interface removed: {}", self.config.display_name(name))),
+        );
+
+        for event in events {
+            self.history.add_annotation(timestamp.clone(), event);
+        }
+    }
+
+    // add timeline markers for interfaces whose link went down or came back up, such as an
+    // unplugged cable or a dropped Wi-Fi association, while the interface itself stays
+    fn annotate_link_changes(&mut self, link_states: &HashMap<String, bool>) {
+        let timestamp = current_timestamp();
+        let mut changed: Vec<(&String, bool)> = link_states
+            .iter()
+            .filter(|(name, up)| self.link_states.get(*name).is_some_and(|was| was != *up))
+            .map(|(name, up)| (name, *up))
+            .collect();
+        changed.sort();
+        for (name, up) in changed {
+            let state = if up { "up" } else { "down" };
+            self.history.add_annotation(
+                timestamp.clone(),
+                format!("Interface {}: {}", state, self.config.display_name(name)),
+            );
+        }
+    }
+}
+
+// exponential moving average of each core's usage; a core that appeared since the previous
+// refresh, or a changed core count, starts again from the raw reading
+fn smooth_cores(
+    previous: &[(Arc<str>, f32, u64)],
+    current: &[(Arc<str>, f32, u64)],
+    factor: f32,
+) -> Vec<(Arc<str>, f32, u64)> {
+    if previous.len() != current.len() {
+        return current.to_vec();
+    }
+    current
+        .iter()
+        .zip(previous)
+        .map(|((name, usage, frequency), (_, average, _))| {
+            (
+                name.clone(),
+                average + factor * (usage - average),
+                *frequency,
+            )
+        })
+        .collect()
+}
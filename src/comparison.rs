@@ -0,0 +1,194 @@
+// The same hours of an earlier day or week read back from the JSON log, drawn as a ghost line
+// behind the history chart to spot changes in daily patterns
+use crate::format::{usage_percent, TIMESTAMP_FORMAT};
+use crate::logging::{rotated_path, LogCompression, LOG_FILE};
+use crate::recording::decompressed_reader;
+use chrono::{NaiveDateTime, TimeDelta};
+use serde::Deserialize;
+use std::io::{self, BufRead, BufReader};
+use std::path::{Path, PathBuf};
+
+// a logged sample further than this before a point of the chart leaves a gap in the ghost line
+const MAX_GAP_SECS: i64 = 120;
+
+// Earlier period the history chart is compared with
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ComparisonPeriod {
+    #[default]
+    Off,
+    Yesterday,
+    LastWeek,
+}
+
+impl ComparisonPeriod {
+    pub const ALL: [ComparisonPeriod; 3] = [
+        ComparisonPeriod::Off,
+        ComparisonPeriod::Yesterday,
+        ComparisonPeriod::LastWeek,
+    ];
+
+    fn offset(&self) -> Option<TimeDelta> {
+        match self {
+            ComparisonPeriod::Off => None,
+            ComparisonPeriod::Yesterday => Some(TimeDelta::days(1)),
+            ComparisonPeriod::LastWeek => Some(TimeDelta::weeks(1)),
+        }
+    }
+}
+
+impl std::fmt::Display for ComparisonPeriod {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ComparisonPeriod::Off => write!(f, "No comparison"),
+            ComparisonPeriod::Yesterday => write!(f, "Same time yesterday"),
+            ComparisonPeriod::LastWeek => write!(f, "Same time last week"),
+        }
+    }
+}
+
+// averages of an aggregated record, the other statistics aren't needed
+#[derive(Deserialize)]
+struct Average {
+    avg: f64,
+}
+
+// the lines of the log this reads, session end records match neither and are skipped
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum LoggedRecord {
+    Sample {
+        timestamp: String,
+        cpu_usage_percent: f32,
+        memory_usage_byte: (u64, u64),
+    },
+    Aggregated {
+        timestamp: String,
+        cpu_usage_percent: Average,
+        memory_used_byte: Average,
+        memory_total_byte: u64,
+    },
+}
+
+impl LoggedRecord {
+    // (time, cpu %, memory %)
+    fn point(self) -> Option<(NaiveDateTime, f32, f32)> {
+        let (timestamp, cpu, memory) = match self {
+            LoggedRecord::Sample {
+                timestamp,
+                cpu_usage_percent,
+                memory_usage_byte,
+            } => (
+                timestamp,
+                cpu_usage_percent,
+                usage_percent(memory_usage_byte) as f32,
+            ),
+            LoggedRecord::Aggregated {
+                timestamp,
+                cpu_usage_percent,
+                memory_used_byte,
+                memory_total_byte,
+            } => (
+                timestamp,
+                cpu_usage_percent.avg as f32,
+                usage_percent((memory_used_byte.avg as u64, memory_total_byte)) as f32,
+            ),
+        };
+        let time = NaiveDateTime::parse_from_str(&timestamp, TIMESTAMP_FORMAT).ok()?;
+        Some((time, cpu, memory))
+    }
+}
+
+// Logged CPU and memory usage of the earlier period, moved forward onto today's times
+#[derive(Debug, Clone)]
+pub struct Comparison {
+    pub period: ComparisonPeriod,
+    // (shifted time, cpu %, memory %), oldest first
+    points: Vec<(NaiveDateTime, f32, f32)>,
+    // shifted timestamp up to which the log was read, later points need a reload
+    pub loaded_until: String,
+}
+
+impl Comparison {
+    // the samples logged one period before `start` - `end` in the log directory; the rotated log
+    // and the compressed logs are read as well, Parquet logs aren't
+    pub fn load(
+        period: ComparisonPeriod,
+        start: &str,
+        end: &str,
+        directory: &Path,
+        rotate_keep: u32,
+    ) -> io::Result<Comparison> {
+        let offset = period
+            .offset()
+            .ok_or_else(|| io::Error::other("no period to compare with"))?;
+        let parse = |timestamp: &str| {
+            NaiveDateTime::parse_from_str(timestamp, TIMESTAMP_FORMAT)
+                .map_err(|e| io::Error::other(format!("invalid timestamp {}: {}", timestamp, e)))
+        };
+        let (from, to) = (parse(start)? - offset, parse(end)? - offset);
+
+        // every rotated file of each log, the points are sorted afterwards
+        let paths: Vec<PathBuf> = [
+            LOG_FILE.to_string(),
+            LogCompression::Gzip.path(),
+            LogCompression::Zstd.path(),
+        ]
+        .iter()
+        .flat_map(|log| {
+            let log = directory.join(log);
+            let rotated: Vec<PathBuf> = (1..=rotate_keep).map(|n| rotated_path(&log, n)).collect();
+            rotated.into_iter().chain([log])
+        })
+        .collect();
+
+        let mut points = Vec::new();
+        for path in paths.iter().filter(|path| path.exists()) {
+            // a compressed log still being written ends in an unfinished frame
+            for line in BufReader::new(decompressed_reader(&path.to_string_lossy())?)
+                .lines()
+                .map_while(Result::ok)
+            {
+                let Ok(record) = serde_json::from_str::<LoggedRecord>(&line) else {
+                    continue;
+                };
+                if let Some((time, cpu, memory)) = record.point() {
+                    if time >= from && time <= to {
+                        points.push((time + offset, cpu, memory));
+                    }
+                }
+            }
+        }
+        if points.is_empty() {
+            return Err(io::Error::other(format!(
+                "{} has no samples from {} to {}",
+                LOG_FILE,
+                from.format(TIMESTAMP_FORMAT),
+                to.format(TIMESTAMP_FORMAT)
+            )));
+        }
+        points.sort_by_key(|point| point.0);
+
+        Ok(Comparison {
+            period,
+            points,
+            loaded_until: end.to_string(),
+        })
+    }
+
+    // cpu and memory usage of the earlier period at each of `timestamps`, None where nothing
+    // was logged shortly before
+    pub fn align<'a>(
+        &self,
+        timestamps: impl Iterator<Item = &'a str>,
+    ) -> (Vec<Option<f32>>, Vec<Option<f32>>) {
+        timestamps
+            .map(|timestamp| {
+                let time = NaiveDateTime::parse_from_str(timestamp, TIMESTAMP_FORMAT).ok()?;
+                let index = self.points.partition_point(|point| point.0 <= time);
+                let (logged, cpu, memory) = self.points.get(index.checked_sub(1)?)?;
+                ((time - *logged).num_seconds() <= MAX_GAP_SECS).then_some((*cpu, *memory))
+            })
+            .map(|point| (point.map(|p| p.0), point.map(|p| p.1)))
+            .unzip()
+    }
+}
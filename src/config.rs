@@ -0,0 +1,392 @@
+use crate::alerts::{AlertMetric, AlertRule, DEFAULT_ALERT_TEMPLATE};
+use crate::format::{NumberFormat, NumberLocale};
+use crate::logging::{LogCompression, LogFormat};
+use crate::models::{Panel, ProcessOrder, ProcessSort, Tab};
+use crate::pacing::TICK_INTERVAL;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::env;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+use sysinfo::MINIMUM_CPU_UPDATE_INTERVAL;
+
+pub const CONFIG_FILE: &str = "monitor_config.json";
+
+// where the agent preset exports its metrics unless the config sets `metrics_textfile`
+pub const AGENT_METRICS_TEXTFILE: &str = "system_monitor.prom";
+
+// Process filter saved for quick reuse
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SavedFilter {
+    pub pattern: String,
+    pub is_regex: bool,
+}
+
+impl std::fmt::Display for SavedFilter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.is_regex {
+            write!(f, "/{}/", self.pattern)
+        } else {
+            write!(f, "{}", self.pattern)
+        }
+    }
+}
+
+// Named combination of process filter, sort order and columns
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SavedView {
+    pub name: String,
+    pub filter: SavedFilter,
+    pub sort: ProcessSort,
+    #[serde(default)]
+    pub sort_reversed: bool,
+    pub show_command_line: bool,
+}
+
+// Horizontal line, or band up to `upper`, drawn on the charts of a metric
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ChartThreshold {
+    pub label: String,
+    pub metric: AlertMetric,
+    pub value: f64,
+    #[serde(default)]
+    pub upper: Option<f64>,
+}
+
+// Settings applied over the config file, chosen with --preset
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Preset {
+    // headless fleet agent that only exports metrics and sends alerts
+    Agent,
+}
+
+impl Preset {
+    pub fn from_name(name: &str) -> Option<Preset> {
+        match name {
+            "agent" => Some(Preset::Agent),
+            _ => None,
+        }
+    }
+
+    // applied again after every config reload, so edits can't turn the preset's settings back
+    pub fn apply(&self, config: &mut Config) {
+        match self {
+            Preset::Agent => {
+                config.hidden_panels = Panel::ALL.to_vec();
+                config.save_to_file = false;
+                config.minimal_collection = true;
+                // idle time only matters to someone sitting at the machine
+                config.idle_after_secs = Some(0);
+                config
+                    .metrics_textfile
+                    .get_or_insert_with(|| String::from(AGENT_METRICS_TEXTFILE));
+            }
+        }
+    }
+}
+
+// Colour theme of the GUI
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ThemeChoice {
+    #[default]
+    Dark,
+    Light,
+}
+
+impl ThemeChoice {
+    pub const ALL: [ThemeChoice; 2] = [ThemeChoice::Dark, ThemeChoice::Light];
+
+    #[cfg(feature = "gui")]
+    pub fn theme(&self) -> iced::Theme {
+        match self {
+            ThemeChoice::Dark => iced::Theme::Dark,
+            ThemeChoice::Light => iced::Theme::Light,
+        }
+    }
+}
+
+impl std::fmt::Display for ThemeChoice {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ThemeChoice::Dark => write!(f, "Dark"),
+            ThemeChoice::Light => write!(f, "Light"),
+        }
+    }
+}
+
+// User settings persisted between runs
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    // seconds between log writes / headless samples, `--interval` takes precedence
+    pub interval_secs: Option<u64>,
+    pub theme: ThemeChoice,
+    // decimal places shown for percentages and sizes, 2 when not set
+    pub decimal_places: Option<usize>,
+    pub number_locale: NumberLocale,
+    pub hidden_panels: Vec<Panel>,
+    pub saved_filters: Vec<SavedFilter>,
+    pub saved_views: Vec<SavedView>,
+    // last sort chosen for the process list, the list opens with it
+    pub process_order: ProcessOrder,
+    // sort the process list always opens with instead, e.g. {"sort": "Cpu"}
+    pub default_process_order: Option<ProcessOrder>,
+    // tab and maximized dashboard panel the window was last left on, it opens on them
+    pub tab: Tab,
+    pub maximized_panel: Option<Panel>,
+    // what the window always opens on instead: a tab, e.g. "History", or a dashboard panel
+    // shown maximized, e.g. "Processes", which takes precedence
+    pub startup_tab: Option<Tab>,
+    pub startup_panel: Option<Panel>,
+    pub show_command_line: bool,
+    // highlight processes younger than this, 30 when not set, 0 turns it off
+    pub new_process_highlight_secs: Option<u64>,
+    // milliseconds between dashboard refreshes, each of which redraws every window, 1000 when
+    // not set
+    pub redraw_interval_ms: Option<u64>,
+    // milliseconds between the CPU and memory samples kept in the history and aggregated logs,
+    // the redraw interval when not set; a shorter one is sampled in the background
+    pub collect_interval_ms: Option<u64>,
+    // seconds between process list refreshes, 1 when not set; the charts still update every tick
+    pub process_refresh_secs: Option<u64>,
+    // the user counts as idle after this many seconds without keyboard or mouse input,
+    // 120 when not set, 0 turns idle detection off
+    pub idle_after_secs: Option<u64>,
+    // shade the history and CPU charts grey while the user was idle
+    pub shade_idle: bool,
+    pub core_heatmap: bool,
+    // weight of the newest reading in the moving average of the per-core usage shown on the
+    // dashboard, 0.3 when not set, 1 shows the raw readings; logs and the exporter stay raw
+    pub core_smoothing: Option<f32>,
+    // thin always-on-top window with per-core usage and network rates, reopened at startup
+    pub cpu_strip: bool,
+    // where the strip was left, in desktop coordinates spanning all monitors; the top left
+    // corner of the primary monitor when not set
+    pub cpu_strip_position: Option<(i32, i32)>,
+    // 900 when not set
+    pub cpu_strip_width: Option<u32>,
+    // brightness and volume sliders in mini mode (Linux)
+    pub quick_controls: bool,
+    // current CPU and RAM use in the title of the main window, so the taskbar entry shows them
+    // while the window is in the background
+    pub title_stats: bool,
+    // friendly names shown instead of network interface names and disk names or mount points,
+    // e.g. "enp3s0": "Home NAS link"
+    pub aliases: BTreeMap<String, String>,
+    // machine to wake through each network interface, by interface name, e.g.
+    // "eth0": "aa:bb:cc:dd:ee:ff"; saved when a wake-on-LAN packet is sent
+    pub wake_macs: BTreeMap<String, String>,
+    // endurance rating in terabytes written of each SSD by device name, 600 when not listed
+    pub ssd_rated_tbw: BTreeMap<String, f64>,
+    // keep the page of the process list on the selected process when the list re-sorts
+    pub follow_selection: bool,
+    pub alert_rules: Vec<AlertRule>,
+    // lines and bands drawn on the charts, besides the thresholds of the enabled alert rules
+    pub chart_thresholds: Vec<ChartThreshold>,
+    // text of alert messages with {rule}, {metric}, {value}, {host} and {time} placeholders
+    pub alert_template: Option<String>,
+    // alert when a process name restarts this often within a minute, 10 when not set, 0 turns it off
+    pub respawn_alert_per_minute: Option<usize>,
+    // a process using this much CPU for this many minutes is listed as runaway
+    // (90% and 10 minutes when not set, 0 minutes turns it off)
+    pub runaway_cpu_percent: Option<f64>,
+    pub runaway_minutes: Option<u64>,
+    // write a post-mortem snapshot when memory or swap use reaches this percentage,
+    // even with logging off (95% and 80% when not set, 0 turns it off)
+    pub emergency_memory_percent: Option<f64>,
+    pub emergency_swap_percent: Option<f64>,
+    // rewrite this file after every refresh, so a supervisor can tell the collector hangs
+    pub heartbeat_file: Option<String>,
+    // send WATCHDOG=1 to systemd after every refresh (needs WatchdogSec= in the unit)
+    pub systemd_watchdog: bool,
+    // rewrite this file with the current metrics in the Prometheus text format after every
+    // refresh, for node_exporter's textfile collector
+    pub metrics_textfile: Option<String>,
+    // POST every alert as JSON to this URL through curl, e.g. a Slack or Mattermost webhook
+    pub alert_webhook: Option<String>,
+    // leave out the readings only the dashboard panels show: sensors, GPU, Raspberry Pi,
+    // battery, interrupts, idle time and pending updates
+    pub minimal_collection: bool,
+    // start logging as soon as monitoring starts
+    pub save_to_file: bool,
+    pub log_format: LogFormat,
+    pub log_compression: LogCompression,
+    // log min/avg/max over each interval instead of the current values (JSON log only)
+    pub log_aggregate: bool,
+    // sync the log file to disk after every sample
+    pub log_fsync: bool,
+    // rotate the log file at this size, 0 keeps a single growing file
+    pub log_rotate_mb: u64,
+    // rotated logs kept next to the current one (system_log.json.1 is the newest), 5 when unset
+    pub log_rotate_keep: Option<u32>,
+    // where logging continues once the working directory can't be written or is nearly full,
+    // defaults to system_monitor in the temp directory
+    pub log_fallback_dir: Option<String>,
+    // set while the log is written to the fallback directory, so a restart keeps writing there;
+    // cleared once the working directory takes the log again
+    pub log_active_dir: Option<String>,
+}
+
+impl Config {
+    // missing or unreadable config falls back to the defaults
+    pub fn load() -> Config {
+        match fs::read_to_string(CONFIG_FILE) {
+            Ok(contents) => serde_json::from_str(&contents).unwrap_or_else(|e| {
+                eprintln!(
+                    "Invalid config file {}: {}\nUsing default settings.",
+                    CONFIG_FILE, e
+                );
+                Config::default()
+            }),
+            Err(_) => Config::default(),
+        }
+    }
+
+    // no config file yet means this is the first launch
+    pub fn exists() -> bool {
+        Path::new(CONFIG_FILE).exists()
+    }
+
+    pub fn panel_visible(&self, panel: Panel) -> bool {
+        !self.hidden_panels.contains(&panel)
+    }
+
+    // like `load`, but reports errors so a bad edit doesn't reset the running settings
+    pub fn read() -> Result<Config, String> {
+        let contents = fs::read_to_string(CONFIG_FILE).map_err(|e| e.to_string())?;
+        serde_json::from_str(&contents).map_err(|e| e.to_string())
+    }
+
+    // last modification time of the config file, used to notice edits
+    pub fn modified() -> Option<SystemTime> {
+        fs::metadata(CONFIG_FILE)
+            .and_then(|metadata| metadata.modified())
+            .ok()
+    }
+
+    pub fn interval_secs_or(&self, cli_interval_secs: Option<u64>) -> u64 {
+        cli_interval_secs
+            .or(self.interval_secs)
+            .filter(|secs| *secs > 0)
+            .unwrap_or(1)
+    }
+
+    pub fn new_process_highlight_secs(&self) -> u64 {
+        self.new_process_highlight_secs.unwrap_or(30)
+    }
+
+    pub fn startup_process_order(&self) -> ProcessOrder {
+        self.default_process_order.unwrap_or(self.process_order)
+    }
+
+    pub fn startup_view(&self) -> (Tab, Option<Panel>) {
+        match (self.startup_tab, self.startup_panel) {
+            (_, Some(panel)) => (Tab::Dashboard, Some(panel)),
+            (Some(tab), None) => (tab, None),
+            (None, None) => (self.tab, self.maximized_panel),
+        }
+    }
+
+    pub fn cpu_strip_width(&self) -> u32 {
+        self.cpu_strip_width.unwrap_or(900)
+    }
+
+    // friendly name of an interface or disk, the name itself when none is set
+    pub fn display_name<'a>(&'a self, name: &'a str) -> &'a str {
+        self.aliases.get(name).map_or(name, String::as_str)
+    }
+
+    pub fn ssd_rated_tbw(&self, device: &str) -> f64 {
+        self.ssd_rated_tbw
+            .get(device)
+            .copied()
+            .filter(|tbw| *tbw > 0.)
+            .unwrap_or(600.)
+    }
+
+    pub fn redraw_interval(&self) -> Duration {
+        self.redraw_interval_ms
+            .filter(|ms| *ms > 0)
+            .map_or(TICK_INTERVAL, Duration::from_millis)
+            .max(MINIMUM_CPU_UPDATE_INTERVAL)
+    }
+
+    // cpu usage needs some time between two readings, shorter intervals are raised to that
+    pub fn collect_interval(&self) -> Duration {
+        self.collect_interval_ms
+            .filter(|ms| *ms > 0)
+            .map_or(self.redraw_interval(), Duration::from_millis)
+            .max(MINIMUM_CPU_UPDATE_INTERVAL)
+    }
+
+    pub fn process_refresh_secs(&self) -> u64 {
+        self.process_refresh_secs
+            .filter(|secs| *secs > 0)
+            .unwrap_or(1)
+    }
+
+    pub fn alert_template(&self) -> &str {
+        self.alert_template
+            .as_deref()
+            .unwrap_or(DEFAULT_ALERT_TEMPLATE)
+    }
+
+    pub fn respawn_alert_per_minute(&self) -> usize {
+        self.respawn_alert_per_minute.unwrap_or(10)
+    }
+
+    pub fn idle_after(&self) -> Duration {
+        Duration::from_secs(self.idle_after_secs.unwrap_or(120))
+    }
+
+    pub fn core_smoothing(&self) -> f32 {
+        self.core_smoothing
+            .filter(|factor| *factor > 0.)
+            .unwrap_or(0.3)
+            .min(1.)
+    }
+
+    pub fn runaway_cpu_percent(&self) -> f64 {
+        self.runaway_cpu_percent.unwrap_or(90.)
+    }
+
+    pub fn runaway_duration(&self) -> Duration {
+        Duration::from_secs(self.runaway_minutes.unwrap_or(10) * 60)
+    }
+
+    pub fn emergency_memory_percent(&self) -> f64 {
+        self.emergency_memory_percent.unwrap_or(95.)
+    }
+
+    pub fn emergency_swap_percent(&self) -> f64 {
+        self.emergency_swap_percent.unwrap_or(80.)
+    }
+
+    pub fn log_rotate_keep(&self) -> u32 {
+        self.log_rotate_keep.unwrap_or(5).max(1)
+    }
+
+    pub fn log_fallback_dir(&self) -> PathBuf {
+        self.log_fallback_dir
+            .as_ref()
+            .map_or_else(|| env::temp_dir().join("system_monitor"), PathBuf::from)
+    }
+
+    // where the log is written now, empty for the working directory
+    pub fn log_directory(&self) -> PathBuf {
+        self.log_active_dir
+            .as_ref()
+            .map_or_else(PathBuf::new, PathBuf::from)
+    }
+
+    pub fn number_format(&self) -> NumberFormat {
+        NumberFormat::new(self.decimal_places.unwrap_or(2), self.number_locale)
+    }
+
+    pub fn save(&self) -> io::Result<()> {
+        let serialized = serde_json::to_string_pretty(self)?;
+        fs::write(CONFIG_FILE, serialized)
+    }
+}
@@ -0,0 +1,118 @@
+// What changed between two refreshes, handed to the panels so they only rebuild the rows of the
+// processes and disks that changed
+use crate::models::{DisksInfo, Process};
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::sync::Arc;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Changes<K> {
+    pub added: Vec<K>,
+    pub removed: Vec<K>,
+    // present both times but with different values
+    pub changed: Vec<K>,
+}
+
+impl<K> Default for Changes<K> {
+    fn default() -> Self {
+        Changes {
+            added: Vec::new(),
+            removed: Vec::new(),
+            changed: Vec::new(),
+        }
+    }
+}
+
+impl<K: Clone + Eq + Hash + Ord> Changes<K> {
+    pub fn between<V: PartialEq>(previous: &HashMap<K, V>, current: &HashMap<K, V>) -> Changes<K> {
+        let mut changes = Changes::default();
+        for (key, value) in current {
+            match previous.get(key) {
+                None => changes.added.push(key.clone()),
+                Some(old) if old != value => changes.changed.push(key.clone()),
+                Some(_) => {}
+            }
+        }
+        changes.removed = previous
+            .keys()
+            .filter(|key| !current.contains_key(key))
+            .cloned()
+            .collect();
+
+        changes.added.sort();
+        changes.removed.sort();
+        changes.changed.sort();
+        changes
+    }
+}
+
+impl<K> Changes<K> {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+}
+
+// Changes of the last refresh; processes are keyed by (pid, start time) so a reused pid counts
+// as one removed and one added process
+#[derive(Debug, Clone, Default)]
+pub struct SnapshotDelta {
+    pub processes: Changes<(u32, u64)>,
+    // by mount point
+    pub disks: Changes<String>,
+}
+
+// what a process row shows: cpu, cpu time, memory, name and command line
+type ProcessValues = (f64, f64, f64, Arc<str>, Arc<str>);
+// what a disk row shows: total, free, used percent, name, label and type
+type DiskValues = (u64, u64, f64, String, String, String);
+
+// Values of the previous refresh to diff the next one against
+#[derive(Debug, Default)]
+pub struct DeltaTracker {
+    processes: HashMap<(u32, u64), ProcessValues>,
+    disks: HashMap<String, DiskValues>,
+}
+
+impl DeltaTracker {
+    pub fn update(&mut self, processes: &[Process], disks: &[DisksInfo]) -> SnapshotDelta {
+        let processes: HashMap<_, _> = processes
+            .iter()
+            .map(|p| {
+                (
+                    (p.id, p.start_time),
+                    (
+                        p.cpu_usage_percent,
+                        p.cpu_time_secs,
+                        p.memory_usage_percent,
+                        p.name.clone(),
+                        p.command.clone(),
+                    ),
+                )
+            })
+            .collect();
+        let disks: HashMap<_, _> = disks
+            .iter()
+            .map(|d| {
+                (
+                    d.mount.clone(),
+                    (
+                        d.total_disk,
+                        d.free_disk,
+                        d.used_disk_percent,
+                        d.name.clone(),
+                        d.label.clone(),
+                        d.kind.clone(),
+                    ),
+                )
+            })
+            .collect();
+
+        let delta = SnapshotDelta {
+            processes: Changes::between(&self.processes, &processes),
+            disks: Changes::between(&self.disks, &disks),
+        };
+        self.processes = processes;
+        self.disks = disks;
+        delta
+    }
+}
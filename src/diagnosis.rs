@@ -0,0 +1,261 @@
+// Guided "find what is slow": watches the machine for a short capture, then names the likely
+// bottleneck (CPU, memory, disk or network) with the processes behind it
+use crate::format::{usage_percent, NumberFormat};
+use crate::models::{SystemData, SystemMonitor};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+// refreshes in a capture, half a minute at the default refresh interval: long enough to see past
+// a single spike, short enough to wait for
+pub const CAPTURE_TICKS: u32 = 30;
+
+// processes (or ports) listed as contributing to the bottleneck
+const CONTRIBUTORS: usize = 5;
+
+// the level at which each resource counts as the bottleneck
+const CPU_BUSY_PERCENT: f64 = 85.;
+const MEMORY_FULL_PERCENT: f64 = 90.;
+// swap growing by this much during a capture means memory is being pushed out
+const SWAP_GROWTH_BYTES: f64 = 64. * 1024. * 1024.;
+// a disk with requests queued up behind each other or slow to answer them
+const DISK_QUEUE_DEPTH: f64 = 2.;
+const DISK_LATENCY_MS: f64 = 20.;
+// about what a 100 Mbit/s link carries, the slowest one still common; the link speed itself
+// isn't known
+const NETWORK_BUSY_RATE: f64 = 11. * 1024. * 1024.;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Bottleneck {
+    Cpu,
+    Memory,
+    Disk,
+    Network,
+}
+
+impl std::fmt::Display for Bottleneck {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Bottleneck::Cpu => write!(f, "CPU-bound"),
+            Bottleneck::Memory => write!(f, "Memory pressure"),
+            Bottleneck::Disk => write!(f, "Disk-bound"),
+            Bottleneck::Network => write!(f, "Network-bound"),
+        }
+    }
+}
+
+// What a process did during the capture, summed over the refreshes it was seen in
+#[derive(Debug, Clone)]
+struct ProcessLoad {
+    name: Arc<str>,
+    pid: u32,
+    cpu_percent: f64,
+    memory_percent: f64,
+    disk_io_rate: f64,
+}
+
+// Readings taken on each refresh of a running capture; CPU, memory and swap come from the history
+#[derive(Debug)]
+pub struct DiagnosisCapture {
+    pushed: u64,
+    ticks: u32,
+    // keyed by pid and start time, like the process selection
+    processes: HashMap<(u32, u64), ProcessLoad>,
+    // the busiest disk's queue depth and latency, the history only keeps IOPS
+    disk_queue_depth: Vec<f64>,
+    disk_latency_ms: Vec<f64>,
+    // the busier direction of the network traffic, bytes per second
+    network_rate: Vec<f64>,
+}
+
+impl DiagnosisCapture {
+    pub fn new(system_monitor: &SystemMonitor) -> DiagnosisCapture {
+        DiagnosisCapture {
+            pushed: system_monitor.history.pushed(),
+            ticks: 0,
+            processes: HashMap::new(),
+            disk_queue_depth: Vec::new(),
+            disk_latency_ms: Vec::new(),
+            network_rate: Vec::new(),
+        }
+    }
+
+    pub fn record(&mut self, system_monitor: &SystemMonitor) {
+        self.ticks += 1;
+        for process in &system_monitor.processes {
+            let load = self
+                .processes
+                .entry((process.id, process.start_time))
+                .or_insert_with(|| ProcessLoad {
+                    name: process.name.clone(),
+                    pid: process.id,
+                    cpu_percent: 0.,
+                    memory_percent: 0.,
+                    disk_io_rate: 0.,
+                });
+            load.cpu_percent += process.cpu_usage_percent;
+            load.memory_percent = load.memory_percent.max(process.memory_usage_percent);
+            load.disk_io_rate += process.disk_io_rate;
+        }
+
+        let disks = &system_monitor.disk_io;
+        self.disk_queue_depth
+            .push(disks.iter().map(|disk| disk.queue_depth).fold(0., f64::max));
+        self.disk_latency_ms.push(
+            disks
+                .iter()
+                .filter_map(|disk| disk.latency_ms)
+                .fold(0., f64::max),
+        );
+        self.network_rate.push(
+            system_monitor
+                .network_sent_rate
+                .max(system_monitor.network_received_rate),
+        );
+    }
+
+    pub fn ticks(&self) -> u32 {
+        self.ticks
+    }
+
+    pub fn finished(&self) -> bool {
+        self.ticks >= CAPTURE_TICKS
+    }
+
+    // how close each resource came to its limit, the likely bottleneck with what caused it
+    pub fn diagnose(&self, system_monitor: &SystemMonitor, numbers: NumberFormat) -> Diagnosis {
+        let samples: Vec<&SystemData> = system_monitor.history.since(self.pushed).collect();
+        let cpu = average(samples.iter().map(|s| s.cpu_usage_percent as f64));
+        let cpu_peak = samples
+            .iter()
+            .map(|s| s.cpu_usage_percent as f64)
+            .fold(0., f64::max);
+        let memory = average(samples.iter().map(|s| usage_percent(s.memory_usage_byte)));
+        let swap_growth = match (samples.first(), samples.last()) {
+            (Some(first), Some(last)) => {
+                last.swap_memory_usage_byte.0 as f64 - first.swap_memory_usage_byte.0 as f64
+            }
+            _ => 0.,
+        };
+        let queue_depth = average(self.disk_queue_depth.iter().copied());
+        let latency = average(self.disk_latency_ms.iter().copied());
+        let network = average(self.network_rate.iter().copied());
+
+        // 1.0 is the level at which a resource counts as the bottleneck
+        let mut findings = vec![
+            (
+                Bottleneck::Cpu,
+                cpu / CPU_BUSY_PERCENT,
+                format!(
+                    "CPU averaged {} (peak {})",
+                    numbers.percent(cpu),
+                    numbers.percent(cpu_peak)
+                ),
+            ),
+            (
+                Bottleneck::Memory,
+                (memory / MEMORY_FULL_PERCENT).max(swap_growth / SWAP_GROWTH_BYTES),
+                if swap_growth > 0. {
+                    format!(
+                        "Memory averaged {}, swap grew by {}",
+                        numbers.percent(memory),
+                        numbers.bytes(swap_growth)
+                    )
+                } else {
+                    format!("Memory averaged {}", numbers.percent(memory))
+                },
+            ),
+            (
+                Bottleneck::Disk,
+                (queue_depth / DISK_QUEUE_DEPTH).max(latency / DISK_LATENCY_MS),
+                format!(
+                    "The busiest disk averaged {} ms per request with {} requests queued",
+                    numbers.number(latency),
+                    numbers.number(queue_depth)
+                ),
+            ),
+            (
+                Bottleneck::Network,
+                network / NETWORK_BUSY_RATE,
+                format!("Network traffic averaged {}", numbers.rate(network)),
+            ),
+        ];
+        findings.sort_by(|a, b| b.1.total_cmp(&a.1));
+
+        let bottleneck = findings
+            .first()
+            .filter(|(_, score, _)| *score >= 1.)
+            .map(|(bottleneck, _, _)| *bottleneck);
+        let contributors = match bottleneck {
+            Some(Bottleneck::Network) => system_monitor
+                .top_ports
+                .iter()
+                .take(CONTRIBUTORS)
+                .map(|port| {
+                    format!(
+                        "Port {}/{}: {} sockets",
+                        port.port, port.protocol, port.sockets
+                    )
+                })
+                .collect(),
+            Some(bottleneck) => self.contributors(bottleneck, numbers),
+            None => Vec::new(),
+        };
+
+        Diagnosis {
+            bottleneck,
+            findings: findings
+                .into_iter()
+                .map(|(_, _, finding)| finding)
+                .collect(),
+            contributors,
+        }
+    }
+
+    // the processes that loaded the bottleneck most over the capture
+    fn contributors(&self, bottleneck: Bottleneck, numbers: NumberFormat) -> Vec<String> {
+        let ticks = self.ticks.max(1) as f64;
+        let load = |process: &ProcessLoad| match bottleneck {
+            Bottleneck::Memory => process.memory_percent,
+            Bottleneck::Disk => process.disk_io_rate / ticks,
+            _ => process.cpu_percent / ticks,
+        };
+        let mut processes: Vec<&ProcessLoad> = self
+            .processes
+            .values()
+            .filter(|process| load(process) > 0.)
+            .collect();
+        processes.sort_by(|a, b| load(b).total_cmp(&load(a)));
+
+        processes
+            .into_iter()
+            .take(CONTRIBUTORS)
+            .map(|process| {
+                let value = match bottleneck {
+                    Bottleneck::Disk => numbers.rate(load(process)),
+                    _ => numbers.percent(load(process)),
+                };
+                format!("{} ({}): {}", process.name, process.pid, value)
+            })
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Diagnosis {
+    // None when nothing came close to its limit
+    pub bottleneck: Option<Bottleneck>,
+    // how each resource did, the likely bottleneck first
+    pub findings: Vec<String>,
+    // processes behind the bottleneck, or the busiest ports for the network since traffic isn't
+    // measured per process
+    pub contributors: Vec<String>,
+}
+
+fn average(values: impl Iterator<Item = f64>) -> f64 {
+    let (sum, count) = values.fold((0., 0), |(sum, count), value| (sum + value, count + 1));
+    if count == 0 {
+        0.
+    } else {
+        sum / count as f64
+    }
+}
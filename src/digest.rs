@@ -0,0 +1,143 @@
+// Digest of what happened while the window was in the background, built from the history
+use crate::format::usage_percent;
+use crate::models::{SystemData, SystemMonitor};
+use crate::utils::current_timestamp;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+// shorter absences are a glance at another window, not worth a digest
+pub const DIGEST_AFTER: Duration = Duration::from_secs(5 * 60);
+
+// state of the monitor when the window lost focus
+#[derive(Debug, Clone)]
+pub struct AwayMark {
+    since: Instant,
+    timestamp: String,
+    pushed: u64,
+    watched: HashMap<(u32, u64), Arc<str>>,
+}
+
+impl AwayMark {
+    pub fn new(system_monitor: &SystemMonitor) -> AwayMark {
+        AwayMark {
+            since: Instant::now(),
+            timestamp: current_timestamp(),
+            pushed: system_monitor.history.pushed(),
+            watched: watched_processes(system_monitor),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Digest {
+    pub away: Duration,
+    // highest CPU, memory and swap use, with when it happened
+    pub peaks: Vec<String>,
+    // alerts and chart annotations, oldest first
+    pub events: Vec<String>,
+    pub started: Vec<String>,
+    pub exited: Vec<String>,
+}
+
+impl Digest {
+    pub fn is_empty(&self) -> bool {
+        self.peaks.is_empty()
+            && self.events.is_empty()
+            && self.started.is_empty()
+            && self.exited.is_empty()
+    }
+}
+
+// what changed since `mark`, None when the window was away too briefly or nothing was recorded
+pub fn build_digest(mark: &AwayMark, system_monitor: &SystemMonitor) -> Option<Digest> {
+    let away = mark.since.elapsed();
+    if away < DIGEST_AFTER {
+        return None;
+    }
+
+    let samples: Vec<&SystemData> = system_monitor.history.since(mark.pushed).collect();
+    let peaks = [
+        peak(&samples, "CPU", |s| s.cpu_usage_percent as f64),
+        peak(&samples, "Memory", |s| usage_percent(s.memory_usage_byte)),
+        peak(&samples, "Swap", |s| {
+            usage_percent(s.swap_memory_usage_byte)
+        }),
+    ]
+    .into_iter()
+    .flatten()
+    .collect();
+
+    // timestamps sort in time order, see TIMESTAMP_FORMAT
+    let alerts = system_monitor
+        .alert_history
+        .iter()
+        .filter(|alert| alert.timestamp >= mark.timestamp)
+        .map(|alert| {
+            (
+                alert.timestamp.as_str(),
+                format!("Alert: {}", alert.rule_name),
+            )
+        });
+    let annotations = system_monitor
+        .history
+        .annotations()
+        .iter()
+        .filter(|annotation| annotation.timestamp >= mark.timestamp)
+        .map(|annotation| (annotation.timestamp.as_str(), annotation.label.clone()));
+    let mut events: Vec<(&str, String)> = alerts.chain(annotations).collect();
+    events.sort_by(|a, b| a.0.cmp(b.0));
+    let events = events
+        .into_iter()
+        .map(|(timestamp, event)| format!("{} {}", timestamp, event))
+        .collect();
+
+    let watched = watched_processes(system_monitor);
+    let started = watched
+        .iter()
+        .filter(|(key, _)| !mark.watched.contains_key(key))
+        .map(|((pid, _), name)| format!("{} ({})", name, pid))
+        .collect();
+    let exited = mark
+        .watched
+        .iter()
+        .filter(|(key, _)| !watched.contains_key(key))
+        .map(|((pid, _), name)| format!("{} ({})", name, pid))
+        .collect();
+
+    let digest = Digest {
+        away,
+        peaks,
+        events,
+        started,
+        exited,
+    };
+    (!digest.is_empty()).then_some(digest)
+}
+
+// highest value of a percentage among the samples, with when it happened
+fn peak(samples: &[&SystemData], name: &str, value: fn(&SystemData) -> f64) -> Option<String> {
+    let peak = samples
+        .iter()
+        .max_by(|a, b| value(a).total_cmp(&value(b)))?;
+    Some(format!(
+        "{} peaked at {:.1}% ({})",
+        name,
+        value(peak),
+        peak.timestamp
+    ))
+}
+
+// the selected processes, and those matching the process filter while one is set
+fn watched_processes(system_monitor: &SystemMonitor) -> HashMap<(u32, u64), Arc<str>> {
+    let filtered = !system_monitor.process_filter.pattern.is_empty();
+    system_monitor
+        .processes
+        .iter()
+        .filter(|process| {
+            system_monitor.selected_processes.get(&process.id) == Some(&process.start_time)
+                || (filtered && system_monitor.process_filter.matches(&process.name))
+        })
+        .map(|process| ((process.id, process.start_time), process.name.clone()))
+        .collect()
+}
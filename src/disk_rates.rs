@@ -0,0 +1,47 @@
+// Recent read and write throughput of each disk, kept in memory for the disk charts; the logs
+// only carry the IOPS summed over all disks
+use crate::models::DiskIo;
+use std::collections::{BTreeMap, VecDeque};
+
+// two minutes at the default refresh interval, like the TCP socket chart
+pub const DISK_RATE_SAMPLES: usize = 120;
+
+// Read and write bytes per second of each refresh, oldest first
+#[derive(Debug, Default)]
+pub struct DiskRateHistory {
+    devices: BTreeMap<String, VecDeque<(f64, f64)>>,
+}
+
+impl DiskRateHistory {
+    // a disk that went away is dropped with its history, one that appeared starts empty
+    pub fn push(&mut self, disk_io: &[DiskIo]) {
+        self.devices
+            .retain(|name, _| disk_io.iter().any(|device| &device.name == name));
+        for device in disk_io {
+            let rates = self.devices.entry(device.name.clone()).or_default();
+            rates.push_back((device.read_rate, device.write_rate));
+            if rates.len() > DISK_RATE_SAMPLES {
+                rates.pop_front();
+            }
+        }
+    }
+
+    pub fn device(&self, name: &str) -> Option<&VecDeque<(f64, f64)>> {
+        self.devices.get(name)
+    }
+
+    // read and write rates summed over all disks, aligned on the latest refresh since a disk
+    // that appeared later has a shorter history
+    pub fn total(&self) -> Vec<(f64, f64)> {
+        let len = self.devices.values().map(VecDeque::len).max().unwrap_or(0);
+        let mut total = vec![(0., 0.); len];
+        for rates in self.devices.values() {
+            let offset = len - rates.len();
+            for (sum, (read, write)) in total[offset..].iter_mut().zip(rates) {
+                sum.0 += read;
+                sum.1 += write;
+            }
+        }
+        total
+    }
+}
@@ -0,0 +1,145 @@
+// Physical disks behind the mounted partitions, and readable names for Windows volumes
+use crate::procfs::SECTOR_SIZE;
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+
+// Model, serial number and size of a physical disk, empty where /sys doesn't tell
+#[derive(Debug, Clone, Default)]
+pub struct DeviceDetails {
+    pub model: String,
+    pub serial: String,
+    pub size_bytes: u64,
+}
+
+// Which physical disk each partition lives on and what the partition is called, looked up
+// once per partition
+#[derive(Debug, Default)]
+pub struct DiskTopology {
+    parents: HashMap<String, String>,
+    details: HashMap<String, DeviceDetails>,
+    // by mount point
+    labels: HashMap<String, String>,
+}
+
+impl DiskTopology {
+    // physical disk of a partition as named by sysinfo, e.g. /dev/nvme0n1p2 -> nvme0n1
+    pub fn device_of(&mut self, partition: &str) -> String {
+        if let Some(device) = self.parents.get(partition) {
+            return device.clone();
+        }
+        let device = read_parent_device(partition).unwrap_or_else(|| device_from_name(partition));
+        self.details
+            .entry(device.clone())
+            .or_insert_with(|| read_device_details(&device));
+        self.parents.insert(partition.to_string(), device.clone());
+        device
+    }
+
+    // name shown for a partition: on Windows "Label (C:)" as in Explorer, since sysinfo names
+    // volumes by their label, which may be empty, or by a \\?\Volume{GUID}\ path
+    pub fn label_of(&mut self, name: &str, mount: &str) -> String {
+        if !cfg!(windows) {
+            return name.to_string();
+        }
+        self.labels
+            .entry(mount.to_string())
+            .or_insert_with(|| windows_volume_name(name, mount))
+            .clone()
+    }
+
+    pub fn details(&self, device: &str) -> Option<&DeviceDetails> {
+        self.details.get(device)
+    }
+}
+
+// the disk under a partition or under a device-mapper device (LVM, LUKS), from /sys/class/block
+fn read_parent_device(partition: &str) -> Option<String> {
+    if !cfg!(target_os = "linux") {
+        return None;
+    }
+    // /dev/mapper/root links to /dev/dm-0
+    let path = fs::canonicalize(partition).ok()?;
+    let name = path.file_name()?.to_str()?;
+    let block = fs::canonicalize(Path::new("/sys/class/block").join(name)).ok()?;
+
+    // a mapped device sits on the partitions listed as its slaves, follow the first
+    if let Some(slave) = fs::read_dir(block.join("slaves"))
+        .ok()
+        .and_then(|mut slaves| slaves.next())
+        .and_then(Result::ok)
+    {
+        return read_parent_device(&format!("/dev/{}", slave.file_name().to_string_lossy()));
+    }
+
+    if block.join("partition").exists() {
+        Some(block.parent()?.file_name()?.to_str()?.to_string())
+    } else {
+        Some(name.to_string())
+    }
+}
+
+// without /sys, take the partition suffix off the name: disk3s1s2 -> disk3 on macOS;
+// names of other platforms (Windows volume labels) stand for themselves
+fn device_from_name(partition: &str) -> String {
+    let name = partition.trim_start_matches("/dev/");
+    match name.strip_prefix("disk") {
+        Some(rest) => {
+            let number: String = rest.chars().take_while(char::is_ascii_digit).collect();
+            format!("disk{}", number)
+        }
+        None => name.to_string(),
+    }
+}
+
+fn read_device_details(device: &str) -> DeviceDetails {
+    let block = Path::new("/sys/block").join(device);
+    let read = |file: &str| {
+        fs::read_to_string(block.join(file))
+            .map(|value| value.trim().to_string())
+            .unwrap_or_default()
+    };
+
+    DeviceDetails {
+        model: read("device/model"),
+        serial: read("device/serial"),
+        // counted in 512 byte sectors like diskstats
+        size_bytes: read("size").parse::<u64>().unwrap_or_default() * SECTOR_SIZE,
+    }
+}
+
+fn windows_volume_name(name: &str, mount: &str) -> String {
+    let drive = mount.trim_end_matches('\\');
+    let has_letter = drive.len() == 2 && drive.ends_with(':');
+    let label = if name.is_empty() || name.starts_with(r"\\?\") {
+        has_letter
+            .then(|| read_volume_label(drive))
+            .flatten()
+            .unwrap_or_default()
+    } else {
+        name.to_string()
+    };
+
+    match (has_letter, label.is_empty()) {
+        (true, false) => format!("{} ({})", label, drive),
+        (true, true) => format!("Local Disk ({})", drive),
+        (false, false) => label,
+        // a volume mounted into a folder is known by that folder
+        (false, true) if !mount.starts_with(r"\\?\") => mount.to_string(),
+        (false, true) => String::from("Volume without a drive letter"),
+    }
+}
+
+// `vol C:` prints "Volume in drive C is <label>", or "has no label." for none
+fn read_volume_label(drive: &str) -> Option<String> {
+    let output = Command::new("cmd")
+        .args(["/C", "vol", drive])
+        .output()
+        .ok()?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let first_line = stdout.lines().find(|line| !line.trim().is_empty())?;
+    first_line
+        .split_once(" is ")
+        .map(|(_, label)| label.trim().to_string())
+}
@@ -0,0 +1,86 @@
+// Post-mortem snapshots written when memory runs out, whether or not logging is on
+use crate::format::usage_percent;
+use crate::models::SystemMonitor;
+use crate::utils::{collect_system_data, current_timestamp};
+use chrono::Local;
+use serde_json::json;
+use std::fs::File;
+use std::io::{self, Write};
+use sysinfo::Pid;
+
+#[derive(Debug, Default)]
+pub struct PressureWatch {
+    // set while memory or swap is above its level, so each episode is captured once
+    triggered: bool,
+}
+
+impl PressureWatch {
+    // returns why memory or swap has just crossed its critical level, if it has
+    // (a level of 0 turns that check off)
+    pub fn update(
+        &mut self,
+        memory: (u64, u64),
+        swap: (u64, u64),
+        memory_limit: f64,
+        swap_limit: f64,
+    ) -> Option<String> {
+        let memory_percent = usage_percent(memory);
+        let swap_percent = usage_percent(swap);
+
+        let reason = if memory_limit > 0. && memory_percent >= memory_limit {
+            Some(format!("memory at {:.1}%", memory_percent))
+        } else if swap_limit > 0. && swap_percent >= swap_limit {
+            Some(format!("swap at {:.1}%", swap_percent))
+        } else {
+            None
+        };
+
+        let crossed = reason.is_some() && !self.triggered;
+        self.triggered = reason.is_some();
+        reason.filter(|_| crossed)
+    }
+}
+
+// write the current sample and every process with its memory to post_mortem_<local time>.json,
+// synced to disk straight away since the machine may not last much longer; returns the file name
+pub fn write_post_mortem(system_monitor: &SystemMonitor, reason: &str) -> io::Result<String> {
+    let path = format!("post_mortem_{}.json", Local::now().format("%Y%m%d-%H%M%S"));
+
+    let mut processes: Vec<_> = system_monitor
+        .processes
+        .iter()
+        .map(|process| {
+            let memory_byte = system_monitor
+                .system
+                .process(Pid::from_u32(process.id))
+                .map(|p| p.memory())
+                .unwrap_or_default();
+            (memory_byte, process)
+        })
+        .collect();
+    processes.sort_by_key(|(memory_byte, _)| std::cmp::Reverse(*memory_byte));
+
+    let snapshot = json!({
+        "timestamp": current_timestamp(),
+        "reason": reason,
+        "sample": collect_system_data(system_monitor),
+        "processes": processes
+            .iter()
+            .map(|(memory_byte, process)| json!({
+                "pid": process.id,
+                "name": &*process.name,
+                "user": &*process.user,
+                "command": &*process.command,
+                "memory_byte": memory_byte,
+                "memory_usage_percent": process.memory_usage_percent,
+                "cpu_usage_percent": process.cpu_usage_percent,
+                "cpu_time_secs": process.cpu_time_secs,
+            }))
+            .collect::<Vec<_>>(),
+    });
+
+    let mut file = File::create(&path)?;
+    file.write_all(serde_json::to_string_pretty(&snapshot)?.as_bytes())?;
+    file.sync_all()?;
+    Ok(path)
+}
@@ -0,0 +1,40 @@
+use regex::Regex;
+
+// Filter for the process list, either a plain substring or a regex
+#[derive(Debug, Default)]
+pub struct ProcessFilter {
+    pub pattern: String,
+    pub is_regex: bool,
+    regex: Option<Regex>,
+    pub error: Option<String>,
+}
+
+impl ProcessFilter {
+    pub fn set(&mut self, pattern: String, is_regex: bool) {
+        self.regex = None;
+        self.error = None;
+
+        if is_regex && !pattern.is_empty() {
+            match Regex::new(&pattern) {
+                Ok(regex) => self.regex = Some(regex),
+                Err(e) => self.error = Some(format!("Invalid regex: {}", e)),
+            }
+        }
+
+        self.pattern = pattern;
+        self.is_regex = is_regex;
+    }
+
+    // an empty or invalid filter lets every process through
+    pub fn matches(&self, name: &str) -> bool {
+        if self.pattern.is_empty() {
+            return true;
+        }
+
+        if self.is_regex {
+            self.regex.as_ref().is_none_or(|regex| regex.is_match(name))
+        } else {
+            name.to_lowercase().contains(&self.pattern.to_lowercase())
+        }
+    }
+}
@@ -0,0 +1,240 @@
+// Number and date formatting for the views and exports, following the precision and locale settings
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+use std::env;
+
+const BYTE_UNITS: [&str; 5] = ["B", "KB", "MB", "GB", "TB"];
+
+// timestamps of samples, logs and annotations (UTC)
+pub const TIMESTAMP_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+
+// the same moments in local time, with the UTC offset so a daylight saving change is visible
+pub const LOCAL_TIMESTAMP_FORMAT: &str = "%Y-%m-%d %H:%M:%S%:z";
+
+// Thousands separator and decimal mark used for numbers
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum NumberLocale {
+    // picked from LC_ALL / LC_NUMERIC / LANG
+    #[default]
+    Auto,
+    // 1234567.89
+    Plain,
+    // 1,234,567.89
+    English,
+    // 1.234.567,89
+    European,
+    // 1 234 567,89
+    Spaced,
+}
+
+impl NumberLocale {
+    pub const ALL: [NumberLocale; 5] = [
+        NumberLocale::Auto,
+        NumberLocale::Plain,
+        NumberLocale::English,
+        NumberLocale::European,
+        NumberLocale::Spaced,
+    ];
+
+    // locale of the environment, by its language code
+    fn from_env() -> NumberLocale {
+        let locale = ["LC_ALL", "LC_NUMERIC", "LANG"]
+            .iter()
+            .filter_map(|name| env::var(name).ok())
+            .find(|value| !value.is_empty())
+            .unwrap_or_default();
+        let language = locale.split(['_', '-', '.', '@']).next().unwrap_or("");
+
+        match language {
+            "C" | "POSIX" | "" => NumberLocale::Plain,
+            "de" | "es" | "it" | "nl" | "pt" | "id" | "tr" | "da" | "el" | "ro" => {
+                NumberLocale::European
+            }
+            "fr" | "ru" | "pl" | "cs" | "sk" | "sv" | "fi" | "nb" | "no" | "uk" | "hu" => {
+                NumberLocale::Spaced
+            }
+            _ => NumberLocale::English,
+        }
+    }
+
+    // thousands separator and decimal mark
+    fn separators(&self) -> (Option<char>, char) {
+        match self {
+            NumberLocale::Auto => NumberLocale::from_env().separators(),
+            NumberLocale::Plain => (None, '.'),
+            NumberLocale::English => (Some(','), '.'),
+            NumberLocale::European => (Some('.'), ','),
+            NumberLocale::Spaced => (Some(' '), ','),
+        }
+    }
+
+    // how dates are written where this number style is used
+    fn date_pattern(&self) -> &'static str {
+        match self {
+            NumberLocale::Auto => NumberLocale::from_env().date_pattern(),
+            NumberLocale::Plain | NumberLocale::English => TIMESTAMP_FORMAT,
+            NumberLocale::European => "%d.%m.%Y %H:%M:%S",
+            NumberLocale::Spaced => "%d/%m/%Y %H:%M:%S",
+        }
+    }
+}
+
+impl std::fmt::Display for NumberLocale {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            NumberLocale::Auto => write!(f, "System Locale"),
+            NumberLocale::Plain => write!(f, "1234.5"),
+            NumberLocale::English => write!(f, "1,234.5"),
+            NumberLocale::European => write!(f, "1.234,5"),
+            NumberLocale::Spaced => write!(f, "1 234,5"),
+        }
+    }
+}
+
+// quote fields holding the separator, a quote or a line break (RFC 4180)
+pub(crate) fn csv_field(value: &str, separator: char) -> String {
+    if value.contains([separator, '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+// shorten text to at most `max_chars` characters, marking the cut with "..."
+pub fn truncate(value: &str, max_chars: usize) -> String {
+    if value.chars().count() <= max_chars {
+        return value.to_string();
+    }
+    let kept: String = value.chars().take(max_chars.saturating_sub(3)).collect();
+    format!("{}...", kept)
+}
+
+// CPU time as hours:minutes:seconds, e.g. "0:03:12" or "27:40:05", the same in every locale
+pub fn cpu_time(secs: f64) -> String {
+    let secs = secs.max(0.) as u64;
+    format!("{}:{:02}:{:02}", secs / 3600, secs / 60 % 60, secs % 60)
+}
+
+// used / total as a percentage, 0 when the total is unknown
+pub fn usage_percent(usage: (u64, u64)) -> f64 {
+    if usage.1 == 0 {
+        return 0.0;
+    }
+    usage.0 as f64 / usage.1 as f64 * 100.
+}
+
+// memory split into (apps, cache and buffers, free) from (used, total) and the reclaimable
+// cache, adding up to the total even when the readings were taken a moment apart
+pub fn memory_categories(usage: (u64, u64), cache: u64) -> (u64, u64, u64) {
+    let apps = usage.0.min(usage.1);
+    let cache = cache.min(usage.1 - apps);
+    (apps, cache, usage.1 - apps - cache)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct NumberFormat {
+    decimals: usize,
+    thousands: Option<char>,
+    decimal_mark: char,
+    date_pattern: &'static str,
+}
+
+impl NumberFormat {
+    pub fn new(decimals: usize, locale: NumberLocale) -> NumberFormat {
+        let (thousands, decimal_mark) = locale.separators();
+        NumberFormat {
+            decimals,
+            thousands,
+            decimal_mark,
+            date_pattern: locale.date_pattern(),
+        }
+    }
+
+    // same without thousands separators, for CSV cells that spreadsheets should read as numbers
+    pub fn ungrouped(self) -> NumberFormat {
+        NumberFormat {
+            thousands: None,
+            ..self
+        }
+    }
+
+    // column separator for CSV files, a semicolon where the comma is the decimal mark
+    pub fn csv_separator(&self) -> char {
+        if self.decimal_mark == ',' {
+            ';'
+        } else {
+            ','
+        }
+    }
+
+    // a sample timestamp in the locale's date order, unchanged if it doesn't parse
+    pub fn timestamp(&self, timestamp: &str) -> String {
+        NaiveDateTime::parse_from_str(timestamp, TIMESTAMP_FORMAT)
+            .map(|parsed| parsed.format(self.date_pattern).to_string())
+            .unwrap_or_else(|_| timestamp.to_string())
+    }
+
+    fn with_decimals(&self, value: f64, decimals: usize) -> String {
+        if !value.is_finite() {
+            return value.to_string();
+        }
+
+        let formatted = format!("{:.*}", decimals, value.abs());
+        let (integer, fraction) = match formatted.split_once('.') {
+            Some((integer, fraction)) => (integer, Some(fraction)),
+            None => (formatted.as_str(), None),
+        };
+
+        let mut result = String::new();
+        // "-0.00" reads oddly, only keep the sign when something non-zero is shown
+        if value < 0. && formatted.chars().any(|c| c.is_ascii_digit() && c != '0') {
+            result.push('-');
+        }
+        for (i, digit) in integer.chars().enumerate() {
+            if let Some(separator) = self.thousands {
+                if i > 0 && (integer.len() - i) % 3 == 0 {
+                    result.push(separator);
+                }
+            }
+            result.push(digit);
+        }
+        if let Some(fraction) = fraction {
+            result.push(self.decimal_mark);
+            result.push_str(fraction);
+        }
+        result
+    }
+
+    pub fn number(&self, value: f64) -> String {
+        self.with_decimals(value, self.decimals)
+    }
+
+    pub fn integer(&self, value: u64) -> String {
+        self.with_decimals(value as f64, 0)
+    }
+
+    pub fn percent(&self, value: f64) -> String {
+        format!("{}%", self.number(value))
+    }
+
+    // bytes in the largest unit that keeps the value at 1 or more, e.g. "512 B" or "3.25 GB"
+    pub fn bytes(&self, bytes: f64) -> String {
+        let mut value = bytes;
+        let mut unit = 0;
+        while value >= 1024. && unit < BYTE_UNITS.len() - 1 {
+            value /= 1024.;
+            unit += 1;
+        }
+
+        if unit == 0 {
+            format!("{} {}", self.with_decimals(value, 0), BYTE_UNITS[unit])
+        } else {
+            format!("{} {}", self.number(value), BYTE_UNITS[unit])
+        }
+    }
+
+    // bytes per second, e.g. "1.20 MB/s"
+    pub fn rate(&self, bytes_per_sec: f64) -> String {
+        format!("{}/s", self.bytes(bytes_per_sec))
+    }
+}
@@ -0,0 +1,151 @@
+// NVIDIA GPUs and the compute processes running on them, read from nvidia-smi, which comes
+// with the driver on Linux and Windows
+use serde::{Deserialize, Serialize};
+use std::process::Command;
+use std::time::Duration;
+use sysinfo::{Pid, ProcessRefreshKind, ProcessesToUpdate, System};
+
+// nvidia-smi takes tens of milliseconds per call, too slow to run on every tick, and up to a few
+// seconds without persistence mode; it runs on a background thread
+pub const GPU_REFRESH_INTERVAL: Duration = Duration::from_secs(5);
+
+const GPU_QUERY: &str = "index,uuid,name,utilization.gpu,memory.used,memory.total,clocks.gr,\
+                         clocks.max.gr,clocks.mem,clocks.max.mem,power.draw,power.limit";
+const PROCESS_QUERY: &str = "pid,process_name,gpu_uuid,used_memory";
+
+// One GPU, None where the card or driver doesn't report a value
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GpuInfo {
+    pub index: u32,
+    pub uuid: String,
+    pub name: String,
+    pub utilization_percent: Option<f64>,
+    pub memory_used_mib: Option<u64>,
+    pub memory_total_mib: Option<u64>,
+    // current and highest (boost) clocks in MHz
+    pub graphics_clock_mhz: Option<u64>,
+    pub max_graphics_clock_mhz: Option<u64>,
+    pub memory_clock_mhz: Option<u64>,
+    pub max_memory_clock_mhz: Option<u64>,
+    pub power_draw_w: Option<f64>,
+    pub power_limit_w: Option<f64>,
+}
+
+// A process with a CUDA (or other compute) context on a GPU
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GpuProcess {
+    pub pid: u32,
+    pub name: String,
+    pub gpu_uuid: String,
+    pub used_memory_mib: Option<u64>,
+    // when the process started, read along with the list so the Kill button can't hit another
+    // process that got the pid later; None when it had already exited
+    pub start_time: Option<u64>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct GpuStatus {
+    pub gpus: Vec<GpuInfo>,
+    pub processes: Vec<GpuProcess>,
+    // why nothing could be read, e.g. no NVIDIA driver
+    pub error: Option<String>,
+}
+
+pub fn read_gpu_status() -> GpuStatus {
+    let gpus = match query(&format!("--query-gpu={}", GPU_QUERY)) {
+        Ok(lines) => lines.iter().filter_map(|line| parse_gpu(line)).collect(),
+        Err(e) => {
+            return GpuStatus {
+                error: Some(e),
+                ..GpuStatus::default()
+            }
+        }
+    };
+    let mut processes: Vec<GpuProcess> = query(&format!("--query-compute-apps={}", PROCESS_QUERY))
+        .map(|lines| {
+            lines
+                .iter()
+                .filter_map(|line| parse_process(line))
+                .collect()
+        })
+        .unwrap_or_default();
+
+    let pids: Vec<Pid> = processes.iter().map(|p| Pid::from_u32(p.pid)).collect();
+    if !pids.is_empty() {
+        let mut system = System::new();
+        system
+            .refresh_processes_specifics(ProcessesToUpdate::Some(&pids), ProcessRefreshKind::new());
+        for process in &mut processes {
+            process.start_time = system
+                .process(Pid::from_u32(process.pid))
+                .map(|p| p.start_time());
+        }
+    }
+
+    GpuStatus {
+        gpus,
+        processes,
+        error: None,
+    }
+}
+
+// one line of comma separated values per GPU or process
+fn query(query: &str) -> Result<Vec<String>, String> {
+    let output = Command::new("nvidia-smi")
+        .args([query, "--format=csv,noheader,nounits"])
+        .output()
+        .map_err(|_| String::from("nvidia-smi not found, no NVIDIA driver installed"))?;
+    if !output.status.success() {
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        return Err(stdout.trim().to_string());
+    }
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(str::to_string)
+        .collect())
+}
+
+// "[N/A]" and "[Not Supported]" become None
+fn value<T: std::str::FromStr>(field: &str) -> Option<T> {
+    field.trim().parse().ok()
+}
+
+fn parse_gpu(line: &str) -> Option<GpuInfo> {
+    let fields: Vec<&str> = line.split(',').map(str::trim).collect();
+    let [index, uuid, name, utilization, memory_used, memory_total, graphics_clock, max_graphics_clock, memory_clock, max_memory_clock, power_draw, power_limit] =
+        fields[..]
+    else {
+        return None;
+    };
+
+    Some(GpuInfo {
+        index: value(index)?,
+        uuid: uuid.to_string(),
+        name: name.to_string(),
+        utilization_percent: value(utilization),
+        memory_used_mib: value(memory_used),
+        memory_total_mib: value(memory_total),
+        graphics_clock_mhz: value(graphics_clock),
+        max_graphics_clock_mhz: value(max_graphics_clock),
+        memory_clock_mhz: value(memory_clock),
+        max_memory_clock_mhz: value(max_memory_clock),
+        power_draw_w: value(power_draw),
+        power_limit_w: value(power_limit),
+    })
+}
+
+// the process name may be a path with commas in it, the other fields can't contain any
+fn parse_process(line: &str) -> Option<GpuProcess> {
+    let (pid, rest) = line.split_once(',')?;
+    let (rest, used_memory) = rest.rsplit_once(',')?;
+    let (name, gpu_uuid) = rest.rsplit_once(',')?;
+
+    Some(GpuProcess {
+        pid: value(pid)?,
+        name: name.trim().to_string(),
+        gpu_uuid: gpu_uuid.trim().to_string(),
+        used_memory_mib: value(used_memory),
+        start_time: None,
+    })
+}
@@ -0,0 +1,207 @@
+use crate::alerts::{export_rules, import_rules, merge_rules, strip_actions, stripped_note};
+use crate::cli::Check;
+use crate::config::{Config, Preset, CONFIG_FILE};
+use crate::logging::log_metrics;
+use crate::models::{SystemData, SystemMonitor};
+use crate::prometheus::render_metrics;
+use crate::shutdown;
+use crate::utils::collect_system_data;
+use std::io::{self, Write};
+use std::thread;
+use std::time::Duration;
+
+// write one JSON sample per interval to stdout until the reader goes away
+pub fn run_stdout(cli_interval_secs: Option<u64>) {
+    let mut system_monitor = SystemMonitor::init();
+    let stdout = io::stdout();
+    shutdown::install_handlers();
+
+    // the lines of the stream are numbered from 0
+    let mut sample_index = 0;
+    loop {
+        system_monitor.reload_config();
+        let interval_secs = system_monitor.config.interval_secs_or(cli_interval_secs);
+
+        // a sample is either written whole or not at all
+        if !shutdown::sleep(Duration::from_secs(interval_secs)) {
+            break;
+        }
+        system_monitor.refresh();
+
+        let data = SystemData {
+            sample_index,
+            ..collect_system_data(&system_monitor)
+        };
+        sample_index += 1;
+        let serialized = serde_json::to_string(&data).expect("Failed to serialize system data");
+
+        // a closed pipe (e.g. `| head`) ends the stream
+        let mut out = stdout.lock();
+        if writeln!(out, "{}", serialized)
+            .and_then(|_| out.flush())
+            .is_err()
+        {
+            break;
+        }
+    }
+}
+
+// merge a shared rule set into monitor_config.json, running monitors pick it up on their
+// next config reload; a config that doesn't parse is left alone rather than reset
+pub fn import_rule_file(path: &str) -> Result<String, String> {
+    let mut imported = import_rules(path)?;
+    let stripped = strip_actions(&mut imported);
+    let mut config = if Config::exists() {
+        Config::read().map_err(|e| format!("Invalid config file {}: {}", CONFIG_FILE, e))?
+    } else {
+        Config::default()
+    };
+
+    let count = imported.len();
+    let added = merge_rules(&mut config.alert_rules, imported);
+    config
+        .save()
+        .map_err(|e| format!("Failed to write {}: {}", CONFIG_FILE, e))?;
+    Ok(format!(
+        "Imported {} alert rules ({} new) into {}{}",
+        count,
+        added,
+        CONFIG_FILE,
+        stripped_note(stripped)
+    ))
+}
+
+pub fn export_rule_file(path: &str) -> Result<String, String> {
+    let rules = Config::load().alert_rules;
+    export_rules(&rules, path)?;
+    Ok(format!("Exported {} alert rules to {}", rules.len(), path))
+}
+
+// append one sample per interval to the log file, used when running as a service
+pub fn run_daemon(cli_interval_secs: Option<u64>) {
+    let mut system_monitor = SystemMonitor::init();
+    let mut elapsed_secs = 0;
+    shutdown::install_handlers();
+    shutdown::install_reload_handler();
+
+    loop {
+        // edits to the config file apply from the next sample on
+        system_monitor.reload_config();
+        let interval_secs = system_monitor.config.interval_secs_or(cli_interval_secs);
+
+        // aggregated records need the 1 second samples in between
+        let tick_secs = if system_monitor.config.log_aggregate {
+            1
+        } else {
+            interval_secs
+        };
+
+        // stop between samples when the service is stopped, not halfway through a write
+        if !shutdown::sleep(Duration::from_secs(tick_secs)) {
+            shutdown::finish_session(&mut system_monitor, true);
+            break;
+        }
+        // SIGHUP, e.g. from logrotate's postrotate: start the next sample in a fresh file, then
+        // carry on with the tick so the schedule doesn't slip by one
+        if shutdown::take_reload() {
+            system_monitor.force_reload_config();
+            if let Err(e) = system_monitor.log_writer.reopen() {
+                eprintln!("Failed to reopen log file: {}", e);
+            }
+        }
+        system_monitor.refresh();
+
+        elapsed_secs += tick_secs;
+        if elapsed_secs < interval_secs {
+            continue;
+        }
+        elapsed_secs = 0;
+
+        if let Err(e) = log_metrics(&mut system_monitor) {
+            eprintln!("Failed to write log file: {}", e);
+        }
+    }
+}
+
+// --preset agent: refresh once per interval for the metrics textfile and the alert webhook,
+// nothing else is written
+pub fn run_agent(cli_interval_secs: Option<u64>) {
+    let mut system_monitor = SystemMonitor::init();
+    system_monitor.set_preset(Preset::Agent);
+    shutdown::install_handlers();
+    shutdown::install_reload_handler();
+
+    if system_monitor.config.alert_webhook.is_none() {
+        eprintln!(
+            "No alert_webhook in {}, alerts only go to stderr",
+            CONFIG_FILE
+        );
+    }
+
+    loop {
+        system_monitor.reload_config();
+        let interval_secs = system_monitor.config.interval_secs_or(cli_interval_secs);
+
+        if !shutdown::sleep(Duration::from_secs(interval_secs)) {
+            break;
+        }
+        if shutdown::take_reload() {
+            system_monitor.force_reload_config();
+        }
+        system_monitor.refresh();
+    }
+}
+
+// exit codes for --once/--check, following the Nagios plugin convention
+pub const EXIT_OK: i32 = 0;
+pub const EXIT_CRITICAL: i32 = 2;
+pub const EXIT_UNKNOWN: i32 = 3;
+
+// take a single sample and print it in the Prometheus text format
+pub fn run_metrics() {
+    let mut system_monitor = SystemMonitor::init();
+    thread::sleep(Duration::from_secs(1));
+    system_monitor.refresh();
+    print!("{}", render_metrics(&system_monitor));
+}
+
+// take a single sample, print it (or the check results) and return the exit code
+pub fn run_once(checks: &[Check]) -> i32 {
+    let mut system_monitor = SystemMonitor::init();
+
+    // cpu usage needs two refreshes some time apart to be accurate
+    thread::sleep(Duration::from_secs(1));
+    system_monitor.refresh();
+
+    if checks.is_empty() {
+        let data = collect_system_data(&system_monitor);
+        return match serde_json::to_string(&data) {
+            Ok(serialized) => {
+                println!("{}", serialized);
+                EXIT_OK
+            }
+            Err(e) => {
+                eprintln!("Failed to serialize system data: {}", e);
+                EXIT_UNKNOWN
+            }
+        };
+    }
+
+    let mut failed = Vec::new();
+    let mut results = Vec::new();
+    for check in checks {
+        let value = check.metric.value(&system_monitor);
+        results.push(format!("{}={:.2}", check.metric, value));
+        if check.comparison.holds(value, check.limit) {
+            failed.push(format!("{} ({:.2})", check.expression, value));
+        }
+    }
+
+    if failed.is_empty() {
+        println!("OK - {}", results.join(", "));
+        EXIT_OK
+    } else {
+        println!("CRITICAL - {}", failed.join(", "));
+        EXIT_CRITICAL
+    }
+}
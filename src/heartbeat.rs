@@ -0,0 +1,44 @@
+// Signs of life for external supervisors, so a hung collector can be detected and restarted
+use crate::utils::current_timestamp;
+use std::fs;
+use std::io;
+
+// rewrite the heartbeat file with the time of the latest refresh, which also updates its mtime
+pub fn write_heartbeat(path: &str) -> io::Result<()> {
+    fs::write(path, format!("{}\n", current_timestamp()))
+}
+
+// send WATCHDOG=1 to systemd (sd_notify), does nothing when not started by systemd
+// (the unit needs WatchdogSec= and NotifyAccess=main for it to have an effect)
+#[cfg(unix)]
+pub fn notify_watchdog() -> io::Result<()> {
+    use std::env;
+    use std::os::unix::net::UnixDatagram;
+
+    let Some(socket_path) = env::var_os("NOTIFY_SOCKET") else {
+        return Ok(());
+    };
+    let socket = UnixDatagram::unbound()?;
+    let message = b"WATCHDOG=1";
+
+    // a leading '@' stands for a socket in the abstract namespace
+    match socket_path.to_string_lossy().strip_prefix('@') {
+        #[cfg(target_os = "linux")]
+        Some(name) => {
+            use std::os::linux::net::SocketAddrExt;
+            use std::os::unix::net::SocketAddr;
+
+            let address = SocketAddr::from_abstract_name(name.as_bytes())?;
+            socket.send_to_addr(message, &address)?;
+        }
+        _ => {
+            socket.send_to(message, &socket_path)?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(not(unix))]
+pub fn notify_watchdog() -> io::Result<()> {
+    Ok(())
+}
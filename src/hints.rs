@@ -0,0 +1,63 @@
+// Suggestions for settings that cost more than they should on this machine, judged from the
+// measured refresh time, the number of processes and the power source
+use crate::models::SystemMonitor;
+
+// above this many processes, enumerating them every second drains a battery noticeably
+const BATTERY_PROCESS_COUNT: u32 = 1000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum HintKind {
+    SlowRefresh,
+    ProcessesOnBattery,
+    FsyncOnBattery,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct OverheadHint {
+    pub kind: HintKind,
+    pub text: String,
+}
+
+// hints for the current settings, without the ones dismissed this session
+pub fn overhead_hints(system_monitor: &SystemMonitor) -> Vec<OverheadHint> {
+    let mut hints = Vec::new();
+    let processes = system_monitor.no_of_processes;
+    let process_refresh_secs = system_monitor.config.process_refresh_secs();
+    let last_refresh = system_monitor.tick_pacer.last_refresh;
+    let on_battery = system_monitor.on_battery == Some(true);
+
+    if last_refresh >= system_monitor.config.redraw_interval() / 2 && process_refresh_secs == 1 {
+        hints.push(OverheadHint {
+            kind: HintKind::SlowRefresh,
+            text: format!(
+                "Each refresh takes {} ms with {} processes — consider refreshing the process list every 3 s",
+                last_refresh.as_millis(),
+                processes
+            ),
+        });
+    }
+    if on_battery && processes >= BATTERY_PROCESS_COUNT && process_refresh_secs == 1 {
+        hints.push(OverheadHint {
+            kind: HintKind::ProcessesOnBattery,
+            text: format!(
+                "{} processes at 1 s interval on battery — consider 3 s",
+                processes
+            ),
+        });
+    }
+    if on_battery
+        && system_monitor.save_to_file
+        && system_monitor.config.log_fsync
+        && system_monitor.interval_in_secs.trim() == "1"
+    {
+        hints.push(OverheadHint {
+            kind: HintKind::FsyncOnBattery,
+            text: String::from(
+                "Syncing the log to disk every second on battery — consider a longer logging interval or turning fsync off",
+            ),
+        });
+    }
+
+    hints.retain(|hint| !system_monitor.dismissed_hints.contains(&hint.kind));
+    hints
+}
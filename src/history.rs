@@ -0,0 +1,237 @@
+use crate::format::{csv_field, NumberFormat, NumberLocale};
+use crate::models::{Annotation, ExportFormat, SystemData};
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+
+// keep about an hour of samples at the 1 second tick rate
+const HISTORY_CAPACITY: usize = 3600;
+
+const CSV_COLUMNS: [&str; 23] = [
+    "timestamp",
+    "local_timestamp",
+    "sample_index",
+    "refresh_index",
+    "cpu_usage_percent",
+    "memory_used_byte",
+    "memory_total_byte",
+    "memory_cache_byte",
+    "swap_used_byte",
+    "swap_total_byte",
+    "disk_used_byte",
+    "disk_total_byte",
+    "network_sent_byte",
+    "network_received_byte",
+    // e.g. "sda=120.0/35.5 nvme0n1=0.0/2.0", reads/writes per second of each device
+    "disk_iops",
+    "tcp_established",
+    "tcp_time_wait",
+    "tcp_close_wait",
+    "tcp_listen",
+    "user_idle",
+    "host_name",
+    "os",
+    "machine_id",
+];
+
+// In-memory history of the samples taken while monitoring
+#[derive(Debug, Default)]
+pub struct History {
+    samples: VecDeque<SystemData>,
+    annotations: Vec<Annotation>,
+    // samples pushed since startup, including the ones that were dropped
+    pushed: u64,
+}
+
+impl History {
+    pub fn push(&mut self, mut sample: SystemData) {
+        if self.samples.len() == HISTORY_CAPACITY {
+            self.samples.pop_front();
+
+            // drop annotations that are older than the oldest sample
+            let oldest = &self.samples[0].timestamp;
+            self.annotations
+                .retain(|annotation| annotation.timestamp >= *oldest);
+        }
+        sample.refresh_index = self.pushed;
+        self.samples.push_back(sample);
+        self.pushed += 1;
+    }
+
+    pub fn pushed(&self) -> u64 {
+        self.pushed
+    }
+
+    // samples pushed after the first `count`, as far as they are still kept
+    pub fn since(&self, count: u64) -> impl Iterator<Item = &SystemData> {
+        let newer = self.pushed.saturating_sub(count) as usize;
+        self.samples
+            .iter()
+            .skip(self.samples.len().saturating_sub(newer))
+    }
+
+    pub fn add_annotation(&mut self, timestamp: String, label: String) {
+        self.annotations.push(Annotation { timestamp, label });
+    }
+
+    pub fn annotations(&self) -> &[Annotation] {
+        &self.annotations
+    }
+
+    // positions (0.0 - 1.0) along the timeline of each annotation
+    pub fn annotation_positions(&self) -> Vec<(f32, String)> {
+        if self.samples.len() < 2 {
+            return Vec::new();
+        }
+
+        let last = (self.samples.len() - 1) as f32;
+        self.annotations
+            .iter()
+            .map(|annotation| {
+                let index = self
+                    .samples
+                    .iter()
+                    .position(|sample| sample.timestamp >= annotation.timestamp)
+                    .unwrap_or(self.samples.len() - 1);
+                (index as f32 / last, annotation.label.clone())
+            })
+            .collect()
+    }
+
+    pub fn samples(&self) -> &VecDeque<SystemData> {
+        &self.samples
+    }
+
+    // timestamp of the sample closest to a position (0.0 - 1.0) along the timeline
+    pub fn timestamp_at(&self, position: f32) -> Option<&str> {
+        if self.samples.is_empty() {
+            return None;
+        }
+
+        let last = self.samples.len() - 1;
+        let index = (position.clamp(0.0, 1.0) * last as f32).round() as usize;
+        Some(&self.samples[index].timestamp)
+    }
+
+    // positions (0.0 - 1.0) along the timeline covered by a time range
+    pub fn position_of(&self, start: &str, end: &str) -> Option<(f32, f32)> {
+        if self.samples.len() < 2 {
+            return None;
+        }
+
+        let last = (self.samples.len() - 1) as f32;
+        let first_index = self
+            .samples
+            .iter()
+            .position(|sample| sample.timestamp.as_str() >= start)?;
+        let last_index = self
+            .samples
+            .iter()
+            .rposition(|sample| sample.timestamp.as_str() <= end)?;
+
+        Some((first_index as f32 / last, last_index as f32 / last))
+    }
+
+    // samples between two timestamps (inclusive)
+    // timestamps are "%Y-%m-%d %H:%M:%S", so they can be compared as strings
+    pub fn range(&self, start: &str, end: &str) -> Vec<&SystemData> {
+        self.samples
+            .iter()
+            .filter(|sample| sample.timestamp.as_str() >= start && sample.timestamp.as_str() <= end)
+            .collect()
+    }
+}
+
+// file name for an exported slice, e.g. history_20241015-140200_20241015-140700.csv
+#[cfg(feature = "gui")]
+pub fn export_file_name(start: &str, end: &str, format: ExportFormat) -> String {
+    let clean = |timestamp: &str| {
+        timestamp
+            .replace('-', "")
+            .replace(' ', "-")
+            .replace(':', "")
+    };
+    let extension = match format {
+        ExportFormat::Csv => "csv",
+        ExportFormat::Json => "json",
+    };
+
+    format!("history_{}_{}.{}", clean(start), clean(end), extension)
+}
+
+// CSV follows the locale's date order and decimal mark (with `;` between columns where that
+// mark is a comma), JSON stays locale-independent for tools
+pub fn export_samples(
+    samples: &[&SystemData],
+    path: &str,
+    format: ExportFormat,
+    locale: NumberLocale,
+) -> io::Result<()> {
+    let mut writer = BufWriter::new(File::create(path)?);
+
+    match format {
+        ExportFormat::Csv => {
+            let percent = NumberFormat::new(2, locale).ungrouped();
+            let iops = NumberFormat::new(1, locale).ungrouped();
+            let separator_char = percent.csv_separator();
+            let separator = separator_char.to_string();
+
+            writeln!(writer, "{}", CSV_COLUMNS.join(&separator))?;
+            // rows are numbered within the export
+            for (index, sample) in samples.iter().enumerate() {
+                let fields = [
+                    percent.timestamp(&sample.timestamp),
+                    sample.local_timestamp.clone(),
+                    index.to_string(),
+                    sample.refresh_index.to_string(),
+                    percent.number(sample.cpu_usage_percent as f64),
+                    sample.memory_usage_byte.0.to_string(),
+                    sample.memory_usage_byte.1.to_string(),
+                    sample.memory_cache_byte.to_string(),
+                    sample.swap_memory_usage_byte.0.to_string(),
+                    sample.swap_memory_usage_byte.1.to_string(),
+                    sample.disk_usage_byte.0.to_string(),
+                    sample.disk_usage_byte.1.to_string(),
+                    sample.network_sent_byte.to_string(),
+                    sample.network_received_byte.to_string(),
+                    sample
+                        .disk_iops
+                        .iter()
+                        .map(|disk| {
+                            format!(
+                                "{}={}/{}",
+                                disk.device,
+                                iops.number(disk.read_iops),
+                                iops.number(disk.write_iops)
+                            )
+                        })
+                        .collect::<Vec<_>>()
+                        .join(" "),
+                    sample.tcp_states.established.to_string(),
+                    sample.tcp_states.time_wait.to_string(),
+                    sample.tcp_states.close_wait.to_string(),
+                    sample.tcp_states.listen.to_string(),
+                    sample
+                        .user_idle
+                        .map_or_else(String::new, |idle| idle.to_string()),
+                    csv_field(&sample.host.host_name, separator_char),
+                    csv_field(&sample.host.os, separator_char),
+                    csv_field(&sample.host.machine_id, separator_char),
+                ];
+                writeln!(writer, "{}", fields.join(&separator))?;
+            }
+        }
+        // one JSON object per line, same layout as system_log.json
+        ExportFormat::Json => {
+            for (index, sample) in samples.iter().enumerate() {
+                let serialized = serde_json::to_string(&SystemData {
+                    sample_index: index as u64,
+                    ..(*sample).clone()
+                })?;
+                writeln!(writer, "{}", serialized)?;
+            }
+        }
+    }
+
+    writer.flush()
+}
@@ -0,0 +1,90 @@
+// Which machine a sample came from, so logs and exports of several machines can be merged
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::process::Command;
+use sysinfo::System;
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HostIdentity {
+    pub host_name: String,
+    // e.g. "Ubuntu 24.04"
+    pub os: String,
+    // survives renames and reinstalls of the monitor, empty when the OS doesn't provide one;
+    // derived from the OS's machine id rather than the id itself, see `app_specific`
+    pub machine_id: String,
+}
+
+pub fn read_host_identity() -> HostIdentity {
+    HostIdentity {
+        host_name: System::host_name().unwrap_or_default(),
+        os: System::long_os_version().unwrap_or_default(),
+        machine_id: machine_id().map(|id| app_specific(&id)).unwrap_or_default(),
+    }
+}
+
+// mixed into the machine id, so the id in logs and exports identifies the machine to this
+// monitor only; never change it, the ids of every machine would change with it
+const APP_ID_SALT: &str = "rust-system-monitor/host-identity/1";
+
+// the OS's machine id is meant to stay secret to the machine (machine-id(5)), and logs and
+// exports get shared; like systemd's sd_id128_get_machine_app_specific, only a hash keyed to
+// this app goes into them: stable per machine, but not the id other software knows it by.
+// 128 bits in hex, as long as /etc/machine-id
+pub fn app_specific(machine_id: &str) -> String {
+    let digest = Sha256::new()
+        .chain_update(APP_ID_SALT)
+        .chain_update(machine_id)
+        .finalize();
+    digest[..16]
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+fn machine_id() -> Option<String> {
+    if cfg!(windows) {
+        windows_machine_guid()
+    } else if cfg!(target_os = "macos") {
+        mac_platform_uuid()
+    } else {
+        // systemd, with the older D-Bus location as fallback
+        ["/etc/machine-id", "/var/lib/dbus/machine-id"]
+            .iter()
+            .find_map(|path| fs::read_to_string(path).ok())
+            .map(|id| id.trim().to_string())
+            .filter(|id| !id.is_empty())
+    }
+}
+
+// `"IOPlatformUUID" = "XXXXXXXX-..."` in the platform expert device
+fn mac_platform_uuid() -> Option<String> {
+    let output = Command::new("ioreg")
+        .args(["-rd1", "-c", "IOPlatformExpertDevice"])
+        .output()
+        .ok()?;
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .find(|line| line.contains("IOPlatformUUID"))
+        .and_then(|line| line.split('"').nth(3))
+        .map(str::to_string)
+}
+
+// `MachineGuid    REG_SZ    xxxxxxxx-...`, set when Windows is installed
+fn windows_machine_guid() -> Option<String> {
+    let output = Command::new("reg")
+        .args([
+            "query",
+            r"HKLM\SOFTWARE\Microsoft\Cryptography",
+            "/v",
+            "MachineGuid",
+        ])
+        .output()
+        .ok()?;
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .find(|line| line.trim_start().starts_with("MachineGuid"))
+        .and_then(|line| line.split_whitespace().last())
+        .map(str::to_string)
+}
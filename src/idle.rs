@@ -0,0 +1,92 @@
+// Time since the last keyboard or mouse input, to tell load while the user is away from load
+// they caused
+#[cfg(feature = "gui")]
+use crate::models::SystemData;
+use std::process::Command;
+use std::time::Duration;
+
+// spawning a helper every tick would show up in the very charts it annotates
+pub const IDLE_POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+// None where the idle time can't be read: Windows, a Wayland desktop other than GNOME, or no
+// xprintidle on X11
+pub fn read_idle_time() -> Option<Duration> {
+    if cfg!(target_os = "macos") {
+        mac_idle_time()
+    } else if cfg!(target_os = "linux") {
+        gnome_idle_time().or_else(x11_idle_time)
+    } else {
+        None
+    }
+}
+
+// xprintidle prints the X server's idle time in milliseconds
+fn x11_idle_time() -> Option<Duration> {
+    let stdout = output("xprintidle", &[])?;
+    Some(Duration::from_millis(stdout.trim().parse().ok()?))
+}
+
+// Mutter answers over D-Bus on X11 and Wayland alike, e.g. "(uint64 12345,)" in milliseconds
+fn gnome_idle_time() -> Option<Duration> {
+    let stdout = output(
+        "gdbus",
+        &[
+            "call",
+            "--session",
+            "--dest",
+            "org.gnome.Mutter.IdleMonitor",
+            "--object-path",
+            "/org/gnome/Mutter/IdleMonitor/Core",
+            "--method",
+            "org.gnome.Mutter.IdleMonitor.GetIdletime",
+        ],
+    )?;
+    let millis = stdout
+        .split_whitespace()
+        .nth(1)?
+        .trim_end_matches([',', ')']);
+    Some(Duration::from_millis(millis.parse().ok()?))
+}
+
+// the HID system reports `"HIDIdleTime" = 1234567890` in nanoseconds
+fn mac_idle_time() -> Option<Duration> {
+    let stdout = output("ioreg", &["-c", "IOHIDSystem", "-d", "4"])?;
+    let line = stdout
+        .lines()
+        .find(|line| line.contains("\"HIDIdleTime\""))?;
+    let nanos = line.rsplit('=').next()?.trim();
+    Some(Duration::from_nanos(nanos.parse().ok()?))
+}
+
+fn output(program: &str, args: &[&str]) -> Option<String> {
+    let output = Command::new(program).args(args).output().ok()?;
+    output
+        .status
+        .success()
+        .then(|| String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+// stretches of idle samples as fractions of the chart width, each sample covering half the
+// distance to its neighbours
+#[cfg(feature = "gui")]
+pub fn idle_spans<'a>(samples: impl ExactSizeIterator<Item = &'a SystemData>) -> Vec<(f32, f32)> {
+    let last = samples.len().saturating_sub(1);
+    if last == 0 {
+        return Vec::new();
+    }
+
+    let half = 0.5 / last as f32;
+    let mut spans: Vec<(f32, f32)> = Vec::new();
+    let mut previous_idle = false;
+    for (i, sample) in samples.enumerate() {
+        let idle = sample.user_idle == Some(true);
+        let position = i as f32 / last as f32;
+        match spans.last_mut() {
+            Some(span) if idle && previous_idle => span.1 = (position + half).min(1.),
+            _ if idle => spans.push(((position - half).max(0.), (position + half).min(1.))),
+            _ => {}
+        }
+        previous_idle = idle;
+    }
+    spans
+}
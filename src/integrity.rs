@@ -0,0 +1,285 @@
+// Checks that a log left running for days is still being written: its directory takes a file and
+// has room left, and samples keep coming with timestamps that move forward
+use crate::history::History;
+use std::fs;
+use std::io;
+use std::mem::discriminant;
+use std::path::Path;
+use std::time::{Duration, Instant};
+use sysinfo::Disks;
+
+// often enough to catch a full disk before hours of samples are lost, without touching the
+// disk on every sample
+pub const CHECK_INTERVAL: Duration = Duration::from_secs(5 * 60);
+
+// below this the log moves on, before the disk is full and the rest of the machine suffers too
+pub const MIN_FREE_BYTES: u64 = 256 * 1024 * 1024;
+
+// written and removed again to see the directory still takes files
+const PROBE_FILE: &str = ".system_monitor_probe";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LogProblem {
+    // the probe file couldn't be written, or the log thread reported failed writes
+    NotWritable(String),
+    // bytes still free on the log's filesystem
+    LowSpace(u64),
+    // no sample since the previous check
+    Stalled,
+    // the newest sample is older than the newest one at the previous check
+    ClockWentBack,
+}
+
+impl LogProblem {
+    // the log directory itself is unusable, writing elsewhere helps
+    pub fn moves_log(&self) -> bool {
+        matches!(self, LogProblem::NotWritable(_) | LogProblem::LowSpace(_))
+    }
+}
+
+impl std::fmt::Display for LogProblem {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LogProblem::NotWritable(reason) => write!(f, "log directory not writable ({})", reason),
+            LogProblem::LowSpace(free) => write!(f, "{} MB left for the log", free / (1024 * 1024)),
+            LogProblem::Stalled => write!(f, "no new samples since the last check"),
+            LogProblem::ClockWentBack => write!(f, "sample timestamps went back"),
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct IntegrityMonitor {
+    checked_at: Option<Instant>,
+    // history samples and the newest timestamp at the previous check
+    pushed: u64,
+    timestamp: Option<String>,
+    // failed writes the log writer had counted at the previous check
+    failures: u64,
+    problems: Vec<LogProblem>,
+}
+
+impl IntegrityMonitor {
+    pub fn due(&self, now: Instant) -> bool {
+        self.checked_at
+            .is_none_or(|checked_at| now.duration_since(checked_at) >= CHECK_INTERVAL)
+    }
+
+    // run every check, returns the problems that weren't there at the previous one so a problem
+    // that persists alerts once
+    pub fn check(
+        &mut self,
+        directory: &Path,
+        failures: u64,
+        history: &History,
+        disks: &Disks,
+        now: Instant,
+    ) -> Vec<LogProblem> {
+        let directory = resolve(directory);
+        let mut problems = Vec::new();
+        if let Err(e) = probe(directory) {
+            problems.push(LogProblem::NotWritable(e.to_string()));
+        } else if failures > self.failures {
+            problems.push(LogProblem::NotWritable(format!(
+                "{} writes failed",
+                failures - self.failures
+            )));
+        }
+        if let Some(free) = free_space(directory, disks).filter(|free| *free < MIN_FREE_BYTES) {
+            problems.push(LogProblem::LowSpace(free));
+        }
+
+        let timestamp = history
+            .samples()
+            .back()
+            .map(|sample| sample.timestamp.clone());
+        if self.checked_at.is_some() {
+            if history.pushed() == self.pushed {
+                problems.push(LogProblem::Stalled);
+            } else if let (Some(previous), Some(latest)) = (&self.timestamp, &timestamp) {
+                // the timestamp format sorts like the time it stands for
+                if latest <= previous {
+                    problems.push(LogProblem::ClockWentBack);
+                }
+            }
+        }
+
+        let new = problems
+            .iter()
+            .filter(|problem| {
+                !self
+                    .problems
+                    .iter()
+                    .any(|previous| discriminant(previous) == discriminant(*problem))
+            })
+            .cloned()
+            .collect();
+        self.checked_at = Some(now);
+        self.pushed = history.pushed();
+        self.timestamp = timestamp;
+        self.failures = failures;
+        self.problems = problems;
+        new
+    }
+
+    // what the latest check found
+    pub fn problems(&self) -> &[LogProblem] {
+        &self.problems
+    }
+}
+
+// the directory takes the log again, with room to spare so the log doesn't move straight back out
+pub fn usable(directory: &Path, disks: &Disks) -> bool {
+    let directory = resolve(directory);
+    probe(directory).is_ok()
+        && free_space(directory, disks).is_none_or(|free| free >= 2 * MIN_FREE_BYTES)
+}
+
+// an empty directory is the working directory
+fn resolve(directory: &Path) -> &Path {
+    if directory.as_os_str().is_empty() {
+        Path::new(".")
+    } else {
+        directory
+    }
+}
+
+fn probe(directory: &Path) -> io::Result<()> {
+    let path = directory.join(PROBE_FILE);
+    fs::write(&path, b"probe")?;
+    fs::remove_file(path)
+}
+
+// free space of the filesystem the directory is on, the mount point that contains it and is
+// deepest
+fn free_space(directory: &Path, disks: &Disks) -> Option<u64> {
+    let directory = fs::canonicalize(directory).ok()?;
+    disks
+        .iter()
+        .filter(|disk| directory.starts_with(disk.mount_point()))
+        .max_by_key(|disk| disk.mount_point().as_os_str().len())
+        .map(|disk| disk.available_space())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::SystemData;
+    use std::path::PathBuf;
+
+    fn sample(timestamp: &str) -> SystemData {
+        serde_json::from_value(serde_json::json!({
+            "timestamp": timestamp,
+            "cpu_usage_percent": 10.0,
+            "memory_usage_byte": [4, 16],
+            "swap_memory_usage_byte": [0, 2],
+            "disk_usage_byte": [10, 100],
+            "network_sent_byte": 0,
+            "network_received_byte": 0,
+        }))
+        .unwrap()
+    }
+
+    // a monitor checking a directory of its own, so tests running at once don't share the probe
+    // file
+    struct Checked {
+        monitor: IntegrityMonitor,
+        history: History,
+        directory: PathBuf,
+        now: Instant,
+    }
+
+    impl Checked {
+        fn new(name: &str) -> Checked {
+            let directory = std::env::temp_dir().join(format!(
+                "integrity_test_{}_{}",
+                std::process::id(),
+                name
+            ));
+            fs::create_dir_all(&directory).unwrap();
+            Checked {
+                monitor: IntegrityMonitor::default(),
+                history: History::default(),
+                directory,
+                now: Instant::now(),
+            }
+        }
+
+        // a check one interval after the previous one
+        fn check(&mut self, failures: u64) -> Vec<LogProblem> {
+            self.now += CHECK_INTERVAL;
+            self.monitor.check(
+                &self.directory,
+                failures,
+                &self.history,
+                &Disks::new(),
+                self.now,
+            )
+        }
+    }
+
+    impl Drop for Checked {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.directory);
+        }
+    }
+
+    #[test]
+    fn a_persisting_problem_is_reported_once() {
+        let mut checked = Checked::new("persisting");
+        checked.history.push(sample("2024-01-01 10:00:00"));
+        assert_eq!(checked.check(0), []);
+
+        checked.history.push(sample("2024-01-01 10:00:01"));
+        assert_eq!(
+            checked.check(2),
+            [LogProblem::NotWritable(String::from("2 writes failed"))]
+        );
+
+        // still failing, with another count: the same kind of problem, not a new one
+        checked.history.push(sample("2024-01-01 10:00:02"));
+        assert_eq!(checked.check(5), []);
+        assert_eq!(checked.monitor.problems().len(), 1);
+
+        // gone, then back: new again
+        checked.history.push(sample("2024-01-01 10:00:03"));
+        assert_eq!(checked.check(5), []);
+        assert!(checked.monitor.problems().is_empty());
+        checked.history.push(sample("2024-01-01 10:00:04"));
+        assert_eq!(checked.check(6).len(), 1);
+    }
+
+    #[test]
+    fn no_sample_since_the_previous_check_is_a_stall() {
+        let mut checked = Checked::new("stalled");
+        // the first check has nothing to compare with
+        assert_eq!(checked.check(0), []);
+        assert_eq!(checked.check(0), [LogProblem::Stalled]);
+
+        checked.history.push(sample("2024-01-01 10:00:00"));
+        assert_eq!(checked.check(0), []);
+        assert!(checked.monitor.problems().is_empty());
+    }
+
+    #[test]
+    fn an_older_newest_sample_means_the_clock_went_back() {
+        let mut checked = Checked::new("clock");
+        checked.history.push(sample("2024-01-01 10:00:05"));
+        assert_eq!(checked.check(0), []);
+
+        checked.history.push(sample("2024-01-01 10:00:01"));
+        assert_eq!(checked.check(0), [LogProblem::ClockWentBack]);
+
+        checked.history.push(sample("2024-01-01 10:00:02"));
+        assert_eq!(checked.check(0), []);
+    }
+
+    #[test]
+    fn checks_are_due_every_interval() {
+        let mut checked = Checked::new("due");
+        assert!(checked.monitor.due(checked.now));
+        checked.check(0);
+        assert!(!checked.monitor.due(checked.now + CHECK_INTERVAL / 2));
+        assert!(checked.monitor.due(checked.now + CHECK_INTERVAL));
+    }
+}
@@ -0,0 +1,142 @@
+// Actions on a network interface from the network panel: waking a machine on its network with a
+// wake-on-LAN magic packet, and bringing the link up or down
+use std::io;
+use std::net::{IpAddr, Ipv4Addr, SocketAddrV4, UdpSocket};
+use std::process::Command;
+use sysinfo::NetworkData;
+
+// the discard port, where wake-on-LAN tools send the packet unless told otherwise
+const WAKE_PORT: u16 = 9;
+
+// "aa:bb:cc:dd:ee:ff" or "aa-bb-cc-dd-ee-ff"
+pub fn parse_mac(mac: &str) -> Option<[u8; 6]> {
+    let parts: Vec<&str> = mac.trim().split([':', '-']).collect();
+    if parts.len() != 6 {
+        return None;
+    }
+    let mut bytes = [0; 6];
+    for (byte, part) in bytes.iter_mut().zip(parts) {
+        // from_str_radix would take a sign as well
+        if part.len() != 2 || !part.bytes().all(|b| b.is_ascii_hexdigit()) {
+            return None;
+        }
+        *byte = u8::from_str_radix(part, 16).ok()?;
+    }
+    Some(bytes)
+}
+
+// six 0xff bytes followed by the MAC sixteen times
+fn magic_packet(mac: [u8; 6]) -> Vec<u8> {
+    let mut packet = vec![0xff; 6];
+    for _ in 0..16 {
+        packet.extend_from_slice(&mac);
+    }
+    packet
+}
+
+// broadcast to the interface's own IPv4 network, so the packet leaves through that interface
+// rather than the one the default route points at
+pub fn send_wake(network: &NetworkData, mac: [u8; 6]) -> io::Result<()> {
+    let broadcast = network
+        .ip_networks()
+        .iter()
+        .find_map(|ip_network| match ip_network.addr {
+            IpAddr::V4(addr) if !addr.is_loopback() => Some(broadcast(addr, ip_network.prefix)),
+            _ => None,
+        })
+        .ok_or_else(|| io::Error::other("the interface has no IPv4 address"))?;
+
+    let socket = UdpSocket::bind(SocketAddrV4::new(Ipv4Addr::UNSPECIFIED, 0))?;
+    socket.set_broadcast(true)?;
+    socket.send_to(&magic_packet(mac), SocketAddrV4::new(broadcast, WAKE_PORT))?;
+    Ok(())
+}
+
+// the address with every host bit set; a /32 is the address itself
+fn broadcast(addr: Ipv4Addr, prefix: u8) -> Ipv4Addr {
+    let host_bits = u32::MAX.checked_shr(prefix.into()).unwrap_or(0);
+    Ipv4Addr::from(u32::from(addr) | host_bits)
+}
+
+// needs root or CAP_NET_ADMIN on Linux, an administrator on macOS and Windows; the error is what
+// the tool printed
+pub fn set_link(interface: &str, up: bool) -> Result<(), String> {
+    let mut command = link_command(interface, up);
+    match command.output() {
+        Ok(output) if output.status.success() => Ok(()),
+        Ok(output) => Err(String::from_utf8_lossy(&output.stderr).trim().to_string()),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+fn link_command(interface: &str, up: bool) -> Command {
+    if cfg!(windows) {
+        let mut netsh = Command::new("netsh");
+        netsh.args([
+            "interface",
+            "set",
+            "interface",
+            &format!("name={}", interface),
+            if up {
+                "admin=enabled"
+            } else {
+                "admin=disabled"
+            },
+        ]);
+        netsh
+    } else if cfg!(target_os = "macos") {
+        let mut ifconfig = Command::new("ifconfig");
+        ifconfig.args([interface, if up { "up" } else { "down" }]);
+        ifconfig
+    } else {
+        let mut ip = Command::new("ip");
+        ip.args([
+            "link",
+            "set",
+            "dev",
+            interface,
+            if up { "up" } else { "down" },
+        ]);
+        ip
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn macs_parse_with_colons_or_dashes() {
+        let mac = [0xaa, 0xbb, 0xcc, 0x01, 0x02, 0xff];
+        assert_eq!(parse_mac("aa:bb:cc:01:02:ff"), Some(mac));
+        assert_eq!(parse_mac(" AA-BB-CC-01-02-FF "), Some(mac));
+    }
+
+    #[test]
+    fn malformed_macs_are_rejected() {
+        assert_eq!(parse_mac(""), None);
+        assert_eq!(parse_mac("aa:bb:cc:01:02"), None);
+        assert_eq!(parse_mac("aa:bb:cc:01:02:ff:00"), None);
+        assert_eq!(parse_mac("a:bb:cc:01:02:fff"), None);
+        assert_eq!(parse_mac("aa:bb:cc:01:02:zz"), None);
+        assert_eq!(parse_mac("+a:bb:cc:01:02:ff"), None);
+    }
+
+    #[test]
+    fn the_magic_packet_repeats_the_mac_after_a_sync_stream() {
+        let mac = [1, 2, 3, 4, 5, 6];
+        let packet = magic_packet(mac);
+        assert_eq!(packet.len(), 102);
+        assert_eq!(packet[..6], [0xff; 6]);
+        assert!(packet[6..].chunks(6).all(|chunk| chunk == mac));
+    }
+
+    #[test]
+    fn the_broadcast_sets_the_host_bits() {
+        let addr = Ipv4Addr::new(192, 168, 1, 42);
+        assert_eq!(broadcast(addr, 24), Ipv4Addr::new(192, 168, 1, 255));
+        assert_eq!(broadcast(addr, 20), Ipv4Addr::new(192, 168, 15, 255));
+        assert_eq!(broadcast(addr, 0), Ipv4Addr::BROADCAST);
+        assert_eq!(broadcast(addr, 32), addr);
+    }
+}
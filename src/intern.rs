@@ -0,0 +1,104 @@
+// Names kept between refreshes, so a process or core that is still there reuses its Arc<str>
+// instead of allocating a fresh String every tick
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+// a process can change its command line (setproctitle) or user (setuid) without an exec, so the
+// names of those still running are looked up again now and then
+const LOOKUP_INTERVAL: Duration = Duration::from_secs(30);
+
+#[derive(Debug, Clone)]
+pub struct ProcessNames {
+    pub name: Arc<str>,
+    pub command: Arc<str>,
+    pub user: Arc<str>,
+}
+
+#[derive(Debug, Default)]
+pub struct NameCache {
+    // by pid and start time, so a reused pid gets its own names
+    processes: HashMap<(u32, u64), ProcessNames>,
+    // entries seen during the current refresh, swapped with `processes` when it ends
+    seen: HashMap<(u32, u64), ProcessNames>,
+    // when every process was last looked up again, and whether the current refresh does
+    looked_up: Option<Instant>,
+    lookup_all: bool,
+    cores: Vec<Arc<str>>,
+}
+
+impl NameCache {
+    // whether this refresh looks up every process again, so the caller can have the command
+    // lines and users read afresh for it
+    pub fn start_refresh(&mut self) -> bool {
+        self.lookup_all = self
+            .looked_up
+            .is_none_or(|looked_up| looked_up.elapsed() >= LOOKUP_INTERVAL);
+        self.lookup_all
+    }
+
+    // names of a process, `lookup` only runs for processes not seen before, whose name changed
+    // (e.g. after an exec) or all of them when start_refresh said so; it returns the command line
+    // and the user, which keep their Arc when they didn't change
+    pub fn process(
+        &mut self,
+        key: (u32, u64),
+        name: &str,
+        lookup: impl FnOnce() -> (String, String),
+    ) -> ProcessNames {
+        let names = match self.processes.remove(&key) {
+            Some(names) if &*names.name == name && !self.lookup_all => names,
+            Some(names) if &*names.name == name => {
+                let (command, user) = lookup();
+                ProcessNames {
+                    command: reuse(names.command, command),
+                    user: reuse(names.user, user),
+                    name: names.name,
+                }
+            }
+            _ => {
+                let (command, user) = lookup();
+                ProcessNames {
+                    name: Arc::from(name),
+                    command: Arc::from(command),
+                    user: Arc::from(user),
+                }
+            }
+        };
+        self.seen.insert(key, names.clone());
+        names
+    }
+
+    // forget the processes that weren't looked up since the previous call, i.e. have exited
+    pub fn finish_refresh(&mut self) {
+        std::mem::swap(&mut self.processes, &mut self.seen);
+        self.seen.clear();
+        if self.lookup_all {
+            self.looked_up = Some(Instant::now());
+            self.lookup_all = false;
+        }
+    }
+
+    pub fn core(&mut self, index: usize, name: &str) -> Arc<str> {
+        match self.cores.get(index) {
+            Some(cached) if &**cached == name => cached.clone(),
+            _ => {
+                let name: Arc<str> = Arc::from(name);
+                if index < self.cores.len() {
+                    self.cores[index] = name.clone();
+                } else {
+                    self.cores.push(name.clone());
+                }
+                name
+            }
+        }
+    }
+}
+
+fn reuse(cached: Arc<str>, fresh: String) -> Arc<str> {
+    if *cached == *fresh {
+        cached
+    } else {
+        Arc::from(fresh)
+    }
+}
@@ -0,0 +1,443 @@
+// Offline descriptions of common processes, to answer "what is this and is it safe to kill?"
+// without a network connection
+
+// What ending a process does to the rest of the system
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KillAdvice {
+    Safe,
+    Restarts,
+    Avoid,
+}
+
+impl std::fmt::Display for KillAdvice {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            KillAdvice::Safe => write!(f, "Safe to end, only the program itself is affected"),
+            KillAdvice::Restarts => write!(
+                f,
+                "Usually started again by the system, ending it briefly interrupts what it provides"
+            ),
+            KillAdvice::Avoid => write!(
+                f,
+                "Don't end it, the system or the desktop session depends on it"
+            ),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct KnownProcess {
+    pub name: &'static str,
+    pub description: &'static str,
+    pub advice: KillAdvice,
+}
+
+const fn known(name: &'static str, description: &'static str, advice: KillAdvice) -> KnownProcess {
+    KnownProcess {
+        name,
+        description,
+        advice,
+    }
+}
+
+// names are matched without case and without a trailing .exe
+const KNOWN_PROCESSES: &[KnownProcess] = &[
+    // Linux kernel threads, whose names carry a CPU or queue after a slash
+    known(
+        "kthreadd",
+        "Kernel thread that starts all other kernel threads",
+        KillAdvice::Avoid,
+    ),
+    known(
+        "kworker",
+        "Kernel worker thread running deferred kernel work",
+        KillAdvice::Avoid,
+    ),
+    known(
+        "ksoftirqd",
+        "Kernel thread handling software interrupts of one CPU",
+        KillAdvice::Avoid,
+    ),
+    known(
+        "migration",
+        "Kernel thread moving tasks between CPUs",
+        KillAdvice::Avoid,
+    ),
+    known(
+        "rcu_sched",
+        "Kernel thread of the RCU synchronization mechanism",
+        KillAdvice::Avoid,
+    ),
+    known(
+        "rcu_preempt",
+        "Kernel thread of the RCU synchronization mechanism",
+        KillAdvice::Avoid,
+    ),
+    known(
+        "kswapd0",
+        "Kernel thread that frees memory by swapping pages out",
+        KillAdvice::Avoid,
+    ),
+    known(
+        "jbd2",
+        "Kernel thread writing the ext4 journal",
+        KillAdvice::Avoid,
+    ),
+    // Linux services
+    known(
+        "systemd",
+        "Init system and service manager (PID 1), or a user's service manager",
+        KillAdvice::Avoid,
+    ),
+    known(
+        "init",
+        "First process of the system, starts and supervises everything else",
+        KillAdvice::Avoid,
+    ),
+    known(
+        "systemd-journald",
+        "Collects and stores the system log",
+        KillAdvice::Restarts,
+    ),
+    known(
+        "systemd-logind",
+        "Tracks user logins and seats, handles power keys",
+        KillAdvice::Avoid,
+    ),
+    known(
+        "systemd-udevd",
+        "Handles devices being added or removed",
+        KillAdvice::Restarts,
+    ),
+    known(
+        "systemd-resolved",
+        "Local DNS resolver and cache",
+        KillAdvice::Restarts,
+    ),
+    known(
+        "systemd-timesyncd",
+        "Keeps the clock in sync over NTP",
+        KillAdvice::Restarts,
+    ),
+    known(
+        "systemd-networkd",
+        "Configures network interfaces",
+        KillAdvice::Restarts,
+    ),
+    known(
+        "dbus-daemon",
+        "Message bus that system services and desktop programs talk over",
+        KillAdvice::Avoid,
+    ),
+    known(
+        "dbus-broker",
+        "Message bus that system services and desktop programs talk over",
+        KillAdvice::Avoid,
+    ),
+    known(
+        "polkitd",
+        "Decides which users may perform privileged actions",
+        KillAdvice::Avoid,
+    ),
+    known(
+        "networkmanager",
+        "Manages network connections (Wi-Fi, Ethernet, VPN)",
+        KillAdvice::Restarts,
+    ),
+    known(
+        "wpa_supplicant",
+        "Wi-Fi authentication",
+        KillAdvice::Restarts,
+    ),
+    known(
+        "sshd",
+        "SSH server accepting remote logins",
+        KillAdvice::Restarts,
+    ),
+    known("cron", "Runs scheduled jobs", KillAdvice::Restarts),
+    known("crond", "Runs scheduled jobs", KillAdvice::Restarts),
+    known("rsyslogd", "System log daemon", KillAdvice::Restarts),
+    known(
+        "chronyd",
+        "Keeps the clock in sync over NTP",
+        KillAdvice::Restarts,
+    ),
+    known(
+        "ntpd",
+        "Keeps the clock in sync over NTP",
+        KillAdvice::Restarts,
+    ),
+    known("cupsd", "Print server", KillAdvice::Restarts),
+    known(
+        "udisksd",
+        "Mounts and manages disks for the desktop",
+        KillAdvice::Restarts,
+    ),
+    known(
+        "upowerd",
+        "Reports battery and power supply status",
+        KillAdvice::Restarts,
+    ),
+    known(
+        "snapd",
+        "Installs and updates snap packages",
+        KillAdvice::Restarts,
+    ),
+    known(
+        "packagekitd",
+        "Package installs and update checks for the desktop",
+        KillAdvice::Safe,
+    ),
+    known(
+        "dockerd",
+        "Docker engine, ending it stops the containers it runs",
+        KillAdvice::Restarts,
+    ),
+    known(
+        "containerd",
+        "Container runtime used by Docker and Kubernetes",
+        KillAdvice::Restarts,
+    ),
+    known(
+        "kubelet",
+        "Kubernetes node agent running the pods of this machine",
+        KillAdvice::Restarts,
+    ),
+    // Linux desktop
+    known(
+        "xorg",
+        "X display server, ending it closes the graphical session",
+        KillAdvice::Avoid,
+    ),
+    known(
+        "xwayland",
+        "Runs X11 programs on a Wayland desktop",
+        KillAdvice::Avoid,
+    ),
+    known(
+        "gnome-shell",
+        "GNOME desktop shell and compositor",
+        KillAdvice::Avoid,
+    ),
+    known(
+        "kwin_wayland",
+        "KDE window manager and compositor",
+        KillAdvice::Avoid,
+    ),
+    known(
+        "plasmashell",
+        "KDE panels and desktop",
+        KillAdvice::Restarts,
+    ),
+    known("pulseaudio", "Sound server", KillAdvice::Restarts),
+    known("pipewire", "Audio and video server", KillAdvice::Restarts),
+    known(
+        "wireplumber",
+        "Session manager for PipeWire",
+        KillAdvice::Restarts,
+    ),
+    known(
+        "gvfsd",
+        "Virtual file systems for the desktop (network shares, phones)",
+        KillAdvice::Restarts,
+    ),
+    known(
+        "tracker-miner-fs-3",
+        "GNOME file indexer for search",
+        KillAdvice::Safe,
+    ),
+    known(
+        "baloo_file",
+        "KDE file indexer for search",
+        KillAdvice::Safe,
+    ),
+    // macOS
+    known(
+        "kernel_task",
+        "The macOS kernel, also shows time spent cooling the CPU down",
+        KillAdvice::Avoid,
+    ),
+    known(
+        "launchd",
+        "macOS init system and service manager",
+        KillAdvice::Avoid,
+    ),
+    known(
+        "windowserver",
+        "Draws everything on the macOS screen",
+        KillAdvice::Avoid,
+    ),
+    known("mds", "Spotlight search index server", KillAdvice::Restarts),
+    known(
+        "mds_stores",
+        "Spotlight search index storage",
+        KillAdvice::Restarts,
+    ),
+    known("mdworker", "Spotlight indexing worker", KillAdvice::Safe),
+    known(
+        "mdworker_shared",
+        "Spotlight indexing worker",
+        KillAdvice::Safe,
+    ),
+    known(
+        "finder",
+        "macOS file manager and desktop",
+        KillAdvice::Restarts,
+    ),
+    known(
+        "dock",
+        "macOS Dock, app switcher and Mission Control",
+        KillAdvice::Restarts,
+    ),
+    known("coreaudiod", "macOS sound server", KillAdvice::Restarts),
+    known("cloudd", "iCloud sync", KillAdvice::Restarts),
+    known(
+        "photoanalysisd",
+        "Analyses the Photos library for faces and objects",
+        KillAdvice::Safe,
+    ),
+    known("backupd", "Time Machine backups", KillAdvice::Safe),
+    // Windows
+    known(
+        "system",
+        "The Windows kernel and drivers",
+        KillAdvice::Avoid,
+    ),
+    known(
+        "registry",
+        "Holds the Windows registry in memory",
+        KillAdvice::Avoid,
+    ),
+    known("smss", "Windows session manager", KillAdvice::Avoid),
+    known(
+        "csrss",
+        "Windows client/server runtime, ending it crashes Windows",
+        KillAdvice::Avoid,
+    ),
+    known(
+        "wininit",
+        "Starts the Windows services and logon",
+        KillAdvice::Avoid,
+    ),
+    known(
+        "winlogon",
+        "Handles logon, logoff and Ctrl+Alt+Del",
+        KillAdvice::Avoid,
+    ),
+    known(
+        "services",
+        "Windows service control manager",
+        KillAdvice::Avoid,
+    ),
+    known(
+        "lsass",
+        "Checks passwords and security policy, ending it restarts Windows",
+        KillAdvice::Avoid,
+    ),
+    known(
+        "svchost",
+        "Hosts Windows services, see the command line for which group",
+        KillAdvice::Avoid,
+    ),
+    known(
+        "explorer",
+        "Windows taskbar, Start menu and file manager",
+        KillAdvice::Restarts,
+    ),
+    known(
+        "dwm",
+        "Desktop window manager, draws the Windows desktop",
+        KillAdvice::Restarts,
+    ),
+    known(
+        "msmpeng",
+        "Microsoft Defender antivirus engine",
+        KillAdvice::Avoid,
+    ),
+    known(
+        "searchindexer",
+        "Windows Search indexer",
+        KillAdvice::Restarts,
+    ),
+    known(
+        "runtimebroker",
+        "Checks permissions of Store apps",
+        KillAdvice::Safe,
+    ),
+    known("spoolsv", "Windows print spooler", KillAdvice::Restarts),
+    known(
+        "taskhostw",
+        "Host for Windows scheduled tasks",
+        KillAdvice::Restarts,
+    ),
+    known(
+        "conhost",
+        "Console window host, ending it closes its console",
+        KillAdvice::Safe,
+    ),
+    known("tiworker", "Installs Windows updates", KillAdvice::Avoid),
+    // common programs
+    known(
+        "chrome",
+        "Google Chrome browser (one process per tab or extension)",
+        KillAdvice::Safe,
+    ),
+    known("firefox", "Mozilla Firefox browser", KillAdvice::Safe),
+    known("msedge", "Microsoft Edge browser", KillAdvice::Safe),
+    known("code", "Visual Studio Code editor", KillAdvice::Safe),
+    known(
+        "java",
+        "Java virtual machine, see the command line for the program it runs",
+        KillAdvice::Safe,
+    ),
+    known(
+        "python3",
+        "Python interpreter, see the command line for the script it runs",
+        KillAdvice::Safe,
+    ),
+    known(
+        "python",
+        "Python interpreter, see the command line for the script it runs",
+        KillAdvice::Safe,
+    ),
+    known(
+        "node",
+        "Node.js runtime, see the command line for the script it runs",
+        KillAdvice::Safe,
+    ),
+    known(
+        "bash",
+        "Shell, ending it closes its terminal and the jobs started from it",
+        KillAdvice::Safe,
+    ),
+    known(
+        "zsh",
+        "Shell, ending it closes its terminal and the jobs started from it",
+        KillAdvice::Safe,
+    ),
+];
+
+// the entry for a process name, trying the part before a '/' for kernel threads like
+// kworker/0:1
+pub fn lookup(name: &str) -> Option<&'static KnownProcess> {
+    let name = name.to_lowercase();
+    let name = name.strip_suffix(".exe").unwrap_or(&name);
+    let find = |name: &str| KNOWN_PROCESSES.iter().find(|known| known.name == name);
+
+    find(name).or_else(|| find(name.split('/').next()?))
+}
+
+// web search for processes that aren't in the table, or for more detail
+pub fn search_url(name: &str) -> String {
+    let query: String = format!("{} process", name)
+        .bytes()
+        .map(|byte| match byte {
+            b'a'..=b'z' | b'A'..=b'Z' | b'0'..=b'9' | b'-' | b'.' | b'_' => {
+                (byte as char).to_string()
+            }
+            b' ' => String::from("+"),
+            _ => format!("%{:02X}", byte),
+        })
+        .collect();
+    format!("https://duckduckgo.com/?q={}", query)
+}
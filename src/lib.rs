@@ -0,0 +1,82 @@
+// Library side of the monitor: the collector, headless modes and the iced app, plus
+// widgets (`widgets`, `chart`) that other iced applications can embed
+
+pub mod alerts;
+mod automation;
+mod background;
+#[cfg(feature = "gui")]
+mod cgroups;
+#[cfg(feature = "gui")]
+pub mod chart;
+pub mod cli;
+mod collector;
+mod comparison;
+pub mod config;
+#[cfg(feature = "gui")]
+mod delta;
+mod diagnosis;
+#[cfg(feature = "gui")]
+mod digest;
+mod disk_rates;
+mod disks;
+mod emergency;
+mod filter;
+pub mod format;
+mod gpu;
+pub mod headless;
+mod heartbeat;
+#[cfg(feature = "gui")]
+mod hints;
+mod history;
+mod identity;
+mod idle;
+mod integrity;
+#[cfg(feature = "gui")]
+mod interface_actions;
+mod intern;
+#[cfg(feature = "gui")]
+mod known_processes;
+mod lifetimes;
+mod logging;
+pub mod mock;
+pub mod models;
+mod pacing;
+#[cfg(feature = "parquet")]
+mod parquet_log;
+mod pi;
+mod power;
+#[cfg(feature = "gui")]
+mod process_actions;
+mod procfs;
+mod prometheus;
+#[cfg(feature = "gui")]
+mod quick_controls;
+pub mod recording;
+#[cfg(feature = "gui")]
+mod report;
+mod respawn;
+#[cfg(feature = "gui")]
+mod rows;
+mod runaway;
+mod sampler;
+#[cfg(feature = "gui")]
+mod screenshot;
+mod sensors;
+pub mod service;
+#[cfg(feature = "gui")]
+mod setup;
+mod shutdown;
+pub mod sinks;
+mod space_scan;
+mod stats;
+#[cfg(feature = "gui")]
+mod system_monitor;
+mod timesync;
+mod updates;
+mod utils;
+mod wear;
+#[cfg(feature = "gui")]
+pub mod widgets;
+
+// the log writer takes extra sinks (see `sinks`), configured with the log's format and compression
+pub use logging::{LogCompression, LogFormat, LogWriter};
@@ -0,0 +1,104 @@
+// When each process was first and last seen while monitoring, for the process timeline that
+// shows which processes came and went during an incident
+use crate::models::Process;
+use crate::utils::current_timestamp;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Instant;
+
+// exited processes kept for the timeline, the oldest go first; short-lived commands in a
+// busy shell script would otherwise pile up for the whole session
+const MAX_EXITED: usize = 500;
+
+#[derive(Debug, Clone)]
+pub struct Lifetime {
+    pub pid: u32,
+    pub name: Arc<str>,
+    // seconds since the tracker started
+    pub first_seen: f64,
+    pub last_seen: f64,
+    pub running: bool,
+    // already running at the first refresh, so only its exit happened during the session
+    pub preexisting: bool,
+    pub peak_cpu_percent: f64,
+}
+
+#[derive(Debug)]
+pub struct LifetimeTracker {
+    started: Instant,
+    pub started_at: String,
+    // keyed by pid and start time, like the process selection
+    lifetimes: HashMap<(u32, u64), Lifetime>,
+}
+
+impl Default for LifetimeTracker {
+    fn default() -> LifetimeTracker {
+        LifetimeTracker {
+            started: Instant::now(),
+            started_at: current_timestamp(),
+            lifetimes: HashMap::new(),
+        }
+    }
+}
+
+impl LifetimeTracker {
+    pub fn update(&mut self, processes: &[Process], now: Instant) {
+        let elapsed = self.elapsed(now);
+        let first_refresh = self.lifetimes.is_empty();
+
+        for lifetime in self.lifetimes.values_mut() {
+            lifetime.running = false;
+        }
+        for process in processes {
+            let lifetime = self
+                .lifetimes
+                .entry((process.id, process.start_time))
+                .or_insert_with(|| Lifetime {
+                    pid: process.id,
+                    name: process.name.clone(),
+                    first_seen: elapsed,
+                    last_seen: elapsed,
+                    running: true,
+                    preexisting: first_refresh,
+                    peak_cpu_percent: 0.,
+                });
+            lifetime.last_seen = elapsed;
+            lifetime.running = true;
+            lifetime.peak_cpu_percent = lifetime.peak_cpu_percent.max(process.cpu_usage_percent);
+        }
+
+        let exited = self.lifetimes.values().filter(|l| !l.running).count();
+        if exited > MAX_EXITED {
+            let mut last_seen: Vec<f64> = self
+                .lifetimes
+                .values()
+                .filter(|l| !l.running)
+                .map(|l| l.last_seen)
+                .collect();
+            last_seen.sort_by(f64::total_cmp);
+            let cutoff = last_seen[exited - MAX_EXITED];
+            self.lifetimes
+                .retain(|_, l| l.running || l.last_seen >= cutoff);
+        }
+    }
+
+    pub fn elapsed(&self, now: Instant) -> f64 {
+        now.duration_since(self.started).as_secs_f64()
+    }
+
+    // processes that started or exited during the session, in the order they appeared
+    pub fn changes(&self) -> Vec<&Lifetime> {
+        let mut changes: Vec<&Lifetime> = self
+            .lifetimes
+            .values()
+            .filter(|l| !(l.preexisting && l.running))
+            .collect();
+        changes.sort_by(|a, b| {
+            a.first_seen
+                .total_cmp(&b.first_seen)
+                .then(a.last_seen.total_cmp(&b.last_seen))
+                .then(a.pid.cmp(&b.pid))
+        });
+        changes
+    }
+}
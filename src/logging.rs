@@ -0,0 +1,651 @@
+// Appending samples to the log file
+use crate::config::Config;
+use crate::models::{AggregatedData, SessionEnd, SystemData, SystemMonitor};
+#[cfg(feature = "parquet")]
+use crate::parquet_log::ParquetLog;
+use crate::sinks::{LogSettings, MetricsSink};
+use crate::stats::MetricStats;
+use crate::utils::{collect_system_data, current_timestamp};
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::thread;
+
+pub const LOG_FILE: &str = "system_log.json";
+
+// File format of the metrics log
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum LogFormat {
+    #[default]
+    Json,
+    Parquet,
+}
+
+impl LogFormat {
+    pub const ALL: [LogFormat; 2] = [LogFormat::Json, LogFormat::Parquet];
+}
+
+impl std::fmt::Display for LogFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LogFormat::Json => write!(f, "JSON"),
+            LogFormat::Parquet => write!(f, "Parquet"),
+        }
+    }
+}
+
+// Compression applied to the JSON log
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum LogCompression {
+    #[default]
+    None,
+    Gzip,
+    Zstd,
+}
+
+impl LogCompression {
+    pub const ALL: [LogCompression; 3] = [
+        LogCompression::None,
+        LogCompression::Gzip,
+        LogCompression::Zstd,
+    ];
+
+    pub fn path(&self) -> String {
+        match self {
+            LogCompression::None => LOG_FILE.to_string(),
+            LogCompression::Gzip => format!("{}.gz", LOG_FILE),
+            LogCompression::Zstd => format!("{}.zst", LOG_FILE),
+        }
+    }
+}
+
+impl std::fmt::Display for LogCompression {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LogCompression::None => write!(f, "Uncompressed"),
+            LogCompression::Gzip => write!(f, "gzip"),
+            LogCompression::Zstd => write!(f, "zstd"),
+        }
+    }
+}
+
+// Encoder kept open between samples so the compressor can use the earlier lines
+// dropping it writes the end of the gzip member / zstd frame
+struct Encoder {
+    writer: Box<dyn Write + Send>,
+    // second handle to the same file, for fsync
+    file: File,
+}
+
+impl std::fmt::Debug for Encoder {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Encoder")
+    }
+}
+
+impl Encoder {
+    // appending starts a new gzip member / zstd frame, readers decode them back to back
+    fn open(path: &Path, compression: LogCompression) -> io::Result<Encoder> {
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        let handle = file.try_clone()?;
+
+        let writer: io::Result<Box<dyn Write + Send>> = match compression {
+            #[cfg(feature = "gzip")]
+            LogCompression::Gzip => Ok(Box::new(flate2::write::GzEncoder::new(
+                file,
+                flate2::Compression::default(),
+            ))),
+            #[cfg(feature = "zstd")]
+            LogCompression::Zstd => zstd::Encoder::new(file, 0)
+                .map(|encoder| Box::new(encoder.auto_finish()) as Box<dyn Write + Send>),
+            _ => Err(io::Error::other(format!(
+                "built without {} support (enable the `{}` feature)",
+                compression,
+                compression.to_string().to_lowercase()
+            ))),
+        };
+
+        Ok(Encoder {
+            writer: writer?,
+            file: handle,
+        })
+    }
+
+    // flushed after every line, so a crash only loses the end-of-stream marker
+    fn write_line(&mut self, line: &str, fsync: bool) -> io::Result<()> {
+        self.writer.write_all(line.as_bytes())?;
+        self.writer.flush()?;
+
+        if fsync {
+            self.file.sync_data()?;
+        }
+        Ok(())
+    }
+}
+
+// Records waiting for the log thread; once it falls this far behind, e.g. on a stalled network
+// filesystem, new records are dropped and counted instead of holding up the caller
+const LOG_QUEUE: usize = 64;
+
+#[derive(Debug)]
+enum LogCommand {
+    Sample(LogSettings, SystemData),
+    // aggregated and session-end records, serialized already
+    Record(LogSettings, String),
+    // write out anything buffered, answering on the sender when one is given
+    Flush(Option<mpsc::Sender<io::Result<()>>>),
+    // close the log files so the next record opens them again by name, e.g. after logrotate
+    // moved them
+    Reopen(mpsc::Sender<io::Result<()>>),
+    AddSink(Box<dyn MetricsSink>),
+}
+
+// Sinks owned by the log thread, the log file first
+#[derive(Debug)]
+struct LogSink {
+    sinks: Vec<Box<dyn MetricsSink>>,
+}
+
+impl LogSink {
+    fn run(mut self, commands: mpsc::Receiver<LogCommand>, errors: mpsc::Sender<io::Error>) {
+        for command in commands {
+            match command {
+                LogCommand::Sample(settings, data) => {
+                    self.each(&errors, |sink| sink.write_sample(&settings, &data))
+                }
+                LogCommand::Record(settings, line) => {
+                    self.each(&errors, |sink| sink.write_record(&settings, &line))
+                }
+                LogCommand::Flush(Some(done)) => {
+                    let _ = done.send(self.all(|sink| sink.flush()));
+                }
+                LogCommand::Flush(None) => self.each(&errors, |sink| sink.flush()),
+                LogCommand::Reopen(done) => {
+                    let _ = done.send(self.all(|sink| sink.rotate()));
+                }
+                LogCommand::AddSink(sink) => self.sinks.push(sink),
+            }
+        }
+
+        // the sending side is gone, finish the files before the thread ends
+        if let Err(e) = self.all(|sink| sink.flush()) {
+            eprintln!("Failed to write log file: {}", e);
+        }
+    }
+
+    // a failing sink doesn't keep the record from the others
+    fn each(
+        &mut self,
+        errors: &mpsc::Sender<io::Error>,
+        mut write: impl FnMut(&mut dyn MetricsSink) -> io::Result<()>,
+    ) {
+        for sink in &mut self.sinks {
+            if let Err(e) = write(sink.as_mut()) {
+                let _ = errors.send(e);
+            }
+        }
+    }
+
+    // like each, answering with the first failure
+    fn all(
+        &mut self,
+        mut write: impl FnMut(&mut dyn MetricsSink) -> io::Result<()>,
+    ) -> io::Result<()> {
+        let mut result = Ok(());
+        for sink in &mut self.sinks {
+            if let Err(e) = write(sink.as_mut()) {
+                result = result.and(Err(e));
+            }
+        }
+        result
+    }
+}
+
+// The JSON or Parquet log in the working directory, in the format the config asks for
+#[derive(Debug, Default)]
+struct FileLog {
+    encoder: Option<(LogCompression, Encoder)>,
+    #[cfg(feature = "parquet")]
+    parquet: ParquetLog,
+    // format and directory of the previous record, a switch finishes the old output
+    format: Option<(LogFormat, PathBuf)>,
+}
+
+impl MetricsSink for FileLog {
+    fn write_sample(&mut self, settings: &LogSettings, sample: &SystemData) -> io::Result<()> {
+        match settings.format {
+            LogFormat::Json => self.write_json(settings, &serde_json::to_string(sample)?),
+            #[cfg(feature = "parquet")]
+            LogFormat::Parquet => {
+                self.switch_format(LogFormat::Parquet, settings);
+                self.parquet.push(sample, &settings.directory)
+            }
+            #[cfg(not(feature = "parquet"))]
+            LogFormat::Parquet => Err(io::Error::other(
+                "built without Parquet support (enable the `parquet` feature)",
+            )),
+        }
+    }
+
+    // only the JSON log has aggregated and session-end records
+    fn write_record(&mut self, settings: &LogSettings, serialized: &str) -> io::Result<()> {
+        self.write_json(settings, serialized)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.finish_encoder();
+        #[cfg(feature = "parquet")]
+        self.parquet.flush()?;
+        Ok(())
+    }
+
+    // uncompressed lines open the file for every write anyway, and Parquet files are only created
+    // when written
+    fn rotate(&mut self) -> io::Result<()> {
+        self.finish_encoder();
+        Ok(())
+    }
+}
+
+impl FileLog {
+    fn switch_format(&mut self, format: LogFormat, settings: &LogSettings) {
+        let current = (format, settings.directory.clone());
+        if self
+            .format
+            .as_ref()
+            .is_some_and(|previous| *previous != current)
+        {
+            if let Err(e) = self.flush() {
+                eprintln!("Failed to write log file: {}", e);
+            }
+        }
+        self.format = Some(current);
+    }
+
+    fn write_json(&mut self, settings: &LogSettings, serialized: &str) -> io::Result<()> {
+        self.switch_format(LogFormat::Json, settings);
+        let line = format!("{}\n", serialized);
+        if settings.compression == LogCompression::None {
+            self.finish_encoder();
+            append_line(settings, &line)
+        } else {
+            self.append_compressed(settings, &line)
+        }
+    }
+
+    fn finish_encoder(&mut self) {
+        self.encoder = None;
+    }
+
+    fn append_compressed(&mut self, settings: &LogSettings, line: &str) -> io::Result<()> {
+        let compression = settings.compression;
+        if self
+            .encoder
+            .as_ref()
+            .is_some_and(|(current, _)| *current != compression)
+        {
+            self.finish_encoder();
+        }
+
+        let path = settings.path(&compression.path());
+        if needs_rotation(settings, &path) {
+            self.finish_encoder();
+            rotate(settings, &path)?;
+        }
+
+        if self.encoder.is_none() {
+            self.encoder = Some((compression, Encoder::open(&path, compression)?));
+        }
+        if let Some((_, encoder)) = self.encoder.as_mut() {
+            encoder.write_line(line, settings.fsync)?;
+        }
+        Ok(())
+    }
+}
+
+// Log thread with its queue, started with the first record
+#[derive(Debug)]
+struct LogWorker {
+    commands: mpsc::SyncSender<LogCommand>,
+    // failures of queued writes, reported on the next call
+    errors: mpsc::Receiver<io::Error>,
+    thread: thread::JoinHandle<()>,
+}
+
+// Handle the collector and GUI write through; the files are written on a separate thread so a
+// slow disk never holds up a refresh or a frame
+#[derive(Debug, Default)]
+pub struct LogWriter {
+    // history samples already covered by an aggregated record
+    aggregated_until: u64,
+    worker: Option<LogWorker>,
+    // records dropped because the log thread was behind
+    dropped: u64,
+    // sinks added before the log thread started, it takes them along
+    sinks: Vec<Box<dyn MetricsSink>>,
+    // where the log files go, empty for the working directory
+    directory: PathBuf,
+    // failed writes reported by the log thread, so the integrity check sees ones already returned
+    failures: u64,
+    // samples and aggregated records handed to the log so far, dropped ones included so that
+    // they leave a gap in the numbering
+    records: u64,
+}
+
+impl LogWriter {
+    pub fn write(&mut self, config: &Config, data: &SystemData) -> io::Result<()> {
+        let settings = self.settings(config);
+        let data = SystemData {
+            sample_index: self.next_sample_index(),
+            ..data.clone()
+        };
+        self.queue(LogCommand::Sample(settings, data))
+    }
+
+    fn next_sample_index(&mut self) -> u64 {
+        self.records += 1;
+        self.records - 1
+    }
+
+    fn write_record(&mut self, config: &Config, serialized: &str) -> io::Result<()> {
+        let settings = self.settings(config);
+        self.queue(LogCommand::Record(settings, serialized.to_string()))
+    }
+
+    // like write_record, but waits for room in the queue instead of dropping the record
+    fn write_record_waiting(&mut self, config: &Config, serialized: &str) -> io::Result<()> {
+        let settings = self.settings(config);
+        self.send(LogCommand::Record(settings, serialized.to_string()))
+    }
+
+    fn settings(&self, config: &Config) -> LogSettings {
+        LogSettings {
+            directory: self.directory.clone(),
+            ..LogSettings::from_config(config)
+        }
+    }
+
+    pub fn in_directory(directory: PathBuf) -> LogWriter {
+        let mut log_writer = LogWriter::default();
+        log_writer.directory = directory;
+        log_writer
+    }
+
+    pub fn directory(&self) -> &Path {
+        &self.directory
+    }
+
+    // from the next record on, the log files are written to this directory instead
+    pub fn set_directory(&mut self, directory: PathBuf) {
+        self.directory = directory;
+    }
+
+    pub fn failures(&self) -> u64 {
+        self.failures
+    }
+
+    // from the next record on, every sample also goes to this sink
+    pub fn add_sink(&mut self, sink: Box<dyn MetricsSink>) -> io::Result<()> {
+        match self.worker {
+            Some(_) => self.send(LogCommand::AddSink(sink)),
+            None => {
+                self.sinks.push(sink);
+                Ok(())
+            }
+        }
+    }
+
+    fn send(&mut self, command: LogCommand) -> io::Result<()> {
+        self.take_error()?;
+        self.worker()?
+            .commands
+            .send(command)
+            .map_err(|_| io::Error::other("log thread has stopped"))
+    }
+
+    // start the next aggregated record after the given history sample count
+    pub fn skip_to(&mut self, pushed: u64) {
+        self.aggregated_until = pushed;
+    }
+
+    pub fn dropped(&self) -> u64 {
+        self.dropped
+    }
+
+    // ask the log thread to write out anything still buffered, e.g. when logging is switched off;
+    // doesn't wait for it, and is skipped while the queue is full (the thread flushes on exit)
+    pub fn flush(&mut self) -> io::Result<()> {
+        match &self.worker {
+            Some(worker) => {
+                let _ = worker.commands.try_send(LogCommand::Flush(None));
+                self.take_error()
+            }
+            None => Ok(()),
+        }
+    }
+
+    // wait until everything queued is on disk, for the end of the session
+    pub fn finish(&mut self) -> io::Result<()> {
+        self.wait_for(|done| LogCommand::Flush(Some(done)))
+    }
+
+    // close the open log file after what is already queued, waiting until that happened
+    pub fn reopen(&mut self) -> io::Result<()> {
+        self.wait_for(LogCommand::Reopen)
+    }
+
+    // send a command behind everything queued and wait for the thread's answer
+    fn wait_for(
+        &mut self,
+        command: impl FnOnce(mpsc::Sender<io::Result<()>>) -> LogCommand,
+    ) -> io::Result<()> {
+        let Some(worker) = &self.worker else {
+            return Ok(());
+        };
+        let (done, answer) = mpsc::channel();
+        worker
+            .commands
+            .send(command(done))
+            .map_err(|_| io::Error::other("log thread has stopped"))?;
+        let result = answer
+            .recv()
+            .unwrap_or_else(|_| Err(io::Error::other("log thread has stopped")));
+        self.take_error().and(result)
+    }
+
+    // queue a record without waiting, counting it as dropped when the queue is full
+    fn queue(&mut self, command: LogCommand) -> io::Result<()> {
+        self.take_error()?;
+        let worker = self.worker()?;
+        match worker.commands.try_send(command) {
+            Ok(()) => Ok(()),
+            Err(mpsc::TrySendError::Full(_)) => {
+                self.dropped += 1;
+                Ok(())
+            }
+            Err(mpsc::TrySendError::Disconnected(_)) => {
+                self.worker = None;
+                Err(io::Error::other("log thread has stopped"))
+            }
+        }
+    }
+
+    fn worker(&mut self) -> io::Result<&LogWorker> {
+        if self.worker.is_none() {
+            let (commands, queued) = mpsc::sync_channel(LOG_QUEUE);
+            let (errors, failures) = mpsc::channel();
+            let mut added = std::mem::take(&mut self.sinks);
+            let thread = thread::Builder::new()
+                .name(String::from("log-writer"))
+                .spawn(move || {
+                    let mut sinks: Vec<Box<dyn MetricsSink>> = vec![Box::new(FileLog::default())];
+                    sinks.append(&mut added);
+                    LogSink { sinks }.run(queued, errors)
+                })?;
+            self.worker = Some(LogWorker {
+                commands,
+                errors: failures,
+                thread,
+            });
+        }
+        Ok(self.worker.as_ref().expect("log worker was just started"))
+    }
+
+    // the first failure of a queued write since the last call
+    fn take_error(&mut self) -> io::Result<()> {
+        match self.worker.as_ref().map(|worker| worker.errors.try_recv()) {
+            Some(Ok(e)) => {
+                self.failures += 1;
+                Err(e)
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+impl Drop for LogWriter {
+    // let the thread write out what is still queued before the process exits
+    fn drop(&mut self) {
+        if let Some(worker) = self.worker.take() {
+            drop(worker.commands);
+            let _ = worker.thread.join();
+        }
+    }
+}
+
+fn needs_rotation(settings: &LogSettings, path: &Path) -> bool {
+    settings.rotate_mb > 0
+        && fs::metadata(path)
+            .map(|metadata| metadata.len() >= settings.rotate_mb * 1024 * 1024)
+            .unwrap_or(false)
+}
+
+// the n-th rotated file of a log, e.g. system_log.json.2; 1 is the most recent
+pub fn rotated_path(path: &Path, n: u32) -> PathBuf {
+    let mut rotated = path.as_os_str().to_owned();
+    rotated.push(format!(".{}", n));
+    PathBuf::from(rotated)
+}
+
+// move the full log aside, e.g. system_log.json -> system_log.json.1, after shifting the rotated
+// ones up (.1 -> .2 ...) so `rotate_keep` of them are kept and only the oldest is replaced;
+// renames replace their target atomically, so a crash leaves every file whole
+fn rotate(settings: &LogSettings, path: &Path) -> io::Result<()> {
+    for n in (1..settings.rotate_keep).rev() {
+        let older = rotated_path(path, n);
+        if older.exists() {
+            fs::rename(&older, rotated_path(path, n + 1))?;
+        }
+    }
+    fs::rename(path, rotated_path(path, 1))?;
+
+    // persist the rename itself, directories can't be opened for syncing on Windows
+    if settings.fsync && cfg!(unix) {
+        File::open(settings.path("."))?.sync_all()?;
+    }
+    Ok(())
+}
+
+fn append_line(settings: &LogSettings, line: &str) -> io::Result<()> {
+    let path = settings.path(LOG_FILE);
+    if needs_rotation(settings, &path) {
+        rotate(settings, &path)?;
+    }
+
+    // one write per line, so a crash can't interleave a partial sample with the next one
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    file.write_all(line.as_bytes())?;
+
+    if settings.fsync {
+        file.sync_data()?;
+    }
+    Ok(())
+}
+
+// min/avg/max/p95 of the samples taken since the previous record
+fn aggregate(samples: &[&SystemData]) -> Option<AggregatedData> {
+    let (first, last) = (samples.first()?, samples.last()?);
+
+    let mut cpu = MetricStats::default();
+    let mut memory = MetricStats::default();
+    let mut swap = MetricStats::default();
+    let mut disk = MetricStats::default();
+    for sample in samples {
+        cpu.push(sample.cpu_usage_percent as f64);
+        memory.push(sample.memory_usage_byte.0 as f64);
+        swap.push(sample.swap_memory_usage_byte.0 as f64);
+        disk.push(sample.disk_usage_byte.0 as f64);
+    }
+
+    Some(AggregatedData {
+        start_timestamp: first.timestamp.clone(),
+        timestamp: last.timestamp.clone(),
+        // numbered by the log writer
+        sample_index: 0,
+        samples: samples.len(),
+        cpu_usage_percent: cpu.summary()?,
+        memory_used_byte: memory.summary()?,
+        memory_total_byte: last.memory_usage_byte.1,
+        swap_used_byte: swap.summary()?,
+        swap_total_byte: last.swap_memory_usage_byte.1,
+        disk_used_byte: disk.summary()?,
+        disk_total_byte: last.disk_usage_byte.1,
+        network_sent_byte: last.network_sent_byte,
+        network_received_byte: last.network_received_byte,
+        host: last.host.clone(),
+    })
+}
+
+pub fn log_metrics(system_monitor: &mut SystemMonitor) -> io::Result<()> {
+    system_monitor.check_log_integrity();
+    let config = &system_monitor.config;
+    let log_writer = &mut system_monitor.log_writer;
+
+    // aggregated records summarize the 1 second samples kept in the history
+    if config.log_aggregate && config.log_format == LogFormat::Json {
+        let history = &system_monitor.history;
+        let samples: Vec<&SystemData> = history.since(log_writer.aggregated_until).collect();
+        log_writer.aggregated_until = history.pushed();
+
+        return match aggregate(&samples) {
+            Some(record) => {
+                let record = AggregatedData {
+                    sample_index: log_writer.next_sample_index(),
+                    ..record
+                };
+                log_writer.write_record(config, &serde_json::to_string(&record)?)
+            }
+            None => Ok(()),
+        };
+    }
+
+    let data = collect_system_data(system_monitor);
+    system_monitor
+        .log_writer
+        .write(&system_monitor.config, &data)
+}
+
+// mark the end of the session in the JSON log, Parquet files only hold samples
+pub fn log_session_end(system_monitor: &mut SystemMonitor) -> io::Result<()> {
+    if system_monitor.config.log_format != LogFormat::Json {
+        return Ok(());
+    }
+
+    let stats = &system_monitor.session_stats;
+    let record = SessionEnd {
+        session_end: current_timestamp(),
+        samples: system_monitor.history.pushed(),
+        cpu_usage_percent: stats.cpu_percent.summary(),
+        memory_usage_percent: stats.memory_percent.summary(),
+        network_sent_rate: stats.network_sent_rate.summary(),
+        network_received_rate: stats.network_received_rate.summary(),
+        dropped_log_records: system_monitor.log_writer.dropped(),
+        host: system_monitor.host_identity.clone(),
+    };
+    let serialized = serde_json::to_string(&record)?;
+    system_monitor
+        .log_writer
+        .write_record_waiting(&system_monitor.config, &serialized)
+}
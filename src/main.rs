@@ -1,10 +1,115 @@
-mod models;
-mod system_monitor;
-mod utils;
-use iced::Application;
-use iced::Settings;
-use models::SystemMonitor;
-
-fn main() -> iced::Result {
-    SystemMonitor::run(Settings::default())
+#[cfg(feature = "gui")]
+use iced::multi_window::Application;
+#[cfg(feature = "gui")]
+use iced::{window, Settings};
+use rust_system_monitor::config::Preset;
+#[cfg(feature = "gui")]
+use rust_system_monitor::models::SystemMonitor;
+#[cfg(feature = "gui")]
+use rust_system_monitor::recording::Replay;
+use rust_system_monitor::{cli, headless, service};
+
+fn main() {
+    let options = match cli::parse_args(std::env::args().skip(1)) {
+        Ok(options) => options,
+        Err(e) => {
+            eprintln!("{}\n\n{}", e, cli::USAGE);
+            std::process::exit(headless::EXIT_UNKNOWN);
+        }
+    };
+
+    if options.help {
+        println!("{}", cli::USAGE);
+        return;
+    }
+
+    if options.install_service || options.uninstall_service {
+        let result = if options.install_service {
+            service::install_service(options.interval_secs)
+        } else {
+            service::uninstall_service()
+        };
+        report(result);
+        return;
+    }
+
+    if let Some(path) = &options.import_rules {
+        report(headless::import_rule_file(path));
+        return;
+    }
+    if let Some(path) = &options.export_rules {
+        report(headless::export_rule_file(path));
+        return;
+    }
+
+    if options.metrics {
+        headless::run_metrics();
+        return;
+    }
+
+    // checks only make sense on a single snapshot
+    if options.once || !options.checks.is_empty() {
+        std::process::exit(headless::run_once(&options.checks));
+    }
+
+    if options.preset == Some(Preset::Agent) {
+        headless::run_agent(options.interval_secs);
+        return;
+    }
+
+    if options.stdout {
+        headless::run_stdout(options.interval_secs);
+        return;
+    }
+
+    if options.daemon {
+        headless::run_daemon(options.interval_secs);
+        return;
+    }
+
+    run_gui(options.replay);
+}
+
+// outcome of a one-off command, failures exit with 1
+fn report(result: Result<String, String>) {
+    match result {
+        Ok(message) => println!("{}", message),
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
+    }
+}
+
+#[cfg(feature = "gui")]
+fn run_gui(replay: Option<String>) {
+    let replay = replay.map(|path| match Replay::load(&path) {
+        Ok(replay) => replay,
+        Err(e) => {
+            eprintln!("Failed to read recording {}: {}", path, e);
+            std::process::exit(1);
+        }
+    });
+
+    // closing the main window is handled by the app, to finish the log before exiting
+    let result = SystemMonitor::run(Settings {
+        window: window::Settings {
+            exit_on_close_request: false,
+            ..window::Settings::default()
+        },
+        ..Settings::with_flags(replay)
+    });
+    if let Err(e) = result {
+        eprintln!("Failed to start the GUI: {}", e);
+        std::process::exit(1);
+    }
+}
+
+#[cfg(not(feature = "gui"))]
+fn run_gui(_replay: Option<String>) {
+    eprintln!(
+        "Built without the GUI (the `gui` feature), use --daemon, --stdout or --once\n\n{}",
+        cli::USAGE
+    );
+    std::process::exit(headless::EXIT_UNKNOWN);
 }
@@ -0,0 +1,34 @@
+// Scripted data source that stands in for the real system, e.g. to drive the app in tests
+use crate::models::Process;
+use std::collections::VecDeque;
+
+// Values reported by one refresh
+#[derive(Debug, Clone, Default)]
+pub struct MockTick {
+    pub cpu_usage: f32,
+    pub memory_usage: (u64, u64),
+    pub swap_memory_usage: (u64, u64),
+    pub processes: Vec<Process>,
+}
+
+#[derive(Debug, Default)]
+pub struct MockSource {
+    ticks: VecDeque<MockTick>,
+}
+
+impl MockSource {
+    pub fn new(ticks: impl IntoIterator<Item = MockTick>) -> MockSource {
+        MockSource {
+            ticks: ticks.into_iter().collect(),
+        }
+    }
+
+    pub fn push(&mut self, tick: MockTick) {
+        self.ticks.push_back(tick);
+    }
+
+    // values of the next refresh, None once the script has run out (the values then stay as they are)
+    pub fn next_tick(&mut self) -> Option<MockTick> {
+        self.ticks.pop_front()
+    }
+}
@@ -1,30 +1,530 @@
+use crate::alerts::{AlertEvent, AlertState, RuleForm};
+use crate::background::BackgroundRead;
+use crate::comparison::{Comparison, ComparisonPeriod};
+use crate::config::{Config, Preset};
+use crate::diagnosis::{Diagnosis, DiagnosisCapture};
+use crate::disk_rates::DiskRateHistory;
+use crate::disks::DiskTopology;
+use crate::emergency::PressureWatch;
+use crate::filter::ProcessFilter;
+use crate::gpu::GpuStatus;
+use crate::history::History;
+use crate::identity::HostIdentity;
+use crate::integrity::IntegrityMonitor;
+use crate::intern::NameCache;
+use crate::lifetimes::LifetimeTracker;
+use crate::logging::LogWriter;
+use crate::mock::MockSource;
+use crate::pacing::TickPacer;
+use crate::pi::PiStatus;
+use crate::power::BatteryInfo;
+use crate::procfs::{DiskCounters, InterruptCounts, ProtocolCounters};
+use crate::recording::{Recorder, Replay};
+use crate::respawn::RespawnTracker;
+use crate::runaway::RunawayTracker;
+use crate::sampler::Sampler;
+use crate::sensors::{SensorBackend, SensorReading};
+use crate::space_scan::{ScanGrouping, SpaceScan};
+use crate::stats::{SessionStats, Summary};
+use crate::timesync::TimeSync;
+use crate::updates::UpdateStatus;
+use crate::wear::WearLedger;
 use serde::{Deserialize, Serialize};
-use sysinfo::{Disks, Networks, System};
+use std::collections::{HashMap, VecDeque};
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime};
+use sysinfo::{Disks, Networks, System, Users};
+#[cfg(feature = "gui")]
+use {
+    crate::alerts::{AlertActionKind, AlertMetric},
+    crate::config::{SavedFilter, ThemeChoice},
+    crate::delta::{DeltaTracker, SnapshotDelta},
+    crate::digest::{AwayMark, Digest},
+    crate::format::NumberLocale,
+    crate::hints::HintKind,
+    crate::logging::{LogCompression, LogFormat},
+    crate::quick_controls::QuickControl,
+    crate::rows::PanelRows,
+    crate::setup::SetupWizard,
+    iced::window::{self, Screenshot},
+    iced::Size,
+    std::collections::HashSet,
+};
 
 // Messages for the application
+#[cfg(feature = "gui")]
 #[derive(Debug, Clone)]
 pub enum Message {
     IntervalChanged(String),
     Tick,
     ToggleMonitoring,
     ToggleSaveToFile(bool),
+    ToggleLogFsync(bool),
+    ToggleLogAggregate(bool),
+    LogFormatSelected(LogFormat),
+    LogCompressionSelected(LogCompression),
     LogToFile,
+    ReloadConfig,
+    TabSelected(Tab),
+    TogglePanelPause(Panel),
+    TogglePanelMaximized(Panel),
+    // Esc, back to the dashboard layout from a maximized panel
+    RestoreLayout,
+    DetachPanel(Panel),
+    WindowClosed(window::Id),
+    WindowFocused(window::Id),
+    WindowMoved(window::Id, i32, i32),
+    WindowResized(window::Id, u32),
+    ToggleCpuStrip,
+    ToggleRecording,
+    DragCpuStrip,
+    WindowUnfocused(window::Id),
+    DismissDigest,
+    StartDiagnosis,
+    CancelDiagnosis,
+    DismissDiagnosis,
+    CloseRequested(window::Id),
+    CheckShutdown,
+    ToggleMiniMode,
+    MiniModeEntered(Size),
+    DragMiniWindow,
+    QuickControlChanged(QuickControl, f32),
+    ApplyQuickControl(QuickControl),
+    TakeScreenshot,
+    CopyMetrics,
+    CopyReport,
+    ScreenshotTaken(Screenshot),
+    HistoryRangeSelected(f32, f32),
+    ClearHistorySelection,
+    ToggleShadeIdle(bool),
+    HistoryComparisonSelected(ComparisonPeriod),
+    // None when the thread reading the log couldn't start
+    ComparisonLoaded(ComparisonPeriod, Option<Result<Comparison, String>>),
+    ExportHistoryRange(ExportFormat),
+    AnnotationChanged(String),
+    AddAnnotation,
+    ResetSessionStats,
+    ProcessLimitChanged(ProcessLimit),
+    ProcessPageChanged(usize),
+    ProcessFilterChanged(String),
+    ToggleFilterRegex(bool),
+    SavedFilterSelected(SavedFilter),
+    SaveProcessFilter,
+    ProcessSortSelected(ProcessSort),
+    ToggleProcessSortReversed(bool),
+    ViewNameChanged(String),
+    SaveProcessView,
+    ApplyProcessView(usize),
+    DeleteProcessView(usize),
+    ToggleCommandColumn(bool),
+    HighlightSecsChanged(String),
+    ProcessRefreshSecsChanged(String),
+    DismissHint(HintKind),
+    ExplainProcess(u32),
+    SearchProcess(String),
+    AliasDeviceSelected(String),
+    AliasChanged(String),
+    SaveAlias,
+    InterfaceActionSelected(String),
+    WakeMacChanged(String),
+    SendWake,
+    SetLink(bool),
+    ConfirmLinkDown,
+    CancelLinkDown,
+    ToggleCoreHeatmap(bool),
+    DecimalPlacesSelected(usize),
+    NumberLocaleSelected(NumberLocale),
+    ToggleFollowSelection(bool),
+    ToggleTitleStats(bool),
+    AlertNameChanged(String),
+    AlertMetricSelected(AlertMetric),
+    AlertThresholdChanged(String),
+    AlertDurationChanged(String),
+    AlertCooldownChanged(String),
+    AlertActionSelected(AlertActionKind),
+    AlertActionCommandChanged(String),
+    AlertTemplateChanged(String),
+    AlertTemplateSubmitted,
+    AddAlertRule,
+    AddStealAlertTemplate,
+    ExportAlertRules(ExportFormat),
+    RuleFileChanged(String),
+    ImportAlertRules,
+    ToggleAlertRule(usize, bool),
+    DeleteAlertRule(usize),
+    ProcessSelected(u32, bool),
+    SelectVisibleProcesses,
+    ClearProcessSelection,
+    KillSelectedProcesses,
+    // pid and start time of the process as the GPU list read it
+    KillGpuProcess(u32, u64),
+    NiceValueChanged(String),
+    ReniceSelectedProcesses,
+    QuotaCpuChanged(String),
+    QuotaMemoryChanged(String),
+    LimitProcess(u32),
+    // pid and the unit it was limited on, or why not
+    ProcessLimited(u32, Option<Result<String, String>>),
+    SpaceScanMountSelected(String),
+    SpaceScanGroupingSelected(ScanGrouping),
+    StartSpaceScan,
+    CancelSpaceScan,
+    // redraw with the scan's latest progress
+    PollSpaceScan,
+    SetChargeLimit(String, u8),
+    CopyProcessTable,
+    ExportProcessesCsv,
+    SetupIntervalChanged(String),
+    SetupPanelToggled(Panel, bool),
+    SetupSaveToFileToggled(bool),
+    SetupLogFormatSelected(LogFormat),
+    SetupThemeSelected(ThemeChoice),
+    SetupBack,
+    SetupNext,
+    SetupSkip,
+}
+
+// How many processes are shown per page of the process panel
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProcessLimit {
+    Top50,
+    Top100,
+    All,
+}
+
+impl ProcessLimit {
+    pub const ALL: [ProcessLimit; 3] =
+        [ProcessLimit::Top50, ProcessLimit::Top100, ProcessLimit::All];
+
+    // None means every process is shown on a single page
+    pub fn page_size(&self) -> Option<usize> {
+        match self {
+            ProcessLimit::Top50 => Some(50),
+            ProcessLimit::Top100 => Some(100),
+            ProcessLimit::All => None,
+        }
+    }
+}
+
+impl std::fmt::Display for ProcessLimit {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ProcessLimit::Top50 => write!(f, "Top 50"),
+            ProcessLimit::Top100 => write!(f, "Top 100"),
+            ProcessLimit::All => write!(f, "All"),
+        }
+    }
+}
+
+// Order of the process list
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ProcessSort {
+    #[default]
+    Memory,
+    Cpu,
+    CpuTime,
+    Name,
+    Pid,
+}
+
+impl ProcessSort {
+    pub const ALL: [ProcessSort; 5] = [
+        ProcessSort::Memory,
+        ProcessSort::Cpu,
+        ProcessSort::CpuTime,
+        ProcessSort::Name,
+        ProcessSort::Pid,
+    ];
+
+    // usages and CPU time put the largest first, names and PIDs the smallest
+    pub fn compare(self, a: &Process, b: &Process) -> std::cmp::Ordering {
+        match self {
+            ProcessSort::Memory => b.memory_usage_percent.total_cmp(&a.memory_usage_percent),
+            ProcessSort::Cpu => b.cpu_usage_percent.total_cmp(&a.cpu_usage_percent),
+            ProcessSort::CpuTime => b.cpu_time_secs.total_cmp(&a.cpu_time_secs),
+            ProcessSort::Name => a.name.to_lowercase().cmp(&b.name.to_lowercase()),
+            ProcessSort::Pid => a.id.cmp(&b.id),
+        }
+    }
+}
+
+// Sort of the process list, reversed turns its usual direction around
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProcessOrder {
+    pub sort: ProcessSort,
+    #[serde(default)]
+    pub reversed: bool,
+}
+
+impl ProcessOrder {
+    pub fn compare(self, a: &Process, b: &Process) -> std::cmp::Ordering {
+        let ordering = self.sort.compare(a, b);
+        if self.reversed {
+            ordering.reverse()
+        } else {
+            ordering
+        }
+    }
+}
+
+impl std::fmt::Display for ProcessSort {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ProcessSort::Memory => write!(f, "Sort: Memory"),
+            ProcessSort::Cpu => write!(f, "Sort: CPU"),
+            ProcessSort::CpuTime => write!(f, "Sort: CPU Time"),
+            ProcessSort::Name => write!(f, "Sort: Name"),
+            ProcessSort::Pid => write!(f, "Sort: PID"),
+        }
+    }
+}
+
+// Tabs of the main window
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Tab {
+    #[default]
+    Dashboard,
+    History,
+    Interrupts,
+    Alerts,
+    Timeline,
+}
+
+// Panels of the dashboard that can be hidden
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Panel {
+    Cpu,
+    Memory,
+    Disk,
+    Network,
+    Processes,
+    SessionStats,
+    Users,
+    Sensors,
+    Gpu,
+    RaspberryPi,
+    Diagnostics,
+}
+
+impl Panel {
+    pub const ALL: [Panel; 11] = [
+        Panel::Cpu,
+        Panel::Memory,
+        Panel::Disk,
+        Panel::Network,
+        Panel::Processes,
+        Panel::SessionStats,
+        Panel::Users,
+        Panel::Sensors,
+        Panel::Gpu,
+        Panel::RaspberryPi,
+        Panel::Diagnostics,
+    ];
+}
+
+impl std::fmt::Display for Panel {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Panel::Cpu => write!(f, "CPU"),
+            Panel::Memory => write!(f, "Memory"),
+            Panel::Disk => write!(f, "Disk"),
+            Panel::Network => write!(f, "Network"),
+            Panel::Processes => write!(f, "Processes"),
+            Panel::SessionStats => write!(f, "Session Statistics"),
+            Panel::Users => write!(f, "Users"),
+            Panel::Sensors => write!(f, "Sensors"),
+            Panel::Gpu => write!(f, "GPU"),
+            Panel::RaspberryPi => write!(f, "Raspberry Pi"),
+            Panel::Diagnostics => write!(f, "Diagnostics"),
+        }
+    }
+}
+
+// File formats for exporting history and alert rules
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    Csv,
+    Json,
+}
+
+// Values shown by the dashboard panels, kept while a panel is paused
+#[derive(Debug, Clone)]
+pub struct PanelSnapshot {
+    pub cpu_usage: f32,
+    pub cpu_steal_percent: f64,
+    pub no_of_processes: u32,
+    pub processors_info: Vec<(Arc<str>, f32, u64)>,
+    pub memory_usage: (u64, u64),
+    pub memory_cache: u64,
+    pub swap_memory_usage: (u64, u64),
+    pub disk_usage: (u64, u64),
+    pub disks_info: Vec<DisksInfo>,
+    pub disk_io: Vec<DiskIo>,
+    pub network_sent: u64,
+    pub network_received: u64,
+    pub network_sent_rate: f64,
+    pub network_received_rate: f64,
+    pub tcp_states: Option<TcpStates>,
+    pub processes: Vec<Process>,
+    pub sensors: Vec<SensorReading>,
+    pub gpu: GpuStatus,
+    pub pi: Option<PiStatus>,
+    pub tick_pacer: TickPacer,
+    #[cfg(feature = "gui")]
+    pub delta: SnapshotDelta,
 }
 
 // Struct for serializing and deserializing system data
-#[derive(Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SystemData {
+    // UTC
     pub timestamp: String,
+    // the same moment in local time with its offset; empty in logs written before it was added
+    #[serde(default)]
+    pub local_timestamp: String,
+    // counts the records of the log, stream or export the sample was written to, so a gap means
+    // records were missed even when the clock was set back or forward in between
+    #[serde(default)]
+    pub sample_index: u64,
+    // counts the refreshes since the monitor started; steps by more than one between records
+    // when they are written less often than the monitor refreshes
+    #[serde(default)]
+    pub refresh_index: u64,
     pub cpu_usage_percent: f32,
     pub memory_usage_byte: (u64, u64),
+    // page cache and buffers the system can reclaim, counted in neither used nor free memory
+    #[serde(default)]
+    pub memory_cache_byte: u64,
     pub swap_memory_usage_byte: (u64, u64),
     pub disk_usage_byte: (u64, u64),
     pub network_sent_byte: u64,
     pub network_received_byte: u64,
+    // reads and writes per second of each block device, missing in logs written before IOPS
+    // were kept per device
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub disk_iops: Vec<DeviceIops>,
+    #[serde(default)]
+    pub tcp_states: TcpStates,
+    // no keyboard or mouse input for a while, None where that can't be told
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub user_idle: Option<bool>,
+    // host_name, os and machine_id, shared by every sample of a run
+    #[serde(flatten)]
+    pub host: Arc<HostIdentity>,
+}
+
+// Cores a selected process may run on and the ones its threads ran on since the previous
+// refresh, highlighted in the per-core view (Linux)
+#[derive(Debug, Clone, Default)]
+pub struct CoreAffinity {
+    pub pid: u32,
+    pub allowed: Vec<u32>,
+    pub running: Vec<u32>,
+    // CPU time of each thread at this read in clock ticks, a thread whose count went up ran since
+    pub thread_ticks: HashMap<u32, u64>,
+}
+
+impl CoreAffinity {
+    // whether the process may use the core with this name, e.g. "cpu3", and whether it ran there
+    pub fn core(&self, name: &str) -> (bool, bool) {
+        let number = name.trim_start_matches(|c: char| !c.is_ascii_digit());
+        match number.parse::<u32>() {
+            Ok(number) => (
+                self.allowed.contains(&number),
+                self.running.contains(&number),
+            ),
+            Err(_) => (true, false),
+        }
+    }
+}
+
+// Number of TCP sockets (IPv4 and IPv6) in the states worth watching
+// a growing close_wait count usually means an application isn't closing its sockets
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+pub struct TcpStates {
+    pub established: u64,
+    pub time_wait: u64,
+    pub close_wait: u64,
+    pub listen: u64,
+}
+
+// TCP segments and UDP datagrams per second, sent and received over IPv4 and IPv6
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+pub struct ProtocolRates {
+    pub tcp_packets: f64,
+    pub udp_packets: f64,
+}
+
+impl ProtocolRates {
+    // percent of the packets that were TCP, None without traffic
+    pub fn tcp_share(&self) -> Option<f64> {
+        let total = self.tcp_packets + self.udp_packets;
+        (total > 0.).then(|| self.tcp_packets / total * 100.)
+    }
+}
+
+// Sockets on one port
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PortCount {
+    pub protocol: String,
+    pub port: u16,
+    pub sockets: u64,
+}
+
+// Samples of one logging interval summarized into a single log record
+// totals and network counters are taken from the last sample
+#[derive(Debug, Clone, Serialize)]
+pub struct AggregatedData {
+    pub start_timestamp: String,
+    pub timestamp: String,
+    // numbered along with the samples of the same log
+    pub sample_index: u64,
+    pub samples: usize,
+    pub cpu_usage_percent: Summary,
+    pub memory_used_byte: Summary,
+    pub memory_total_byte: u64,
+    pub swap_used_byte: Summary,
+    pub swap_total_byte: u64,
+    pub disk_used_byte: Summary,
+    pub disk_total_byte: u64,
+    pub network_sent_byte: u64,
+    pub network_received_byte: u64,
+    #[serde(flatten)]
+    pub host: Arc<HostIdentity>,
+}
+
+// Last record of a log, written on a clean exit with statistics over the whole session
+#[derive(Debug, Clone, Serialize)]
+pub struct SessionEnd {
+    pub session_end: String,
+    pub samples: u64,
+    pub cpu_usage_percent: Option<Summary>,
+    pub memory_usage_percent: Option<Summary>,
+    pub network_sent_rate: Option<Summary>,
+    pub network_received_rate: Option<Summary>,
+    // records the log thread couldn't keep up with
+    pub dropped_log_records: u64,
+    #[serde(flatten)]
+    pub host: Arc<HostIdentity>,
+}
+
+// Interrupts handled since the previous refresh
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct InterruptInfo {
+    pub per_cpu: Vec<(String, u64)>,
+    // irq, description and count, busiest first
+    pub top_sources: Vec<(String, String, u64)>,
+}
+
+// Timestamped note shown as a marker on the charts
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Annotation {
+    pub timestamp: String,
+    pub label: String,
 }
 
 // Basic system info
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SystemBaseInfo {
     pub system_name: String,
     pub kernal_version: String,
@@ -33,9 +533,15 @@ pub struct SystemBaseInfo {
 }
 
 // Info for each disk
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DisksInfo {
     pub name: String,
+    // physical disk the partition lives on, e.g. nvme0n1
+    #[serde(default)]
+    pub device: String,
+    // what to call the partition, "Label (C:)" on Windows and the name elsewhere
+    #[serde(default)]
+    pub label: String,
     pub kind: String,
     pub mount: String,
     pub total_disk: u64,
@@ -43,47 +549,290 @@ pub struct DisksInfo {
     pub used_disk_percent: f64,
 }
 
-// Info for each process
+// Operations per second of one block device in a sample
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DeviceIops {
+    pub device: String,
+    pub read_iops: f64,
+    pub write_iops: f64,
+}
+
+// I/O of a block device since the previous refresh
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DiskIo {
+    pub name: String,
+    pub read_iops: f64,
+    pub write_iops: f64,
+    // bytes per second
+    pub read_rate: f64,
+    pub write_rate: f64,
+    // average time per completed request, None when nothing completed
+    pub latency_ms: Option<f64>,
+    // requests in flight right now, and the average over the interval
+    pub in_flight: u64,
+    pub queue_depth: f64,
+}
+
+// Open socket of a process
 #[derive(Debug, Clone)]
+pub struct Connection {
+    pub protocol: &'static str,
+    pub local: String,
+    pub remote: String,
+    pub state: &'static str,
+}
+
+// Info for each process
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Process {
     pub id: u32,
-    pub name: String,
+    // seconds since the epoch, tells a process apart from a later one that reuses its pid
+    pub start_time: u64,
+    pub name: Arc<str>,
+    pub command: Arc<str>,
+    // account the process runs as, the numeric id when the name is unknown
+    pub user: Arc<str>,
     pub cpu_usage_percent: f64,
+    // CPU seconds used since the process started, so busy processes that have gone idle stand out
+    pub cpu_time_secs: f64,
     pub memory_usage_percent: f64,
+    // bytes read and written per second since the previous process refresh, including what
+    // the page cache absorbed; missing in recordings made before it was measured
+    #[serde(default)]
+    pub disk_io_rate: f64,
+}
+
+// CPU and memory summed over the processes of one account
+#[derive(Debug, Clone)]
+pub struct UserTotal {
+    pub user: Arc<str>,
+    pub cpu_usage_percent: f64,
+    pub memory_usage_percent: f64,
+    pub processes: usize,
 }
 
 // SystemMonitor struct holding all system information
 #[derive(Debug)]
 pub struct SystemMonitor {
     pub system: System,
+    pub users: Users,
     pub disks: Disks,
     pub networks: Networks,
     pub system_base_info: SystemBaseInfo,
+    pub host_identity: Arc<HostIdentity>,
+    // None when no time service could be queried
+    pub time_sync: Option<TimeSync>,
+    pub time_sync_read: BackgroundRead<Option<TimeSync>>,
+    // the SSD wear ledger is saved once a minute
+    pub wear_saved: Instant,
+    // None where pending updates and reboots can't be detected
+    pub update_status: Option<UpdateStatus>,
+    pub update_read: BackgroundRead<Option<UpdateStatus>>,
+    // None on desktops and where the power source can't be read
+    pub on_battery: Option<bool>,
+    pub battery: Option<BatteryInfo>,
+    pub power_polled: Option<Instant>,
+    // outcome of the last charge limit change
+    pub power_status: String,
+    // overhead hints closed by the user, until the next start
+    #[cfg(feature = "gui")]
+    pub dismissed_hints: HashSet<HintKind>,
 
     // CPU info
     pub cpu_usage: f32,
+    pub cpu_steal_percent: f64,
+    // total and steal jiffies at the previous refresh
+    pub cpu_times: Option<(u64, u64)>,
     pub no_of_processes: u32,
     pub physical_cores: u32,
     pub logical_processors: u32,
-    pub processors_info: Vec<(String, f32)>,
+    // name, usage percent and frequency in MHz of each logical processor
+    pub processors_info: Vec<(Arc<str>, f32, u64)>,
+    // the same with the usage averaged over the last few refreshes, shown on the dashboard
+    pub smoothed_cores: Vec<(Arc<str>, f32, u64)>,
+    pub offline_cpus: Vec<u32>,
+    // link state of each network interface that reports one, true while up
+    pub link_states: HashMap<String, bool>,
+    pub interrupt_counts: Option<InterruptCounts>,
+    pub interrupt_info: InterruptInfo,
 
     // Memory
     pub memory_usage: (u64, u64),
+    // reclaimable page cache and buffers, see SystemData::memory_cache_byte
+    pub memory_cache: u64,
     pub swap_memory_usage: (u64, u64),
 
     // Disk
     pub disk_usage: (u64, u64),
     pub disks_info: Vec<DisksInfo>,
+    pub disk_counters: Option<DiskCounters>,
+    pub disk_io: Vec<DiskIo>,
+    pub disk_rates: DiskRateHistory,
+    // SSD writes counted across runs, saved to disk_wear.json
+    pub disk_wear: WearLedger,
+    pub disk_topology: DiskTopology,
+    // interface or disk being renamed, and the name typed for it
+    pub alias_device: Option<String>,
+    pub alias_input: String,
+    // space of a mount point by owner or directory, scanned when asked for
+    pub space_scan: Option<SpaceScan>,
+    pub space_scan_mount: Option<String>,
+    pub space_scan_grouping: ScanGrouping,
 
     // Network
     pub network_sent: u64,
     pub network_received: u64,
+    // bytes per second since the previous refresh
+    pub network_sent_rate: f64,
+    pub network_received_rate: f64,
+    // None where /proc/net/tcp isn't available
+    pub tcp_states: Option<TcpStates>,
+    pub protocol_counters: Option<ProtocolCounters>,
+    // TCP and UDP packet rates of recent refreshes, oldest first
+    pub protocol_rates: VecDeque<ProtocolRates>,
+    // the rates the last refresh added, for the session recording
+    pub new_protocol_rates: Option<ProtocolRates>,
+    pub top_ports: Vec<PortCount>,
+    // interface the wake-on-LAN and link buttons act on, and the MAC typed for it
+    pub interface_action: Option<String>,
+    pub wake_mac_input: String,
+    pub interface_action_status: String,
+    // Link Down was pressed and waits for a confirmation, it can cut the connection to a remote
+    // machine
+    pub link_down_pending: bool,
+
+    // Sensors
+    pub sensor_backend: Box<dyn SensorBackend>,
+    pub sensors: Vec<SensorReading>,
+
+    // GPU, read every few seconds
+    pub gpu: GpuStatus,
+    pub gpu_read: BackgroundRead<GpuStatus>,
+    pub gpu_action_status: String,
+    // Some only on a Raspberry Pi
+    pub pi: Option<PiStatus>,
+    pub pi_read: BackgroundRead<Option<PiStatus>>,
+    // whether the user is away from the keyboard and mouse, read every few seconds
+    pub user_idle: Option<bool>,
+    pub idle_read: BackgroundRead<Option<Duration>>,
 
     // Processes
     pub processes: Vec<Process>,
+    pub process_limit: ProcessLimit,
+    pub process_page: usize,
+    pub process_filter: ProcessFilter,
+    pub process_sort: ProcessSort,
+    pub process_sort_reversed: bool,
+    pub view_name_input: String,
+    pub highlight_secs_input: String,
+    pub process_refresh_input: String,
+    // pid -> start time, so a recycled pid doesn't inherit the selection
+    pub selected_processes: HashMap<u32, u64>,
+    pub nice_value: String,
+    pub process_action_status: String,
+    // sockets of the process shown in the detail pane, None when they can't be read
+    pub process_connections: Option<Vec<Connection>>,
+    // cores the process in the detail pane may use and ran on, shown in the CPU panel
+    pub process_affinity: Option<CoreAffinity>,
+    // cgroup of the process in the detail pane and the limits typed in for it (Linux)
+    pub process_cgroup: Option<String>,
+    pub quota_cpu_input: String,
+    pub quota_memory_input: String,
+    // process whose description from the offline table is shown in the detail pane
+    pub explained_process: Option<u32>,
+    // per-user sums for the Users panel, only rebuilt when a process changed
+    #[cfg(feature = "gui")]
+    pub user_totals: Vec<UserTotal>,
+    pub names: NameCache,
+
+    // Alerts
+    pub alert_state: AlertState,
+    pub respawn_tracker: RespawnTracker,
+    pub process_lifetimes: LifetimeTracker,
+    pub runaway_tracker: RunawayTracker,
+    pub alert_history: Vec<AlertEvent>,
+    pub rule_form: RuleForm,
+    // rule file to import, and what the last import or export did
+    pub rule_file_input: String,
+    pub rule_file_status: String,
+    pub pressure_watch: PressureWatch,
+
+    // Session
+    pub session_stats: SessionStats,
+    pub last_refresh: Instant,
+    // when the process list was last enumerated, None until the first refresh
+    pub processes_refreshed: Option<Instant>,
+    pub tick_pacer: TickPacer,
+    // samples CPU and memory between refreshes when the collect interval is the shorter one
+    pub sampler: Option<Sampler>,
+    #[cfg(feature = "gui")]
+    pub delta_tracker: DeltaTracker,
+    // what the last refresh changed, and the process and disk rows rebuilt for it
+    #[cfg(feature = "gui")]
+    pub delta: SnapshotDelta,
+    #[cfg(feature = "gui")]
+    pub panel_rows: PanelRows,
+
+    // History
+    pub history: History,
+    pub history_selection: Option<(String, String)>,
+    // earlier day or week drawn behind the history chart
+    pub history_comparison: Option<Comparison>,
+    // period whose log is being read in the background, a result for any other is stale
+    pub comparison_loading: Option<ComparisonPeriod>,
+    pub comparison_status: String,
+    pub export_status: String,
+    pub annotation_input: String,
 
     // Other
+    pub config: Config,
+    pub config_modified: Option<SystemTime>,
+    // applied over every config that is loaded, from --preset
+    pub preset: Option<Preset>,
+    #[cfg(feature = "gui")]
+    pub setup: Option<SetupWizard>,
+    pub active_tab: Tab,
+    // panels popped out into their own always-on-top windows
+    #[cfg(feature = "gui")]
+    pub detached_panels: HashMap<window::Id, Panel>,
+    // panels whose values are frozen, the data keeps being collected underneath
+    pub paused_panels: HashMap<Panel, PanelSnapshot>,
+    // panel expanded to fill the main window, in place of the dashboard
+    pub maximized_panel: Option<Panel>,
+    // window size to restore when leaving mini mode, Some while in mini mode
+    #[cfg(feature = "gui")]
+    pub mini_mode: Option<Size>,
+    // levels behind the mini mode sliders, read when entering mini mode, None when there is
+    // nothing to control
+    pub brightness: Option<f32>,
+    pub volume: Option<f32>,
+    // error of the last slider change
+    pub quick_control_status: String,
+    // the CPU strip window while it is open
+    #[cfg(feature = "gui")]
+    pub cpu_strip: Option<window::Id>,
+    // set while the main window is in the background, for the digest shown on return
+    #[cfg(feature = "gui")]
+    pub away_mark: Option<AwayMark>,
+    #[cfg(feature = "gui")]
+    pub digest: Option<Digest>,
+    // the "find what is slow" capture while it runs, then its result until dismissed
+    pub diagnosis_capture: Option<DiagnosisCapture>,
+    pub diagnosis: Option<Diagnosis>,
+    pub screenshot_status: String,
+    // outcome of the last "Copy Metrics"
+    pub metrics_status: String,
     pub is_monitoring: bool,
     pub save_to_file: bool,
+    pub log_writer: LogWriter,
+    // periodic checks that the log is still being written, while logging
+    pub log_integrity: IntegrityMonitor,
+    // scripted values used instead of the real system, None normally
+    pub mock: Option<MockSource>,
+    // recorded session played back instead of the real system, None normally
+    pub replay: Option<Replay>,
+    // Some while "Record Session" is on
+    pub recorder: Option<Recorder>,
+    pub recording_status: String,
     pub interval_in_secs: String,
 }
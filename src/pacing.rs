@@ -0,0 +1,34 @@
+// Coalesces ticks that pile up behind a refresh slower than the tick interval
+use std::time::{Duration, Instant};
+
+// the dashboard refreshes once per tick, every second unless `redraw_interval_ms` is set
+pub const TICK_INTERVAL: Duration = Duration::from_secs(1);
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TickPacer {
+    // how long the last refresh took
+    pub last_refresh: Duration,
+    finished: Option<Instant>,
+    // ticks skipped because they were queued behind a slow refresh
+    pub dropped: u64,
+}
+
+impl TickPacer {
+    // whether a tick arriving now was queued while the last refresh ran over the interval;
+    // those are dropped, the next on-time tick picks up current values anyway
+    pub fn should_skip(&mut self, interval: Duration) -> bool {
+        let queued = self.last_refresh >= interval
+            && self
+                .finished
+                .is_some_and(|finished| finished.elapsed() < interval / 2);
+        if queued {
+            self.dropped += 1;
+        }
+        queued
+    }
+
+    pub fn record(&mut self, started: Instant) {
+        self.last_refresh = started.elapsed();
+        self.finished = Some(Instant::now());
+    }
+}
@@ -0,0 +1,282 @@
+// Parquet log sink, samples are buffered and written as one file per hour
+use crate::models::SystemData;
+use parquet::basic::Compression;
+use parquet::data_type::{BoolType, ByteArray, ByteArrayType, DoubleType, FloatType, Int64Type};
+use parquet::errors::Result as ParquetResult;
+use parquet::file::properties::WriterProperties;
+use parquet::file::writer::SerializedFileWriter;
+use parquet::schema::parser::parse_message_type;
+use std::fs::File;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+// a Parquet file is only readable once its footer is written, so the files are kept short
+const ROWS_PER_FILE: usize = 3600;
+const FILE_DURATION: Duration = Duration::from_secs(3600);
+
+const SCHEMA: &str = "
+message system_log {
+    required binary timestamp (UTF8);
+    required binary local_timestamp (UTF8);
+    required int64 sample_index;
+    required int64 refresh_index;
+    required float cpu_usage_percent;
+    required int64 memory_used_byte;
+    required int64 memory_total_byte;
+    required int64 memory_cache_byte;
+    required int64 swap_used_byte;
+    required int64 swap_total_byte;
+    required int64 disk_used_byte;
+    required int64 disk_total_byte;
+    required int64 network_sent_byte;
+    required int64 network_received_byte;
+    repeated group disk_iops {
+        required binary device (UTF8);
+        required double read_iops;
+        required double write_iops;
+    }
+    required int64 tcp_established;
+    required int64 tcp_time_wait;
+    required int64 tcp_close_wait;
+    required int64 tcp_listen;
+    optional boolean user_idle;
+    required binary host_name (UTF8);
+    required binary os (UTF8);
+    required binary machine_id (UTF8);
+}
+";
+
+#[derive(Debug, Default)]
+pub struct ParquetLog {
+    rows: Vec<SystemData>,
+    started: Option<Instant>,
+    // where the buffered rows are written, the directory of the latest one
+    directory: PathBuf,
+}
+
+impl ParquetLog {
+    pub fn push(&mut self, data: &SystemData, directory: &Path) -> io::Result<()> {
+        self.rows.push(data.clone());
+        self.directory = directory.to_path_buf();
+        let started = *self.started.get_or_insert_with(Instant::now);
+
+        if self.rows.len() >= ROWS_PER_FILE || started.elapsed() >= FILE_DURATION {
+            self.flush()?;
+        }
+        Ok(())
+    }
+
+    // write the buffered samples to a new file named after the first one
+    pub fn flush(&mut self) -> io::Result<()> {
+        let Some(first) = self.rows.first() else {
+            return Ok(());
+        };
+
+        let path = self.directory.join(format!(
+            "system_log_{}.parquet",
+            first
+                .timestamp
+                .replace('-', "")
+                .replace(' ', "-")
+                .replace(':', "")
+        ));
+        write_file(&path, &self.rows).map_err(io::Error::other)?;
+
+        self.rows.clear();
+        self.started = None;
+        Ok(())
+    }
+}
+
+fn write_file(path: &Path, rows: &[SystemData]) -> ParquetResult<()> {
+    let schema = Arc::new(parse_message_type(SCHEMA)?);
+    let properties = Arc::new(
+        WriterProperties::builder()
+            .set_compression(Compression::SNAPPY)
+            .build(),
+    );
+    let mut writer = SerializedFileWriter::new(File::create(path)?, schema, properties)?;
+    let mut row_group = writer.next_row_group()?;
+
+    let timestamps: Vec<ByteArray> = rows
+        .iter()
+        .map(|row| ByteArray::from(row.timestamp.as_str()))
+        .collect();
+    let local_timestamps: Vec<ByteArray> = rows
+        .iter()
+        .map(|row| ByteArray::from(row.local_timestamp.as_str()))
+        .collect();
+    let sample_indexes: Vec<i64> = rows.iter().map(|row| row.sample_index as i64).collect();
+    let refresh_indexes: Vec<i64> = rows.iter().map(|row| row.refresh_index as i64).collect();
+    let cpu: Vec<f32> = rows.iter().map(|row| row.cpu_usage_percent).collect();
+    let int_columns: [Vec<i64>; 9] = [
+        rows.iter()
+            .map(|row| row.memory_usage_byte.0 as i64)
+            .collect(),
+        rows.iter()
+            .map(|row| row.memory_usage_byte.1 as i64)
+            .collect(),
+        rows.iter()
+            .map(|row| row.memory_cache_byte as i64)
+            .collect(),
+        rows.iter()
+            .map(|row| row.swap_memory_usage_byte.0 as i64)
+            .collect(),
+        rows.iter()
+            .map(|row| row.swap_memory_usage_byte.1 as i64)
+            .collect(),
+        rows.iter()
+            .map(|row| row.disk_usage_byte.0 as i64)
+            .collect(),
+        rows.iter()
+            .map(|row| row.disk_usage_byte.1 as i64)
+            .collect(),
+        rows.iter()
+            .map(|row| row.network_sent_byte as i64)
+            .collect(),
+        rows.iter()
+            .map(|row| row.network_received_byte as i64)
+            .collect(),
+    ];
+    // one entry per device of every row; a row without devices still takes a level, with
+    // definition 0, and the repetition level tells where the next row's devices start
+    let devices: Vec<ByteArray> = rows
+        .iter()
+        .flat_map(|row| &row.disk_iops)
+        .map(|disk| ByteArray::from(disk.device.as_str()))
+        .collect();
+    let iops_columns: [Vec<f64>; 2] = [
+        rows.iter()
+            .flat_map(|row| &row.disk_iops)
+            .map(|disk| disk.read_iops)
+            .collect(),
+        rows.iter()
+            .flat_map(|row| &row.disk_iops)
+            .map(|disk| disk.write_iops)
+            .collect(),
+    ];
+    let (device_definitions, device_repetitions): (Vec<i16>, Vec<i16>) = rows
+        .iter()
+        .flat_map(|row| match row.disk_iops.len() {
+            0 => vec![(0, 0)],
+            n => (0..n).map(|i| (1, i16::from(i > 0))).collect(),
+        })
+        .unzip();
+    let tcp_columns: [Vec<i64>; 4] = [
+        rows.iter()
+            .map(|row| row.tcp_states.established as i64)
+            .collect(),
+        rows.iter()
+            .map(|row| row.tcp_states.time_wait as i64)
+            .collect(),
+        rows.iter()
+            .map(|row| row.tcp_states.close_wait as i64)
+            .collect(),
+        rows.iter()
+            .map(|row| row.tcp_states.listen as i64)
+            .collect(),
+    ];
+    // only the known values are written, the definition levels mark which rows have one
+    let idle: Vec<bool> = rows.iter().filter_map(|row| row.user_idle).collect();
+    let idle_levels: Vec<i16> = rows
+        .iter()
+        .map(|row| i16::from(row.user_idle.is_some()))
+        .collect();
+
+    let host_columns: [Vec<ByteArray>; 3] = [
+        rows.iter()
+            .map(|row| ByteArray::from(row.host.host_name.as_str()))
+            .collect(),
+        rows.iter()
+            .map(|row| ByteArray::from(row.host.os.as_str()))
+            .collect(),
+        rows.iter()
+            .map(|row| ByteArray::from(row.host.machine_id.as_str()))
+            .collect(),
+    ];
+
+    // columns come out of the row group in schema order
+    if let Some(mut column) = row_group.next_column()? {
+        column
+            .typed::<ByteArrayType>()
+            .write_batch(&timestamps, None, None)?;
+        column.close()?;
+    }
+    if let Some(mut column) = row_group.next_column()? {
+        column
+            .typed::<ByteArrayType>()
+            .write_batch(&local_timestamps, None, None)?;
+        column.close()?;
+    }
+    if let Some(mut column) = row_group.next_column()? {
+        column
+            .typed::<Int64Type>()
+            .write_batch(&sample_indexes, None, None)?;
+        column.close()?;
+    }
+    if let Some(mut column) = row_group.next_column()? {
+        column
+            .typed::<Int64Type>()
+            .write_batch(&refresh_indexes, None, None)?;
+        column.close()?;
+    }
+    if let Some(mut column) = row_group.next_column()? {
+        column.typed::<FloatType>().write_batch(&cpu, None, None)?;
+        column.close()?;
+    }
+    for values in &int_columns {
+        if let Some(mut column) = row_group.next_column()? {
+            column
+                .typed::<Int64Type>()
+                .write_batch(values, None, None)?;
+            column.close()?;
+        }
+    }
+    if let Some(mut column) = row_group.next_column()? {
+        column.typed::<ByteArrayType>().write_batch(
+            &devices,
+            Some(&device_definitions),
+            Some(&device_repetitions),
+        )?;
+        column.close()?;
+    }
+    for values in &iops_columns {
+        if let Some(mut column) = row_group.next_column()? {
+            column.typed::<DoubleType>().write_batch(
+                values,
+                Some(&device_definitions),
+                Some(&device_repetitions),
+            )?;
+            column.close()?;
+        }
+    }
+    for values in &tcp_columns {
+        if let Some(mut column) = row_group.next_column()? {
+            column
+                .typed::<Int64Type>()
+                .write_batch(values, None, None)?;
+            column.close()?;
+        }
+    }
+    if let Some(mut column) = row_group.next_column()? {
+        column
+            .typed::<BoolType>()
+            .write_batch(&idle, Some(&idle_levels), None)?;
+        column.close()?;
+    }
+
+    for values in &host_columns {
+        if let Some(mut column) = row_group.next_column()? {
+            column
+                .typed::<ByteArrayType>()
+                .write_batch(values, None, None)?;
+            column.close()?;
+        }
+    }
+
+    row_group.close()?;
+    writer.close()?;
+    Ok(())
+}
@@ -0,0 +1,115 @@
+// Raspberry Pi firmware readings through vcgencmd: SoC temperature, core voltage, ARM clock
+// and the throttling flags that tell an undersized power supply or poor cooling apart
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::process::Command;
+use std::time::Duration;
+
+// vcgencmd is a separate process per reading, a few seconds apart is enough for a dashboard;
+// the readings are taken on a thread of their own
+pub const PI_REFRESH_INTERVAL: Duration = Duration::from_secs(5);
+
+// bits of `vcgencmd get_throttled`, the low ones are the current state and the same conditions
+// 16 bits up have happened since boot
+const THROTTLE_FLAGS: [(u32, &str); 4] = [
+    (0, "Under-voltage"),
+    (1, "ARM frequency capped"),
+    (2, "Throttled"),
+    (3, "Soft temperature limit"),
+];
+
+// One throttling condition, now and since boot
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ThrottleFlag {
+    pub label: &'static str,
+    pub active: bool,
+    pub occurred: bool,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct PiStatus {
+    // e.g. "Raspberry Pi 4 Model B Rev 1.4"
+    pub model: String,
+    pub soc_temperature: Option<f64>,
+    pub core_volts: Option<f64>,
+    pub arm_clock_mhz: Option<u64>,
+    // None without vcgencmd, e.g. in a container without /dev/vchiq
+    pub throttled: Option<u32>,
+}
+
+impl PiStatus {
+    pub fn throttle_flags(&self) -> Vec<ThrottleFlag> {
+        let Some(bits) = self.throttled else {
+            return Vec::new();
+        };
+        THROTTLE_FLAGS
+            .iter()
+            .map(|(bit, label)| ThrottleFlag {
+                label,
+                active: bits & (1 << bit) != 0,
+                occurred: bits & (1 << (bit + 16)) != 0,
+            })
+            .collect()
+    }
+}
+
+// None on anything that isn't a Raspberry Pi, so the panel only shows up on one
+pub fn read_pi_status() -> Option<PiStatus> {
+    let model = fs::read_to_string("/proc/device-tree/model").ok()?;
+    let model = model.trim_end_matches('\0').trim().to_string();
+    if !model.starts_with("Raspberry Pi") {
+        return None;
+    }
+
+    // the kernel has the temperature (in thousandths of a degree) and the clock (in kHz) without
+    // a process, vcgencmd is only asked for what it alone knows
+    let soc_temperature =
+        read_number("/sys/class/thermal/thermal_zone0/temp").map(|millis| millis / 1000.);
+    let arm_clock_mhz = read_number("/sys/devices/system/cpu/cpu0/cpufreq/scaling_cur_freq")
+        .map(|khz| khz as u64 / 1000);
+    let mut commands = vec!["measure_volts core", "get_throttled"];
+    if soc_temperature.is_none() {
+        commands.push("measure_temp");
+    }
+    if arm_clock_mhz.is_none() {
+        commands.push("measure_clock arm");
+    }
+    let answers = vcgencmd(&commands);
+    let answer = |key: &str| answers.get(key).map(String::as_str);
+
+    Some(PiStatus {
+        model,
+        soc_temperature: soc_temperature
+            .or_else(|| answer("temp")?.trim_end_matches("'C").parse().ok()),
+        core_volts: answer("volt").and_then(|volts| volts.trim_end_matches('V').parse().ok()),
+        arm_clock_mhz: arm_clock_mhz.or_else(|| {
+            let hz: u64 = answer("frequency(48)")?.parse().ok()?;
+            Some(hz / 1_000_000)
+        }),
+        throttled: answer("throttled")
+            .and_then(|bits| u32::from_str_radix(bits.trim_start_matches("0x"), 16).ok()),
+    })
+}
+
+fn read_number(path: &str) -> Option<f64> {
+    fs::read_to_string(path).ok()?.trim().parse().ok()
+}
+
+// run the commands one after another in a single shell, answering e.g. "temp=48.3'C",
+// "volt=0.8600V" or "frequency(48)=1500000000"; keyed by what is before the '='
+fn vcgencmd(commands: &[&str]) -> HashMap<String, String> {
+    let script = commands
+        .iter()
+        .map(|command| format!("vcgencmd {}", command))
+        .collect::<Vec<_>>()
+        .join("; ");
+    let Ok(output) = Command::new("sh").args(["-c", &script]).output() else {
+        return HashMap::new();
+    };
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(|line| line.trim().split_once('='))
+        .map(|(key, value)| (key.to_string(), value.to_string()))
+        .collect()
+}
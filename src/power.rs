@@ -0,0 +1,97 @@
+// Whether the machine is running on battery, where the OS makes that easy to detect, and the
+// battery's level and charge limit on Linux laptops
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::process::Command;
+
+// None on desktops and on systems where the power source can't be read
+pub fn read_on_battery() -> Option<bool> {
+    if cfg!(target_os = "linux") {
+        linux_on_battery()
+    } else if cfg!(target_os = "macos") {
+        mac_on_battery()
+    } else {
+        None
+    }
+}
+
+// /sys/class/power_supply has a "Mains" (or "USB") supply with `online` set while plugged in
+fn linux_on_battery() -> Option<bool> {
+    let mut has_battery = false;
+    let mut plugged_in = false;
+    for supply in fs::read_dir("/sys/class/power_supply").ok()?.flatten() {
+        let path = supply.path();
+        let kind = fs::read_to_string(path.join("type")).unwrap_or_default();
+        match kind.trim() {
+            "Battery" => has_battery = true,
+            "Mains" | "USB" => {
+                plugged_in |= fs::read_to_string(path.join("online"))
+                    .is_ok_and(|online| online.trim() == "1");
+            }
+            _ => {}
+        }
+    }
+    has_battery.then_some(!plugged_in)
+}
+
+// `pmset -g batt` starts with "Now drawing from 'Battery Power'" or "'AC Power'"
+fn mac_on_battery() -> Option<bool> {
+    let output = Command::new("pmset").args(["-g", "batt"]).output().ok()?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let first_line = stdout.lines().next()?;
+    stdout
+        .contains("InternalBattery")
+        .then(|| first_line.contains("Battery Power"))
+}
+
+// Laptop battery as reported by /sys/class/power_supply
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct BatteryInfo {
+    // supply name, e.g. BAT0
+    pub name: String,
+    pub percent: Option<u8>,
+    // "Charging", "Discharging", "Full" or "Not charging" (held at the charge limit)
+    pub status: String,
+    // charging stops at this level, None where the firmware doesn't offer a limit
+    pub charge_limit: Option<u8>,
+}
+
+// the first battery, None on desktops and on systems other than Linux
+pub fn read_battery() -> Option<BatteryInfo> {
+    if !cfg!(target_os = "linux") {
+        return None;
+    }
+    let mut supplies: Vec<_> = fs::read_dir("/sys/class/power_supply")
+        .ok()?
+        .flatten()
+        .map(|supply| supply.path())
+        .filter(|path| {
+            fs::read_to_string(path.join("type")).is_ok_and(|kind| kind.trim() == "Battery")
+        })
+        .collect();
+    supplies.sort();
+    let path = supplies.into_iter().next()?;
+
+    let read =
+        |file: &str| fs::read_to_string(path.join(file)).map(|value| value.trim().to_string());
+    Some(BatteryInfo {
+        name: path.file_name()?.to_string_lossy().into_owned(),
+        percent: read("capacity")
+            .ok()
+            .and_then(|percent| percent.parse().ok()),
+        status: read("status").unwrap_or_default(),
+        charge_limit: read("charge_control_end_threshold")
+            .ok()
+            .and_then(|limit| limit.parse().ok()),
+    })
+}
+
+// writing the threshold needs root, or a udev rule giving the user write access to it
+#[cfg(feature = "gui")]
+pub fn set_charge_limit(battery: &str, percent: u8) -> Result<(), String> {
+    let path = format!(
+        "/sys/class/power_supply/{}/charge_control_end_threshold",
+        battery
+    );
+    fs::write(&path, percent.to_string()).map_err(|e| format!("Can't write {}: {}", path, e))
+}
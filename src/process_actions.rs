@@ -0,0 +1,131 @@
+use crate::format::{csv_field, NumberFormat, NumberLocale};
+use crate::models::Process;
+use chrono::Local;
+use std::fs;
+use std::process::Command;
+use sysinfo::{Pid, System};
+
+const TABLE_HEADER: [&str; 6] = [
+    "pid",
+    "name",
+    "cpu_percent",
+    "cpu_time_secs",
+    "memory_percent",
+    "command",
+];
+
+// send the kill signal to each (pid, start time), returns how many were killed
+// a pid whose process started at another time has been reused and is left alone
+pub fn kill_processes(system: &System, processes: &[(u32, u64)]) -> usize {
+    processes
+        .iter()
+        .filter(|(pid, start_time)| {
+            system
+                .process(Pid::from_u32(*pid))
+                .filter(|process| process.start_time() == *start_time)
+                .map(|process| process.kill())
+                .unwrap_or(false)
+        })
+        .count()
+}
+
+fn table_row(process: &Process, numbers: NumberFormat) -> [String; 6] {
+    [
+        process.id.to_string(),
+        process.name.to_string(),
+        numbers.number(process.cpu_usage_percent),
+        numbers.number(process.cpu_time_secs),
+        numbers.number(process.memory_usage_percent),
+        process.command.to_string(),
+    ]
+}
+
+// tab separated with a header row, pastes into spreadsheets and ticket tables as columns
+pub fn process_table_text(processes: &[&Process], numbers: NumberFormat) -> String {
+    let mut lines = vec![TABLE_HEADER.join("\t")];
+    for process in processes {
+        let fields =
+            table_row(process, numbers).map(|field| field.replace(['\t', '\n', '\r'], " "));
+        lines.push(fields.join("\t"));
+    }
+    lines.join("\n")
+}
+
+// write the processes to processes_<local time>.csv, returns the file name; numbers use the
+// locale's decimal mark, with `;` between columns where that mark is a comma
+pub fn export_processes_csv(
+    processes: &[&Process],
+    locale: NumberLocale,
+) -> Result<String, String> {
+    let path = format!("processes_{}.csv", Local::now().format("%Y%m%d-%H%M%S"));
+    let numbers = NumberFormat::new(2, locale).ungrouped();
+    let separator = numbers.csv_separator();
+
+    let mut csv = TABLE_HEADER.join(&separator.to_string());
+    csv.push('\n');
+    for process in processes {
+        let fields = table_row(process, numbers).map(|field| csv_field(&field, separator));
+        csv.push_str(&fields.join(&separator.to_string()));
+        csv.push('\n');
+    }
+
+    fs::write(&path, csv).map_err(|e| format!("Failed to write {}: {}", path, e))?;
+    Ok(path)
+}
+
+// open a link in the default browser
+pub fn open_in_browser(url: &str) -> Result<(), String> {
+    let mut command = if cfg!(windows) {
+        let mut command = Command::new("cmd");
+        command.args(["/C", "start", ""]);
+        command
+    } else if cfg!(target_os = "macos") {
+        Command::new("open")
+    } else {
+        Command::new("xdg-open")
+    };
+    command
+        .arg(url)
+        .spawn()
+        .map(|_| ())
+        .map_err(|e| format!("Failed to open {}: {}", url, e))
+}
+
+// change the scheduling priority of the processes with the `renice` command
+pub fn renice_processes(
+    system: &System,
+    processes: &[(u32, u64)],
+    nice: i32,
+) -> Result<usize, String> {
+    if cfg!(windows) {
+        return Err(String::from("Renice is not supported on Windows"));
+    }
+
+    // like kill_processes, a pid that now belongs to another process is left alone
+    let pids: Vec<u32> = processes
+        .iter()
+        .filter(|(pid, start_time)| {
+            system
+                .process(Pid::from_u32(*pid))
+                .is_some_and(|process| process.start_time() == *start_time)
+        })
+        .map(|(pid, _)| *pid)
+        .collect();
+    if pids.is_empty() {
+        return Ok(0);
+    }
+
+    let output = Command::new("renice")
+        .arg("-n")
+        .arg(nice.to_string())
+        .arg("-p")
+        .args(pids.iter().map(|pid| pid.to_string()))
+        .output()
+        .map_err(|e| format!("Failed to run renice: {}", e))?;
+
+    if output.status.success() {
+        Ok(pids.len())
+    } else {
+        Err(String::from_utf8_lossy(&output.stderr).trim().to_string())
+    }
+}
@@ -0,0 +1,580 @@
+// Readers for Linux-only data under /proc and /sys
+// on other platforms the files don't exist and the readers return empty values
+#[cfg(feature = "gui")]
+use crate::models::{Connection, CoreAffinity};
+use crate::models::{DiskIo, InterruptInfo, PortCount, ProtocolRates, TcpStates};
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+// parse a kernel cpu list such as "0-3,5,7-8"
+pub fn parse_cpu_list(list: &str) -> Vec<u32> {
+    list.trim()
+        .split(',')
+        .filter(|part| !part.is_empty())
+        .flat_map(|part| match part.split_once('-') {
+            Some((start, end)) => match (start.parse::<u32>(), end.parse::<u32>()) {
+                (Ok(start), Ok(end)) => (start..=end).collect(),
+                _ => Vec::new(),
+            },
+            None => part.parse::<u32>().into_iter().collect(),
+        })
+        .collect()
+}
+
+// the other way round, e.g. [0, 1, 2, 3, 5] gives "0-3,5"; expects the CPUs in order
+#[cfg(feature = "gui")]
+pub fn format_cpu_list(cpus: &[u32]) -> String {
+    let mut ranges: Vec<(u32, u32)> = Vec::new();
+    for &cpu in cpus {
+        match ranges.last_mut() {
+            Some((_, end)) if *end + 1 == cpu => *end = cpu,
+            _ => ranges.push((cpu, cpu)),
+        }
+    }
+    ranges
+        .iter()
+        .map(|&(start, end)| {
+            if start == end {
+                start.to_string()
+            } else {
+                format!("{}-{}", start, end)
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+// logical CPUs that exist but are currently offline (hot-unplugged or parked)
+pub fn offline_cpus() -> Vec<u32> {
+    let read = |name: &str| {
+        fs::read_to_string(format!("/sys/devices/system/cpu/{}", name))
+            .map(|list| parse_cpu_list(&list))
+            .unwrap_or_default()
+    };
+
+    let online = read("online");
+    read("possible")
+        .into_iter()
+        .filter(|cpu| !online.contains(cpu))
+        .collect()
+}
+
+// whether the link of each network interface is up, from its operstate; interfaces that report
+// no state ("unknown", as loopback and most tunnels do) are left out
+pub fn link_states() -> HashMap<String, bool> {
+    let Ok(entries) = fs::read_dir("/sys/class/net") else {
+        return HashMap::new();
+    };
+    entries
+        .flatten()
+        .filter_map(|entry| {
+            let state = fs::read_to_string(entry.path().join("operstate")).ok()?;
+            let up = match state.trim() {
+                "up" => true,
+                "down" | "lowerlayerdown" | "notpresent" | "dormant" => false,
+                _ => return None,
+            };
+            Some((entry.file_name().to_string_lossy().into_owned(), up))
+        })
+        .collect()
+}
+
+// Raw interrupt counters from /proc/interrupts
+#[derive(Debug, Clone, Default)]
+pub struct InterruptCounts {
+    pub cpus: Vec<String>,
+    // irq, description and count per cpu
+    pub sources: Vec<(String, String, Vec<u64>)>,
+}
+
+pub fn read_interrupts() -> Option<InterruptCounts> {
+    let contents = fs::read_to_string("/proc/interrupts").ok()?;
+    let mut lines = contents.lines();
+    let cpus: Vec<String> = lines.next()?.split_whitespace().map(String::from).collect();
+
+    let sources = lines
+        .filter_map(|line| {
+            let (irq, rest) = line.split_once(':')?;
+            let mut tokens = rest.split_whitespace().peekable();
+
+            // some rows (ERR, MIS) only have a single total instead of one count per cpu
+            let mut counts = Vec::new();
+            while counts.len() < cpus.len() {
+                match tokens.peek().and_then(|token| token.parse::<u64>().ok()) {
+                    Some(count) => {
+                        counts.push(count);
+                        tokens.next();
+                    }
+                    None => break,
+                }
+            }
+
+            let description = tokens.collect::<Vec<_>>().join(" ");
+            Some((irq.trim().to_string(), description, counts))
+        })
+        .collect();
+
+    Some(InterruptCounts { cpus, sources })
+}
+
+impl InterruptCounts {
+    // interrupts handled since an earlier reading, per cpu and per source (busiest first)
+    pub fn since(&self, previous: &InterruptCounts) -> InterruptInfo {
+        let mut per_cpu: Vec<(String, u64)> =
+            self.cpus.iter().map(|cpu| (cpu.clone(), 0)).collect();
+        let mut sources = Vec::new();
+
+        for (irq, description, counts) in &self.sources {
+            let before = previous
+                .sources
+                .iter()
+                .find(|(previous_irq, _, _)| previous_irq == irq)
+                .map(|(_, _, counts)| counts.as_slice())
+                .unwrap_or(&[]);
+
+            let mut total = 0;
+            for (i, count) in counts.iter().enumerate() {
+                let delta = count.saturating_sub(before.get(i).copied().unwrap_or(0));
+                if let Some(cpu) = per_cpu.get_mut(i) {
+                    cpu.1 += delta;
+                }
+                total += delta;
+            }
+            sources.push((irq.clone(), description.clone(), total));
+        }
+
+        sources.sort_by_key(|source| std::cmp::Reverse(source.2));
+        InterruptInfo {
+            per_cpu,
+            top_sources: sources,
+        }
+    }
+}
+
+// Raw counters of a block device from /proc/diskstats
+#[derive(Debug, Clone, Default)]
+pub struct DiskStat {
+    pub name: String,
+    pub reads: u64,
+    pub writes: u64,
+    pub sectors_read: u64,
+    pub sectors_written: u64,
+    // milliseconds spent on completed reads / writes
+    pub read_ms: u64,
+    pub write_ms: u64,
+    pub in_flight: u64,
+    // milliseconds spent doing I/O, weighted by the number of requests in flight
+    pub weighted_ms: u64,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct DiskCounters {
+    pub devices: Vec<DiskStat>,
+}
+
+// diskstats counts in 512 byte sectors whatever the device's sector size
+pub(crate) const SECTOR_SIZE: u64 = 512;
+
+// whole block devices only, partitions and loop/ram devices would count the same I/O twice
+fn is_physical_disk(name: &str) -> bool {
+    !name.starts_with("loop")
+        && !name.starts_with("ram")
+        && Path::new("/sys/block").join(name).exists()
+}
+
+fn parse_diskstat(line: &str) -> Option<DiskStat> {
+    // major minor name, then the counters described in Documentation/admin-guide/iostats.rst
+    let fields: Vec<&str> = line.split_whitespace().collect();
+    let counter = |i: usize| fields.get(i).and_then(|field| field.parse::<u64>().ok());
+
+    Some(DiskStat {
+        name: fields.get(2)?.to_string(),
+        reads: counter(3)?,
+        sectors_read: counter(5)?,
+        read_ms: counter(6)?,
+        writes: counter(7)?,
+        sectors_written: counter(9)?,
+        write_ms: counter(10)?,
+        in_flight: counter(11)?,
+        weighted_ms: counter(13)?,
+    })
+}
+
+pub fn read_diskstats() -> Option<DiskCounters> {
+    let contents = fs::read_to_string("/proc/diskstats").ok()?;
+    let devices = contents
+        .lines()
+        .filter_map(parse_diskstat)
+        .filter(|stat| is_physical_disk(&stat.name))
+        .collect();
+
+    Some(DiskCounters { devices })
+}
+
+impl DiskCounters {
+    // rates, latency and queue depth since an earlier reading
+    pub fn since(&self, previous: &DiskCounters, elapsed_secs: f64) -> Vec<DiskIo> {
+        self.devices
+            .iter()
+            .map(|current| {
+                let before = previous
+                    .devices
+                    .iter()
+                    .find(|stat| stat.name == current.name)
+                    .unwrap_or(current);
+
+                let reads = current.reads.saturating_sub(before.reads);
+                let writes = current.writes.saturating_sub(before.writes);
+                let busy_ms = current.read_ms.saturating_sub(before.read_ms)
+                    + current.write_ms.saturating_sub(before.write_ms);
+                let per_sec = |count: u64| count as f64 / elapsed_secs;
+
+                DiskIo {
+                    name: current.name.clone(),
+                    read_iops: per_sec(reads),
+                    write_iops: per_sec(writes),
+                    read_rate: per_sec(
+                        current.sectors_read.saturating_sub(before.sectors_read) * SECTOR_SIZE,
+                    ),
+                    write_rate: per_sec(
+                        current
+                            .sectors_written
+                            .saturating_sub(before.sectors_written)
+                            * SECTOR_SIZE,
+                    ),
+                    latency_ms: (reads + writes > 0)
+                        .then(|| busy_ms as f64 / (reads + writes) as f64),
+                    in_flight: current.in_flight,
+                    queue_depth: current.weighted_ms.saturating_sub(before.weighted_ms) as f64
+                        / (elapsed_secs * 1000.),
+                }
+            })
+            .collect()
+    }
+}
+
+// socket states of /proc/net/tcp and /proc/net/tcp6, None when neither can be read
+pub fn read_tcp_states() -> Option<TcpStates> {
+    let tables: Vec<String> = ["/proc/net/tcp", "/proc/net/tcp6"]
+        .iter()
+        .filter_map(|path| fs::read_to_string(path).ok())
+        .collect();
+    if tables.is_empty() {
+        return None;
+    }
+
+    let mut states = TcpStates::default();
+    // sl local_address rem_address st ..., the state is a hex code from include/net/tcp_states.h
+    for line in tables.iter().flat_map(|table| table.lines().skip(1)) {
+        match line.split_whitespace().nth(3) {
+            Some("01") => states.established += 1,
+            Some("06") => states.time_wait += 1,
+            Some("08") => states.close_wait += 1,
+            Some("0A") => states.listen += 1,
+            _ => {}
+        }
+    }
+    Some(states)
+}
+
+// Packet counters of /proc/net/snmp and /proc/net/snmp6, sent and received together
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ProtocolCounters {
+    pub tcp_segments: u64,
+    pub udp_datagrams: u64,
+}
+
+// "Tcp: RtoAlgorithm ... InSegs" followed by "Tcp: 1 ... 120" gives each counter's value
+fn snmp_counter(snmp: &str, protocol: &str, name: &str) -> Option<u64> {
+    let prefix = format!("{}: ", protocol);
+    let mut lines = snmp.lines().filter(|line| line.starts_with(&prefix));
+    let names = lines.next()?;
+    let values = lines.next()?;
+    let index = names.split_whitespace().position(|field| field == name)?;
+    values.split_whitespace().nth(index)?.parse().ok()
+}
+
+// snmp6 has a "Udp6InDatagrams    42" line per counter
+fn snmp6_counter(snmp6: &str, name: &str) -> Option<u64> {
+    snmp6.lines().find_map(|line| {
+        let mut fields = line.split_whitespace();
+        (fields.next()? == name).then(|| fields.next()?.parse().ok())?
+    })
+}
+
+// the Tcp counters of snmp cover IPv6 as well, UDP over IPv6 is only counted in snmp6
+pub fn read_protocol_counters() -> Option<ProtocolCounters> {
+    let snmp = fs::read_to_string("/proc/net/snmp").ok()?;
+    let snmp6 = fs::read_to_string("/proc/net/snmp6").unwrap_or_default();
+    let counter = |protocol, name| snmp_counter(&snmp, protocol, name).unwrap_or(0);
+    let counter6 = |name| snmp6_counter(&snmp6, name).unwrap_or(0);
+
+    Some(ProtocolCounters {
+        tcp_segments: counter("Tcp", "InSegs") + counter("Tcp", "OutSegs"),
+        udp_datagrams: counter("Udp", "InDatagrams")
+            + counter("Udp", "OutDatagrams")
+            + counter6("Udp6InDatagrams")
+            + counter6("Udp6OutDatagrams"),
+    })
+}
+
+impl ProtocolCounters {
+    pub fn since(&self, previous: &ProtocolCounters, elapsed_secs: f64) -> ProtocolRates {
+        ProtocolRates {
+            tcp_packets: self.tcp_segments.saturating_sub(previous.tcp_segments) as f64
+                / elapsed_secs,
+            udp_packets: self.udp_datagrams.saturating_sub(previous.udp_datagrams) as f64
+                / elapsed_secs,
+        }
+    }
+}
+
+// established TCP connections counted by the service's port, the lower of the two ends since
+// clients pick high ephemeral ports, and UDP sockets by the same rule or their local port when
+// unconnected; the busiest first
+pub fn read_top_ports(limit: usize) -> Vec<PortCount> {
+    let mut counts: HashMap<(&'static str, u16), u64> = HashMap::new();
+    let tables = [
+        ("tcp", "/proc/net/tcp"),
+        ("tcp", "/proc/net/tcp6"),
+        ("udp", "/proc/net/udp"),
+        ("udp", "/proc/net/udp6"),
+    ];
+    for (protocol, path) in tables {
+        let Ok(table) = fs::read_to_string(path) else {
+            continue;
+        };
+        for line in table.lines().skip(1) {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            if protocol == "tcp" && fields.get(3) != Some(&"01") {
+                continue;
+            }
+            let port = |field: Option<&&str>| {
+                let (_, port) = field?.split_once(':')?;
+                u16::from_str_radix(port, 16).ok()
+            };
+            let (Some(local), Some(remote)) = (port(fields.get(1)), port(fields.get(2))) else {
+                continue;
+            };
+            let port = if remote == 0 {
+                local
+            } else {
+                local.min(remote)
+            };
+            *counts.entry((protocol, port)).or_default() += 1;
+        }
+    }
+
+    let mut ports: Vec<PortCount> = counts
+        .into_iter()
+        .map(|((protocol, port), sockets)| PortCount {
+            protocol: protocol.to_string(),
+            port,
+            sockets,
+        })
+        .collect();
+    ports.sort_by(|a, b| b.sockets.cmp(&a.sockets).then(a.port.cmp(&b.port)));
+    ports.truncate(limit);
+    ports
+}
+
+#[cfg(feature = "gui")]
+fn tcp_state_name(code: &str) -> &'static str {
+    match code {
+        "01" => "ESTABLISHED",
+        "02" => "SYN_SENT",
+        "03" => "SYN_RECV",
+        "04" => "FIN_WAIT1",
+        "05" => "FIN_WAIT2",
+        "06" => "TIME_WAIT",
+        "07" => "CLOSE",
+        "08" => "CLOSE_WAIT",
+        "09" => "LAST_ACK",
+        "0A" => "LISTEN",
+        "0B" => "CLOSING",
+        _ => "UNKNOWN",
+    }
+}
+
+// "0100007F:1F90" -> "127.0.0.1:8080", the address is in the kernel's byte order per 32-bit word
+#[cfg(feature = "gui")]
+fn parse_socket_address(address: &str) -> Option<String> {
+    let (ip, port) = address.split_once(':')?;
+    let port = u16::from_str_radix(port, 16).ok()?;
+
+    let words = (0..ip.len() / 8)
+        .map(|i| u32::from_str_radix(&ip[i * 8..i * 8 + 8], 16).map(u32::from_be))
+        .collect::<Result<Vec<u32>, _>>()
+        .ok()?;
+    let bytes: Vec<u8> = words.iter().flat_map(|word| word.to_be_bytes()).collect();
+
+    match bytes.len() {
+        4 => {
+            let ip = std::net::Ipv4Addr::new(bytes[0], bytes[1], bytes[2], bytes[3]);
+            Some(format!("{}:{}", ip, port))
+        }
+        16 => {
+            let octets: [u8; 16] = bytes.try_into().ok()?;
+            Some(format!("[{}]:{}", std::net::Ipv6Addr::from(octets), port))
+        }
+        _ => None,
+    }
+}
+
+// sockets opened by a process, matched by inode against the kernel's socket tables
+// None when its file descriptors can't be read (another user's process, or not Linux)
+#[cfg(feature = "gui")]
+pub fn read_process_connections(pid: u32) -> Option<Vec<Connection>> {
+    let inodes: Vec<String> = fs::read_dir(format!("/proc/{}/fd", pid))
+        .ok()?
+        .filter_map(|entry| fs::read_link(entry.ok()?.path()).ok())
+        .filter_map(|target| {
+            let target = target.to_string_lossy();
+            Some(
+                target
+                    .strip_prefix("socket:[")?
+                    .strip_suffix(']')?
+                    .to_string(),
+            )
+        })
+        .collect();
+
+    let mut connections = Vec::new();
+    for (protocol, path) in [
+        ("TCP", "/proc/net/tcp"),
+        ("TCP6", "/proc/net/tcp6"),
+        ("UDP", "/proc/net/udp"),
+        ("UDP6", "/proc/net/udp6"),
+    ] {
+        let Ok(table) = fs::read_to_string(path) else {
+            continue;
+        };
+        // sl local_address rem_address st tx:rx tr:when retrnsmt uid timeout inode
+        for line in table.lines().skip(1) {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            let Some(inode) = fields.get(9) else {
+                continue;
+            };
+            if !inodes.iter().any(|socket| socket == inode) {
+                continue;
+            }
+
+            let state = match (protocol.starts_with("UDP"), fields.get(3)) {
+                // an unconnected udp socket reports TCP_CLOSE
+                (true, Some(&"07")) => "UNCONNECTED",
+                (_, Some(code)) => tcp_state_name(code),
+                (_, None) => "UNKNOWN",
+            };
+            connections.push(Connection {
+                protocol,
+                local: fields
+                    .get(1)
+                    .and_then(|a| parse_socket_address(a))
+                    .unwrap_or_default(),
+                remote: fields
+                    .get(2)
+                    .and_then(|a| parse_socket_address(a))
+                    .unwrap_or_default(),
+                state,
+            });
+        }
+    }
+    Some(connections)
+}
+
+// cores a process may run on (its affinity mask) and the cores its threads ran on since
+// `previous` was read, or are running on right now for the first read
+#[cfg(feature = "gui")]
+pub fn read_affinity(pid: u32, previous: Option<&CoreAffinity>) -> Option<CoreAffinity> {
+    let status = fs::read_to_string(format!("/proc/{}/status", pid)).ok()?;
+    let allowed = status
+        .lines()
+        .find_map(|line| line.strip_prefix("Cpus_allowed_list:"))
+        .map(parse_cpu_list)?;
+
+    let mut affinity = CoreAffinity {
+        pid,
+        allowed,
+        ..CoreAffinity::default()
+    };
+    for entry in fs::read_dir(format!("/proc/{}/task", pid)).ok()?.flatten() {
+        let Ok(tid) = entry.file_name().to_string_lossy().parse::<u32>() else {
+            continue;
+        };
+        // threads can exit between listing and reading
+        let Ok(stat) = fs::read_to_string(entry.path().join("stat")) else {
+            continue;
+        };
+        // after the name: state first, utime and stime 12th and 13th, the core last run on 37th
+        let Some((_, rest)) = stat.rsplit_once(')') else {
+            continue;
+        };
+        let fields: Vec<&str> = rest.split_whitespace().collect();
+        let ticks = match (fields.get(11), fields.get(12)) {
+            (Some(user), Some(system)) => {
+                user.parse::<u64>().unwrap_or(0) + system.parse::<u64>().unwrap_or(0)
+            }
+            _ => continue,
+        };
+        let Some(core) = fields.get(36).and_then(|field| field.parse::<u32>().ok()) else {
+            continue;
+        };
+
+        let ran = match previous.and_then(|previous| previous.thread_ticks.get(&tid)) {
+            Some(previous_ticks) => ticks > *previous_ticks,
+            None => fields.first() == Some(&"R"),
+        };
+        if ran && !affinity.running.contains(&core) {
+            affinity.running.push(core);
+        }
+        affinity.thread_ticks.insert(tid, ticks);
+    }
+    affinity.running.sort_unstable();
+    Some(affinity)
+}
+
+// total and steal jiffies from the aggregate cpu line of /proc/stat
+pub fn read_cpu_times() -> Option<(u64, u64)> {
+    let contents = fs::read_to_string("/proc/stat").ok()?;
+    let line = contents.lines().find(|line| line.starts_with("cpu "))?;
+
+    // user nice system idle iowait irq softirq steal (guest time is already part of user)
+    let fields: Vec<u64> = line
+        .split_whitespace()
+        .skip(1)
+        .take(8)
+        .filter_map(|field| field.parse().ok())
+        .collect();
+
+    Some((fields.iter().sum(), fields.get(7).copied().unwrap_or(0)))
+}
+
+// user + system CPU seconds a process has used since it started, from /proc/<pid>/stat
+pub fn read_process_cpu_time(pid: u32) -> Option<f64> {
+    let contents = fs::read_to_string(format!("/proc/{}/stat", pid)).ok()?;
+
+    // the name in parentheses may contain spaces, the fields after it start with the state;
+    // utime and stime are the 14th and 15th fields, in clock ticks
+    let (_, rest) = contents.rsplit_once(')')?;
+    let mut fields = rest.split_whitespace().skip(11);
+    let user: u64 = fields.next()?.parse().ok()?;
+    let system: u64 = fields.next()?.parse().ok()?;
+    Some((user + system) as f64 / clock_ticks_per_sec())
+}
+
+#[cfg(unix)]
+fn clock_ticks_per_sec() -> f64 {
+    // sysconf only reads a system constant
+    let ticks = unsafe { libc::sysconf(libc::_SC_CLK_TCK) };
+    if ticks > 0 {
+        ticks as f64
+    } else {
+        100.
+    }
+}
+
+#[cfg(not(unix))]
+fn clock_ticks_per_sec() -> f64 {
+    100.
+}
@@ -0,0 +1,218 @@
+// Current metrics in the Prometheus text exposition format, to paste into an issue or drop
+// into a node_exporter textfile directory
+use crate::models::SystemMonitor;
+use crate::utils::current_timestamp;
+#[cfg(feature = "gui")]
+use chrono::Local;
+use std::fmt::Write;
+use std::fs;
+use std::io;
+
+const PREFIX: &str = "system_monitor";
+
+// Builds the exposition text one metric family at a time
+struct Exposition {
+    text: String,
+}
+
+impl Exposition {
+    // HELP and TYPE lines followed by one line per set of labels
+    fn family<'a>(
+        &mut self,
+        name: &str,
+        kind: &str,
+        help: &str,
+        samples: impl IntoIterator<Item = (Vec<(&'a str, &'a str)>, f64)>,
+    ) {
+        let _ = writeln!(self.text, "# HELP {}_{} {}", PREFIX, name, help);
+        let _ = writeln!(self.text, "# TYPE {}_{} {}", PREFIX, name, kind);
+        for (labels, value) in samples {
+            let labels: Vec<String> = labels
+                .iter()
+                .map(|(label, value)| format!("{}=\"{}\"", label, escape_label(value)))
+                .collect();
+            if labels.is_empty() {
+                let _ = writeln!(self.text, "{}_{} {}", PREFIX, name, value);
+            } else {
+                let _ = writeln!(
+                    self.text,
+                    "{}_{}{{{}}} {}",
+                    PREFIX,
+                    name,
+                    labels.join(","),
+                    value
+                );
+            }
+        }
+    }
+
+    fn gauge(&mut self, name: &str, help: &str, value: f64) {
+        self.family(name, "gauge", help, [(Vec::new(), value)]);
+    }
+}
+
+// backslashes, quotes and line breaks are the only characters escaped in label values
+fn escape_label(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+pub fn render_metrics(system_monitor: &SystemMonitor) -> String {
+    let mut exposition = Exposition {
+        text: format!(
+            "# snapshot of {} taken at {}\n",
+            system_monitor.host_identity.host_name,
+            current_timestamp()
+        ),
+    };
+
+    exposition.gauge(
+        "cpu_usage_percent",
+        "CPU usage over all cores.",
+        system_monitor.cpu_usage as f64,
+    );
+    exposition.gauge(
+        "cpu_steal_percent",
+        "CPU time taken by the hypervisor for other guests.",
+        system_monitor.cpu_steal_percent,
+    );
+    exposition.family(
+        "cpu_core_usage_percent",
+        "gauge",
+        "CPU usage of each logical processor.",
+        system_monitor
+            .processors_info
+            .iter()
+            .map(|(name, usage, _)| (vec![("core", &**name)], *usage as f64)),
+    );
+
+    exposition.gauge(
+        "memory_used_bytes",
+        "Memory used by programs.",
+        system_monitor.memory_usage.0 as f64,
+    );
+    exposition.gauge(
+        "memory_cache_bytes",
+        "Page cache and buffers the system can reclaim.",
+        system_monitor.memory_cache as f64,
+    );
+    exposition.gauge(
+        "memory_total_bytes",
+        "Installed memory.",
+        system_monitor.memory_usage.1 as f64,
+    );
+    exposition.gauge(
+        "swap_used_bytes",
+        "Swap in use.",
+        system_monitor.swap_memory_usage.0 as f64,
+    );
+    exposition.gauge(
+        "swap_total_bytes",
+        "Swap size.",
+        system_monitor.swap_memory_usage.1 as f64,
+    );
+
+    let disks = &system_monitor.disks_info;
+    exposition.family(
+        "disk_used_bytes",
+        "gauge",
+        "Space used on each mounted partition.",
+        disks.iter().map(|disk| {
+            (
+                vec![("name", disk.name.as_str()), ("mount", disk.mount.as_str())],
+                disk.total_disk.saturating_sub(disk.free_disk) as f64,
+            )
+        }),
+    );
+    exposition.family(
+        "disk_total_bytes",
+        "gauge",
+        "Size of each mounted partition.",
+        disks.iter().map(|disk| {
+            (
+                vec![("name", disk.name.as_str()), ("mount", disk.mount.as_str())],
+                disk.total_disk as f64,
+            )
+        }),
+    );
+    let disk_io = &system_monitor.disk_io;
+    exposition.family(
+        "disk_read_iops",
+        "gauge",
+        "Read operations per second of each block device.",
+        disk_io
+            .iter()
+            .map(|disk| (vec![("device", disk.name.as_str())], disk.read_iops)),
+    );
+    exposition.family(
+        "disk_write_iops",
+        "gauge",
+        "Write operations per second of each block device.",
+        disk_io
+            .iter()
+            .map(|disk| (vec![("device", disk.name.as_str())], disk.write_iops)),
+    );
+
+    exposition.family(
+        "network_sent_bytes_total",
+        "counter",
+        "Bytes sent over all interfaces.",
+        [(Vec::new(), system_monitor.network_sent as f64)],
+    );
+    exposition.family(
+        "network_received_bytes_total",
+        "counter",
+        "Bytes received over all interfaces.",
+        [(Vec::new(), system_monitor.network_received as f64)],
+    );
+    exposition.gauge(
+        "network_sent_bytes_per_second",
+        "Send rate over all interfaces.",
+        system_monitor.network_sent_rate,
+    );
+    exposition.gauge(
+        "network_received_bytes_per_second",
+        "Receive rate over all interfaces.",
+        system_monitor.network_received_rate,
+    );
+    if let Some(tcp) = system_monitor.tcp_states {
+        exposition.family(
+            "tcp_sockets",
+            "gauge",
+            "TCP sockets (IPv4 and IPv6) by state.",
+            [
+                ("established", tcp.established),
+                ("time_wait", tcp.time_wait),
+                ("close_wait", tcp.close_wait),
+                ("listen", tcp.listen),
+            ]
+            .into_iter()
+            .map(|(state, count)| (vec![("state", state)], count as f64)),
+        );
+    }
+
+    exposition.gauge(
+        "processes",
+        "Running processes.",
+        system_monitor.no_of_processes as f64,
+    );
+    exposition.text
+}
+
+// write the snapshot to metrics_<local time>.prom, returns the file name
+#[cfg(feature = "gui")]
+pub fn save_metrics(metrics: &str) -> io::Result<String> {
+    let path = format!("metrics_{}.prom", Local::now().format("%Y%m%d-%H%M%S"));
+    fs::write(&path, metrics)?;
+    Ok(path)
+}
+
+// replace the textfile collector's file in one step, node_exporter could otherwise read it
+// half written
+pub fn write_textfile(system_monitor: &SystemMonitor, path: &str) -> io::Result<()> {
+    let partial = format!("{}.tmp", path);
+    fs::write(&partial, render_metrics(system_monitor))?;
+    fs::rename(&partial, path)
+}
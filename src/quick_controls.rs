@@ -0,0 +1,117 @@
+// Screen brightness and audio volume of a Linux desktop, for the sliders in mini mode
+use std::fs;
+use std::path::PathBuf;
+use std::process::Command;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QuickControl {
+    Brightness,
+    Volume,
+}
+
+impl std::fmt::Display for QuickControl {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            QuickControl::Brightness => write!(f, "Brightness"),
+            QuickControl::Volume => write!(f, "Volume"),
+        }
+    }
+}
+
+// current level as a percentage, None when there is nothing to control (no backlight, no
+// sound server) or on other systems
+pub fn read_level(control: QuickControl) -> Option<f32> {
+    if !cfg!(target_os = "linux") {
+        return None;
+    }
+    match control {
+        QuickControl::Brightness => read_brightness(),
+        QuickControl::Volume => read_volume(),
+    }
+}
+
+pub fn set_level(control: QuickControl, percent: f32) -> Result<(), String> {
+    let percent = percent.clamp(0., 100.);
+    match control {
+        QuickControl::Brightness => set_brightness(percent),
+        QuickControl::Volume => set_volume(percent),
+    }
+}
+
+// the first display backlight, laptops usually have exactly one
+fn backlight() -> Option<PathBuf> {
+    let mut devices: Vec<PathBuf> = fs::read_dir("/sys/class/backlight")
+        .ok()?
+        .flatten()
+        .map(|device| device.path())
+        .collect();
+    devices.sort();
+    devices.into_iter().next()
+}
+
+fn read_number(path: PathBuf) -> Option<f32> {
+    fs::read_to_string(path).ok()?.trim().parse().ok()
+}
+
+fn read_brightness() -> Option<f32> {
+    let device = backlight()?;
+    let max = read_number(device.join("max_brightness"))?;
+    let current = read_number(device.join("brightness"))?;
+    (max > 0.).then(|| current / max * 100.)
+}
+
+// writing /sys needs a udev rule or root, brightnessctl is setuid or talks to logind instead
+fn set_brightness(percent: f32) -> Result<(), String> {
+    let device = backlight().ok_or_else(|| String::from("No backlight found"))?;
+    let max = read_number(device.join("max_brightness"))
+        .ok_or_else(|| String::from("Can't read the maximum brightness"))?;
+    // never all the way to a black screen
+    let level = (max * percent / 100.).round().max(1.);
+    if fs::write(device.join("brightness"), format!("{}", level)).is_ok() {
+        return Ok(());
+    }
+    run(
+        "brightnessctl",
+        &["set", &format!("{}%", percent.round().max(1.))],
+    )
+}
+
+// PipeWire's wpctl prints "Volume: 0.45" (with " [MUTED]" when muted), pactl prints the
+// volume of every channel as "65536 /  100% / 0.00 dB"
+fn read_volume() -> Option<f32> {
+    if let Some(stdout) = output("wpctl", &["get-volume", "@DEFAULT_AUDIO_SINK@"]) {
+        let volume: f32 = stdout.split_whitespace().nth(1)?.parse().ok()?;
+        return Some(volume * 100.);
+    }
+    let stdout = output("pactl", &["get-sink-volume", "@DEFAULT_SINK@"])?;
+    stdout
+        .split('/')
+        .find_map(|field| field.trim().strip_suffix('%')?.trim().parse().ok())
+}
+
+fn set_volume(percent: f32) -> Result<(), String> {
+    let level = format!("{:.0}%", percent);
+    run("wpctl", &["set-volume", "@DEFAULT_AUDIO_SINK@", &level])
+        .or_else(|_| run("pactl", &["set-sink-volume", "@DEFAULT_SINK@", &level]))
+}
+
+fn output(program: &str, args: &[&str]) -> Option<String> {
+    let output = Command::new(program).args(args).output().ok()?;
+    output
+        .status
+        .success()
+        .then(|| String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+fn run(program: &str, args: &[&str]) -> Result<(), String> {
+    let output = Command::new(program)
+        .args(args)
+        .output()
+        .map_err(|e| format!("{} failed: {}", program, e))?;
+    if output.status.success() {
+        Ok(())
+    } else {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        Err(format!("{} failed: {}", program, stderr.trim()))
+    }
+}
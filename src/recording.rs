@@ -0,0 +1,337 @@
+// Session recordings: the full snapshot of every refresh, so a replay goes through the same
+// alerts, history, charts and process list as the live session did
+use crate::gpu::GpuStatus;
+use crate::identity::HostIdentity;
+use crate::models::{
+    DiskIo, DisksInfo, InterruptInfo, PortCount, Process, ProtocolRates, SystemBaseInfo,
+    SystemMonitor, TcpStates,
+};
+use crate::pi::PiStatus;
+use crate::power::BatteryInfo;
+use crate::sensors::SensorReading;
+use crate::timesync::TimeSync;
+use crate::updates::UpdateStatus;
+use chrono::Local;
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Lines, Read, Write};
+use std::sync::{mpsc, Arc};
+use std::thread;
+
+// first line of a recording, what doesn't change between refreshes
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecordingHeader {
+    pub system_base_info: SystemBaseInfo,
+    pub host: HostIdentity,
+}
+
+// Everything one refresh reads from the system
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TickSnapshot {
+    pub timestamp: String,
+    pub cpu_usage: f32,
+    pub cpu_steal_percent: f64,
+    pub physical_cores: u32,
+    pub logical_processors: u32,
+    pub processors_info: Vec<(Arc<str>, f32, u64)>,
+    pub offline_cpus: Vec<u32>,
+    pub interrupt_info: InterruptInfo,
+    pub memory_usage: (u64, u64),
+    #[serde(default)]
+    pub memory_cache: u64,
+    pub swap_memory_usage: (u64, u64),
+    pub disk_usage: (u64, u64),
+    pub disks_info: Vec<DisksInfo>,
+    pub disk_io: Vec<DiskIo>,
+    pub network_sent: u64,
+    pub network_received: u64,
+    pub network_sent_rate: f64,
+    pub network_received_rate: f64,
+    pub tcp_states: Option<TcpStates>,
+    pub sensors: Vec<SensorReading>,
+    pub no_of_processes: u32,
+    pub processes: Vec<Process>,
+    // the readings below are missing in recordings made before they were recorded
+    #[serde(default)]
+    pub gpu: GpuStatus,
+    #[serde(default)]
+    pub pi: Option<PiStatus>,
+    #[serde(default)]
+    pub user_idle: Option<bool>,
+    // the packet rates this refresh added to the chart, None when it added none
+    #[serde(default)]
+    pub protocol_rates: Option<ProtocolRates>,
+    #[serde(default)]
+    pub top_ports: Vec<PortCount>,
+    #[serde(default)]
+    pub on_battery: Option<bool>,
+    #[serde(default)]
+    pub battery: Option<BatteryInfo>,
+    #[serde(default)]
+    pub time_sync: Option<TimeSync>,
+    #[serde(default)]
+    pub update_status: Option<UpdateStatus>,
+}
+
+impl TickSnapshot {
+    pub fn capture(system_monitor: &SystemMonitor) -> TickSnapshot {
+        TickSnapshot {
+            timestamp: system_monitor.sample_timestamp(),
+            cpu_usage: system_monitor.cpu_usage,
+            cpu_steal_percent: system_monitor.cpu_steal_percent,
+            physical_cores: system_monitor.physical_cores,
+            logical_processors: system_monitor.logical_processors,
+            processors_info: system_monitor.processors_info.clone(),
+            offline_cpus: system_monitor.offline_cpus.clone(),
+            interrupt_info: system_monitor.interrupt_info.clone(),
+            memory_usage: system_monitor.memory_usage,
+            memory_cache: system_monitor.memory_cache,
+            swap_memory_usage: system_monitor.swap_memory_usage,
+            disk_usage: system_monitor.disk_usage,
+            disks_info: system_monitor.disks_info.clone(),
+            disk_io: system_monitor.disk_io.clone(),
+            network_sent: system_monitor.network_sent,
+            network_received: system_monitor.network_received,
+            network_sent_rate: system_monitor.network_sent_rate,
+            network_received_rate: system_monitor.network_received_rate,
+            tcp_states: system_monitor.tcp_states,
+            sensors: system_monitor.sensors.clone(),
+            no_of_processes: system_monitor.no_of_processes,
+            processes: system_monitor.processes.clone(),
+            gpu: system_monitor.gpu.clone(),
+            pi: system_monitor.pi.clone(),
+            user_idle: system_monitor.user_idle,
+            protocol_rates: system_monitor.new_protocol_rates,
+            top_ports: system_monitor.top_ports.clone(),
+            on_battery: system_monitor.on_battery,
+            battery: system_monitor.battery.clone(),
+            time_sync: system_monitor.time_sync.clone(),
+            update_status: system_monitor.update_status,
+        }
+    }
+
+    pub fn apply(self, system_monitor: &mut SystemMonitor) {
+        system_monitor.cpu_usage = self.cpu_usage;
+        system_monitor.cpu_steal_percent = self.cpu_steal_percent;
+        system_monitor.physical_cores = self.physical_cores;
+        system_monitor.logical_processors = self.logical_processors;
+        system_monitor.processors_info = self.processors_info;
+        system_monitor.offline_cpus = self.offline_cpus;
+        system_monitor.interrupt_info = self.interrupt_info;
+        system_monitor.memory_usage = self.memory_usage;
+        system_monitor.memory_cache = self.memory_cache;
+        system_monitor.swap_memory_usage = self.swap_memory_usage;
+        system_monitor.disk_usage = self.disk_usage;
+        system_monitor.disks_info = self.disks_info;
+        system_monitor.disk_io = self.disk_io;
+        system_monitor.network_sent = self.network_sent;
+        system_monitor.network_received = self.network_received;
+        system_monitor.network_sent_rate = self.network_sent_rate;
+        system_monitor.network_received_rate = self.network_received_rate;
+        system_monitor.tcp_states = self.tcp_states;
+        system_monitor.sensors = self.sensors;
+        system_monitor.no_of_processes = self.no_of_processes;
+        system_monitor.processes = self.processes;
+        system_monitor.gpu = self.gpu;
+        system_monitor.pi = self.pi;
+        system_monitor.user_idle = self.user_idle;
+        if let Some(rates) = self.protocol_rates {
+            system_monitor.push_protocol_rates(rates);
+        }
+        system_monitor.top_ports = self.top_ports;
+        system_monitor.on_battery = self.on_battery;
+        system_monitor.battery = self.battery;
+        system_monitor.time_sync = self.time_sync;
+        system_monitor.update_status = self.update_status;
+    }
+}
+
+// Writes one JSON line per refresh into a compressed session_<local time>.rec file. The frames
+// are serialized and compressed on a thread of the recording's own, fed through a queue without
+// a limit so that no refresh is left out however far behind the thread is
+#[derive(Debug)]
+pub struct Recorder {
+    pub path: String,
+    pub frames: u64,
+    queue: mpsc::Sender<TickSnapshot>,
+    // ends early only when a write failed, with that failure
+    thread: thread::JoinHandle<io::Result<()>>,
+}
+
+impl Recorder {
+    pub fn create(system_monitor: &mut SystemMonitor) -> io::Result<Recorder> {
+        let path = format!(
+            "session_{}.rec{}",
+            Local::now().format("%Y%m%d-%H%M%S"),
+            COMPRESSED_EXTENSION
+        );
+        let mut writer = compressed_writer(File::create(&path)?)?;
+        let header = RecordingHeader {
+            system_base_info: system_monitor.system_base_info.clone(),
+            host: (*system_monitor.host_identity).clone(),
+        };
+        writeln!(writer, "{}", serde_json::to_string(&header)?)?;
+        let (queue, frames) = mpsc::channel();
+        let thread = thread::Builder::new()
+            .name(String::from("recording-writer"))
+            .spawn(move || write_frames(writer, frames))?;
+        Ok(Recorder {
+            path,
+            frames: 0,
+            queue,
+            thread,
+        })
+    }
+
+    // an error means the recording has stopped, finish tells why
+    pub fn record(&mut self, snapshot: TickSnapshot) -> io::Result<()> {
+        if self.thread.is_finished() || self.queue.send(snapshot).is_err() {
+            return Err(io::Error::other("the recording thread stopped"));
+        }
+        self.frames += 1;
+        Ok(())
+    }
+
+    // wait until every queued frame is written, then end the stream
+    pub fn finish(self) -> io::Result<()> {
+        drop(self.queue);
+        self.thread
+            .join()
+            .unwrap_or_else(|_| Err(io::Error::other("the recording thread panicked")))
+    }
+}
+
+// serializing and compressing the whole process list is the expensive part of a refresh, which
+// is why it happens here rather than in the collector
+fn write_frames(
+    mut writer: Box<dyn Write + Send>,
+    frames: mpsc::Receiver<TickSnapshot>,
+) -> io::Result<()> {
+    for snapshot in frames {
+        writeln!(writer, "{}", serde_json::to_string(&snapshot)?)?;
+    }
+    writer.flush()
+}
+
+#[cfg(feature = "zstd")]
+const COMPRESSED_EXTENSION: &str = ".zst";
+#[cfg(all(not(feature = "zstd"), feature = "gzip"))]
+const COMPRESSED_EXTENSION: &str = ".gz";
+#[cfg(not(any(feature = "zstd", feature = "gzip")))]
+const COMPRESSED_EXTENSION: &str = "";
+
+// zstd where available, otherwise gzip, otherwise plain JSON lines
+fn compressed_writer(file: File) -> io::Result<Box<dyn Write + Send>> {
+    #[cfg(feature = "zstd")]
+    let writer: Box<dyn Write + Send> = Box::new(zstd::Encoder::new(file, 0)?.auto_finish());
+    #[cfg(all(not(feature = "zstd"), feature = "gzip"))]
+    let writer: Box<dyn Write + Send> = Box::new(flate2::write::GzEncoder::new(
+        io::BufWriter::new(file),
+        flate2::Compression::default(),
+    ));
+    #[cfg(not(any(feature = "zstd", feature = "gzip")))]
+    let writer: Box<dyn Write + Send> = Box::new(io::BufWriter::new(file));
+    Ok(writer)
+}
+
+// Recorded refreshes fed back one per tick in place of the real system, read from the file as
+// they are shown so a long recording never has to fit in memory
+pub struct Replay {
+    pub path: String,
+    frames: Lines<BufReader<Box<dyn Read + Send>>>,
+    position: usize,
+    pub total: usize,
+    // time of the frame shown last, None before the first
+    timestamp: Option<String>,
+}
+
+impl std::fmt::Debug for Replay {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Replay({}, {}/{})", self.path, self.position, self.total)
+    }
+}
+
+impl Replay {
+    pub fn load(path: &str) -> io::Result<(RecordingHeader, Replay)> {
+        let mut lines = BufReader::new(decompressed_reader(path)?).lines();
+        let header = lines
+            .next()
+            .ok_or_else(|| io::Error::other(format!("{} is empty", path)))??;
+        let header: RecordingHeader = serde_json::from_str(&header)?;
+
+        let replay = Replay {
+            path: path.to_string(),
+            frames: lines,
+            position: 0,
+            total: count_frames(path)?,
+            timestamp: None,
+        };
+        Ok((header, replay))
+    }
+
+    // None once the recording has run out, the values then stay as they are; a frame that can't
+    // be read ends the replay there, e.g. a recording cut short by a crash
+    pub fn next_frame(&mut self) -> Option<TickSnapshot> {
+        let frame = self
+            .frames
+            .next()?
+            .and_then(|line| serde_json::from_str::<TickSnapshot>(&line).map_err(io::Error::from));
+        let frame = match frame {
+            Ok(frame) => frame,
+            Err(e) => {
+                eprintln!(
+                    "Failed to read frame {} of {}: {}",
+                    self.position + 1,
+                    self.path,
+                    e
+                );
+                self.total = self.position;
+                return None;
+            }
+        };
+        self.position += 1;
+        self.timestamp = Some(frame.timestamp.clone());
+        Some(frame)
+    }
+
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    pub fn timestamp(&self) -> Option<&str> {
+        self.timestamp.as_deref()
+    }
+}
+
+// lines after the header, counted in a pass of their own that keeps none of them; counting stops
+// where a recording cut short stops being readable
+fn count_frames(path: &str) -> io::Result<usize> {
+    let mut reader = BufReader::new(decompressed_reader(path)?);
+    let mut line = Vec::new();
+    let mut lines: usize = 0;
+    while let Ok(1..) = reader.read_until(b'\n', &mut line) {
+        lines += 1;
+        line.clear();
+    }
+    Ok(lines.saturating_sub(1))
+}
+
+// the compression is told by the extension, as written by Recorder
+pub fn decompressed_reader(path: &str) -> io::Result<Box<dyn Read + Send>> {
+    let file = File::open(path)?;
+    // a rotated log keeps its compression, e.g. system_log.json.gz.2
+    let name = path
+        .rsplit_once('.')
+        .filter(|(_, n)| !n.is_empty() && n.bytes().all(|b| b.is_ascii_digit()))
+        .map_or(path, |(name, _)| name);
+    match name.rsplit_once('.').map(|(_, extension)| extension) {
+        #[cfg(feature = "zstd")]
+        Some("zst") => Ok(Box::new(zstd::Decoder::new(file)?)),
+        #[cfg(feature = "gzip")]
+        Some("gz") => Ok(Box::new(flate2::read::MultiGzDecoder::new(file))),
+        Some(extension) if extension == "zst" || extension == "gz" => Err(io::Error::other(
+            format!("built without support for the compression of {}", path),
+        )),
+        _ => Ok(Box::new(file)),
+    }
+}
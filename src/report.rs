@@ -0,0 +1,113 @@
+// Markdown summary of the machine and its current state, to paste into a forum post or a
+// support ticket
+use crate::format::{usage_percent, NumberFormat, NumberLocale};
+use crate::models::{Process, SystemMonitor};
+use crate::utils::current_timestamp;
+use std::fmt::Write;
+use sysinfo::System;
+
+// enough to show what is keeping the machine busy without turning the post into a wall of text
+const REPORT_PROCESSES: usize = 10;
+
+// the report is read by people on other locales, so numbers use the plain format
+pub fn render_report(system_monitor: &SystemMonitor) -> String {
+    let numbers = NumberFormat::new(2, NumberLocale::Plain);
+    let base = &system_monitor.system_base_info;
+    let mut report = String::new();
+
+    let _ = writeln!(report, "## System report ({})\n", current_timestamp());
+
+    let _ = writeln!(report, "### Hardware and OS\n");
+    let cpu_brand = system_monitor
+        .system
+        .cpus()
+        .first()
+        .map_or("unknown", |cpu| cpu.brand().trim());
+    let _ = writeln!(
+        report,
+        "- **OS:** {} {} (kernel {})",
+        base.system_name, base.os_version, base.kernal_version
+    );
+    let _ = writeln!(
+        report,
+        "- **CPU:** {}, {} logical cores",
+        cpu_brand,
+        system_monitor.processors_info.len()
+    );
+    let _ = writeln!(
+        report,
+        "- **Memory:** {}, swap {}",
+        numbers.bytes(system_monitor.memory_usage.1 as f64),
+        numbers.bytes(system_monitor.swap_memory_usage.1 as f64)
+    );
+    for gpu in &system_monitor.gpu.gpus {
+        let _ = writeln!(report, "- **GPU {}:** {}", gpu.index, gpu.name);
+    }
+    let _ = writeln!(
+        report,
+        "- **Uptime:** {} hours",
+        numbers.number(System::uptime() as f64 / 3600.)
+    );
+
+    let _ = writeln!(report, "\n### Current state\n");
+    let _ = writeln!(
+        report,
+        "- **CPU:** {} ({} processes)",
+        numbers.percent(system_monitor.cpu_usage as f64),
+        system_monitor.no_of_processes
+    );
+    let _ = writeln!(
+        report,
+        "- **Memory:** {} of {} ({})",
+        numbers.bytes(system_monitor.memory_usage.0 as f64),
+        numbers.bytes(system_monitor.memory_usage.1 as f64),
+        numbers.percent(usage_percent(system_monitor.memory_usage))
+    );
+    let _ = writeln!(
+        report,
+        "- **Swap:** {} of {}",
+        numbers.bytes(system_monitor.swap_memory_usage.0 as f64),
+        numbers.bytes(system_monitor.swap_memory_usage.1 as f64)
+    );
+    if let Some(on_battery) = system_monitor.on_battery {
+        let source = if on_battery { "battery" } else { "AC" };
+        let _ = writeln!(report, "- **Power:** on {}", source);
+    }
+
+    let _ = writeln!(report, "\n### Disks\n");
+    let _ = writeln!(report, "| Disk | Mount | Used | Size |");
+    let _ = writeln!(report, "|---|---|---|---|");
+    for disk in &system_monitor.disks_info {
+        let _ = writeln!(
+            report,
+            "| {} | {} | {} | {} |",
+            cell(&disk.label),
+            cell(&disk.mount),
+            numbers.percent(disk.used_disk_percent),
+            numbers.bytes(disk.total_disk as f64)
+        );
+    }
+
+    let _ = writeln!(report, "\n### Top processes by CPU\n");
+    let _ = writeln!(report, "| PID | Name | User | CPU | Memory |");
+    let _ = writeln!(report, "|---|---|---|---|---|");
+    let mut processes: Vec<&Process> = system_monitor.processes.iter().collect();
+    processes.sort_by(|a, b| b.cpu_usage_percent.total_cmp(&a.cpu_usage_percent));
+    for process in processes.into_iter().take(REPORT_PROCESSES) {
+        let _ = writeln!(
+            report,
+            "| {} | {} | {} | {} | {} |",
+            process.id,
+            cell(&process.name),
+            cell(&process.user),
+            numbers.percent(process.cpu_usage_percent),
+            numbers.percent(process.memory_usage_percent)
+        );
+    }
+    report
+}
+
+// pipes would end the table cell
+fn cell(value: &str) -> String {
+    value.replace('|', "\\|")
+}
@@ -0,0 +1,96 @@
+// Detects crash loops: processes of the same name that keep exiting and starting again
+use crate::models::Process;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+const WINDOW: Duration = Duration::from_secs(60);
+
+#[derive(Debug, Default)]
+pub struct RespawnTracker {
+    // name of every process at the previous refresh, keyed by pid and start time
+    previous: HashMap<(u32, u64), Arc<str>>,
+    // when a process of each name last exited
+    last_exit: HashMap<Arc<str>, Instant>,
+    // restarts of each name within the last minute
+    restarts: HashMap<Arc<str>, VecDeque<Instant>>,
+    // names that were reported and haven't calmed down since
+    reported: HashSet<Arc<str>>,
+}
+
+impl RespawnTracker {
+    // compare with the previous refresh, returns the names that just crossed
+    // `threshold` restarts per minute together with their restart count
+    pub fn update(
+        &mut self,
+        processes: &[Process],
+        now: Instant,
+        threshold: usize,
+    ) -> Vec<(Arc<str>, usize)> {
+        let current: HashMap<(u32, u64), Arc<str>> = processes
+            .iter()
+            .map(|p| ((p.id, p.start_time), p.name.clone()))
+            .collect();
+
+        for (key, name) in &self.previous {
+            if !current.contains_key(key) {
+                self.last_exit.insert(name.clone(), now);
+            }
+        }
+
+        // the first refresh has nothing to compare with
+        if !self.previous.is_empty() {
+            for (key, name) in &current {
+                let restarted = !self.previous.contains_key(key)
+                    && self
+                        .last_exit
+                        .get(name)
+                        .is_some_and(|exit| now.duration_since(*exit) <= WINDOW);
+                if restarted {
+                    self.restarts
+                        .entry(name.clone())
+                        .or_default()
+                        .push_back(now);
+                }
+            }
+        }
+        self.previous = current;
+
+        self.last_exit
+            .retain(|_, exit| now.duration_since(*exit) <= WINDOW);
+        for times in self.restarts.values_mut() {
+            while times
+                .front()
+                .is_some_and(|time| now.duration_since(*time) > WINDOW)
+            {
+                times.pop_front();
+            }
+        }
+        self.restarts.retain(|_, times| !times.is_empty());
+
+        if threshold == 0 {
+            return Vec::new();
+        }
+
+        let mut looping = Vec::new();
+        for (name, times) in &self.restarts {
+            if times.len() >= threshold && self.reported.insert(name.clone()) {
+                looping.push((name.clone(), times.len()));
+            }
+        }
+        // report again once a name has calmed down and starts looping anew
+        let restarts = &self.restarts;
+        self.reported.retain(|name| {
+            restarts
+                .get(name)
+                .is_some_and(|times| times.len() >= threshold)
+        });
+
+        looping
+    }
+
+    // restarts in the last minute of the name that restarts most
+    pub fn highest_rate(&self) -> usize {
+        self.restarts.values().map(VecDeque::len).max().unwrap_or(0)
+    }
+}
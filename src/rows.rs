@@ -0,0 +1,98 @@
+// Text of the process and disk rows, kept between refreshes and only formatted again for the
+// processes and disks the refresh's delta lists as added or changed
+use crate::delta::SnapshotDelta;
+use crate::format::NumberFormat;
+use crate::models::{DisksInfo, Process};
+use crate::widgets::ProcessRow;
+use std::collections::{HashMap, HashSet};
+
+#[derive(Debug, Clone)]
+pub struct DiskRow {
+    pub kind: String,
+    pub mount: String,
+    pub total: String,
+    pub free: String,
+    pub used: String,
+}
+
+impl DiskRow {
+    pub fn new(disk: &DisksInfo, numbers: NumberFormat) -> DiskRow {
+        DiskRow {
+            kind: format!("Type: {}", disk.kind),
+            mount: format!("Mount Point: {}", disk.mount),
+            total: format!(
+                "Total Disk Space: {}",
+                numbers.bytes(disk.total_disk as f64)
+            ),
+            free: format!("Free Disk Space: {}", numbers.bytes(disk.free_disk as f64)),
+            used: format!("Used Disk: {}", numbers.percent(disk.used_disk_percent)),
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct PanelRows {
+    // by pid and start time
+    pub processes: HashMap<(u32, u64), ProcessRow>,
+    // by mount point
+    pub disks: HashMap<String, DiskRow>,
+    // the format the rows were written in, None before the first refresh
+    numbers: Option<NumberFormat>,
+}
+
+impl PanelRows {
+    pub fn apply(
+        &mut self,
+        delta: &SnapshotDelta,
+        processes: &[Process],
+        disks: &[DisksInfo],
+        numbers: NumberFormat,
+    ) {
+        // another number format changes every row
+        if self.numbers != Some(numbers) {
+            self.processes = processes
+                .iter()
+                .map(|p| ((p.id, p.start_time), ProcessRow::new(p, numbers)))
+                .collect();
+            self.disks = disks
+                .iter()
+                .map(|d| (d.mount.clone(), DiskRow::new(d, numbers)))
+                .collect();
+            self.numbers = Some(numbers);
+            return;
+        }
+
+        for key in &delta.processes.removed {
+            self.processes.remove(key);
+        }
+        let touched: HashSet<&(u32, u64)> = delta
+            .processes
+            .added
+            .iter()
+            .chain(&delta.processes.changed)
+            .collect();
+        if !touched.is_empty() {
+            for process in processes {
+                let key = (process.id, process.start_time);
+                if touched.contains(&key) {
+                    self.processes
+                        .insert(key, ProcessRow::new(process, numbers));
+                }
+            }
+        }
+
+        for mount in &delta.disks.removed {
+            self.disks.remove(mount);
+        }
+        let touched: HashSet<&String> = delta
+            .disks
+            .added
+            .iter()
+            .chain(&delta.disks.changed)
+            .collect();
+        for disk in disks.iter().filter(|disk| touched.contains(&disk.mount)) {
+            self.disks
+                .insert(disk.mount.clone(), DiskRow::new(disk, numbers));
+        }
+    }
+}
@@ -0,0 +1,56 @@
+// Flags processes that have kept a CPU busy for a long time
+use crate::models::Process;
+use std::collections::{HashMap, HashSet};
+use std::time::{Duration, Instant};
+
+#[derive(Debug, Default)]
+pub struct RunawayTracker {
+    // when each process (pid and start time) went above the threshold
+    busy_since: HashMap<(u32, u64), Instant>,
+    reported: HashSet<(u32, u64)>,
+}
+
+impl RunawayTracker {
+    // track the processes above `cpu_percent`, returns the ones that have just been busy
+    // for `duration` and weren't reported before
+    pub fn update(
+        &mut self,
+        processes: &[Process],
+        cpu_percent: f64,
+        duration: Duration,
+        now: Instant,
+    ) -> Vec<(u32, u64)> {
+        let busy: HashSet<(u32, u64)> = processes
+            .iter()
+            .filter(|p| p.cpu_usage_percent >= cpu_percent)
+            .map(|p| (p.id, p.start_time))
+            .collect();
+
+        // a single quiet sample resets the clock, as with alert rules
+        self.busy_since.retain(|key, _| busy.contains(key));
+        self.reported.retain(|key| busy.contains(key));
+        for key in &busy {
+            self.busy_since.entry(*key).or_insert(now);
+        }
+
+        let mut runaways = Vec::new();
+        for (key, since) in &self.busy_since {
+            if now.duration_since(*since) >= duration && self.reported.insert(*key) {
+                runaways.push(*key);
+            }
+        }
+        runaways
+    }
+
+    // processes that have been busy for at least `duration`, longest first
+    pub fn runaways(&self, duration: Duration, now: Instant) -> Vec<((u32, u64), Duration)> {
+        let mut runaways: Vec<_> = self
+            .busy_since
+            .iter()
+            .map(|(key, since)| (*key, now.duration_since(*since)))
+            .filter(|(_, busy)| *busy >= duration)
+            .collect();
+        runaways.sort_by_key(|(_, busy)| std::cmp::Reverse(*busy));
+        runaways
+    }
+}
@@ -0,0 +1,88 @@
+// CPU and memory sampled on a background thread between dashboard refreshes, so the history
+// and the log can be finer grained than the redraw rate; every refresh redraws every window
+use crate::utils::{current_timestamp, memory_cache};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+use sysinfo::System;
+
+// One background reading
+#[derive(Debug, Clone)]
+pub struct FastSample {
+    pub timestamp: String,
+    pub cpu_usage: f32,
+    pub memory_usage: (u64, u64),
+    pub memory_cache: u64,
+    pub swap_memory_usage: (u64, u64),
+}
+
+#[derive(Debug)]
+pub struct Sampler {
+    pub interval: Duration,
+    samples: Arc<Mutex<Vec<FastSample>>>,
+    stop: Arc<AtomicBool>,
+}
+
+impl Sampler {
+    pub fn start(interval: Duration) -> Sampler {
+        let samples = Arc::new(Mutex::new(Vec::new()));
+        let stop = Arc::new(AtomicBool::new(false));
+
+        let (thread_samples, thread_stop) = (samples.clone(), stop.clone());
+        let spawned = thread::Builder::new()
+            .name(String::from("sampler"))
+            .spawn(move || {
+                // cpu usage is measured between two refreshes
+                let mut system = System::new();
+                system.refresh_cpu_all();
+
+                loop {
+                    thread::sleep(interval);
+                    if thread_stop.load(Ordering::Relaxed) {
+                        break;
+                    }
+                    system.refresh_cpu_all();
+                    system.refresh_memory();
+
+                    let sample = FastSample {
+                        timestamp: current_timestamp(),
+                        cpu_usage: system.global_cpu_usage(),
+                        memory_usage: (system.used_memory(), system.total_memory()),
+                        memory_cache: memory_cache(&system),
+                        swap_memory_usage: (system.used_swap(), system.total_swap()),
+                    };
+                    thread_samples
+                        .lock()
+                        .unwrap_or_else(|poisoned| poisoned.into_inner())
+                        .push(sample);
+                }
+            });
+        if let Err(e) = spawned {
+            eprintln!("Failed to start the background sampler: {}", e);
+        }
+
+        Sampler {
+            interval,
+            samples,
+            stop,
+        }
+    }
+
+    // readings taken since the last call, oldest first
+    pub fn take(&self) -> Vec<FastSample> {
+        std::mem::take(
+            &mut *self
+                .samples
+                .lock()
+                .unwrap_or_else(|poisoned| poisoned.into_inner()),
+        )
+    }
+}
+
+// the thread notices on its next wake-up and exits
+impl Drop for Sampler {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+    }
+}
@@ -0,0 +1,26 @@
+use chrono::Local;
+use iced::window::Screenshot;
+use std::fs::File;
+use std::io::BufWriter;
+
+// write a window capture to screenshot_<local time>.png, returns the file name
+pub fn save_png(screenshot: &Screenshot) -> Result<String, String> {
+    let path = format!("screenshot_{}.png", Local::now().format("%Y%m%d-%H%M%S"));
+    let file = File::create(&path).map_err(|e| format!("Failed to create {}: {}", path, e))?;
+
+    let mut encoder = png::Encoder::new(
+        BufWriter::new(file),
+        screenshot.size.width,
+        screenshot.size.height,
+    );
+    // captures are RGBA with 8 bits per channel
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+
+    encoder
+        .write_header()
+        .and_then(|mut writer| writer.write_image_data(&screenshot.bytes))
+        .map_err(|e| format!("Failed to write {}: {}", path, e))?;
+
+    Ok(path)
+}
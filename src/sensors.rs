@@ -0,0 +1,186 @@
+// Hardware sensors (temperatures, fans, power) behind one backend per OS
+use serde::{Deserialize, Serialize};
+use sysinfo::Components;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum SensorKind {
+    Temperature,
+    Fan,
+    Power,
+}
+
+impl SensorKind {
+    pub fn unit(&self) -> &'static str {
+        match self {
+            SensorKind::Temperature => "°C",
+            SensorKind::Fan => "RPM",
+            SensorKind::Power => "W",
+        }
+    }
+}
+
+impl std::fmt::Display for SensorKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SensorKind::Temperature => write!(f, "temp"),
+            SensorKind::Fan => write!(f, "fan"),
+            SensorKind::Power => write!(f, "power"),
+        }
+    }
+}
+
+// Same sensor across backends and refreshes, shown as e.g. "temp/coretemp/Package id 0"
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct SensorId {
+    pub kind: SensorKind,
+    // chip or driver the sensor belongs to, empty when the backend doesn't say
+    pub chip: String,
+    pub label: String,
+}
+
+impl std::fmt::Display for SensorId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.chip.is_empty() {
+            write!(f, "{}/{}", self.kind, self.label)
+        } else {
+            write!(f, "{}/{}/{}", self.kind, self.chip, self.label)
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SensorReading {
+    pub id: SensorId,
+    // in the unit of the sensor kind
+    pub value: f64,
+}
+
+pub trait SensorBackend: std::fmt::Debug + Send {
+    // shown when no sensors are found, so it's clear where they were looked for
+    fn name(&self) -> &'static str;
+    // current values, empty when the platform exposes none
+    fn read(&mut self) -> Vec<SensorReading>;
+}
+
+// backend for the current OS
+pub fn platform_backend() -> Box<dyn SensorBackend> {
+    if cfg!(target_os = "linux") {
+        Box::new(Hwmon)
+    } else {
+        Box::new(SystemComponents::new())
+    }
+}
+
+// Linux /sys/class/hwmon, the only one with fans and power readings
+#[derive(Debug)]
+pub struct Hwmon;
+
+impl Hwmon {
+    // "<prefix><n>_input" files of a chip with their labels, scaled to the sensor unit
+    fn read_chip(path: &std::path::Path, chip: &str, readings: &mut Vec<SensorReading>) {
+        let Ok(entries) = std::fs::read_dir(path) else {
+            return;
+        };
+        let sources = [
+            ("temp", "_input", SensorKind::Temperature, 1000.),
+            ("fan", "_input", SensorKind::Fan, 1.),
+            ("power", "_input", SensorKind::Power, 1_000_000.),
+            ("power", "_average", SensorKind::Power, 1_000_000.),
+        ];
+
+        for entry in entries.flatten() {
+            let file_name = entry.file_name().to_string_lossy().to_string();
+            for (prefix, suffix, kind, scale) in sources {
+                let Some(channel) = file_name
+                    .strip_prefix(prefix)
+                    .and_then(|rest| rest.strip_suffix(suffix))
+                    .filter(|channel| channel.chars().all(|c| c.is_ascii_digit()))
+                else {
+                    continue;
+                };
+                let Some(raw) = std::fs::read_to_string(entry.path())
+                    .ok()
+                    .and_then(|value| value.trim().parse::<f64>().ok())
+                else {
+                    continue;
+                };
+                let label = std::fs::read_to_string(path.join(format!("{prefix}{channel}_label")))
+                    .map(|label| label.trim().to_string())
+                    .unwrap_or_else(|_| format!("{prefix}{channel}"));
+
+                readings.push(SensorReading {
+                    id: SensorId {
+                        kind,
+                        chip: chip.to_string(),
+                        label,
+                    },
+                    value: raw / scale,
+                });
+            }
+        }
+    }
+}
+
+impl SensorBackend for Hwmon {
+    fn name(&self) -> &'static str {
+        "hwmon"
+    }
+
+    fn read(&mut self) -> Vec<SensorReading> {
+        let mut readings = Vec::new();
+        let Ok(chips) = std::fs::read_dir("/sys/class/hwmon") else {
+            return readings;
+        };
+        for chip in chips.flatten() {
+            let path = chip.path();
+            let name = std::fs::read_to_string(path.join("name"))
+                .map(|name| name.trim().to_string())
+                .unwrap_or_else(|_| chip.file_name().to_string_lossy().to_string());
+            Hwmon::read_chip(&path, &name, &mut readings);
+        }
+        readings.sort_by(|a, b| a.id.cmp(&b.id));
+        readings
+    }
+}
+
+// temperatures through sysinfo, which reads the SMC on macOS and WMI on Windows
+#[derive(Debug)]
+pub struct SystemComponents {
+    components: Components,
+}
+
+impl SystemComponents {
+    pub fn new() -> SystemComponents {
+        SystemComponents {
+            components: Components::new_with_refreshed_list(),
+        }
+    }
+}
+
+impl SensorBackend for SystemComponents {
+    fn name(&self) -> &'static str {
+        if cfg!(target_os = "macos") {
+            "SMC"
+        } else if cfg!(windows) {
+            "WMI"
+        } else {
+            "sysinfo"
+        }
+    }
+
+    fn read(&mut self) -> Vec<SensorReading> {
+        self.components.refresh();
+        self.components
+            .iter()
+            .filter(|component| component.temperature().is_finite())
+            .map(|component| SensorReading {
+                id: SensorId {
+                    kind: SensorKind::Temperature,
+                    chip: String::new(),
+                    label: component.label().to_string(),
+                },
+                value: component.temperature() as f64,
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,273 @@
+// Registers the headless collector (`--daemon`) to start with the system
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+const SERVICE_NAME: &str = "rust-system-monitor";
+
+// run a command and turn a non-zero exit into an error with its output
+fn run(program: &str, args: &[&str]) -> Result<(), String> {
+    let output = Command::new(program)
+        .args(args)
+        .output()
+        .map_err(|e| format!("Failed to run {}: {}", program, e))?;
+
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(format!(
+            "{} {} failed: {}",
+            program,
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        ))
+    }
+}
+
+// the daemon runs from the current directory so it uses the same config and log file
+fn daemon_command() -> Result<(PathBuf, PathBuf), String> {
+    let exe = env::current_exe().map_err(|e| format!("Cannot find executable: {}", e))?;
+    let dir = env::current_dir().map_err(|e| format!("Cannot find working directory: {}", e))?;
+    Ok((exe, dir))
+}
+
+// without --interval the daemon follows interval_secs in the config file
+fn daemon_args(interval_secs: Option<u64>) -> Vec<String> {
+    let mut args = vec![String::from("--daemon")];
+    if let Some(secs) = interval_secs {
+        args.push(String::from("--interval"));
+        args.push(secs.to_string());
+    }
+    args
+}
+
+fn home_dir() -> Result<PathBuf, String> {
+    env::var_os("HOME")
+        .map(PathBuf::from)
+        .ok_or_else(|| String::from("HOME is not set"))
+}
+
+// system-wide unit when running as root, otherwise a user unit
+fn systemd_unit_path() -> Result<(PathBuf, bool), String> {
+    let is_root = Command::new("id")
+        .arg("-u")
+        .output()
+        .map(|output| String::from_utf8_lossy(&output.stdout).trim() == "0")
+        .unwrap_or(false);
+
+    if is_root {
+        Ok((
+            PathBuf::from(format!("/etc/systemd/system/{}.service", SERVICE_NAME)),
+            false,
+        ))
+    } else {
+        Ok((
+            home_dir()?.join(format!(".config/systemd/user/{}.service", SERVICE_NAME)),
+            true,
+        ))
+    }
+}
+
+// a path as it goes into a unit file; a control character would end the line early and systemd
+// trims the value, so paths it can't hold unchanged are refused instead of written mangled
+fn unit_path(path: &Path) -> Result<&str, String> {
+    let text = path
+        .to_str()
+        .ok_or_else(|| format!("{} is not valid UTF-8", path.display()))?;
+    if text.chars().any(char::is_control) || text.trim() != text {
+        return Err(format!(
+            "{} cannot be written to a systemd unit",
+            path.display()
+        ));
+    }
+    Ok(text)
+}
+
+// an ExecStart argument in double quotes, escaped so systemd passes it on unchanged: backslash
+// and quote as C escapes, and '%' and '$' doubled so they aren't read as specifiers or variables
+fn exec_arg(arg: &str) -> String {
+    let mut quoted = String::from("\"");
+    for c in arg.chars() {
+        match c {
+            '\\' | '"' => {
+                quoted.push('\\');
+                quoted.push(c);
+            }
+            '%' | '$' => {
+                quoted.push(c);
+                quoted.push(c);
+            }
+            _ => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+fn systemd_unit(interval_secs: Option<u64>, user: bool) -> Result<String, String> {
+    let (exe, dir) = daemon_command()?;
+    let exec_start: Vec<String> = std::iter::once(unit_path(&exe)?.to_string())
+        .chain(daemon_args(interval_secs))
+        .map(|arg| exec_arg(&arg))
+        .collect();
+    Ok(format!(
+        "[Unit]
+Description=Real-Time System Monitor collector
+After=network.target
+
+[Service]
+Type=simple
+WorkingDirectory={}
+ExecStart={}
+ExecReload=/bin/kill -HUP $MAINPID
+Restart=on-failure
+
+[Install]
+WantedBy={}
+",
+        // WorkingDirectory takes no quotes, only specifiers need escaping
+        unit_path(&dir)?.replace('%', "%%"),
+        exec_start.join(" "),
+        if user {
+            "default.target"
+        } else {
+            "multi-user.target"
+        }
+    ))
+}
+
+fn launchd_plist(interval_secs: Option<u64>) -> Result<String, String> {
+    let (exe, dir) = daemon_command()?;
+    Ok(format!(
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE plist PUBLIC "-//Apple//DTD PLIST 1.0//EN" "http://www.apple.com/DTDs/PropertyList-1.0.dtd">
+<plist version="1.0">
+<dict>
+    <key>Label</key>
+    <string>com.{}</string>
+    <key>ProgramArguments</key>
+    <array>
+        <string>{}</string>
+{}
+    </array>
+    <key>WorkingDirectory</key>
+    <string>{}</string>
+    <key>RunAtLoad</key>
+    <true/>
+    <key>KeepAlive</key>
+    <true/>
+</dict>
+</plist>
+"#,
+        SERVICE_NAME,
+        exe.display(),
+        daemon_args(interval_secs)
+            .iter()
+            .map(|arg| format!("        <string>{}</string>", arg))
+            .collect::<Vec<_>>()
+            .join("\n"),
+        dir.display()
+    ))
+}
+
+fn launchd_plist_path() -> Result<PathBuf, String> {
+    Ok(home_dir()?.join(format!("Library/LaunchAgents/com.{}.plist", SERVICE_NAME)))
+}
+
+pub fn install_service(interval_secs: Option<u64>) -> Result<String, String> {
+    if cfg!(target_os = "macos") {
+        let path = launchd_plist_path()?;
+        write_file(&path, &launchd_plist(interval_secs)?)?;
+        run("launchctl", &["load", "-w", &path.to_string_lossy()])?;
+        Ok(format!("Installed launchd agent {}", path.display()))
+    } else if cfg!(windows) {
+        // a scheduled task started at logon stands in for a native Windows service
+        let (exe, dir) = daemon_command()?;
+        let task = format!(
+            "cmd /c cd /d \"{}\" && \"{}\" {}",
+            dir.display(),
+            exe.display(),
+            daemon_args(interval_secs).join(" ")
+        );
+        run(
+            "schtasks",
+            &[
+                "/Create",
+                "/F",
+                "/SC",
+                "ONLOGON",
+                "/TN",
+                SERVICE_NAME,
+                "/TR",
+                &task,
+            ],
+        )?;
+        run("schtasks", &["/Run", "/TN", SERVICE_NAME])?;
+        Ok(format!("Installed scheduled task {}", SERVICE_NAME))
+    } else {
+        let (path, user) = systemd_unit_path()?;
+        write_file(&path, &systemd_unit(interval_secs, user)?)?;
+
+        let unit = format!("{}.service", SERVICE_NAME);
+        let scope: &[&str] = if user { &["--user"] } else { &[] };
+        run("systemctl", &[scope, &["daemon-reload"]].concat())?;
+        run("systemctl", &[scope, &["enable", "--now", &unit]].concat())?;
+        Ok(format!(
+            "Installed and started systemd unit {}",
+            path.display()
+        ))
+    }
+}
+
+pub fn uninstall_service() -> Result<String, String> {
+    if cfg!(target_os = "macos") {
+        let path = launchd_plist_path()?;
+        run("launchctl", &["unload", "-w", &path.to_string_lossy()])?;
+        remove_file(&path)?;
+        Ok(format!("Removed launchd agent {}", path.display()))
+    } else if cfg!(windows) {
+        run("schtasks", &["/Delete", "/F", "/TN", SERVICE_NAME])?;
+        Ok(format!("Removed scheduled task {}", SERVICE_NAME))
+    } else {
+        let (path, user) = systemd_unit_path()?;
+
+        let unit = format!("{}.service", SERVICE_NAME);
+        let scope: &[&str] = if user { &["--user"] } else { &[] };
+        run("systemctl", &[scope, &["disable", "--now", &unit]].concat())?;
+        remove_file(&path)?;
+        run("systemctl", &[scope, &["daemon-reload"]].concat())?;
+        Ok(format!("Removed systemd unit {}", path.display()))
+    }
+}
+
+fn write_file(path: &PathBuf, contents: &str) -> Result<(), String> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+    }
+    fs::write(path, contents).map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+}
+
+fn remove_file(path: &PathBuf) -> Result<(), String> {
+    fs::remove_file(path).map_err(|e| format!("Failed to remove {}: {}", path.display(), e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn exec_args_are_quoted_and_escaped() {
+        assert_eq!(exec_arg("/opt/my monitor/bin"), "\"/opt/my monitor/bin\"");
+        assert_eq!(exec_arg(r#"a"b\c"#), r#""a\"b\\c""#);
+        assert_eq!(exec_arg("100%$HOME"), "\"100%%$$HOME\"");
+    }
+
+    #[test]
+    fn unit_paths_refuse_what_a_unit_cannot_hold() {
+        assert_eq!(unit_path(Path::new("/srv/a b")), Ok("/srv/a b"));
+        assert!(unit_path(Path::new("/srv/a\nb")).is_err());
+        assert!(unit_path(Path::new("/srv/a ")).is_err());
+    }
+}
@@ -0,0 +1,156 @@
+use crate::config::{Config, ThemeChoice};
+use crate::logging::LogFormat;
+use crate::models::{Message, Panel};
+use iced::widget::{button, checkbox, column, pick_list, row, text, Column, TextInput};
+use iced::{Alignment, Element, Length};
+
+const STEPS: [&str; 4] = ["Interval", "Panels", "Logging", "Theme"];
+
+// Guided first launch, collects the settings for the initial config file
+#[derive(Debug)]
+pub struct SetupWizard {
+    step: usize,
+    pub interval: String,
+    pub config: Config,
+    error: Option<String>,
+}
+
+impl Default for SetupWizard {
+    fn default() -> Self {
+        SetupWizard {
+            step: 0,
+            interval: String::from("5"),
+            config: Config::default(),
+            error: None,
+        }
+    }
+}
+
+impl SetupWizard {
+    pub fn set_panel(&mut self, panel: Panel, visible: bool) {
+        self.config.hidden_panels.retain(|hidden| *hidden != panel);
+        if !visible {
+            self.config.hidden_panels.push(panel);
+        }
+    }
+
+    pub fn back(&mut self) {
+        self.step = self.step.saturating_sub(1);
+        self.error = None;
+    }
+
+    // go to the next step, returns the finished config after the last one
+    pub fn next(&mut self) -> Option<Config> {
+        if self.step == 0 {
+            match self.interval.trim().parse::<u64>() {
+                Ok(secs) if secs > 0 => self.config.interval_secs = Some(secs),
+                _ => {
+                    self.error = Some(String::from("Interval must be a whole number of seconds"));
+                    return None;
+                }
+            }
+        }
+        self.error = None;
+
+        if self.step + 1 < STEPS.len() {
+            self.step += 1;
+            None
+        } else {
+            Some(self.config.clone())
+        }
+    }
+
+    fn view_step(&self) -> Column<'_, Message> {
+        match self.step {
+            0 => column![
+                text("How often should samples be logged? The dashboard itself refreshes every second.")
+                    .size(16),
+                TextInput::new("Seconds", &self.interval)
+                    .padding(10)
+                    .width(Length::Fixed(200.))
+                    .on_input(Message::SetupIntervalChanged)
+                    .on_submit(Message::SetupNext),
+            ],
+            1 => Panel::ALL.iter().fold(
+                column![text("Which panels should the dashboard show?").size(16)],
+                |panels, panel| {
+                    let panel = *panel;
+                    panels.push(
+                        checkbox(panel.to_string(), self.config.panel_visible(panel))
+                            .on_toggle(move |visible| Message::SetupPanelToggled(panel, visible)),
+                    )
+                },
+            ),
+            2 => column![
+                text("Should samples be saved to a file while monitoring?").size(16),
+                checkbox("Save To File", self.config.save_to_file)
+                    .on_toggle(Message::SetupSaveToFileToggled),
+                row![
+                    text("Format").size(16),
+                    pick_list(
+                        &LogFormat::ALL[..],
+                        Some(self.config.log_format),
+                        Message::SetupLogFormatSelected,
+                    )
+                ]
+                .spacing(10)
+                .align_items(Alignment::Center),
+            ],
+            _ => column![
+                text("Pick a theme.").size(16),
+                pick_list(
+                    &ThemeChoice::ALL[..],
+                    Some(self.config.theme),
+                    Message::SetupThemeSelected,
+                ),
+            ],
+        }
+        .spacing(12)
+    }
+
+    pub fn view(&self) -> Element<'_, Message> {
+        let title = text(format!(
+            "Setup ({}/{}): {}",
+            self.step + 1,
+            STEPS.len(),
+            STEPS[self.step]
+        ))
+        .size(26)
+        .style(iced::theme::Text::Color(iced::Color::from_rgb(
+            0.2, 0.6, 1.0,
+        )));
+
+        let back_button = button(text("Back").size(14))
+            .padding(10)
+            .on_press_maybe((self.step > 0).then_some(Message::SetupBack));
+        let next_button = button(
+            text(if self.step + 1 == STEPS.len() {
+                "Finish"
+            } else {
+                "Next"
+            })
+            .size(14),
+        )
+        .padding(10)
+        .on_press(Message::SetupNext);
+        let skip_button = button(text("Skip, use defaults").size(14))
+            .padding(10)
+            .style(iced::theme::Button::Secondary)
+            .on_press(Message::SetupSkip);
+
+        let mut content = column![title, self.view_step()]
+            .spacing(20)
+            .padding(20)
+            .width(Length::Fixed(600.));
+
+        if let Some(error) = &self.error {
+            content = content.push(text(error).size(14).style(iced::theme::Text::Color(
+                iced::Color::from_rgb(1.0, 0.3, 0.3),
+            )));
+        }
+
+        content
+            .push(row![back_button, next_button, skip_button].spacing(10))
+            .into()
+    }
+}
@@ -0,0 +1,110 @@
+// Graceful shutdown on SIGTERM/SIGINT or when the window is closed
+use crate::history::export_samples;
+use crate::logging::log_session_end;
+use crate::models::{ExportFormat, SystemData, SystemMonitor};
+use crate::wear::WEAR_FILE;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+use std::time::{Duration, Instant};
+
+// samples of the last session, rewritten on every exit
+pub const SESSION_HISTORY_FILE: &str = "session_history.json";
+
+static TERMINATE: AtomicBool = AtomicBool::new(false);
+static FINISHED: AtomicBool = AtomicBool::new(false);
+static RELOAD: AtomicBool = AtomicBool::new(false);
+
+#[cfg(unix)]
+extern "C" fn request_termination(_signal: libc::c_int) {
+    // only async-signal-safe work here, the main loop does the rest
+    TERMINATE.store(true, Ordering::SeqCst);
+}
+
+// turn SIGTERM and SIGINT into a shutdown request instead of an immediate exit
+#[cfg(unix)]
+pub fn install_handlers() {
+    let handler = request_termination as extern "C" fn(libc::c_int) as libc::sighandler_t;
+    unsafe {
+        libc::signal(libc::SIGTERM, handler);
+        libc::signal(libc::SIGINT, handler);
+    }
+}
+
+#[cfg(not(unix))]
+pub fn install_handlers() {}
+
+#[cfg(unix)]
+extern "C" fn request_reload(_signal: libc::c_int) {
+    RELOAD.store(true, Ordering::SeqCst);
+}
+
+// SIGHUP asks the daemon to reload its config and reopen the log, as logrotate expects
+#[cfg(unix)]
+pub fn install_reload_handler() {
+    let handler = request_reload as extern "C" fn(libc::c_int) as libc::sighandler_t;
+    unsafe {
+        libc::signal(libc::SIGHUP, handler);
+    }
+}
+
+#[cfg(not(unix))]
+pub fn install_reload_handler() {}
+
+pub fn requested() -> bool {
+    TERMINATE.load(Ordering::SeqCst)
+}
+
+// whether a reload was requested since the last call
+pub fn take_reload() -> bool {
+    RELOAD.swap(false, Ordering::SeqCst)
+}
+
+// sleep in short steps so a shutdown request isn't stuck behind a long interval,
+// returns false when one came in; a reload request ends the sleep early as well
+pub fn sleep(duration: Duration) -> bool {
+    let until = Instant::now() + duration;
+    while !requested() {
+        if RELOAD.load(Ordering::SeqCst) {
+            return true;
+        }
+        let left = until.saturating_duration_since(Instant::now());
+        if left.is_zero() {
+            return true;
+        }
+        thread::sleep(left.min(Duration::from_millis(100)));
+    }
+    false
+}
+
+// finish the log with a session-end record, write out buffered samples and keep the history
+// and the SSD write counts
+pub fn finish_session(system_monitor: &mut SystemMonitor, logging: bool) {
+    // a signal and a window close can both arrive, only end the session once
+    if FINISHED.swap(true, Ordering::SeqCst) {
+        return;
+    }
+
+    if logging {
+        if let Err(e) = log_session_end(system_monitor) {
+            eprintln!("Failed to write session-end record: {}", e);
+        }
+    }
+    if let Err(e) = system_monitor.log_writer.finish() {
+        eprintln!("Failed to write log file: {}", e);
+    }
+    if let Err(e) = system_monitor.disk_wear.save() {
+        eprintln!("Failed to write {}: {}", WEAR_FILE, e);
+    }
+
+    let samples: Vec<&SystemData> = system_monitor.history.samples().iter().collect();
+    if !samples.is_empty() {
+        if let Err(e) = export_samples(
+            &samples,
+            SESSION_HISTORY_FILE,
+            ExportFormat::Json,
+            system_monitor.config.number_locale,
+        ) {
+            eprintln!("Failed to write {}: {}", SESSION_HISTORY_FILE, e);
+        }
+    }
+}
@@ -0,0 +1,56 @@
+// Destinations the log thread writes to; the log file is one, and others can be added to the log
+// writer (`LogWriter::add_sink`) without the collector or `log_metrics` knowing about them
+use crate::config::Config;
+use crate::logging::{LogCompression, LogFormat};
+use crate::models::SystemData;
+use std::io;
+use std::path::PathBuf;
+
+// The parts of the config sinks follow, sent along with each record so a change applies from the
+// next record on
+#[derive(Debug, Clone)]
+pub struct LogSettings {
+    pub format: LogFormat,
+    pub compression: LogCompression,
+    pub rotate_mb: u64,
+    // rotated files kept, the oldest is deleted by the next rotation
+    pub rotate_keep: u32,
+    pub fsync: bool,
+    // where the log files go: empty for the working directory, or the fallback directory the
+    // log moved to once the integrity check found the working directory unusable
+    pub directory: PathBuf,
+}
+
+impl LogSettings {
+    pub fn from_config(config: &Config) -> LogSettings {
+        LogSettings {
+            format: config.log_format,
+            compression: config.log_compression,
+            rotate_mb: config.log_rotate_mb,
+            rotate_keep: config.log_rotate_keep(),
+            fsync: config.log_fsync,
+            directory: PathBuf::new(),
+        }
+    }
+
+    pub fn path(&self, name: &str) -> PathBuf {
+        self.directory.join(name)
+    }
+}
+
+// Runs on the log thread, so a slow destination holds up the other sinks but never a refresh;
+// an error is reported to the collector on its next write and the sink keeps getting records
+pub trait MetricsSink: Send + std::fmt::Debug {
+    fn write_sample(&mut self, settings: &LogSettings, sample: &SystemData) -> io::Result<()>;
+
+    // aggregated and session-end records as JSON, left out by sinks that only keep samples
+    fn write_record(&mut self, _settings: &LogSettings, _serialized: &str) -> io::Result<()> {
+        Ok(())
+    }
+
+    // write out anything buffered, e.g. before the format changes or the monitor exits
+    fn flush(&mut self) -> io::Result<()>;
+
+    // close the output so the next record opens it again by name, e.g. after logrotate moved it
+    fn rotate(&mut self) -> io::Result<()>;
+}
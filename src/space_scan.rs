@@ -0,0 +1,215 @@
+// On-demand walk of a mount point that adds up the space used by each owner or top-level
+// directory, to find who is filling a shared disk; runs on its own thread since a big mount
+// takes minutes
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+// What the space is attributed to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScanGrouping {
+    Owner,
+    Directory,
+}
+
+impl ScanGrouping {
+    pub const ALL: [ScanGrouping; 2] = [ScanGrouping::Owner, ScanGrouping::Directory];
+}
+
+impl std::fmt::Display for ScanGrouping {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ScanGrouping::Owner => write!(f, "By owner"),
+            ScanGrouping::Directory => write!(f, "By top-level directory"),
+        }
+    }
+}
+
+// Owner's uid or the name of a directory directly under the mount point
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum ScanKey {
+    Owner(u32),
+    Directory(String),
+}
+
+// Where a scan has got to, filled in by the scan thread and read on every redraw
+#[derive(Debug, Clone, Default)]
+pub struct ScanProgress {
+    pub files: u64,
+    pub bytes: u64,
+    pub totals: HashMap<ScanKey, u64>,
+    // directories that couldn't be read, e.g. other users' home directories without root
+    pub unreadable: u64,
+    pub finished: bool,
+}
+
+#[derive(Debug)]
+pub struct SpaceScan {
+    pub mount: String,
+    pub grouping: ScanGrouping,
+    progress: Arc<Mutex<ScanProgress>>,
+    stop: Arc<AtomicBool>,
+}
+
+// progress is published every this many files, the lock is not worth taking per file
+const PUBLISH_EVERY: u64 = 1000;
+
+impl SpaceScan {
+    pub fn start(mount: String, grouping: ScanGrouping) -> SpaceScan {
+        let progress = Arc::new(Mutex::new(ScanProgress::default()));
+        let stop = Arc::new(AtomicBool::new(false));
+
+        let (thread_progress, thread_stop) = (progress.clone(), stop.clone());
+        let root = PathBuf::from(&mount);
+        let spawned = thread::Builder::new()
+            .name(String::from("space-scan"))
+            .spawn(move || {
+                let mut walk = Walk {
+                    device: device_of(&root),
+                    grouping,
+                    progress: ScanProgress::default(),
+                    shared: thread_progress,
+                    stop: thread_stop,
+                };
+                walk.directory(&root, None);
+                walk.progress.finished = true;
+                walk.publish();
+            });
+        if let Err(e) = spawned {
+            eprintln!("Failed to start the disk space scan: {}", e);
+            progress
+                .lock()
+                .unwrap_or_else(|poisoned| poisoned.into_inner())
+                .finished = true;
+        }
+
+        SpaceScan {
+            mount,
+            grouping,
+            progress,
+            stop,
+        }
+    }
+
+    pub fn progress(&self) -> ScanProgress {
+        self.progress
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .clone()
+    }
+
+    pub fn finished(&self) -> bool {
+        self.progress
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .finished
+    }
+}
+
+// the thread stops at the next file it looks at
+impl Drop for SpaceScan {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+    }
+}
+
+struct Walk {
+    // other filesystems mounted below the mount point are left out, they have their own entry
+    device: Option<u64>,
+    grouping: ScanGrouping,
+    progress: ScanProgress,
+    shared: Arc<Mutex<ScanProgress>>,
+    stop: Arc<AtomicBool>,
+}
+
+impl Walk {
+    // `top` is the directory under the mount point the walk is in, None at the mount point
+    fn directory(&mut self, path: &Path, top: Option<&str>) {
+        let Ok(entries) = fs::read_dir(path) else {
+            self.progress.unreadable += 1;
+            return;
+        };
+        for entry in entries.flatten() {
+            if self.stop.load(Ordering::Relaxed) {
+                return;
+            }
+            // symlinks are counted as themselves, not followed
+            let Ok(metadata) = entry.path().symlink_metadata() else {
+                continue;
+            };
+            if self.device.is_some() && device(&metadata) != self.device {
+                continue;
+            }
+            let name = entry.file_name().to_string_lossy().to_string();
+            let top = top.unwrap_or(&name).to_string();
+
+            let key = match self.grouping {
+                ScanGrouping::Owner => ScanKey::Owner(owner(&metadata)),
+                ScanGrouping::Directory => ScanKey::Directory(top.clone()),
+            };
+            let size = allocated(&metadata);
+            *self.progress.totals.entry(key).or_default() += size;
+            self.progress.bytes += size;
+            self.progress.files += 1;
+            if self.progress.files.is_multiple_of(PUBLISH_EVERY) {
+                self.publish();
+            }
+
+            if metadata.is_dir() {
+                self.directory(&entry.path(), Some(&top));
+            }
+        }
+    }
+
+    fn publish(&self) {
+        *self
+            .shared
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner()) = self.progress.clone();
+    }
+}
+
+fn device_of(path: &Path) -> Option<u64> {
+    fs::metadata(path)
+        .ok()
+        .and_then(|metadata| device(&metadata))
+}
+
+#[cfg(unix)]
+fn device(metadata: &fs::Metadata) -> Option<u64> {
+    use std::os::unix::fs::MetadataExt;
+    Some(metadata.dev())
+}
+
+#[cfg(not(unix))]
+fn device(_metadata: &fs::Metadata) -> Option<u64> {
+    None
+}
+
+// space actually taken, so sparse files and small files in whole blocks count as they do on
+// the disk
+#[cfg(unix)]
+fn allocated(metadata: &fs::Metadata) -> u64 {
+    use std::os::unix::fs::MetadataExt;
+    metadata.blocks() * 512
+}
+
+#[cfg(not(unix))]
+fn allocated(metadata: &fs::Metadata) -> u64 {
+    metadata.len()
+}
+
+#[cfg(unix)]
+fn owner(metadata: &fs::Metadata) -> u32 {
+    use std::os::unix::fs::MetadataExt;
+    metadata.uid()
+}
+
+// files have no numeric owner here, the GUI only offers the grouping by directory
+#[cfg(not(unix))]
+fn owner(_metadata: &fs::Metadata) -> u32 {
+    0
+}
@@ -0,0 +1,152 @@
+use serde::Serialize;
+
+// Summary of a metric over the monitoring session
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct Summary {
+    pub min: f64,
+    pub avg: f64,
+    pub max: f64,
+    pub p95: f64,
+}
+
+const PERCENTILE: f64 = 0.95;
+
+// Running min/avg/max of a metric and its 95th percentile, estimated with the P² algorithm (Jain
+// and Chlamtac) from five markers, so a session of any length takes the same memory and time per
+// value. The summary is updated with every value rather than worked out each time it is shown
+#[derive(Debug, Default)]
+pub struct MetricStats {
+    count: u64,
+    sum: f64,
+    // marker heights, the middle one estimates the percentile; the first five values themselves
+    // until there are five
+    heights: [f64; 5],
+    // actual and desired marker positions
+    positions: [f64; 5],
+    desired: [f64; 5],
+    summary: Option<Summary>,
+}
+
+impl MetricStats {
+    pub fn push(&mut self, value: f64) {
+        if value.is_nan() {
+            return;
+        }
+        self.count += 1;
+        self.sum += value;
+
+        if self.count <= 5 {
+            let seen = self.count as usize;
+            self.heights[seen - 1] = value;
+            self.heights[..seen].sort_by(f64::total_cmp);
+            if seen == 5 {
+                self.positions = [0., 1., 2., 3., 4.];
+                self.desired = [
+                    0.,
+                    2. * PERCENTILE,
+                    4. * PERCENTILE,
+                    2. + 2. * PERCENTILE,
+                    4.,
+                ];
+            }
+        } else {
+            self.estimate(value);
+        }
+        self.summary = Some(self.summarize());
+    }
+
+    fn estimate(&mut self, value: f64) {
+        let heights = &mut self.heights;
+        let positions = &mut self.positions;
+
+        // the cell the value falls into, widening the outer markers when it is a new extreme
+        let cell = if value < heights[0] {
+            heights[0] = value;
+            0
+        } else if value >= heights[4] {
+            heights[4] = value;
+            3
+        } else {
+            (0..4)
+                .find(|&i| value < heights[i + 1])
+                .expect("value is below the top marker")
+        };
+        for position in &mut positions[cell + 1..] {
+            *position += 1.;
+        }
+        let increments = [0., PERCENTILE / 2., PERCENTILE, (1. + PERCENTILE) / 2., 1.];
+        for (desired, increment) in self.desired.iter_mut().zip(increments) {
+            *desired += increment;
+        }
+
+        // move the middle markers towards where they should be, one position at a time
+        for i in 1..4 {
+            let off = self.desired[i] - positions[i];
+            if (off >= 1. && positions[i + 1] - positions[i] > 1.)
+                || (off <= -1. && positions[i - 1] - positions[i] < -1.)
+            {
+                let step = off.signum();
+                let parabolic = heights[i]
+                    + step / (positions[i + 1] - positions[i - 1])
+                        * ((positions[i] - positions[i - 1] + step)
+                            * (heights[i + 1] - heights[i])
+                            / (positions[i + 1] - positions[i])
+                            + (positions[i + 1] - positions[i] - step)
+                                * (heights[i] - heights[i - 1])
+                                / (positions[i] - positions[i - 1]));
+                heights[i] = if heights[i - 1] < parabolic && parabolic < heights[i + 1] {
+                    parabolic
+                } else {
+                    let next = if step > 0. { i + 1 } else { i - 1 };
+                    heights[i]
+                        + step * (heights[next] - heights[i]) / (positions[next] - positions[i])
+                };
+                positions[i] += step;
+            }
+        }
+    }
+
+    fn summarize(&self) -> Summary {
+        let seen = self.count.min(5) as usize;
+        let p95 = if self.count <= 5 {
+            // nearest rank of the values kept so far
+            let rank = (PERCENTILE * seen as f64).ceil() as usize;
+            self.heights[rank.saturating_sub(1)]
+        } else {
+            self.heights[2]
+        };
+        Summary {
+            min: self.heights[0],
+            avg: self.sum / self.count as f64,
+            max: self.heights[seen - 1],
+            p95,
+        }
+    }
+
+    // highest value so far
+    pub fn max(&self) -> Option<f64> {
+        self.summary.map(|summary| summary.max)
+    }
+
+    pub fn summary(&self) -> Option<Summary> {
+        self.summary
+    }
+}
+
+// Statistics for the current monitoring session
+#[derive(Debug, Default)]
+pub struct SessionStats {
+    pub cpu_percent: MetricStats,
+    pub memory_percent: MetricStats,
+    pub network_sent_rate: MetricStats,
+    pub network_received_rate: MetricStats,
+}
+
+impl SessionStats {
+    pub fn record(&mut self, cpu: f64, memory: f64, sent_rate: f64, received_rate: f64) {
+        self.cpu_percent.push(cpu);
+        self.memory_percent.push(memory);
+        self.network_sent_rate.push(sent_rate);
+        self.network_received_rate.push(received_rate);
+    }
+}
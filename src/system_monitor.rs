@@ -1,19 +1,101 @@
-use crate::models::{DisksInfo, Message, Process, SystemBaseInfo, SystemMonitor};
-use crate::utils::{calculate_disk_usage, convert_from_bytes, log_metrics};
+use crate::alerts::{
+    export_rules, import_rules, merge_rules, strip_actions, stripped_note, AlertActionKind,
+    AlertMetric, AlertRule, RuleForm, DEFAULT_ALERT_TEMPLATE,
+};
+use crate::background::off_thread;
+use crate::cgroups::{limit_process, read_cgroup, ProcessQuota};
+use crate::chart::{
+    view_chart, CoreHeatmap, CoreStrip, LineChart, ProcessTimeline, StackedAreaChart, Threshold,
+};
+use crate::comparison::{Comparison, ComparisonPeriod};
+use crate::config::{Config, SavedFilter, SavedView};
+use crate::diagnosis::{DiagnosisCapture, CAPTURE_TICKS};
+use crate::digest::{build_digest, AwayMark};
+use crate::format::{
+    memory_categories, usage_percent, NumberFormat, NumberLocale, TIMESTAMP_FORMAT,
+};
+use crate::hints::overhead_hints;
+use crate::history::{export_file_name, export_samples};
+use crate::idle::idle_spans;
+use crate::interface_actions::{parse_mac, send_wake, set_link};
+use crate::known_processes::{lookup, search_url};
+use crate::logging::{log_metrics, LogCompression, LogFormat};
+use crate::models::{
+    DisksInfo, ExportFormat, Message, Panel, PanelSnapshot, Process, ProcessLimit, ProcessSort,
+    ProtocolRates, SystemData, SystemMonitor, Tab, TcpStates,
+};
+use crate::power::{read_battery, set_charge_limit, BatteryInfo};
+use crate::process_actions::{
+    export_processes_csv, kill_processes, open_in_browser, process_table_text, renice_processes,
+};
+use crate::procfs::{format_cpu_list, read_affinity, read_process_connections};
+use crate::prometheus::{render_metrics, save_metrics};
+use crate::quick_controls::{read_level, set_level, QuickControl};
+use crate::recording::{Recorder, RecordingHeader, Replay};
+use crate::report::render_report;
+use crate::rows::DiskRow;
+use crate::sampler::Sampler;
+use crate::screenshot::save_png;
+use crate::setup::SetupWizard;
+use crate::shutdown;
+use crate::space_scan::{ScanGrouping, ScanKey, SpaceScan};
+use crate::stats::{MetricStats, SessionStats, Summary};
+use crate::utils::{current_timestamp, user_totals};
+use crate::widgets::{cpu_chart, ProcessTable};
+use chrono::Local;
+use iced::multi_window::Application;
 use iced::time;
 use iced::widget::{
-    button, checkbox, column, container, horizontal_rule, row, scrollable, text, Column, TextInput,
+    button, checkbox, column, container, horizontal_rule, horizontal_space, mouse_area, pick_list,
+    progress_bar, row, scrollable, slider, text, tooltip, Column, Row, TextInput,
+};
+use iced::{
+    clipboard, event, executor, keyboard, window, Alignment, Command, Element, Event, Length,
+    Point, Size, Subscription, Theme,
 };
-use iced::{executor, Alignment, Application, Command, Element, Length, Subscription, Theme};
-use std::{thread, time::Duration};
-use sysinfo::{Disks, Networks, System};
+use std::collections::{BTreeMap, HashMap};
+use std::time::{Duration, Instant};
+use sysinfo::Uid;
+
+// above this many logical processors the per-core list is replaced by the heatmap
+const HEATMAP_CORE_THRESHOLD: usize = 16;
+
+// frameless always-on-top gadget shown in mini mode
+const MINI_MODE_SIZE: Size = Size::new(420., 90.);
+
+// taller with the brightness and volume sliders
+const MINI_MODE_CONTROLS_SIZE: Size = Size::new(420., 154.);
+
+// the sparkline in mini mode covers a minute at the default refresh interval
+const SPARKLINE_SAMPLES: usize = 60;
+
+// charge limits offered for laptops whose firmware supports one, 80% is the usual choice for a
+// laptop that stays plugged in
+const CHARGE_LIMITS: [u8; 3] = [60, 80, 100];
+
+// the CPU strip is a thin bar, its width is up to the user
+const CPU_STRIP_HEIGHT: f32 = 28.;
+
+// the chart in a detached or maximized CPU panel covers the last two minutes
+const DETACHED_CHART_SAMPLES: usize = 120;
+
+// the TCP socket chart in the network panel covers the last two minutes as well
+const TCP_CHART_SAMPLES: usize = 120;
+
+// the memory category chart covers the last five minutes, enough to see the cache grow
+const MEMORY_CHART_SAMPLES: usize = 300;
+
+// owners or directories listed by the space scan, the biggest first
+const SPACE_SCAN_ENTRIES: usize = 10;
+
+const DECIMAL_PLACES: [usize; 4] = [0, 1, 2, 3];
 
 impl SystemMonitor {
-    fn create_control_row(&self) -> Element<Message> {
+    fn create_control_row(&self) -> Element<'_, Message> {
         let interval_input = TextInput::new("Interval(s)", self.interval_in_secs.trim())
             .padding(10)
             .width(Length::Fixed(120.0))
-            .on_input(|s| Message::IntervalChanged(s));
+            .on_input(Message::IntervalChanged);
 
         let monitoring_button = button(
             text(if self.is_monitoring {
@@ -31,518 +113,4276 @@ impl SystemMonitor {
             .spacing(8)
             .on_toggle(Message::ToggleSaveToFile);
 
-        row![interval_input, monitoring_button, save_checkbox]
-            .spacing(20)
-            .align_items(Alignment::Center)
-            .padding(20)
-            .into()
+        let fsync_checkbox = tooltip(
+            checkbox("Fsync", self.config.log_fsync)
+                .spacing(8)
+                .on_toggle(Message::ToggleLogFsync),
+            text("Sync the log file to disk after every sample").size(14),
+            tooltip::Position::Bottom,
+        )
+        .style(iced::theme::Container::Box);
+
+        let aggregate_checkbox = tooltip(
+            checkbox("Aggregate", self.config.log_aggregate)
+                .spacing(8)
+                .on_toggle(Message::ToggleLogAggregate),
+            text("Log min/avg/max of the 1 second samples over each interval (JSON log)").size(14),
+            tooltip::Position::Bottom,
+        )
+        .style(iced::theme::Container::Box);
+
+        let format_list = pick_list(
+            &LogFormat::ALL[..],
+            Some(self.config.log_format),
+            Message::LogFormatSelected,
+        )
+        .padding(10);
+
+        // only the JSON log is compressed, Parquet files are compressed internally
+        let compression_list = pick_list(
+            &LogCompression::ALL[..],
+            Some(self.config.log_compression),
+            Message::LogCompressionSelected,
+        )
+        .padding(10);
+
+        let mini_button = tooltip(
+            button(text("Mini Mode").size(14))
+                .padding(10)
+                .on_press(Message::ToggleMiniMode),
+            text("Shrink to a small always-on-top widget (Ctrl+M)").size(14),
+            tooltip::Position::Bottom,
+        )
+        .style(iced::theme::Container::Box);
+
+        let strip_button = tooltip(
+            button(text("CPU Strip").size(14))
+                .padding(10)
+                .style(if self.cpu_strip.is_some() {
+                    iced::theme::Button::Primary
+                } else {
+                    iced::theme::Button::Secondary
+                })
+                .on_press(Message::ToggleCpuStrip),
+            text("Thin always-on-top bar with per-core usage and network rates, drag it to any screen edge").size(14),
+            tooltip::Position::Bottom,
+        )
+        .style(iced::theme::Container::Box);
+
+        let record_button = tooltip(
+            button(
+                text(if self.recorder.is_some() {
+                    "Stop Recording"
+                } else {
+                    "Record Session"
+                })
+                .size(14),
+            )
+            .padding(10)
+            .style(if self.recorder.is_some() {
+                iced::theme::Button::Destructive
+            } else {
+                iced::theme::Button::Secondary
+            })
+            .on_press(Message::ToggleRecording),
+            text("Save every refresh to a compressed file that --replay plays back").size(14),
+            tooltip::Position::Bottom,
+        )
+        .style(iced::theme::Container::Box);
+
+        let screenshot_button = button(text("Screenshot").size(14))
+            .padding(10)
+            .on_press(Message::TakeScreenshot);
+
+        let metrics_button = tooltip(
+            button(text("Copy Metrics").size(14))
+                .padding(10)
+                .on_press(Message::CopyMetrics),
+            text("Copy the current metrics in the Prometheus text format and save them to a .prom file")
+                .size(14),
+            tooltip::Position::Bottom,
+        )
+        .style(iced::theme::Container::Box);
+
+        let report_button = tooltip(
+            button(text("Copy Report").size(14))
+                .padding(10)
+                .on_press(Message::CopyReport),
+            text("Copy a Markdown summary of the hardware, OS, current load and top processes, for a forum post or support ticket")
+                .size(14),
+            tooltip::Position::Bottom,
+        )
+        .style(iced::theme::Container::Box);
+
+        // the capture needs refreshes, so it only starts while monitoring
+        let diagnosis_button = tooltip(
+            button(text("Find What's Slow").size(14))
+                .padding(10)
+                .on_press_maybe(
+                    (self.is_monitoring && self.diagnosis_capture.is_none())
+                        .then_some(Message::StartDiagnosis),
+                ),
+            text(format!(
+                "Watch the next {} refreshes and name the likely bottleneck with the processes behind it",
+                CAPTURE_TICKS
+            ))
+            .size(14),
+            tooltip::Position::Bottom,
+        )
+        .style(iced::theme::Container::Box);
+
+        let annotation_input = TextInput::new("Annotation", &self.annotation_input)
+            .padding(10)
+            .width(Length::Fixed(200.0))
+            .on_input(Message::AnnotationChanged)
+            .on_submit(Message::AddAnnotation);
+
+        let annotation_button = button(text("Add Marker").size(14))
+            .padding(10)
+            .on_press(Message::AddAnnotation);
+
+        row![
+            interval_input,
+            monitoring_button,
+            save_checkbox,
+            format_list,
+            compression_list,
+            aggregate_checkbox,
+            fsync_checkbox,
+            annotation_input,
+            annotation_button,
+            mini_button,
+            strip_button,
+            record_button,
+            text(&self.recording_status).size(14),
+            screenshot_button,
+            text(&self.screenshot_status).size(14),
+            metrics_button,
+            report_button,
+            diagnosis_button,
+            text(&self.metrics_status).size(14)
+        ]
+        .spacing(20)
+        .align_items(Alignment::Center)
+        .padding(20)
+        .into()
     }
 
-    fn view_sys_base_info(&self) -> Column<Message> {
-        // System info line
-        let system_base_info = text(format!(
-            "System Name: {} | OS Version: {} | Kernel Version: {} | Host: {}",
-            self.system_base_info.system_name,
-            self.system_base_info.os_version,
-            self.system_base_info.kernal_version,
-            self.system_base_info.host_name
+    fn create_tab_row(&self) -> Element<'_, Message> {
+        let tab_button = |label, tab| {
+            let style = if self.active_tab == tab {
+                iced::theme::Button::Primary
+            } else {
+                iced::theme::Button::Secondary
+            };
+
+            button(text(label).size(14))
+                .padding(8)
+                .style(style)
+                .on_press(Message::TabSelected(tab))
+        };
+
+        let detach_list = pick_list(&Panel::ALL[..], None::<Panel>, Message::DetachPanel)
+            .placeholder("Pop Out Panel")
+            .padding(8);
+
+        let decimals_list = pick_list(
+            &DECIMAL_PLACES[..],
+            Some(self.config.decimal_places.unwrap_or(2)),
+            Message::DecimalPlacesSelected,
+        )
+        .padding(8);
+        let locale_list = pick_list(
+            &NumberLocale::ALL[..],
+            Some(self.config.number_locale),
+            Message::NumberLocaleSelected,
+        )
+        .padding(8);
+
+        let title_checkbox = tooltip(
+            checkbox("Stats in Title", self.config.title_stats)
+                .spacing(8)
+                .on_toggle(Message::ToggleTitleStats),
+            text("Show CPU and RAM use in the window title, and so on the taskbar").size(14),
+            tooltip::Position::Bottom,
+        )
+        .style(iced::theme::Container::Box);
+
+        row![
+            tab_button("Dashboard", Tab::Dashboard),
+            tab_button("History", Tab::History),
+            tab_button("Interrupts", Tab::Interrupts),
+            tab_button("Alerts", Tab::Alerts),
+            tab_button("Process Timeline", Tab::Timeline),
+            detach_list,
+            text("Decimals").size(14),
+            decimals_list,
+            locale_list,
+            title_checkbox
+        ]
+        .spacing(10)
+        .align_items(Alignment::Center)
+        .into()
+    }
+
+    // CPU, RAM and network rates on a single line, the whole widget can be dragged
+    fn view_mini_mode(&self) -> Element<'_, Message> {
+        let numbers = self.config.number_format();
+        let values = text(format!(
+            "CPU {}  RAM {}  Up {}  Down {}",
+            numbers.percent(self.cpu_usage as f64),
+            numbers.percent(usage_percent(self.memory_usage)),
+            numbers.rate(self.network_sent_rate),
+            numbers.rate(self.network_received_rate)
         ))
-        .size(20)
+        .size(14)
         .style(iced::theme::Text::Color(iced::Color::from_rgb(
-            1.0, 0.92, 0.0,
+            0.1, 0.8, 0.2,
         )));
 
-        // align the text centrally, with padding for better look
-        column!(system_base_info)
-            .spacing(20)
-            .padding(15)
+        let mut content = row![values].spacing(10).align_items(Alignment::Center);
+        if !self.is_monitoring {
+            content = content.push(
+                button(text("Start").size(12))
+                    .padding(4)
+                    .on_press(Message::ToggleMonitoring),
+            );
+        }
+        content = content.push(
+            button(text("Expand").size(12))
+                .padding(4)
+                .on_press(Message::ToggleMiniMode),
+        );
+
+        // the last minute and the session's peaks, so a glance shows more than this second
+        let samples = self.history.samples();
+        let recent: Vec<&SystemData> = samples
+            .iter()
+            .skip(samples.len().saturating_sub(SPARKLINE_SAMPLES))
+            .collect();
+        let sparkline = LineChart {
+            series: vec![
+                (
+                    iced::Color::from_rgb(0.1, 0.8, 0.2),
+                    recent.iter().map(|s| s.cpu_usage_percent).collect(),
+                ),
+                (
+                    iced::Color::from_rgb(0.2, 0.6, 1.0),
+                    recent
+                        .iter()
+                        .map(|s| usage_percent(s.memory_usage_byte) as f32)
+                        .collect(),
+                ),
+            ],
+            ghosts: Vec::new(),
+            shaded: Vec::new(),
+            selection: None,
+            markers: Vec::new(),
+            on_select: None,
+            thresholds: Vec::new(),
+        };
+        let peak = |stats: &MetricStats| {
+            stats
+                .max()
+                .map_or(String::from("-"), |max| numbers.percent(max))
+        };
+        let peaks = text(format!(
+            "Peak CPU {}\nPeak RAM {}",
+            peak(&self.session_stats.cpu_percent),
+            peak(&self.session_stats.memory_percent)
+        ))
+        .size(11)
+        .style(iced::theme::Text::Color(iced::Color::from_rgb(
+            0.5, 0.5, 0.5,
+        )));
+
+        let mut content = column![
+            content,
+            row![view_chart(sparkline, 28.), peaks]
+                .spacing(10)
+                .align_items(Alignment::Center),
+        ]
+        .spacing(6);
+        for (control, level) in [
+            (QuickControl::Brightness, self.brightness),
+            (QuickControl::Volume, self.volume),
+        ] {
+            if let Some(level) = level {
+                content = content.push(
+                    row![
+                        text(control).size(12).width(Length::Fixed(70.)),
+                        slider(0.0..=100.0, level, move |level| {
+                            Message::QuickControlChanged(control, level)
+                        })
+                        .on_release(Message::ApplyQuickControl(control)),
+                        text(format!("{:.0}%", level)).size(12),
+                    ]
+                    .spacing(10)
+                    .align_items(Alignment::Center),
+                );
+            }
+        }
+        if !self.quick_control_status.is_empty() {
+            content = content.push(text(&self.quick_control_status).size(12).style(
+                iced::theme::Text::Color(iced::Color::from_rgb(1.0, 0.3, 0.3)),
+            ));
+        }
+
+        mouse_area(
+            container(content)
+                .padding(10)
+                .width(Length::Fill)
+                .height(Length::Fill)
+                .center_y(),
+        )
+        .on_press(Message::DragMiniWindow)
+        .into()
+    }
+
+    // per-core usage and network rates in a thin always-on-top window, dragged by any point
+    fn view_cpu_strip(&self) -> Element<'_, Message> {
+        let numbers = self.config.number_format();
+        let usages = self
+            .smoothed_cores
+            .iter()
+            .map(|(_, usage, _)| *usage)
+            .collect();
+        let rates = text(format!(
+            "CPU {}  Up {}  Down {}",
+            numbers.percent(self.cpu_usage as f64),
+            numbers.rate(self.network_sent_rate),
+            numbers.rate(self.network_received_rate)
+        ))
+        .size(12)
+        .style(iced::theme::Text::Color(iced::Color::from_rgb(
+            0.1, 0.8, 0.2,
+        )));
+
+        mouse_area(
+            container(
+                row![CoreStrip { usages }.view(CPU_STRIP_HEIGHT - 8.), rates]
+                    .spacing(10)
+                    .align_items(Alignment::Center),
+            )
+            .padding(4)
             .width(Length::Fill)
-            .align_items(Alignment::Center)
+            .height(Length::Fill)
+            .center_y(),
+        )
+        .on_press(Message::DragCpuStrip)
+        .into()
+    }
+
+    // a single panel on its own, in a detached window or maximized in the main one
+    fn view_single_panel(&self, panel: Panel, chart_height: f32) -> Element<'_, Message> {
+        let content: Element<'_, Message> = match panel {
+            Panel::Cpu => {
+                let samples = self.history.samples();
+                let recent = samples
+                    .iter()
+                    .skip(samples.len().saturating_sub(DETACHED_CHART_SAMPLES));
+
+                let thresholds = self.chart_thresholds(
+                    AlertMetric::CpuUsage,
+                    iced::Color::from_rgb(0.2, 0.6, 1.0),
+                    100.,
+                );
+                let shaded = if self.config.shade_idle {
+                    idle_spans(recent.clone())
+                } else {
+                    Vec::new()
+                };
+                column![
+                    self.view_cpu_info(),
+                    cpu_chart(recent, thresholds, shaded, chart_height)
+                ]
+                .spacing(10)
+                .into()
+            }
+            Panel::Memory => self.view_memory_info().into(),
+            Panel::Disk => self.view_disk_info().into(),
+            Panel::Network => self.view_network_info().into(),
+            Panel::Processes => scrollable(self.view_process_info()).into(),
+            Panel::SessionStats => self.view_session_stats().into(),
+            Panel::Users => self.view_users().into(),
+            Panel::Sensors => self.view_sensors().into(),
+            Panel::Gpu => self.view_gpu().into(),
+            Panel::RaspberryPi => self.view_pi().into(),
+            Panel::Diagnostics => self.view_diagnostics().into(),
+        };
+
+        container(scrollable(content))
+            .padding(10)
+            .width(Length::Fill)
+            .height(Length::Fill)
+            .into()
     }
 
-    fn view_cpu_info(&self) -> Column<Message> {
+    fn view_history(&self) -> Column<'_, Message> {
+        let samples = self.history.samples();
+        let cpu_series = samples.iter().map(|s| s.cpu_usage_percent).collect();
+        let memory_series = samples
+            .iter()
+            .map(|s| usage_percent(s.memory_usage_byte) as f32)
+            .collect();
+
+        let ghosts = match &self.history_comparison {
+            Some(comparison) => {
+                let (cpu, memory) = comparison.align(samples.iter().map(|s| s.timestamp.as_str()));
+                vec![
+                    (iced::Color::from_rgb(0.2, 0.6, 1.0), cpu),
+                    (iced::Color::from_rgb(0.1, 0.8, 0.2), memory),
+                ]
+            }
+            None => Vec::new(),
+        };
+
+        let chart = LineChart {
+            series: vec![
+                (iced::Color::from_rgb(0.2, 0.6, 1.0), cpu_series),
+                (iced::Color::from_rgb(0.1, 0.8, 0.2), memory_series),
+            ],
+            ghosts,
+            shaded: if self.config.shade_idle {
+                idle_spans(samples.iter())
+            } else {
+                Vec::new()
+            },
+            selection: self
+                .history_selection
+                .as_ref()
+                .and_then(|(start, end)| self.history.position_of(start, end)),
+            markers: self.history.annotation_positions(),
+            on_select: Some(Message::HistoryRangeSelected),
+            thresholds: [
+                self.chart_thresholds(
+                    AlertMetric::CpuUsage,
+                    iced::Color::from_rgb(0.2, 0.6, 1.0),
+                    100.,
+                ),
+                self.chart_thresholds(
+                    AlertMetric::MemoryUsage,
+                    iced::Color::from_rgb(0.1, 0.8, 0.2),
+                    100.,
+                ),
+            ]
+            .concat(),
+        };
+
+        let selection_text = match &self.history_selection {
+            Some((start, end)) => format!(
+                "Selected: {} - {} ({} samples)",
+                start,
+                end,
+                self.history.range(start, end).len()
+            ),
+            None => String::from("Drag on the chart to select a time range"),
+        };
+
+        let export_button = |label, format| {
+            button(text(label).size(14)).padding(8).on_press_maybe(
+                self.history_selection
+                    .as_ref()
+                    .map(|_| Message::ExportHistoryRange(format)),
+            )
+        };
+
         column![
-            text("CPU Usage\n")
+            text("History")
                 .size(22)
                 .style(iced::theme::Text::Color(iced::Color::from_rgb(
                     0.2, 0.6, 1.0,
                 ))),
-            text("---------------")
-                .size(22)
+            row![
+                text(format!(
+                    "CPU % (blue) | Memory % (green) | {} samples",
+                    samples.len()
+                ))
+                .size(16)
                 .style(iced::theme::Text::Color(iced::Color::from_rgb(
                     0.1, 0.8, 0.2,
                 ))),
-            text(format!("Total: {:.2}%", self.cpu_usage))
-                .size(18)
-                .style(iced::theme::Text::Color(iced::Color::from_rgb(
-                    0.1, 0.8, 0.2
-                ))),
-            text(format!("Processes: {}", self.no_of_processes))
-                .size(18)
-                .style(iced::theme::Text::Color(iced::Color::from_rgb(
-                    0.1, 0.8, 0.2
-                ))),
-            text(format!("Plysical Cores: {}", self.physical_cores))
-                .size(18)
-                .style(iced::theme::Text::Color(iced::Color::from_rgb(
-                    0.1, 0.8, 0.2
-                ))),
-            text(format!("Logical Processors: {}", self.logical_processors))
-                .size(18)
-                .style(iced::theme::Text::Color(iced::Color::from_rgb(
-                    0.1, 0.8, 0.2
-                ))),
-            text("\n"),
-            horizontal_rule(5),
-            text("\n"),
-            self.view_per_core_usage(),
+                horizontal_space(),
+                checkbox("Shade idle time", self.config.shade_idle)
+                    .on_toggle(Message::ToggleShadeIdle)
+                    .text_size(14),
+                text(&self.comparison_status).size(14),
+                pick_list(
+                    &ComparisonPeriod::ALL[..],
+                    Some(
+                        self.history_comparison
+                            .as_ref()
+                            .map(|comparison| comparison.period)
+                            .or(self.comparison_loading)
+                            .unwrap_or(ComparisonPeriod::Off)
+                    ),
+                    Message::HistoryComparisonSelected,
+                )
+                .text_size(14),
+            ]
+            .spacing(10)
+            .align_items(Alignment::Center),
+            view_chart(chart, 300.),
+            text(selection_text).size(16),
+            self.view_annotations(),
+            row![
+                export_button("Export CSV", ExportFormat::Csv),
+                export_button("Export JSON", ExportFormat::Json),
+                button(text("Clear Selection").size(14))
+                    .padding(8)
+                    .on_press(Message::ClearHistorySelection),
+            ]
+            .spacing(10),
+            text(&self.export_status).size(14),
         ]
-        .padding(5)
+        .spacing(10)
+        .padding(10)
     }
 
-    fn view_memory_info(&self) -> Column<Message> {
+    // processes that started or exited since the monitor started, one bar each
+    fn view_process_timeline(&self) -> Column<'_, Message> {
+        let tracker = &self.process_lifetimes;
+        let elapsed = tracker.elapsed(Instant::now()).max(1.) as f32;
+        let changes = tracker.changes();
+        let bars = changes
+            .iter()
+            .map(|lifetime| {
+                let end = if lifetime.running {
+                    String::from("running")
+                } else {
+                    format!(
+                        "exited after {:.0} s",
+                        lifetime.last_seen - lifetime.first_seen
+                    )
+                };
+                (
+                    format!("{} ({}) {}", lifetime.name, lifetime.pid, end),
+                    lifetime.first_seen as f32 / elapsed,
+                    lifetime.last_seen as f32 / elapsed,
+                    lifetime.peak_cpu_percent as f32,
+                )
+            })
+            .collect();
+
         column![
-            text("Memory Usage\n")
+            text("Process Timeline")
                 .size(22)
                 .style(iced::theme::Text::Color(iced::Color::from_rgb(
                     0.2, 0.6, 1.0,
                 ))),
-            text("------------\n")
-                .size(22)
-                .style(iced::theme::Text::Color(iced::Color::from_rgb(
-                    0.1, 0.8, 0.2,
-                ))),
             text(format!(
-                "{:.2} GB / {:.2} GB ({:.2}%)",
-                convert_from_bytes(self.memory_usage.0, 3),
-                convert_from_bytes(self.memory_usage.1, 3),
-                (convert_from_bytes(self.memory_usage.0, 3)
-                    / convert_from_bytes(self.memory_usage.1, 3))
-                    * 100.
+                "Processes that started or exited from {} until now, coloured by peak CPU (green low, red high) | {} processes",
+                tracker.started_at,
+                changes.len()
             ))
             .size(16)
             .style(iced::theme::Text::Color(iced::Color::from_rgb(
                 0.1, 0.8, 0.2,
             ))),
-            text("\nSwap Memory Usage\n")
-                .size(22)
-                .style(iced::theme::Text::Color(iced::Color::from_rgb(
-                    0.2, 0.6, 1.0,
-                ))),
-            text("------------\n")
-                .size(22)
-                .style(iced::theme::Text::Color(iced::Color::from_rgb(
-                    0.1, 0.8, 0.2,
-                ))),
-            text(format!(
-                "{:.2} GB / {:.2} GB ({:.2}%)",
-                convert_from_bytes(self.swap_memory_usage.0, 3),
-                convert_from_bytes(self.swap_memory_usage.1, 3),
-                (convert_from_bytes(self.swap_memory_usage.0, 3)
-                    / convert_from_bytes(self.swap_memory_usage.1, 3))
-                    * 100.
-            ))
-            .size(16)
-            .style(iced::theme::Text::Color(iced::Color::from_rgb(
-                0.1, 0.8, 0.2,
-            )))
+            ProcessTimeline { bars }.view(),
         ]
+        .spacing(10)
         .padding(10)
     }
 
-    fn view_disk_info(&self) -> Column<Message> {
-        let mut disk_display = column![
-            text("Disk Usage")
-                .size(22)
-                .style(iced::theme::Text::Color(iced::Color::from_rgb(
-                    0.2, 0.6, 1.0,
-                ))),
-            text("------------\n")
-                .size(22)
-                .style(iced::theme::Text::Color(iced::Color::from_rgb(
-                    0.1, 0.8, 0.2,
-                ))),
-            text(format!(
-                "Total Disk Usage: {:.2} GB / {:.2} GB ({:.2}%)",
-                convert_from_bytes(self.disk_usage.0, 3),
-                convert_from_bytes(self.disk_usage.1, 3),
-                (convert_from_bytes(self.disk_usage.0, 3)
-                    / convert_from_bytes(self.disk_usage.1, 3))
-                    * 100.
-            ))
-            .size(16)
-            .style(iced::theme::Text::Color(iced::Color::from_rgb(
-                0.1, 0.8, 0.2,
-            )))
-        ];
+    // thresholds of the enabled alert rules on `metric` and the configured lines and bands,
+    // as (from, to, label)
+    fn metric_thresholds(&self, metric: AlertMetric) -> Vec<(f64, f64, &str)> {
+        let rules = self
+            .config
+            .alert_rules
+            .iter()
+            .filter(|rule| rule.enabled && rule.metric == metric)
+            .map(|rule| (rule.threshold, rule.threshold, rule.name.as_str()));
+        let configured = self
+            .config
+            .chart_thresholds
+            .iter()
+            .filter(|threshold| threshold.metric == metric)
+            .map(|threshold| {
+                let upper = threshold.upper.unwrap_or(threshold.value);
+                (threshold.value, upper, threshold.label.as_str())
+            });
+        rules.chain(configured).collect()
+    }
 
-        for disk in &self.disks_info {
-            let disk_info = column![
-                text(format!("Disk Name: {}\n", disk.name)).size(20).style(
-                    iced::theme::Text::Color(iced::Color::from_rgb(0.2, 0.6, 1.0))
-                ),
-                text(format!("Type: {}", disk.kind))
+    // the thresholds of `metric` in the colour of its series, on a chart whose top is `full`
+    fn chart_thresholds(
+        &self,
+        metric: AlertMetric,
+        color: iced::Color,
+        full: f64,
+    ) -> Vec<Threshold> {
+        let scale = |value: f64| (value / full * 100.) as f32;
+        self.metric_thresholds(metric)
+            .into_iter()
+            .map(|(from, to, label)| Threshold {
+                from: scale(from),
+                to: scale(to),
+                color,
+                label: label.to_string(),
+            })
+            .collect()
+    }
+
+    fn view_dashboard(&self) -> Element<'_, Message> {
+        let visible = |panel| self.config.panel_visible(panel);
+
+        // Arrange a column for each info category in a row with proper spacing, skipping hidden panels
+        let mut metrics_row = row![].spacing(15).padding(5).align_items(Alignment::Center);
+
+        if visible(Panel::Cpu) {
+            metrics_row = metrics_row.push(self.view_cpu_info().padding(5));
+        }
+
+        let mut middle_column = column![].padding(5);
+        if visible(Panel::Memory) {
+            middle_column = middle_column.push(self.view_memory_info().padding(5));
+        }
+        if visible(Panel::Network) {
+            middle_column = middle_column.push(self.view_network_info().padding(5));
+        }
+        metrics_row = metrics_row.push(middle_column);
+
+        if visible(Panel::Disk) {
+            metrics_row = metrics_row.push(self.view_disk_info().padding(10));
+        }
+        if visible(Panel::Processes) {
+            let process_info = self.view_process_info().padding(5);
+            metrics_row = metrics_row.push(scrollable(process_info).height(Length::FillPortion(3)));
+        }
+
+        let mut dashboard = column![metrics_row].align_items(Alignment::Center);
+        if visible(Panel::SessionStats) {
+            dashboard = dashboard.push(self.view_session_stats());
+        }
+        if visible(Panel::Users) {
+            dashboard = dashboard.push(self.view_users());
+        }
+        if visible(Panel::Sensors) {
+            dashboard = dashboard.push(self.view_sensors());
+        }
+        if visible(Panel::Gpu) {
+            dashboard = dashboard.push(self.view_gpu());
+        }
+        if visible(Panel::RaspberryPi) && self.pi.is_some() {
+            dashboard = dashboard.push(self.view_pi());
+        }
+        if visible(Panel::Diagnostics) {
+            dashboard = dashboard.push(self.view_diagnostics());
+        }
+        dashboard.into()
+    }
+
+    // cpu and memory summed per account, busiest first, to see who is loading a shared machine
+    fn view_users(&self) -> Column<'_, Message> {
+        // the live totals are kept up to date by refresh(), a paused panel sums its own copy
+        let paused_totals;
+        let totals = match self.paused_panels.get(&Panel::Users) {
+            Some(snapshot) => {
+                paused_totals = user_totals(&snapshot.processes);
+                &paused_totals
+            }
+            None => &self.user_totals,
+        };
+        let numbers = self.config.number_format();
+
+        totals.iter().fold(
+            column![self.panel_header("CPU and Memory by User", 22, Panel::Users)]
+                .spacing(5)
+                .padding(10),
+            |col, total| {
+                col.push(
+                    text(format!(
+                        "{}: CPU {} | Memory {} | {} processes",
+                        total.user,
+                        numbers.percent(total.cpu_usage_percent),
+                        numbers.percent(total.memory_usage_percent),
+                        numbers.integer(total.processes as u64)
+                    ))
                     .size(16)
                     .style(iced::theme::Text::Color(iced::Color::from_rgb(
                         0.1, 0.8, 0.2,
                     ))),
-                text(format!("Mount Point: {}", disk.mount)).size(16).style(
-                    iced::theme::Text::Color(iced::Color::from_rgb(0.1, 0.8, 0.2,))
-                ),
-                text(format!(
-                    "Total Disk Space: {:.2} GB",
-                    convert_from_bytes(disk.total_disk, 3)
-                ))
-                .size(18)
-                .style(iced::theme::Text::Color(iced::Color::from_rgb(
-                    0.1, 0.8, 0.2,
-                ))),
-                text(format!(
-                    "Free Disk Space: {:.2} GB",
-                    convert_from_bytes(disk.free_disk, 3)
-                ))
-                .size(18)
-                .style(iced::theme::Text::Color(iced::Color::from_rgb(
-                    0.1, 0.8, 0.2,
-                ))),
-                text(format!("Used Disk: {:.2}%", disk.used_disk_percent))
+                )
+            },
+        )
+    }
+
+    // NVIDIA GPUs with their clocks and power limit, and the compute processes on them
+    fn view_gpu(&self) -> Column<'_, Message> {
+        let gpu = self
+            .paused_panels
+            .get(&Panel::Gpu)
+            .map_or(&self.gpu, |s| &s.gpu);
+        let numbers = self.config.number_format();
+        let integer =
+            |value: Option<u64>| value.map_or(String::from("n/a"), |v| numbers.integer(v));
+        let number = |value: Option<f64>| value.map_or(String::from("n/a"), |v| numbers.number(v));
+
+        let mut gpu_display = column![self.panel_header("GPU", 22, Panel::Gpu)]
+            .spacing(5)
+            .padding(10);
+        if let Some(error) = &gpu.error {
+            return gpu_display.push(text(error).size(14).style(iced::theme::Text::Color(
+                iced::Color::from_rgb(0.5, 0.5, 0.5),
+            )));
+        }
+
+        for info in &gpu.gpus {
+            gpu_display = gpu_display
+                .push(
+                    text(format!(
+                        "GPU {}: {} | {} | {} / {} MiB",
+                        info.index,
+                        info.name,
+                        info.utilization_percent
+                            .map_or(String::from("n/a"), |u| numbers.percent(u)),
+                        integer(info.memory_used_mib),
+                        integer(info.memory_total_mib)
+                    ))
+                    .size(16)
+                    .style(iced::theme::Text::Color(iced::Color::from_rgb(
+                        0.2, 0.6, 1.0,
+                    ))),
+                )
+                .push(
+                    text(format!(
+                        "Graphics clock {} MHz (boost {} MHz) | Memory clock {} MHz (max {} MHz)",
+                        integer(info.graphics_clock_mhz),
+                        integer(info.max_graphics_clock_mhz),
+                        integer(info.memory_clock_mhz),
+                        integer(info.max_memory_clock_mhz)
+                    ))
+                    .size(14)
+                    .style(iced::theme::Text::Color(iced::Color::from_rgb(
+                        0.1, 0.8, 0.2,
+                    ))),
+                )
+                .push(
+                    text(format!(
+                        "Power {} W of {} W limit",
+                        number(info.power_draw_w),
+                        number(info.power_limit_w)
+                    ))
+                    .size(14)
+                    .style(iced::theme::Text::Color(iced::Color::from_rgb(
+                        0.1, 0.8, 0.2,
+                    ))),
+                );
+        }
+
+        gpu_display = gpu_display.push(text("\nCompute Processes").size(18).style(
+            iced::theme::Text::Color(iced::Color::from_rgb(0.2, 0.6, 1.0)),
+        ));
+        if gpu.processes.is_empty() {
+            gpu_display = gpu_display.push(text("None").size(14).style(iced::theme::Text::Color(
+                iced::Color::from_rgb(0.5, 0.5, 0.5),
+            )));
+        }
+        for process in &gpu.processes {
+            let index = gpu
+                .gpus
+                .iter()
+                .find(|info| info.uuid == process.gpu_uuid)
+                .map_or(String::from("?"), |info| info.index.to_string());
+            gpu_display = gpu_display.push(
+                row![
+                    text(format!(
+                        "{} {} on GPU {} | {} MiB",
+                        process.pid,
+                        process.name,
+                        index,
+                        integer(process.used_memory_mib)
+                    ))
+                    .size(14),
+                    button(text("Kill").size(14))
+                        .padding(4)
+                        .style(iced::theme::Button::Destructive)
+                        .on_press_maybe(process.start_time.map(|start_time| {
+                            Message::KillGpuProcess(process.pid, start_time)
+                        }),),
+                ]
+                .spacing(10)
+                .align_items(Alignment::Center),
+            );
+        }
+        if !self.gpu_action_status.is_empty() {
+            gpu_display = gpu_display.push(text(&self.gpu_action_status).size(14));
+        }
+        gpu_display
+    }
+
+    // SoC temperature, core voltage, clock and throttling of a Raspberry Pi; a flag that is set
+    // now is red, one that was set earlier since boot is yellow
+    fn view_pi(&self) -> Column<'_, Message> {
+        let pi = self
+            .paused_panels
+            .get(&Panel::RaspberryPi)
+            .map_or(&self.pi, |s| &s.pi);
+        let numbers = self.config.number_format();
+
+        let mut pi_display = column![self.panel_header("Raspberry Pi", 22, Panel::RaspberryPi)]
+            .spacing(5)
+            .padding(10);
+        let Some(pi) = pi else {
+            return pi_display.push(text("Not a Raspberry Pi").size(14).style(
+                iced::theme::Text::Color(iced::Color::from_rgb(0.5, 0.5, 0.5)),
+            ));
+        };
+
+        let number = |value: Option<f64>| value.map_or(String::from("n/a"), |v| numbers.number(v));
+        pi_display =
+            pi_display
+                .push(text(&pi.model).size(16).style(iced::theme::Text::Color(
+                    iced::Color::from_rgb(0.2, 0.6, 1.0),
+                )))
+                .push(
+                    text(format!(
+                        "SoC {} °C | Core {} V | ARM {} MHz",
+                        number(pi.soc_temperature),
+                        number(pi.core_volts),
+                        pi.arm_clock_mhz
+                            .map_or(String::from("n/a"), |mhz| numbers.integer(mhz))
+                    ))
                     .size(16)
                     .style(iced::theme::Text::Color(iced::Color::from_rgb(
                         0.1, 0.8, 0.2,
                     ))),
-                // add a little space between each disk
-                text("-------------------------------------------------")
+                );
+
+        if pi.throttled.is_none() {
+            return pi_display.push(
+                text("Throttling flags need vcgencmd (and access to /dev/vchiq)")
+                    .size(14)
+                    .style(iced::theme::Text::Color(iced::Color::from_rgb(
+                        0.5, 0.5, 0.5,
+                    ))),
+            );
+        }
+        for flag in pi.throttle_flags() {
+            let (state, color) = if flag.active {
+                ("now", iced::Color::from_rgb(1.0, 0.3, 0.3))
+            } else if flag.occurred {
+                ("since boot", iced::Color::from_rgb(1.0, 0.92, 0.0))
+            } else {
+                ("no", iced::Color::from_rgb(0.1, 0.8, 0.2))
+            };
+            pi_display = pi_display.push(
+                text(format!("{}: {}", flag.label, state))
+                    .size(14)
+                    .style(iced::theme::Text::Color(color)),
+            );
+        }
+        pi_display
+    }
+
+    // temperatures, fans and power draw, as far as the platform backend exposes them
+    fn view_sensors(&self) -> Column<'_, Message> {
+        let sensors = self
+            .paused_panels
+            .get(&Panel::Sensors)
+            .map_or(&self.sensors, |s| &s.sensors);
+        let numbers = self.config.number_format();
+
+        let mut sensors_display = column![self.panel_header("Sensors", 22, Panel::Sensors)]
+            .spacing(5)
+            .padding(10);
+        if let Some(battery) = &self.battery {
+            sensors_display = sensors_display.push(self.view_battery(battery));
+        }
+        if sensors.is_empty() {
+            return sensors_display.push(
+                text(format!("No sensors found ({})", self.sensor_backend.name()))
+                    .size(14)
+                    .style(iced::theme::Text::Color(iced::Color::from_rgb(
+                        0.5, 0.5, 0.5,
+                    ))),
+            );
+        }
+
+        for reading in sensors {
+            sensors_display = sensors_display.push(
+                text(format!(
+                    "{}: {} {}",
+                    reading.id,
+                    numbers.number(reading.value),
+                    reading.id.kind.unit()
+                ))
+                .size(16)
+                .style(iced::theme::Text::Color(iced::Color::from_rgb(
+                    0.1, 0.8, 0.2,
+                ))),
+            );
+        }
+        sensors_display
+    }
+
+    // power source and level, with the charge limit where the firmware offers one
+    fn view_battery(&self, battery: &BatteryInfo) -> Column<'_, Message> {
+        let source = match self.on_battery {
+            Some(true) => "On battery",
+            _ => "On AC",
+        };
+        let level = battery
+            .percent
+            .map_or_else(String::new, |percent| format!(", {}%", percent));
+        let mut battery_display = column![text(format!(
+            "{}: {}{} ({})",
+            battery.name, source, level, battery.status
+        ))
+        .size(16)
+        .style(iced::theme::Text::Color(iced::Color::from_rgb(
+            0.1, 0.8, 0.2,
+        )))]
+        .spacing(5);
+
+        if let Some(limit) = battery.charge_limit {
+            let mut limits = row![text("Charge limit").size(14)]
+                .spacing(10)
+                .align_items(Alignment::Center);
+            for percent in CHARGE_LIMITS {
+                limits = limits.push(
+                    button(text(format!("{}%", percent)).size(14))
+                        .padding(4)
+                        .style(if percent == limit {
+                            iced::theme::Button::Primary
+                        } else {
+                            iced::theme::Button::Secondary
+                        })
+                        .on_press(Message::SetChargeLimit(battery.name.clone(), percent)),
+                );
+            }
+            battery_display = battery_display
+                .push(limits)
+                .push(text(&self.power_status).size(14));
+        }
+        battery_display
+    }
+
+    // how the monitor itself keeps up: refresh time and ticks dropped because a refresh ran long
+    fn view_diagnostics(&self) -> Column<'_, Message> {
+        let shown = self.paused_panels.get(&Panel::Diagnostics);
+        let pacer = shown.map_or(&self.tick_pacer, |s| &s.tick_pacer);
+        let changes = shown.map_or(&self.delta, |s| &s.delta);
+        let numbers = self.config.number_format();
+        let slow = pacer.last_refresh >= self.config.redraw_interval();
+
+        column![
+            self.panel_header("Diagnostics", 22, Panel::Diagnostics),
+            text(format!(
+                "Last refresh: {} ms",
+                numbers.number(pacer.last_refresh.as_secs_f64() * 1000.)
+            ))
+            .size(16)
+            .style(iced::theme::Text::Color(if slow {
+                iced::Color::from_rgb(1.0, 0.3, 0.3)
+            } else {
+                iced::Color::from_rgb(0.1, 0.8, 0.2)
+            })),
+            text(format!(
+                "Redraw every {} ms, samples every {} ms{}",
+                numbers.integer(self.config.redraw_interval().as_millis() as u64),
+                numbers.integer(self.config.collect_interval().as_millis() as u64),
+                if self.sampler.is_some() {
+                    " (in the background)"
+                } else {
+                    ""
+                }
+            ))
+            .size(16)
+            .style(iced::theme::Text::Color(iced::Color::from_rgb(
+                0.1, 0.8, 0.2,
+            ))),
+            text(format!("Dropped ticks: {}", numbers.integer(pacer.dropped)))
+                .size(16)
+                .style(iced::theme::Text::Color(iced::Color::from_rgb(
+                    0.1, 0.8, 0.2,
+                ))),
+            text(format!(
+                "Rows rebuilt: {} processes changed (+{} -{}), {} disks changed (+{} -{})",
+                changes.processes.changed.len(),
+                changes.processes.added.len(),
+                changes.processes.removed.len(),
+                changes.disks.changed.len(),
+                changes.disks.added.len(),
+                changes.disks.removed.len(),
+            ))
+            .size(16)
+            .style(iced::theme::Text::Color(iced::Color::from_rgb(
+                0.1, 0.8, 0.2,
+            ))),
+            text(format!(
+                "Dropped log records: {}",
+                numbers.integer(self.log_writer.dropped())
+            ))
+            .size(16)
+            .style(iced::theme::Text::Color(iced::Color::from_rgb(
+                0.1, 0.8, 0.2,
+            ))),
+            text(if self.log_writer.directory().as_os_str().is_empty() {
+                String::from("Log directory: working directory")
+            } else {
+                format!(
+                    "Log directory: {} (fallback)",
+                    self.log_writer.directory().display()
+                )
+            })
+            .size(16)
+            .style(iced::theme::Text::Color(iced::Color::from_rgb(
+                0.1, 0.8, 0.2,
+            ))),
+        ]
+        .spacing(5)
+        .padding(10)
+    }
+
+    // processes that have kept a CPU busy for the configured time
+    fn view_runaways(&self) -> Column<'_, Message> {
+        let numbers = self.config.number_format();
+        let duration = self.config.runaway_duration();
+        let mut runaway_display =
+            column![text("Runaway Processes")
+                .size(22)
+                .style(iced::theme::Text::Color(iced::Color::from_rgb(
+                    0.2, 0.6, 1.0
+                )))]
+            .spacing(8);
+
+        if duration.is_zero() {
+            return runaway_display.push(
+                text("Turned off (\"runaway_minutes\": 0)").size(14).style(
+                    iced::theme::Text::Color(iced::Color::from_rgb(0.5, 0.5, 0.5)),
+                ),
+            );
+        }
+
+        let runaways = self.runaway_tracker.runaways(duration, Instant::now());
+        if runaways.is_empty() {
+            return runaway_display.push(
+                text(format!(
+                    "No process above {} CPU for {}+ minutes",
+                    numbers.percent(self.config.runaway_cpu_percent()),
+                    duration.as_secs() / 60
+                ))
+                .size(14)
+                .style(iced::theme::Text::Color(iced::Color::from_rgb(
+                    0.5, 0.5, 0.5,
+                ))),
+            );
+        }
+
+        for ((pid, start_time), busy) in runaways {
+            let Some(process) = self
+                .processes
+                .iter()
+                .find(|p| p.id == pid && p.start_time == start_time)
+            else {
+                continue;
+            };
+            runaway_display = runaway_display.push(
+                text(format!(
+                    "{} ({}): CPU {} | busy for {} minutes",
+                    process.name,
+                    pid,
+                    numbers.percent(process.cpu_usage_percent),
+                    busy.as_secs() / 60
+                ))
+                .size(16)
+                .style(iced::theme::Text::Color(iced::Color::from_rgb(
+                    1.0, 0.3, 0.3,
+                ))),
+            );
+        }
+        runaway_display
+    }
+
+    fn view_alerts(&self) -> Column<'_, Message> {
+        let mut alert_display = column![text("Alert Rules").size(22).style(
+            iced::theme::Text::Color(iced::Color::from_rgb(0.2, 0.6, 1.0,))
+        )]
+        .spacing(8)
+        .padding(10);
+
+        for (i, rule) in self.config.alert_rules.iter().enumerate() {
+            let state = if self.alert_state.is_firing(&rule.name) {
+                "FIRING"
+            } else {
+                "ok"
+            };
+
+            let cooldown = if rule.cooldown_secs > 0 {
+                format!(", at most every {}s", rule.cooldown_secs)
+            } else {
+                String::new()
+            };
+            let action = rule
+                .action
+                .as_ref()
+                .map(|action| format!(", then {}", action))
+                .unwrap_or_default();
+
+            alert_display = alert_display.push(
+                row![
+                    checkbox("", rule.enabled).on_toggle(move |x| Message::ToggleAlertRule(i, x)),
+                    text(format!(
+                        "{}: {} > {} for {}s{}{} [{}]",
+                        rule.name,
+                        rule.metric,
+                        rule.threshold,
+                        rule.duration_secs,
+                        cooldown,
+                        action,
+                        state
+                    ))
+                    .size(16),
+                    button(text("Delete").size(14))
+                        .padding(4)
+                        .style(iced::theme::Button::Destructive)
+                        .on_press(Message::DeleteAlertRule(i)),
+                ]
+                .spacing(10)
+                .align_items(Alignment::Center),
+            );
+        }
+
+        let form = &self.rule_form;
+        alert_display = alert_display.push(
+            row![
+                TextInput::new("Rule name", &form.name)
+                    .padding(6)
+                    .width(Length::Fixed(180.))
+                    .on_input(Message::AlertNameChanged),
+                pick_list(
+                    &AlertMetric::ALL[..],
+                    Some(form.metric),
+                    Message::AlertMetricSelected
+                )
+                .text_size(14),
+                TextInput::new("Threshold %", &form.threshold)
+                    .padding(6)
+                    .width(Length::Fixed(100.))
+                    .on_input(Message::AlertThresholdChanged),
+                TextInput::new("For (s)", &form.duration_secs)
+                    .padding(6)
+                    .width(Length::Fixed(80.))
+                    .on_input(Message::AlertDurationChanged),
+                TextInput::new("Cooldown (s)", &form.cooldown_secs)
+                    .padding(6)
+                    .width(Length::Fixed(110.))
+                    .on_input(Message::AlertCooldownChanged),
+                button(text("Add Rule").size(14))
+                    .padding(6)
+                    .on_press(Message::AddAlertRule),
+                button(text("Add Steal Template").size(14))
+                    .padding(6)
+                    .on_press(Message::AddStealAlertTemplate),
+            ]
+            .spacing(10)
+            .align_items(Alignment::Center),
+        );
+
+        // what the rule does besides notifying
+        let mut action_row = row![
+            text("When it fires:").size(14),
+            pick_list(
+                &AlertActionKind::ALL[..],
+                Some(form.action),
+                Message::AlertActionSelected
+            )
+            .text_size(14),
+        ]
+        .spacing(10)
+        .align_items(Alignment::Center);
+        if form.action == AlertActionKind::Command {
+            action_row = action_row.push(
+                TextInput::new(
+                    "Shell command, gets ALERT_RULE, ALERT_VALUE, ALERT_MESSAGE",
+                    &form.action_command,
+                )
+                .padding(6)
+                .width(Length::Fixed(420.))
+                .on_input(Message::AlertActionCommandChanged),
+            );
+        }
+        alert_display = alert_display.push(action_row);
+
+        if let Some(error) = &form.error {
+            alert_display = alert_display.push(text(error).size(14).style(
+                iced::theme::Text::Color(iced::Color::from_rgb(1.0, 0.3, 0.3)),
+            ));
+        }
+
+        // rule sets shared between machines
+        alert_display = alert_display.push(
+            row![
+                button(text("Export Rules CSV").size(14))
+                    .padding(6)
+                    .on_press(Message::ExportAlertRules(ExportFormat::Csv)),
+                button(text("Export Rules JSON").size(14))
+                    .padding(6)
+                    .on_press(Message::ExportAlertRules(ExportFormat::Json)),
+                TextInput::new("Rule file (.json or .csv)", &self.rule_file_input)
+                    .padding(6)
+                    .width(Length::Fixed(260.))
+                    .on_input(Message::RuleFileChanged)
+                    .on_submit(Message::ImportAlertRules),
+                button(text("Import Rules").size(14))
+                    .padding(6)
+                    .on_press_maybe(
+                        (!self.rule_file_input.trim().is_empty())
+                            .then_some(Message::ImportAlertRules)
+                    ),
+                text(&self.rule_file_status).size(14),
+            ]
+            .spacing(10)
+            .align_items(Alignment::Center),
+        );
+
+        alert_display = alert_display.push(
+            row![
+                text("Message template").size(16),
+                TextInput::new(
+                    DEFAULT_ALERT_TEMPLATE,
+                    self.config.alert_template.as_deref().unwrap_or_default()
+                )
+                .padding(6)
+                .width(Length::Fixed(420.))
+                .on_input(Message::AlertTemplateChanged)
+                .on_submit(Message::AlertTemplateSubmitted),
+                text("{rule} {metric} {value} {host} {time}")
+                    .size(14)
+                    .style(iced::theme::Text::Color(iced::Color::from_rgb(
+                        0.5, 0.5, 0.5,
+                    ))),
+            ]
+            .spacing(10)
+            .align_items(Alignment::Center),
+        );
+
+        alert_display = alert_display.push(self.view_runaways());
+
+        alert_display = alert_display.push(text("Alert History").size(22).style(
+            iced::theme::Text::Color(iced::Color::from_rgb(0.2, 0.6, 1.0)),
+        ));
+        let template = self.config.alert_template();
+        let host = &self.system_base_info.host_name;
+        for event in self.alert_history.iter().rev() {
+            let mut message = event.message(template, host);
+            if event.suppressed > 0 {
+                message.push_str(&format!(" (+{} suppressed)", event.suppressed));
+            }
+            alert_display = alert_display.push(text(message).size(14).style(
+                iced::theme::Text::Color(iced::Color::from_rgb(1.0, 0.3, 0.3)),
+            ));
+        }
+
+        alert_display
+    }
+
+    fn view_interrupts(&self) -> Column<'_, Message> {
+        let mut irq_display = column![text("Interrupts").size(22).style(iced::theme::Text::Color(
+            iced::Color::from_rgb(0.2, 0.6, 1.0,)
+        ))]
+        .spacing(8)
+        .padding(10);
+
+        if self.interrupt_counts.is_none() {
+            return irq_display
+                .push(text("Interrupt counters are only available on Linux").size(16));
+        }
+
+        // per-cpu share of the interrupts handled since the last refresh
+        let busiest = self
+            .interrupt_info
+            .per_cpu
+            .iter()
+            .map(|(_, count)| *count)
+            .max()
+            .unwrap_or(0)
+            .max(1);
+        irq_display = irq_display.push(text("Per CPU (since last refresh)").size(18));
+        for (cpu, count) in &self.interrupt_info.per_cpu {
+            irq_display = irq_display.push(
+                row![
+                    text(cpu).size(14).width(Length::Fixed(60.)),
+                    progress_bar(0.0..=busiest as f32, *count as f32)
+                        .width(Length::Fixed(300.))
+                        .height(Length::Fixed(14.)),
+                    text(count).size(14),
+                ]
+                .spacing(10)
+                .align_items(Alignment::Center),
+            );
+        }
+
+        irq_display = irq_display.push(text("Top IRQ sources").size(18));
+        for (irq, description, count) in self.interrupt_info.top_sources.iter().take(10) {
+            irq_display = irq_display.push(
+                text(format!("{}: {} | {}", irq, count, description))
+                    .size(14)
+                    .style(iced::theme::Text::Color(iced::Color::from_rgb(
+                        0.1, 0.8, 0.2,
+                    ))),
+            );
+        }
+
+        irq_display
+    }
+
+    fn view_session_stats(&self) -> Column<'_, Message> {
+        let numbers = self.config.number_format();
+        let percent = |value: f64| numbers.percent(value);
+        let rate = |value: f64| numbers.rate(value);
+        let summary_line =
+            |label: &str, summary: Option<Summary>, format: &dyn Fn(f64) -> String| {
+                let line = match summary {
+                    Some(summary) => format!(
+                        "{}: min {} | avg {} | max {} | p95 {}",
+                        label,
+                        format(summary.min),
+                        format(summary.avg),
+                        format(summary.max),
+                        format(summary.p95),
+                    ),
+                    None => format!("{}: no samples yet", label),
+                };
+
+                text(line)
                     .size(16)
+                    .style(iced::theme::Text::Color(iced::Color::from_rgb(
+                        0.1, 0.8, 0.2,
+                    )))
+            };
+
+        column![
+            row![
+                text("Session Statistics")
+                    .size(22)
                     .style(iced::theme::Text::Color(iced::Color::from_rgb(
                         0.2, 0.6, 1.0,
                     ))),
-            ];
+                button(text("Reset").size(14))
+                    .padding(6)
+                    .on_press(Message::ResetSessionStats),
+            ]
+            .spacing(20)
+            .align_items(Alignment::Center),
+            summary_line("CPU", self.session_stats.cpu_percent.summary(), &percent),
+            summary_line(
+                "Memory",
+                self.session_stats.memory_percent.summary(),
+                &percent
+            ),
+            summary_line(
+                "Network Sent",
+                self.session_stats.network_sent_rate.summary(),
+                &rate
+            ),
+            summary_line(
+                "Network Received",
+                self.session_stats.network_received_rate.summary(),
+                &rate
+            ),
+        ]
+        .spacing(5)
+        .padding(10)
+    }
+
+    fn view_annotations(&self) -> Column<'_, Message> {
+        self.history
+            .annotations()
+            .iter()
+            .fold(Column::new(), |col, annotation| {
+                col.push(
+                    text(format!("{} - {}", annotation.timestamp, annotation.label))
+                        .size(14)
+                        .style(iced::theme::Text::Color(iced::Color::from_rgb(
+                            1.0, 0.92, 0.0,
+                        ))),
+                )
+            })
+    }
+
+    fn view_sys_base_info(&self) -> Column<'_, Message> {
+        // System info line
+        let system_base_info = text(format!(
+            "System Name: {} | OS Version: {} | Kernel Version: {} | Host: {}",
+            self.system_base_info.system_name,
+            self.system_base_info.os_version,
+            self.system_base_info.kernal_version,
+            self.system_base_info.host_name
+        ))
+        .size(20)
+        .style(iced::theme::Text::Color(iced::Color::from_rgb(
+            1.0, 0.92, 0.0,
+        )));
+
+        // a skewed clock makes the logged timestamps misleading
+        let numbers = self.config.number_format();
+        let time_sync = match &self.time_sync {
+            Some(sync) => {
+                let status = if sync.synchronized {
+                    "synchronized"
+                } else {
+                    "NOT synchronized"
+                };
+                let offset = sync
+                    .offset_ms
+                    .map(|offset| {
+                        let sign = if offset > 0. { "+" } else { "" };
+                        format!(", offset {}{} ms", sign, numbers.number(offset))
+                    })
+                    .unwrap_or_default();
+                text(format!("Clock: {} ({}){}", status, sync.source, offset))
+                    .size(14)
+                    .style(iced::theme::Text::Color(if sync.synchronized {
+                        iced::Color::from_rgb(0.5, 0.5, 0.5)
+                    } else {
+                        iced::Color::from_rgb(1.0, 0.3, 0.3)
+                    }))
+            }
+            None => {
+                text("Clock: sync status unavailable")
+                    .size(14)
+                    .style(iced::theme::Text::Color(iced::Color::from_rgb(
+                        0.5, 0.5, 0.5,
+                    )))
+            }
+        };
+
+        let update_status = self.update_status.map(|status| {
+            let (message, color) = match (status.reboot_required, status.pending_updates) {
+                (true, _) => (
+                    String::from("Reboot required"),
+                    iced::Color::from_rgb(1.0, 0.3, 0.3),
+                ),
+                (false, Some(count)) if count > 0 => (
+                    format!("{} updates pending", count),
+                    iced::Color::from_rgb(1.0, 0.92, 0.0),
+                ),
+                _ => (
+                    String::from("No pending reboot"),
+                    iced::Color::from_rgb(0.5, 0.5, 0.5),
+                ),
+            };
+            text(message)
+                .size(14)
+                .style(iced::theme::Text::Color(color))
+        });
+
+        // settings that cost more than they should here, until dismissed
+        let hints =
+            overhead_hints(self).into_iter().map(|hint| {
+                row![
+                    text(hint.text).size(14).style(iced::theme::Text::Color(
+                        iced::Color::from_rgb(1.0, 0.92, 0.0,)
+                    )),
+                    button(text("Dismiss").size(12))
+                        .padding(4)
+                        .style(iced::theme::Button::Secondary)
+                        .on_press(Message::DismissHint(hint.kind))
+                ]
+                .spacing(10)
+                .align_items(Alignment::Center)
+                .into()
+            });
+
+        // align the text centrally, with padding for better look
+        column!(
+            system_base_info,
+            row![time_sync].push_maybe(update_status).spacing(20)
+        )
+        .extend(hints)
+        .spacing(5)
+        .padding(15)
+        .width(Length::Fill)
+        .align_items(Alignment::Center)
+    }
+
+    // what happened while the window was in the background, until dismissed
+    fn view_digest(&self) -> Option<Column<'_, Message>> {
+        let digest = self.digest.as_ref()?;
+        let mut lines = column![row![
+            text(format!(
+                "While you were away ({} min)",
+                digest.away.as_secs() / 60
+            ))
+            .size(18)
+            .style(iced::theme::Text::Color(iced::Color::from_rgb(
+                0.2, 0.6, 1.0,
+            ))),
+            button(text("Dismiss").size(12))
+                .padding(4)
+                .style(iced::theme::Button::Secondary)
+                .on_press(Message::DismissDigest)
+        ]
+        .spacing(10)
+        .align_items(Alignment::Center)]
+        .spacing(3);
+
+        let sections = [
+            ("", &digest.peaks),
+            ("", &digest.events),
+            ("Started: ", &digest.started),
+            ("Exited: ", &digest.exited),
+        ];
+        for (prefix, entries) in sections {
+            for entry in entries {
+                lines = lines.push(text(format!("{}{}", prefix, entry)).size(14).style(
+                    iced::theme::Text::Color(iced::Color::from_rgb(1.0, 0.92, 0.0)),
+                ));
+            }
+        }
+        Some(lines.padding(10))
+    }
+
+    // progress of the "find what is slow" capture, then its result until dismissed
+    fn view_diagnosis(&self) -> Option<Column<'_, Message>> {
+        let blue = iced::Color::from_rgb(0.2, 0.6, 1.0);
+        if let Some(capture) = &self.diagnosis_capture {
+            return Some(
+                column![row![
+                    text(format!(
+                        "Finding what is slow... {}/{} refreshes",
+                        capture.ticks(),
+                        CAPTURE_TICKS
+                    ))
+                    .size(18)
+                    .style(iced::theme::Text::Color(blue)),
+                    button(text("Cancel").size(12))
+                        .padding(4)
+                        .style(iced::theme::Button::Secondary)
+                        .on_press(Message::CancelDiagnosis)
+                ]
+                .spacing(10)
+                .align_items(Alignment::Center)]
+                .padding(10),
+            );
+        }
+
+        let diagnosis = self.diagnosis.as_ref()?;
+        let title = match diagnosis.bottleneck {
+            Some(bottleneck) => format!("Likely bottleneck: {}", bottleneck),
+            None => String::from("Nothing stands out, no resource came close to its limit"),
+        };
+        let mut lines = column![row![
+            text(title).size(18).style(iced::theme::Text::Color(blue)),
+            button(text("Dismiss").size(12))
+                .padding(4)
+                .style(iced::theme::Button::Secondary)
+                .on_press(Message::DismissDiagnosis)
+        ]
+        .spacing(10)
+        .align_items(Alignment::Center)]
+        .spacing(3);
+
+        for finding in &diagnosis.findings {
+            lines = lines.push(text(finding).size(14));
+        }
+        if !diagnosis.contributors.is_empty() {
+            lines = lines.push(text("Top contributors:").size(14));
+        }
+        for contributor in &diagnosis.contributors {
+            lines = lines.push(text(contributor).size(14).style(iced::theme::Text::Color(
+                iced::Color::from_rgb(1.0, 0.92, 0.0),
+            )));
+        }
+        Some(lines.padding(10))
+    }
+
+    // panel title with buttons that freeze the values shown in the panel and expand it to
+    // fill the window
+    fn panel_header(&self, title: &str, size: u16, panel: Panel) -> Row<'_, Message> {
+        let paused = self.paused_panels.contains_key(&panel);
+        let maximized = self.maximized_panel == Some(panel);
+
+        row![
+            text(title)
+                .size(size)
+                .style(iced::theme::Text::Color(iced::Color::from_rgb(
+                    0.2, 0.6, 1.0,
+                ))),
+            button(text(if paused { "Resume" } else { "Pause" }).size(12))
+                .padding(4)
+                .style(if paused {
+                    iced::theme::Button::Primary
+                } else {
+                    iced::theme::Button::Secondary
+                })
+                .on_press(Message::TogglePanelPause(panel)),
+            button(text(if maximized { "Restore" } else { "Maximize" }).size(12))
+                .padding(4)
+                .style(iced::theme::Button::Secondary)
+                .on_press(Message::TogglePanelMaximized(panel))
+        ]
+        .spacing(10)
+        .align_items(Alignment::Center)
+    }
+
+    // copy of the currently shown values, for pausing a panel
+    fn panel_snapshot(&self) -> PanelSnapshot {
+        PanelSnapshot {
+            cpu_usage: self.cpu_usage,
+            cpu_steal_percent: self.cpu_steal_percent,
+            no_of_processes: self.no_of_processes,
+            processors_info: self.smoothed_cores.clone(),
+            memory_usage: self.memory_usage,
+            memory_cache: self.memory_cache,
+            swap_memory_usage: self.swap_memory_usage,
+            disk_usage: self.disk_usage,
+            disks_info: self.disks_info.clone(),
+            disk_io: self.disk_io.clone(),
+            network_sent: self.network_sent,
+            network_received: self.network_received,
+            network_sent_rate: self.network_sent_rate,
+            network_received_rate: self.network_received_rate,
+            tcp_states: self.tcp_states,
+            processes: self.processes.clone(),
+            sensors: self.sensors.clone(),
+            gpu: self.gpu.clone(),
+            pi: self.pi.clone(),
+            tick_pacer: self.tick_pacer,
+            delta: self.delta.clone(),
+        }
+    }
+
+    fn view_cpu_info(&self) -> Column<'_, Message> {
+        let shown = self.paused_panels.get(&Panel::Cpu);
+        let cpu_usage = shown.map_or(self.cpu_usage, |s| s.cpu_usage);
+        let cpu_steal_percent = shown.map_or(self.cpu_steal_percent, |s| s.cpu_steal_percent);
+        let no_of_processes = shown.map_or(self.no_of_processes, |s| s.no_of_processes);
+        let numbers = self.config.number_format();
+
+        column![
+            self.panel_header("CPU Usage\n", 22, Panel::Cpu),
+            text("---------------")
+                .size(22)
+                .style(iced::theme::Text::Color(iced::Color::from_rgb(
+                    0.1, 0.8, 0.2,
+                ))),
+            text(format!("Total: {}", numbers.percent(cpu_usage as f64)))
+                .size(18)
+                .style(iced::theme::Text::Color(iced::Color::from_rgb(
+                    0.1, 0.8, 0.2
+                ))),
+            text(format!(
+                "Processes: {}",
+                numbers.integer(no_of_processes as u64)
+            ))
+            .size(18)
+            .style(iced::theme::Text::Color(iced::Color::from_rgb(
+                0.1, 0.8, 0.2
+            ))),
+            text(format!("Plysical Cores: {}", self.physical_cores))
+                .size(18)
+                .style(iced::theme::Text::Color(iced::Color::from_rgb(
+                    0.1, 0.8, 0.2
+                ))),
+            text(format!("Logical Processors: {}", self.logical_processors))
+                .size(18)
+                .style(iced::theme::Text::Color(iced::Color::from_rgb(
+                    0.1, 0.8, 0.2
+                ))),
+            text(match self.cpu_times {
+                Some(_) => format!("Steal: {}", numbers.percent(cpu_steal_percent)),
+                None => String::from("Steal: n/a"),
+            })
+            .size(18)
+            .style(iced::theme::Text::Color(iced::Color::from_rgb(
+                0.1, 0.8, 0.2
+            ))),
+            text("\n"),
+            horizontal_rule(5),
+            text("\n"),
+            self.view_per_core_usage(),
+        ]
+        .padding(5)
+    }
+
+    // read (green) and write (blue) throughput of the last two minutes, scaled to the busier of
+    // the two since a disk can sit idle at a few KB/s or stream at GB/s
+    fn view_disk_rate_chart(
+        &self,
+        label: &str,
+        rates: &[(f64, f64)],
+        height: f32,
+    ) -> Column<'_, Message> {
+        let numbers = self.config.number_format();
+        let peak = rates
+            .iter()
+            .map(|(read, write)| read.max(*write))
+            .fold(1., f64::max);
+        let series = |rate: fn(&(f64, f64)) -> f64| {
+            rates
+                .iter()
+                .map(|sample| (rate(sample) / peak * 100.) as f32)
+                .collect()
+        };
+        let chart = LineChart {
+            series: vec![
+                (iced::Color::from_rgb(0.1, 0.8, 0.2), series(|r| r.0)),
+                (iced::Color::from_rgb(0.2, 0.6, 1.0), series(|r| r.1)),
+            ],
+            ghosts: Vec::new(),
+            shaded: Vec::new(),
+            selection: None,
+            markers: Vec::new(),
+            on_select: None,
+            thresholds: Vec::new(),
+        };
+        column![
+            view_chart(chart, height),
+            text(format!(
+                "{}: green read, blue write, scaled to a peak of {}",
+                label,
+                numbers.rate(peak)
+            ))
+            .size(12)
+            .style(iced::theme::Text::Color(iced::Color::from_rgb(
+                0.5, 0.5, 0.5,
+            ))),
+        ]
+    }
+
+    fn view_memory_info(&self) -> Column<'_, Message> {
+        let shown = self.paused_panels.get(&Panel::Memory);
+        let memory_usage = shown.map_or(self.memory_usage, |s| s.memory_usage);
+        let swap_memory_usage = shown.map_or(self.swap_memory_usage, |s| s.swap_memory_usage);
+        let memory_cache = shown.map_or(self.memory_cache, |s| s.memory_cache);
+        let numbers = self.config.number_format();
+
+        let (apps, cache, free) = memory_categories(memory_usage, memory_cache);
+        let samples = self.history.samples();
+        let recent: Vec<(u64, u64, u64)> = samples
+            .iter()
+            .skip(samples.len().saturating_sub(MEMORY_CHART_SAMPLES))
+            .map(|s| memory_categories(s.memory_usage_byte, s.memory_cache_byte))
+            .collect();
+        let series = |category: fn(&(u64, u64, u64)) -> u64| {
+            recent
+                .iter()
+                .map(|sample| {
+                    let total = (sample.0 + sample.1 + sample.2).max(1);
+                    category(sample) as f32 / total as f32 * 100.
+                })
+                .collect()
+        };
+        let chart = StackedAreaChart {
+            series: vec![
+                (iced::Color::from_rgb(0.1, 0.8, 0.2), series(|s| s.0)),
+                (iced::Color::from_rgb(0.2, 0.6, 1.0), series(|s| s.1)),
+                (iced::Color::from_rgb(0.5, 0.5, 0.5), series(|s| s.2)),
+            ],
+        };
+
+        column![
+            self.panel_header("Memory Usage\n", 22, Panel::Memory),
+            text("------------\n")
+                .size(22)
+                .style(iced::theme::Text::Color(iced::Color::from_rgb(
+                    0.1, 0.8, 0.2,
+                ))),
+            text(format!(
+                "{} / {} ({})",
+                numbers.bytes(memory_usage.0 as f64),
+                numbers.bytes(memory_usage.1 as f64),
+                numbers.percent(usage_percent(memory_usage))
+            ))
+            .size(16)
+            .style(iced::theme::Text::Color(iced::Color::from_rgb(
+                0.1, 0.8, 0.2,
+            ))),
+            text(format!(
+                "Apps: {} | Cache and buffers: {} | Free: {}",
+                numbers.bytes(apps as f64),
+                numbers.bytes(cache as f64),
+                numbers.bytes(free as f64)
+            ))
+            .size(16)
+            .style(iced::theme::Text::Color(iced::Color::from_rgb(
+                0.1, 0.8, 0.2,
+            ))),
+            chart.view(80.),
+            text(
+                "green apps, blue cache and buffers (handed back to apps when they need it), grey free"
+            )
+            .size(12)
+            .style(iced::theme::Text::Color(iced::Color::from_rgb(
+                0.5, 0.5, 0.5,
+            ))),
+            text("\nSwap Memory Usage\n")
+                .size(22)
+                .style(iced::theme::Text::Color(iced::Color::from_rgb(
+                    0.2, 0.6, 1.0,
+                ))),
+            text("------------\n")
+                .size(22)
+                .style(iced::theme::Text::Color(iced::Color::from_rgb(
+                    0.1, 0.8, 0.2,
+                ))),
+            text(format!(
+                "{} / {} ({})",
+                numbers.bytes(swap_memory_usage.0 as f64),
+                numbers.bytes(swap_memory_usage.1 as f64),
+                numbers.percent(usage_percent(swap_memory_usage))
+            ))
+            .size(16)
+            .style(iced::theme::Text::Color(iced::Color::from_rgb(
+                0.1, 0.8, 0.2,
+            )))
+        ]
+        .padding(10)
+    }
+
+    fn view_disk_info(&self) -> Column<'_, Message> {
+        let shown = self.paused_panels.get(&Panel::Disk);
+        let disk_usage = shown.map_or(self.disk_usage, |s| s.disk_usage);
+        let disks_info = shown.map_or(&self.disks_info, |s| &s.disks_info);
+        let disk_io = shown.map_or(&self.disk_io, |s| &s.disk_io);
+        let numbers = self.config.number_format();
+
+        let mut disk_display = column![
+            self.panel_header("Disk Usage", 22, Panel::Disk),
+            text("------------\n")
+                .size(22)
+                .style(iced::theme::Text::Color(iced::Color::from_rgb(
+                    0.1, 0.8, 0.2,
+                ))),
+            text(format!(
+                "Total Disk Usage: {} / {} ({})",
+                numbers.bytes(disk_usage.0 as f64),
+                numbers.bytes(disk_usage.1 as f64),
+                numbers.percent(usage_percent(disk_usage))
+            ))
+            .size(16)
+            .style(iced::theme::Text::Color(iced::Color::from_rgb(
+                0.1, 0.8, 0.2,
+            )))
+        ];
+
+        // per-device IOPS from /proc/diskstats, Linux only
+        if self.disk_counters.is_some() {
+            disk_display = disk_display
+                .push(
+                    text("\nI/O Operations")
+                        .size(20)
+                        .style(iced::theme::Text::Color(iced::Color::from_rgb(
+                            0.2, 0.6, 1.0,
+                        ))),
+                )
+                .push(self.view_disk_rate_chart("All disks", &self.disk_rates.total(), 80.));
+            for device in disk_io {
+                let latency = device
+                    .latency_ms
+                    .map_or(String::from("-"), |ms| format!("{} ms", numbers.number(ms)));
+                disk_display = disk_display.push(
+                    text(format!(
+                        "{}: read {} ({} ops/s) | write {} ({} ops/s) | latency {} | queue {} (avg {})",
+                        self.config.display_name(&device.name),
+                        numbers.rate(device.read_rate),
+                        numbers.number(device.read_iops),
+                        numbers.rate(device.write_rate),
+                        numbers.number(device.write_iops),
+                        latency,
+                        device.in_flight,
+                        numbers.number(device.queue_depth)
+                    ))
+                    .size(16)
+                    .style(iced::theme::Text::Color(iced::Color::from_rgb(
+                        0.1, 0.8, 0.2,
+                    ))),
+                );
+                if let Some(rates) = self.disk_rates.device(&device.name) {
+                    let rates: Vec<(f64, f64)> = rates.iter().copied().collect();
+                    disk_display = disk_display.push(self.view_disk_rate_chart(
+                        self.config.display_name(&device.name),
+                        &rates,
+                        40.,
+                    ));
+                }
+            }
+        }
+
+        // writes counted across runs against each SSD's endurance rating
+        if !self.disk_wear.devices.is_empty() {
+            disk_display = disk_display.push(text("\nSSD Wear").size(20).style(
+                iced::theme::Text::Color(iced::Color::from_rgb(0.2, 0.6, 1.0)),
+            ));
+            for (name, device) in &self.disk_wear.devices {
+                let rated_tbw = self.config.ssd_rated_tbw(name);
+                disk_display = disk_display.push(
+                    text(format!(
+                        "{} {}: {} written since {}, ~{} of {} TBW",
+                        self.config.display_name(name),
+                        device.model,
+                        numbers.bytes(device.bytes_written as f64),
+                        device.since,
+                        numbers.percent(device.percent_of(rated_tbw)),
+                        numbers.number(rated_tbw)
+                    ))
+                    .size(16)
+                    .style(iced::theme::Text::Color(iced::Color::from_rgb(
+                        0.1, 0.8, 0.2,
+                    ))),
+                );
+            }
+            if let Some(amplification) = self.disk_wear.write_amplification() {
+                disk_display = disk_display.push(
+                    text(format!(
+                        "Write amplification: {}x (disk writes / process writes)",
+                        numbers.number(amplification)
+                    ))
+                    .size(16)
+                    .style(iced::theme::Text::Color(iced::Color::from_rgb(
+                        0.1, 0.8, 0.2,
+                    ))),
+                );
+            }
+        }
+
+        // partitions grouped under the physical disk they are on; recordings made before the
+        // disk was known list each partition on its own
+        let mut physical_disks: BTreeMap<&str, Vec<&DisksInfo>> = BTreeMap::new();
+        for disk in disks_info {
+            let device = if disk.device.is_empty() {
+                &disk.name
+            } else {
+                &disk.device
+            };
+            physical_disks.entry(device).or_default().push(disk);
+        }
+
+        for (device, partitions) in physical_disks {
+            disk_display = disk_display.push(self.view_physical_disk(device, &partitions));
+            for disk in partitions {
+                // a paused panel shows its own values, the kept rows follow the live ones
+                let formatted;
+                let cells = match self
+                    .panel_rows
+                    .disks
+                    .get(&disk.mount)
+                    .filter(|_| shown.is_none())
+                {
+                    Some(row) => row,
+                    None => {
+                        formatted = DiskRow::new(disk, numbers);
+                        &formatted
+                    }
+                };
+                let disk_info = column![
+                    text(format!("Partition: {}\n", self.disk_display_name(disk)))
+                        .size(18)
+                        .style(iced::theme::Text::Color(iced::Color::from_rgb(
+                            0.2, 0.6, 1.0
+                        ))),
+                    text(&cells.kind).size(16).style(iced::theme::Text::Color(
+                        iced::Color::from_rgb(0.1, 0.8, 0.2,)
+                    )),
+                    text(&cells.mount).size(16).style(iced::theme::Text::Color(
+                        iced::Color::from_rgb(0.1, 0.8, 0.2,)
+                    )),
+                    text(&cells.total).size(18).style(iced::theme::Text::Color(
+                        iced::Color::from_rgb(0.1, 0.8, 0.2,)
+                    )),
+                    text(&cells.free).size(18).style(iced::theme::Text::Color(
+                        iced::Color::from_rgb(0.1, 0.8, 0.2,)
+                    )),
+                    text(&cells.used).size(16).style(iced::theme::Text::Color(
+                        iced::Color::from_rgb(0.1, 0.8, 0.2,)
+                    )),
+                    // add a little space between each disk
+                    text("-------------------------------------------------")
+                        .size(16)
+                        .style(iced::theme::Text::Color(iced::Color::from_rgb(
+                            0.2, 0.6, 1.0,
+                        ))),
+                ];
+
+                // Add the disk info to the main display column, indented under its disk
+                disk_display = disk_display.push(container(disk_info).padding([10, 10, 10, 30]));
+            }
+        }
+
+        // disks by name (Windows volumes by drive letter, their names can be GUID paths),
+        // and block devices by their /proc/diskstats name
+        let mut devices: Vec<String> = disks_info
+            .iter()
+            .map(|disk| {
+                if cfg!(windows) {
+                    disk.mount.clone()
+                } else {
+                    disk.name.clone()
+                }
+            })
+            .chain(
+                disks_info
+                    .iter()
+                    .filter(|_| !cfg!(windows))
+                    .map(|disk| disk.device.clone()),
+            )
+            .chain(disk_io.iter().map(|device| device.name.clone()))
+            .chain(self.disk_wear.devices.keys().cloned())
+            .filter(|name| !name.is_empty())
+            .collect();
+        devices.sort();
+        devices.dedup();
+
+        // return display column
+        disk_display
+            .push(self.view_space_scan())
+            .push(self.view_alias_editor(devices))
+    }
+
+    // space under a mount point by owning user or top-level directory, for shared servers;
+    // scanned on demand since walking a big mount takes a while
+    fn view_space_scan(&self) -> Column<'_, Message> {
+        let numbers = self.config.number_format();
+        let mounts: Vec<String> = self
+            .disks_info
+            .iter()
+            .map(|disk| disk.mount.clone())
+            .collect();
+        // file owners are only known on Unix
+        let groupings: Vec<ScanGrouping> = ScanGrouping::ALL
+            .into_iter()
+            .filter(|grouping| cfg!(unix) || *grouping != ScanGrouping::Owner)
+            .collect();
+        let running = self
+            .space_scan
+            .as_ref()
+            .is_some_and(|scan| !scan.finished());
+
+        let scan_button = if running {
+            button(text("Cancel").size(14))
+                .padding(6)
+                .style(iced::theme::Button::Secondary)
+                .on_press(Message::CancelSpaceScan)
+        } else {
+            button(text("Scan").size(14)).padding(6).on_press_maybe(
+                self.space_scan_mount
+                    .is_some()
+                    .then_some(Message::StartSpaceScan),
+            )
+        };
+        let mut scan_display = column![
+            text("\nSpace by Owner")
+                .size(20)
+                .style(iced::theme::Text::Color(iced::Color::from_rgb(
+                    0.2, 0.6, 1.0,
+                ))),
+            row![
+                pick_list(
+                    mounts,
+                    self.space_scan_mount.clone(),
+                    Message::SpaceScanMountSelected
+                )
+                .placeholder("Mount point")
+                .text_size(14),
+                pick_list(
+                    groupings,
+                    Some(self.space_scan_grouping),
+                    Message::SpaceScanGroupingSelected
+                )
+                .text_size(14),
+                scan_button,
+            ]
+            .spacing(10)
+            .align_items(Alignment::Center),
+        ]
+        .spacing(5);
+
+        let Some(scan) = &self.space_scan else {
+            return scan_display;
+        };
+        let progress = scan.progress();
+        let mut status = format!(
+            "{}: {} in {} files{}",
+            scan.mount,
+            numbers.bytes(progress.bytes as f64),
+            numbers.integer(progress.files),
+            if running { ", scanning..." } else { "" }
+        );
+        if progress.unreadable > 0 {
+            status.push_str(&format!(
+                ", {} directories could not be read",
+                numbers.integer(progress.unreadable)
+            ));
+        }
+        scan_display = scan_display.push(text(status).size(14).style(iced::theme::Text::Color(
+            iced::Color::from_rgb(0.5, 0.5, 0.5),
+        )));
+
+        let mut totals: Vec<(&ScanKey, &u64)> = progress.totals.iter().collect();
+        totals.sort_by(|a, b| b.1.cmp(a.1));
+        for (key, bytes) in totals.into_iter().take(SPACE_SCAN_ENTRIES) {
+            let label = match key {
+                ScanKey::Owner(uid) => Uid::try_from(*uid as usize)
+                    .ok()
+                    .and_then(|uid| self.users.get_user_by_id(&uid))
+                    .map_or_else(|| uid.to_string(), |user| user.name().to_string()),
+                ScanKey::Directory(name) => name.clone(),
+            };
+            scan_display = scan_display.push(
+                text(format!(
+                    "{}: {} ({})",
+                    label,
+                    numbers.bytes(*bytes as f64),
+                    numbers.percent(*bytes as f64 / progress.bytes.max(1) as f64 * 100.)
+                ))
+                .size(16)
+                .style(iced::theme::Text::Color(iced::Color::from_rgb(
+                    0.1, 0.8, 0.2,
+                ))),
+            );
+        }
+        scan_display
+    }
+
+    // model, serial number and the space summed over the partitions of a physical disk
+    fn view_physical_disk(&self, device: &str, partitions: &[&DisksInfo]) -> Column<'_, Message> {
+        let numbers = self.config.number_format();
+        let total: u64 = partitions.iter().map(|disk| disk.total_disk).sum();
+        let free: u64 = partitions.iter().map(|disk| disk.free_disk).sum();
+        let used = (total.saturating_sub(free), total);
+
+        let mut header = column![text(self.config.display_name(device).to_string())
+            .size(20)
+            .style(iced::theme::Text::Color(iced::Color::from_rgb(
+                0.2, 0.6, 1.0
+            )))];
+
+        if let Some(details) = self.disk_topology.details(device) {
+            let mut facts = Vec::new();
+            if !details.model.is_empty() {
+                facts.push(format!("Model: {}", details.model));
+            }
+            if !details.serial.is_empty() {
+                facts.push(format!("Serial: {}", details.serial));
+            }
+            if details.size_bytes > 0 {
+                facts.push(format!(
+                    "Size: {}",
+                    numbers.bytes(details.size_bytes as f64)
+                ));
+            }
+            if !facts.is_empty() {
+                header = header.push(text(facts.join(" | ")).size(16).style(
+                    iced::theme::Text::Color(iced::Color::from_rgb(0.1, 0.8, 0.2)),
+                ));
+            }
+        }
+
+        header.push(
+            text(format!(
+                "{} partitions: {} / {} used ({})",
+                partitions.len(),
+                numbers.bytes(used.0 as f64),
+                numbers.bytes(used.1 as f64),
+                numbers.percent(usage_percent(used))
+            ))
+            .size(16)
+            .style(iced::theme::Text::Color(iced::Color::from_rgb(
+                0.1, 0.8, 0.2,
+            ))),
+        )
+    }
+
+    // disks can be renamed by device name or, e.g. for Windows volumes, by mount point;
+    // without a name of their own they show their label ("Label (C:)" on Windows)
+    fn disk_display_name<'a>(&'a self, disk: &'a DisksInfo) -> &'a str {
+        let label = if disk.label.is_empty() {
+            &disk.name
+        } else {
+            &disk.label
+        };
+        self.config
+            .aliases
+            .get(&disk.mount)
+            .or_else(|| self.config.aliases.get(&disk.name))
+            .map_or(label, String::as_str)
+    }
+
+    // picks one of `devices` and gives it a friendly name; a panel only shows the name input
+    // while one of its own devices is picked
+    fn view_alias_editor(&self, devices: Vec<String>) -> Row<'_, Message> {
+        let selected = self
+            .alias_device
+            .clone()
+            .filter(|device| devices.contains(device));
+        let editing = selected.is_some();
+
+        let mut editor = row![
+            text("Rename:").size(14),
+            pick_list(devices, selected, Message::AliasDeviceSelected)
+                .placeholder("Device")
+                .text_size(14),
+        ]
+        .spacing(10)
+        .align_items(Alignment::Center);
+        if editing {
+            editor = editor
+                .push(
+                    TextInput::new("Friendly name (empty resets)", &self.alias_input)
+                        .padding(6)
+                        .width(Length::Fixed(200.0))
+                        .on_input(Message::AliasChanged)
+                        .on_submit(Message::SaveAlias),
+                )
+                .push(
+                    button(text("Save Name").size(14))
+                        .padding(6)
+                        .on_press(Message::SaveAlias),
+                );
+        }
+        editor
+    }
+
+    // wake a machine on the interface's network, or bring the interface up or down
+    fn view_interface_actions(&self, interfaces: Vec<String>) -> Column<'_, Message> {
+        let selected = self
+            .interface_action
+            .clone()
+            .filter(|interface| interfaces.contains(interface));
+        let chosen = selected.is_some();
+
+        let mut actions = row![
+            text("Interface:").size(14),
+            pick_list(interfaces, selected, Message::InterfaceActionSelected)
+                .placeholder("Interface")
+                .text_size(14),
+        ]
+        .spacing(10)
+        .align_items(Alignment::Center);
+        if chosen {
+            actions = actions
+                .push(
+                    TextInput::new("MAC to wake, e.g. aa:bb:cc:dd:ee:ff", &self.wake_mac_input)
+                        .padding(6)
+                        .width(Length::Fixed(240.0))
+                        .on_input(Message::WakeMacChanged)
+                        .on_submit(Message::SendWake),
+                )
+                .push(
+                    button(text("Wake").size(14))
+                        .padding(6)
+                        .on_press(Message::SendWake),
+                )
+                .push(
+                    button(text("Link Up").size(14))
+                        .padding(6)
+                        .on_press(Message::SetLink(true)),
+                )
+                .push(
+                    button(text("Link Down").size(14))
+                        .padding(6)
+                        .on_press(Message::SetLink(false)),
+                );
+        }
+        let mut interface_actions = column![actions].spacing(5);
+        if let Some(interface) = self
+            .interface_action
+            .as_ref()
+            .filter(|_| self.link_down_pending)
+        {
+            interface_actions = interface_actions.push(
+                row![
+                    text(format!(
+                        "Bring {} down? Connections through it drop, a remote session too.",
+                        interface
+                    ))
+                    .size(14),
+                    button(text("Link Down").size(14))
+                        .padding(6)
+                        .on_press(Message::ConfirmLinkDown),
+                    button(text("Cancel").size(14))
+                        .padding(6)
+                        .on_press(Message::CancelLinkDown),
+                ]
+                .spacing(10)
+                .align_items(Alignment::Center),
+            );
+        }
+        interface_actions.push(text(&self.interface_action_status).size(14))
+    }
+
+    fn view_network_info(&self) -> Column<'_, Message> {
+        let shown = self.paused_panels.get(&Panel::Network);
+        let sent = shown.map_or(self.network_sent, |s| s.network_sent);
+        let received = shown.map_or(self.network_received, |s| s.network_received);
+        let sent_rate = shown.map_or(self.network_sent_rate, |s| s.network_sent_rate);
+        let received_rate = shown.map_or(self.network_received_rate, |s| s.network_received_rate);
+        let tcp_states = shown.map_or(self.tcp_states, |s| s.tcp_states);
+        let numbers = self.config.number_format();
+
+        let mut network_display = column![
+            self.panel_header("Network Usage\n", 22, Panel::Network),
+            text("---------------")
+                .size(22)
+                .style(iced::theme::Text::Color(iced::Color::from_rgb(
+                    0.1, 0.8, 0.2,
+                ))),
+            text(format!(
+                "- Sent: {} ({})\n\n- Received: {} ({})",
+                numbers.bytes(sent as f64),
+                numbers.rate(sent_rate),
+                numbers.bytes(received as f64),
+                numbers.rate(received_rate)
+            ))
+            .size(16)
+            .style(iced::theme::Text::Color(iced::Color::from_rgb(
+                0.1, 0.8, 0.2,
+            )))
+        ];
+
+        let mut interfaces: Vec<_> = self.networks.iter().collect();
+        interfaces.sort_by(|a, b| a.0.cmp(b.0));
+        for (name, network) in &interfaces {
+            network_display = network_display.push(
+                text(format!(
+                    "{}: sent {}, received {}",
+                    self.config.display_name(name),
+                    numbers.bytes(network.total_transmitted() as f64),
+                    numbers.bytes(network.total_received() as f64)
+                ))
+                .size(14)
+                .style(iced::theme::Text::Color(iced::Color::from_rgb(
+                    0.5, 0.5, 0.5,
+                ))),
+            );
+        }
+        let names: Vec<String> = interfaces
+            .iter()
+            .map(|(name, _)| name.to_string())
+            .collect();
+        let mut network_display = network_display.push(self.view_alias_editor(names.clone()));
+        // a replayed session's interfaces may not be this machine's
+        if self.replay.is_none() {
+            network_display = network_display.push(self.view_interface_actions(names));
+        }
+
+        let Some(tcp_states) = tcp_states else {
+            return network_display;
+        };
+
+        // socket counts of recent samples, scaled so the busiest state or the highest
+        // CLOSE_WAIT threshold fills the chart
+        let samples = self.history.samples();
+        let recent: Vec<TcpStates> = samples
+            .iter()
+            .skip(samples.len().saturating_sub(TCP_CHART_SAMPLES))
+            .map(|sample| sample.tcp_states)
+            .collect();
+        let peak = recent
+            .iter()
+            .map(|states| {
+                states
+                    .established
+                    .max(states.time_wait)
+                    .max(states.close_wait)
+            })
+            .max()
+            .unwrap_or(0)
+            .max(1);
+        let peak = self
+            .metric_thresholds(AlertMetric::TcpCloseWait)
+            .iter()
+            .map(|(_, to, _)| to.ceil() as u64)
+            .fold(peak, u64::max);
+        let series = |state: fn(&TcpStates) -> u64| {
+            recent
+                .iter()
+                .map(|states| state(states) as f32 / peak as f32 * 100.)
+                .collect()
+        };
+        let chart = LineChart {
+            series: vec![
+                (
+                    iced::Color::from_rgb(0.1, 0.8, 0.2),
+                    series(|s| s.established),
+                ),
+                (
+                    iced::Color::from_rgb(0.2, 0.6, 1.0),
+                    series(|s| s.time_wait),
+                ),
+                (
+                    iced::Color::from_rgb(1.0, 0.3, 0.3),
+                    series(|s| s.close_wait),
+                ),
+            ],
+            ghosts: Vec::new(),
+            shaded: Vec::new(),
+            selection: None,
+            markers: Vec::new(),
+            on_select: None,
+            thresholds: self.chart_thresholds(
+                AlertMetric::TcpCloseWait,
+                iced::Color::from_rgb(1.0, 0.3, 0.3),
+                peak as f64,
+            ),
+        };
+
+        network_display
+            .push(
+                text("\nTCP Sockets")
+                    .size(20)
+                    .style(iced::theme::Text::Color(iced::Color::from_rgb(
+                        0.2, 0.6, 1.0,
+                    ))),
+            )
+            .push(
+                text(format!(
+                    "ESTABLISHED: {} | TIME_WAIT: {} | CLOSE_WAIT: {} | LISTEN: {}",
+                    numbers.integer(tcp_states.established),
+                    numbers.integer(tcp_states.time_wait),
+                    numbers.integer(tcp_states.close_wait),
+                    numbers.integer(tcp_states.listen)
+                ))
+                .size(16)
+                .style(iced::theme::Text::Color(iced::Color::from_rgb(
+                    0.1, 0.8, 0.2,
+                ))),
+            )
+            .push(view_chart(chart, 80.))
+            .push(
+                text(format!(
+                    "green ESTABLISHED, blue TIME_WAIT, red CLOSE_WAIT, scaled to a peak of {}",
+                    numbers.integer(peak)
+                ))
+                .size(12)
+                .style(iced::theme::Text::Color(iced::Color::from_rgb(
+                    0.5, 0.5, 0.5,
+                ))),
+            )
+            .push(self.view_protocols())
+    }
+
+    // share of TCP and UDP packets over the last two minutes and the ports with most sockets
+    fn view_protocols(&self) -> Column<'_, Message> {
+        let numbers = self.config.number_format();
+        let Some(latest) = self.protocol_rates.back() else {
+            return column![];
+        };
+
+        let shares: Vec<Option<f64>> = self
+            .protocol_rates
+            .iter()
+            .map(ProtocolRates::tcp_share)
+            .collect();
+        // refreshes without any traffic are left empty
+        let series = |share: fn(f64) -> f64| {
+            shares
+                .iter()
+                .map(|tcp| tcp.map_or(0., share) as f32)
+                .collect()
+        };
+        let chart = StackedAreaChart {
+            series: vec![
+                (iced::Color::from_rgb(0.1, 0.8, 0.2), series(|tcp| tcp)),
+                (
+                    iced::Color::from_rgb(0.2, 0.6, 1.0),
+                    series(|tcp| 100. - tcp),
+                ),
+            ],
+        };
+        let share =
+            |tcp_share: Option<f64>| tcp_share.map_or(String::from("-"), |s| numbers.percent(s));
+
+        let ports = self
+            .top_ports
+            .iter()
+            .map(|port| format!("{}/{} ({})", port.protocol, port.port, port.sockets))
+            .collect::<Vec<String>>()
+            .join(", ");
+
+        column![
+            text("\nProtocols")
+                .size(20)
+                .style(iced::theme::Text::Color(iced::Color::from_rgb(
+                    0.2, 0.6, 1.0,
+                ))),
+            text(format!(
+                "TCP: {} packets/s ({}) | UDP: {} packets/s ({})",
+                numbers.number(latest.tcp_packets),
+                share(latest.tcp_share()),
+                numbers.number(latest.udp_packets),
+                share(latest.tcp_share().map(|tcp| 100. - tcp))
+            ))
+            .size(16)
+            .style(iced::theme::Text::Color(iced::Color::from_rgb(
+                0.1, 0.8, 0.2,
+            ))),
+            chart.view(60.),
+            text("green TCP, blue UDP, share of the packets sent and received")
+                .size(12)
+                .style(iced::theme::Text::Color(iced::Color::from_rgb(
+                    0.5, 0.5, 0.5,
+                ))),
+            text(format!("Top ports by sockets: {}", ports))
+                .size(16)
+                .style(iced::theme::Text::Color(iced::Color::from_rgb(
+                    0.1, 0.8, 0.2,
+                ))),
+        ]
+    }
+
+    fn view_per_core_usage(&self) -> Column<'_, Message> {
+        let processors_info = self
+            .paused_panels
+            .get(&Panel::Cpu)
+            .map_or(&self.smoothed_cores, |s| &s.processors_info);
+        let numbers = self.config.number_format();
+
+        // a text line per core doesn't scale to big hosts, so they always get the heatmap
+        let many_cores = processors_info.len() > HEATMAP_CORE_THRESHOLD;
+        let heatmap_checkbox = checkbox("Heatmap", self.config.core_heatmap || many_cores)
+            .spacing(6)
+            .on_toggle_maybe((!many_cores).then_some(Message::ToggleCoreHeatmap));
+
+        // the cores the process in the detail pane may use and ran on
+        let affinity = self.process_affinity.as_ref();
+        let selected = affinity.and_then(|affinity| {
+            self.processes
+                .iter()
+                .find(|p| p.id == affinity.pid)
+                .map(|p| format!("{} ({})", p.name, p.id))
+        });
+        let caption = selected.map(|process| {
+            text(format!(
+                "{}: {} of {} cores allowed, highlighted where it ran since the last refresh",
+                process,
+                affinity.map_or(0, |affinity| affinity.allowed.len()),
+                self.logical_processors
+            ))
+            .size(12)
+            .style(iced::theme::Text::Color(iced::Color::from_rgb(
+                0.5, 0.5, 0.5,
+            )))
+        });
+
+        if self.config.core_heatmap || many_cores {
+            let heatmap = CoreHeatmap {
+                cores: processors_info.clone(),
+                numbers,
+                affinity: affinity.map_or_else(Vec::new, |affinity| {
+                    processors_info
+                        .iter()
+                        .map(|(name, _, _)| affinity.core(name))
+                        .collect()
+                }),
+            };
+            return column![heatmap_checkbox]
+                .push_maybe(caption)
+                .push(heatmap.view())
+                .spacing(10);
+        }
+
+        let online = processors_info.iter().fold(
+            column![heatmap_checkbox].push_maybe(caption),
+            |col, (name, usage, _)| {
+                let (allowed, ran) = affinity.map_or((true, false), |affinity| affinity.core(name));
+                let (marker, color) = match (allowed, ran) {
+                    (_, true) => (" <- running", iced::Color::from_rgb(0.2, 0.6, 1.0)),
+                    (false, false) => (" (not allowed)", iced::Color::from_rgb(0.5, 0.5, 0.5)),
+                    (true, false) => ("", iced::Color::from_rgb(0.1, 0.8, 0.2)),
+                };
+                col.push(
+                    text(format!(
+                        "{}: {}{}",
+                        name,
+                        numbers.percent(*usage as f64),
+                        marker
+                    ))
+                    .size(16)
+                    .style(iced::theme::Text::Color(color)),
+                )
+            },
+        );
+
+        // offline cores have no usage and are left out of the total
+        self.offline_cpus.iter().fold(online, |col, cpu| {
+            col.push(
+                text(format!("cpu{}: offline", cpu))
+                    .size(16)
+                    .style(iced::theme::Text::Color(iced::Color::from_rgb(
+                        0.5, 0.5, 0.5,
+                    ))),
+            )
+        })
+    }
+
+    fn view_process_info(&self) -> Column<'_, Message> {
+        let process_display = column![
+            self.panel_header("Running Processes", 24, Panel::Processes),
+            text("---------------")
+                .size(22)
+                .style(iced::theme::Text::Color(iced::Color::from_rgb(
+                    0.1, 0.8, 0.2,
+                ))),
+            self.view_process_filter(),
+            self.view_process_paging(),
+            self.view_process_actions(),
+            self.view_process_details(),
+        ];
+
+        let processes = self.visible_processes();
+        let mut table = ProcessTable::new(&processes, self.config.number_format());
+        // a paused list shows its own values, the kept rows follow the live ones
+        if !self.paused_panels.contains_key(&Panel::Processes) {
+            table = table.rows(&self.panel_rows.processes);
+        }
+        let table = table
+            .selectable(
+                self.selected_processes.keys().copied().collect(),
+                Message::ProcessSelected,
+            )
+            .show_command_line(self.config.show_command_line)
+            .highlight_new(self.config.new_process_highlight_secs())
+            .view();
+
+        process_display.push(table)
+    }
+
+    fn view_process_filter(&self) -> Column<'_, Message> {
+        let filter_input = TextInput::new("Filter processes", &self.process_filter.pattern)
+            .padding(6)
+            .width(Length::Fixed(200.0))
+            .on_input(Message::ProcessFilterChanged);
+
+        let regex_checkbox = checkbox("Regex", self.process_filter.is_regex)
+            .spacing(6)
+            .on_toggle(Message::ToggleFilterRegex);
+
+        let saved_filters = pick_list(
+            self.config.saved_filters.clone(),
+            None::<SavedFilter>,
+            Message::SavedFilterSelected,
+        )
+        .placeholder("Saved filters")
+        .text_size(14);
+
+        let save_button = button(text("Save Filter").size(14))
+            .padding(6)
+            .on_press_maybe(
+                (!self.process_filter.pattern.is_empty()).then_some(Message::SaveProcessFilter),
+            );
+
+        let command_checkbox = checkbox("Command Line", self.config.show_command_line)
+            .spacing(6)
+            .on_toggle(Message::ToggleCommandColumn);
+
+        let highlight_input = TextInput::new("Seconds", &self.highlight_secs_input)
+            .padding(6)
+            .width(Length::Fixed(70.0))
+            .on_input(Message::HighlightSecsChanged);
+        let highlight_setting = tooltip(
+            row![text("Highlight new (s)").size(14), highlight_input]
+                .spacing(6)
+                .align_items(Alignment::Center),
+            text("Processes started within this many seconds are shown in yellow, 0 turns it off")
+                .size(14),
+            tooltip::Position::Bottom,
+        )
+        .style(iced::theme::Container::Box);
+
+        let refresh_input = TextInput::new("Seconds", &self.process_refresh_input)
+            .padding(6)
+            .width(Length::Fixed(70.0))
+            .on_input(Message::ProcessRefreshSecsChanged);
+        let refresh_setting = tooltip(
+            row![text("Refresh every (s)").size(14), refresh_input]
+                .spacing(6)
+                .align_items(Alignment::Center),
+            text("The process list is enumerated this often, the charts still update every second")
+                .size(14),
+            tooltip::Position::Bottom,
+        )
+        .style(iced::theme::Container::Box);
+
+        let sort_list = pick_list(
+            &ProcessSort::ALL[..],
+            Some(self.process_sort),
+            Message::ProcessSortSelected,
+        )
+        .text_size(14);
+        let reverse_checkbox = checkbox("Reverse", self.process_sort_reversed)
+            .spacing(8)
+            .text_size(14)
+            .on_toggle(Message::ToggleProcessSortReversed);
+
+        let view_name_input = TextInput::new("View name", &self.view_name_input)
+            .padding(6)
+            .width(Length::Fixed(150.0))
+            .on_input(Message::ViewNameChanged)
+            .on_submit(Message::SaveProcessView);
+
+        let save_view_button = button(text("Save View").size(14))
+            .padding(6)
+            .on_press_maybe(
+                (!self.view_name_input.trim().is_empty()).then_some(Message::SaveProcessView),
+            );
+
+        // one click switches to a saved view
+        let views = self.config.saved_views.iter().enumerate().fold(
+            row![
+                sort_list,
+                reverse_checkbox,
+                view_name_input,
+                save_view_button
+            ]
+            .spacing(10)
+            .align_items(Alignment::Center),
+            |views, (i, view)| {
+                views.push(
+                    row![
+                        button(text(&view.name).size(14))
+                            .padding(6)
+                            .style(iced::theme::Button::Secondary)
+                            .on_press(Message::ApplyProcessView(i)),
+                        button(text("x").size(14))
+                            .padding(6)
+                            .style(iced::theme::Button::Text)
+                            .on_press(Message::DeleteProcessView(i)),
+                    ]
+                    .align_items(Alignment::Center),
+                )
+            },
+        );
+
+        let mut filter_display = column![
+            row![
+                filter_input,
+                regex_checkbox,
+                saved_filters,
+                save_button,
+                command_checkbox,
+                highlight_setting,
+                refresh_setting
+            ]
+            .spacing(10)
+            .align_items(Alignment::Center),
+            views
+        ]
+        .spacing(5);
+
+        if let Some(error) = &self.process_filter.error {
+            filter_display = filter_display.push(text(error).size(14).style(
+                iced::theme::Text::Color(iced::Color::from_rgb(1.0, 0.3, 0.3)),
+            ));
+        }
+
+        filter_display
+    }
+
+    fn view_process_actions(&self) -> Column<'_, Message> {
+        let selected = self.selected_processes.len();
+        let has_selection = selected > 0;
+
+        let nice_input = TextInput::new("Nice", &self.nice_value)
+            .padding(6)
+            .width(Length::Fixed(60.0))
+            .on_input(Message::NiceValueChanged);
+
+        column![
+            row![
+                button(text("Select Page").size(14))
+                    .padding(6)
+                    .on_press(Message::SelectVisibleProcesses),
+                button(text("Clear").size(14))
+                    .padding(6)
+                    .on_press_maybe(has_selection.then_some(Message::ClearProcessSelection)),
+                button(text(format!("Kill Selected ({})", selected)).size(14))
+                    .padding(6)
+                    .style(iced::theme::Button::Destructive)
+                    .on_press_maybe(has_selection.then_some(Message::KillSelectedProcesses)),
+                nice_input,
+                button(text("Renice").size(14))
+                    .padding(6)
+                    .on_press_maybe(has_selection.then_some(Message::ReniceSelectedProcesses)),
+                checkbox("Follow Selection", self.config.follow_selection)
+                    .spacing(6)
+                    .on_toggle(Message::ToggleFollowSelection),
+                button(text("Copy Table").size(14))
+                    .padding(6)
+                    .on_press(Message::CopyProcessTable),
+                button(text("Export CSV").size(14))
+                    .padding(6)
+                    .on_press(Message::ExportProcessesCsv),
+            ]
+            .spacing(10)
+            .align_items(Alignment::Center),
+            text(&self.process_action_status).size(14),
+        ]
+        .spacing(5)
+    }
+
+    fn view_process_details(&self) -> Column<'_, Message> {
+        let mut details = Column::new().spacing(5);
+        if self.selected_processes.len() != 1 {
+            return details;
+        }
+        let Some(process) = self
+            .filtered_processes()
+            .into_iter()
+            .find(|p| self.selected_processes.contains_key(&p.id))
+        else {
+            return details;
+        };
+
+        details = details
+            .push(
+                row![
+                    text(format!("{} ({})", process.name, process.id))
+                        .size(18)
+                        .style(iced::theme::Text::Color(iced::Color::from_rgb(
+                            0.2, 0.6, 1.0,
+                        ))),
+                    button(text("Explain").size(14))
+                        .padding(4)
+                        .on_press(Message::ExplainProcess(process.id)),
+                    button(text("Search the Web").size(14))
+                        .padding(4)
+                        .style(iced::theme::Button::Secondary)
+                        .on_press(Message::SearchProcess(process.name.to_string())),
+                ]
+                .spacing(10)
+                .align_items(Alignment::Center),
+            )
+            .push(
+                text(&process.command)
+                    .size(14)
+                    .style(iced::theme::Text::Color(iced::Color::from_rgb(
+                        0.6, 0.6, 0.6,
+                    ))),
+            );
+
+        if let Some(cgroup) = &self.process_cgroup {
+            details = details.push(
+                row![
+                    text(format!("cgroup {}", cgroup)).size(14),
+                    TextInput::new("CPU %", &self.quota_cpu_input)
+                        .padding(4)
+                        .size(14)
+                        .width(Length::Fixed(70.0))
+                        .on_input(Message::QuotaCpuChanged),
+                    TextInput::new("Memory MiB", &self.quota_memory_input)
+                        .padding(4)
+                        .size(14)
+                        .width(Length::Fixed(100.0))
+                        .on_input(Message::QuotaMemoryChanged),
+                    tooltip(
+                        button(text("Limit").size(14))
+                            .padding(4)
+                            .on_press(Message::LimitProcess(process.id)),
+                        text("Throttle instead of killing: CPU in percent of one core, empty fields remove that limit").size(14),
+                        tooltip::Position::Bottom,
+                    )
+                    .style(iced::theme::Container::Box),
+                ]
+                .spacing(10)
+                .align_items(Alignment::Center),
+            );
+        }
+
+        // the same cores are highlighted in the CPU panel
+        if let Some(affinity) = &self.process_affinity {
+            let ran = if affinity.running.is_empty() {
+                String::from("none since the last refresh")
+            } else {
+                format_cpu_list(&affinity.running)
+            };
+            details = details.push(
+                text(format!(
+                    "Cores allowed: {}, ran on: {}",
+                    format_cpu_list(&affinity.allowed),
+                    ran
+                ))
+                .size(14),
+            );
+        }
+
+        if self.explained_process == Some(process.id) {
+            let explanation = match lookup(&process.name) {
+                Some(known) => format!("{}\n{}", known.description, known.advice),
+                None => {
+                    String::from("Not in the offline list of common processes, try Search the Web")
+                }
+            };
+            details = details.push(text(explanation).size(14).style(iced::theme::Text::Color(
+                iced::Color::from_rgb(1.0, 0.92, 0.0),
+            )));
+        }
+
+        match &self.process_connections {
+            None => details.push(
+                text("Open sockets can't be read (Linux only, other users' processes need root)")
+                    .size(14)
+                    .style(iced::theme::Text::Color(iced::Color::from_rgb(
+                        0.5, 0.5, 0.5,
+                    ))),
+            ),
+            Some(connections) if connections.is_empty() => details.push(
+                text("No open sockets")
+                    .size(14)
+                    .style(iced::theme::Text::Color(iced::Color::from_rgb(
+                        0.5, 0.5, 0.5,
+                    ))),
+            ),
+            Some(connections) => connections.iter().fold(details, |col, connection| {
+                col.push(
+                    text(format!(
+                        "{} {} -> {} {}",
+                        connection.protocol, connection.local, connection.remote, connection.state
+                    ))
+                    .size(14)
+                    .style(iced::theme::Text::Color(iced::Color::from_rgb(
+                        0.1, 0.8, 0.2,
+                    ))),
+                )
+            }),
+        }
+    }
+
+    fn view_process_paging(&self) -> Element<'_, Message> {
+        let page_count = self.process_page_count();
+
+        row![
+            pick_list(
+                &ProcessLimit::ALL[..],
+                Some(self.process_limit),
+                Message::ProcessLimitChanged
+            )
+            .text_size(14),
+            button(text("< Prev").size(14)).padding(6).on_press_maybe(
+                (self.process_page > 0).then(|| Message::ProcessPageChanged(self.process_page - 1))
+            ),
+            text(format!("Page {} / {}", self.process_page + 1, page_count)).size(14),
+            button(text("Next >").size(14)).padding(6).on_press_maybe(
+                (self.process_page + 1 < page_count)
+                    .then(|| Message::ProcessPageChanged(self.process_page + 1))
+            ),
+        ]
+        .spacing(10)
+        .align_items(Alignment::Center)
+        .into()
+    }
+}
+
+impl SystemMonitor {
+    // processes that pass the process filter
+    fn filtered_processes(&self) -> Vec<&Process> {
+        let mut processes: Vec<&Process> = self
+            .paused_panels
+            .get(&Panel::Processes)
+            .map_or(&self.processes, |s| &s.processes)
+            .iter()
+            .filter(|process| self.process_filter.matches(&process.name))
+            .collect();
+
+        // a paused list keeps the order it had, so sort again for a newly chosen one
+        let order = self.process_order();
+        processes.sort_by(|a, b| order.compare(a, b));
+        processes
+    }
+
+    fn process_page_count(&self) -> usize {
+        match self.process_limit.page_size() {
+            Some(size) => self.filtered_processes().len().div_ceil(size).max(1),
+            None => 1,
+        }
+    }
+
+    // processes on the current page of the process panel
+    fn visible_processes(&self) -> Vec<&Process> {
+        let filtered = self.filtered_processes();
+        match self.process_limit.page_size() {
+            Some(size) => filtered
+                .into_iter()
+                .skip(self.process_page * size)
+                .take(size)
+                .collect(),
+            None => filtered,
+        }
+    }
+
+    // the detail pane is shown while exactly one process is selected
+    fn refresh_process_details(&mut self) {
+        let selected = self
+            .selected_processes
+            .keys()
+            .next()
+            .filter(|_| self.selected_processes.len() == 1);
+        self.process_connections = selected.and_then(|pid| read_process_connections(*pid));
+        // the thread CPU times of the previous read tell which cores were used since
+        let previous = self
+            .process_affinity
+            .take()
+            .filter(|affinity| selected == Some(&affinity.pid));
+        self.process_affinity = selected.and_then(|pid| read_affinity(*pid, previous.as_ref()));
+        self.process_cgroup = selected.and_then(|pid| read_cgroup(*pid));
+    }
+
+    // move to the page showing the first selected process, as the list re-sorts every tick
+    fn follow_selection(&mut self) {
+        let Some(size) = self.process_limit.page_size() else {
+            return;
+        };
+        let index = self
+            .filtered_processes()
+            .iter()
+            .position(|p| self.selected_processes.contains_key(&p.id));
+        if let Some(index) = index {
+            self.process_page = index / size;
+        }
+    }
+
+    // apply and write the settings picked in the first-run wizard
+    fn finish_setup(&mut self, config: Config) {
+        self.setup = None;
+        self.interval_in_secs = config
+            .interval_secs
+            .map(|secs| secs.to_string())
+            .unwrap_or_default();
+        self.save_to_file = config.save_to_file;
+        self.config = config;
+        self.save_config();
+    }
+
+    // end the session cleanly and close every window, which exits the app
+    fn shut_down(&mut self) -> Command<Message> {
+        shutdown::finish_session(self, self.is_monitoring && self.save_to_file);
+        if let Some(recorder) = self.recorder.take() {
+            if let Err(e) = recorder.finish() {
+                eprintln!("Failed to finish the session recording: {}", e);
+            }
+        }
+        // keep where the strip was left, it reopens there
+        if self.cpu_strip.is_some() {
+            self.save_config();
+        }
+        Command::batch(
+            self.detached_panels
+                .keys()
+                .copied()
+                .chain(self.cpu_strip)
+                .chain([window::Id::MAIN])
+                .map(window::close),
+        )
+    }
+
+    // frameless and always on top, placed where it was last left, which may be on any monitor
+    fn open_cpu_strip(&mut self) -> Command<Message> {
+        let position = self
+            .config
+            .cpu_strip_position
+            .map_or(Point::ORIGIN, |(x, y)| Point::new(x as f32, y as f32));
+        let (id, spawn) = window::spawn(window::Settings {
+            size: Size::new(self.config.cpu_strip_width() as f32, CPU_STRIP_HEIGHT),
+            position: window::Position::Specific(position),
+            min_size: Some(Size::new(200., CPU_STRIP_HEIGHT)),
+            decorations: false,
+            level: window::Level::AlwaysOnTop,
+            ..window::Settings::default()
+        });
+        self.cpu_strip = Some(id);
+        spawn
+    }
+
+    // buffered sinks (Parquet, compressed JSON) are completed when logging stops
+    fn flush_log(&mut self) {
+        if let Err(e) = self.log_writer.flush() {
+            eprintln!("Failed to write log file: {}", e);
+        }
+    }
+
+    // read the earlier period for the whole history and the hour ahead of it, on a thread of its
+    // own as a week of log takes a while; the comparison shown so far stays until it is done
+    fn load_comparison(&mut self, period: ComparisonPeriod) -> Command<Message> {
+        let now = Local::now();
+        let start = self
+            .history
+            .samples()
+            .front()
+            .map(|s| s.timestamp.clone())
+            .unwrap_or_else(current_timestamp);
+        let end = (now + chrono::TimeDelta::hours(1))
+            .format(TIMESTAMP_FORMAT)
+            .to_string();
+
+        let directory = self.log_writer.directory().to_path_buf();
+        let rotate_keep = self.config.log_rotate_keep();
+        self.comparison_loading = Some(period);
+        Command::perform(
+            off_thread("comparison-load", move || {
+                Comparison::load(period, &start, &end, &directory, rotate_keep)
+                    .map_err(|e| e.to_string())
+            }),
+            move |result| Message::ComparisonLoaded(period, result),
+        )
+    }
+
+    // sample in the background while the collect interval is shorter than the redraw interval,
+    // restarting when it was changed; mock data and replayed sessions have nothing to sample
+    fn sync_sampler(&mut self) {
+        let interval = self.config.collect_interval();
+        let wanted = interval < self.config.redraw_interval()
+            && self.mock.is_none()
+            && self.replay.is_none();
+        if !wanted {
+            self.sampler = None;
+        } else if self
+            .sampler
+            .as_ref()
+            .is_none_or(|sampler| sampler.interval != interval)
+        {
+            self.sampler = Some(Sampler::start(interval));
+        }
+    }
+
+    fn set_interface_link(&mut self, up: bool) {
+        if let Some(interface) = &self.interface_action {
+            let state = if up { "up" } else { "down" };
+            self.interface_action_status = match set_link(interface, up) {
+                Ok(()) => format!("Brought {} {}", interface, state),
+                Err(e) => format!("Failed to bring {} {}: {}", interface, state, e),
+            };
+        }
+    }
+
+    // remembered for the next run unless a fixed default is configured, which it would hide
+    fn remember_process_order(&mut self) {
+        self.config.process_order = self.process_order();
+        if self.config.default_process_order.is_none() {
+            self.save_config();
+        }
+    }
+
+    // the window opens on the same tab and panel next time, unless it always opens on a fixed one
+    fn remember_view(&mut self) {
+        self.config.tab = self.active_tab;
+        self.config.maximized_panel = self.maximized_panel;
+        if self.config.startup_tab.is_none() && self.config.startup_panel.is_none() {
+            self.save_config();
+        }
+    }
+}
+
+impl Application for SystemMonitor {
+    type Executor = executor::Default;
+    type Message = Message;
+    type Theme = Theme;
+    // a recorded session to play back instead of the real system
+    type Flags = Option<(RecordingHeader, Replay)>;
+
+    fn theme(&self, _window: window::Id) -> iced::Theme {
+        // the wizard previews the theme being picked
+        match &self.setup {
+            Some(wizard) => wizard.config.theme.theme(),
+            None => self.config.theme.theme(),
+        }
+    }
+
+    fn new(replay: Self::Flags) -> (Self, Command<Self::Message>) {
+        let mut system_monitor = match replay {
+            Some((header, replay)) => SystemMonitor::with_replay(Config::load(), header, replay),
+            None => SystemMonitor::init(),
+        };
+        shutdown::install_handlers();
+        if !Config::exists() {
+            system_monitor.setup = Some(SetupWizard::default());
+        }
+        let strip = if system_monitor.config.cpu_strip {
+            system_monitor.open_cpu_strip()
+        } else {
+            Command::none()
+        };
+        (system_monitor, strip)
+    }
+
+    fn title(&self, window: window::Id) -> String {
+        match self.detached_panels.get(&window) {
+            Some(panel) => format!("{} - Real-Time System Monitor", panel),
+            None if self.cpu_strip == Some(window) => String::from("CPU Strip"),
+            None => {
+                let title = match &self.replay {
+                    Some(replay) => format!(
+                        "Replay of {} ({}/{}) - Real-Time System Monitor",
+                        replay.path,
+                        replay.position(),
+                        replay.total
+                    ),
+                    None => String::from("Real-Time System Monitor"),
+                };
+                // nothing to show before the first refresh or once monitoring stopped
+                if self.config.title_stats && self.is_monitoring {
+                    let numbers = NumberFormat::new(0, self.config.number_locale);
+                    format!(
+                        "{} - CPU {} · RAM {}",
+                        title,
+                        numbers.percent(self.cpu_usage as f64),
+                        numbers.percent(usage_percent(self.memory_usage))
+                    )
+                } else {
+                    title
+                }
+            }
+        }
+    }
+
+    fn update(&mut self, message: Self::Message) -> Command<Self::Message> {
+        match message {
+            Message::Tick => {
+                // ticks queued behind a slow refresh are dropped rather than worked through one by one
+                let interval = self.config.redraw_interval();
+                if self.is_monitoring && !self.tick_pacer.should_skip(interval) {
+                    let started = Instant::now();
+                    self.sync_sampler();
+                    self.refresh();
+
+                    // forget selected processes that have exited, or whose pid now belongs to another process
+                    let running: HashMap<u32, u64> = self
+                        .processes
+                        .iter()
+                        .map(|p| (p.id, p.start_time))
+                        .collect();
+                    self.selected_processes
+                        .retain(|pid, start_time| running.get(pid) == Some(start_time));
+
+                    // the list may have shrunk below the current page
+                    self.process_page = self.process_page.min(self.process_page_count() - 1);
+
+                    if self.config.follow_selection {
+                        self.follow_selection();
+                    }
+                    self.refresh_process_details();
+
+                    self.tick_pacer.record(started);
+
+                    // the comparison is read an hour ahead, then again once the history gets there
+                    if let Some(comparison) = &self.history_comparison {
+                        let last = self.history.samples().back().map(|s| s.timestamp.as_str());
+                        if self.comparison_loading.is_none()
+                            && last.is_some_and(|last| last > comparison.loaded_until.as_str())
+                        {
+                            return self.load_comparison(comparison.period);
+                        }
+                    }
+                }
+            }
+
+            Message::ProcessLimitChanged(limit) => {
+                self.process_limit = limit;
+                self.process_page = 0;
+            }
+
+            Message::ProcessPageChanged(page) => {
+                self.process_page = page.min(self.process_page_count() - 1);
+            }
+
+            Message::ProcessFilterChanged(pattern) => {
+                let is_regex = self.process_filter.is_regex;
+                self.process_filter.set(pattern, is_regex);
+                self.process_page = 0;
+            }
+
+            Message::ToggleFilterRegex(is_regex) => {
+                let pattern = self.process_filter.pattern.clone();
+                self.process_filter.set(pattern, is_regex);
+                self.process_page = 0;
+            }
+
+            Message::SavedFilterSelected(saved) => {
+                self.process_filter.set(saved.pattern, saved.is_regex);
+                self.process_page = 0;
+            }
+
+            Message::SaveProcessFilter => {
+                let saved = SavedFilter {
+                    pattern: self.process_filter.pattern.clone(),
+                    is_regex: self.process_filter.is_regex,
+                };
+
+                if !self.config.saved_filters.contains(&saved) {
+                    self.config.saved_filters.push(saved);
+                    self.save_config();
+                }
+            }
+
+            Message::ProcessSortSelected(sort) => {
+                self.process_sort = sort;
+                self.process_page = 0;
+                self.remember_process_order();
+            }
+
+            Message::ToggleProcessSortReversed(reversed) => {
+                self.process_sort_reversed = reversed;
+                self.process_page = 0;
+                self.remember_process_order();
+            }
+
+            Message::AliasDeviceSelected(device) => {
+                self.alias_input = self
+                    .config
+                    .aliases
+                    .get(&device)
+                    .cloned()
+                    .unwrap_or_default();
+                self.alias_device = Some(device);
+            }
+
+            Message::AliasChanged(alias) => {
+                self.alias_input = alias;
+            }
+
+            Message::InterfaceActionSelected(interface) => {
+                self.wake_mac_input = self
+                    .config
+                    .wake_macs
+                    .get(&interface)
+                    .cloned()
+                    .unwrap_or_default();
+                self.interface_action_status.clear();
+                self.link_down_pending = false;
+                self.interface_action = Some(interface);
+            }
+
+            Message::WakeMacChanged(mac) => {
+                self.wake_mac_input = mac;
+            }
+
+            // the MAC is saved for the interface once a packet went out with it
+            Message::SendWake => {
+                if let Some(interface) = self.interface_action.clone() {
+                    let mac = self.wake_mac_input.trim().to_lowercase();
+                    let sent = match (parse_mac(&mac), self.networks.get(&interface)) {
+                        (None, _) => Err(format!("not a MAC address: {}", mac)),
+                        (_, None) => Err(format!("{} is gone", interface)),
+                        (Some(bytes), Some(network)) => {
+                            send_wake(network, bytes).map_err(|e| e.to_string())
+                        }
+                    };
+                    match sent {
+                        Ok(()) => {
+                            self.interface_action_status =
+                                format!("Sent a wake-on-LAN packet to {} on {}", mac, interface);
+                            if self.config.wake_macs.get(&interface) != Some(&mac) {
+                                self.config.wake_macs.insert(interface, mac);
+                                self.save_config();
+                            }
+                        }
+                        Err(e) => {
+                            self.interface_action_status =
+                                format!("Failed to send a wake-on-LAN packet: {}", e);
+                        }
+                    }
+                }
+            }
+
+            // bringing a link down asks first
+            Message::SetLink(false) => {
+                self.link_down_pending = self.interface_action.is_some();
+            }
+
+            Message::SetLink(true) => {
+                self.link_down_pending = false;
+                self.set_interface_link(true);
+            }
+
+            Message::ConfirmLinkDown => {
+                self.link_down_pending = false;
+                self.set_interface_link(false);
+            }
+
+            Message::CancelLinkDown => {
+                self.link_down_pending = false;
+            }
+
+            // an empty name goes back to the system one
+            Message::SaveAlias => {
+                if let Some(device) = self.alias_device.take() {
+                    let alias = self.alias_input.trim();
+                    if alias.is_empty() {
+                        self.config.aliases.remove(&device);
+                    } else {
+                        self.config.aliases.insert(device, alias.to_string());
+                    }
+                    self.alias_input.clear();
+                    self.save_config();
+                }
+            }
+
+            Message::ExplainProcess(pid) => {
+                self.explained_process = Some(pid);
+            }
+
+            Message::SearchProcess(name) => {
+                if let Err(e) = open_in_browser(&search_url(&name)) {
+                    self.process_action_status = e;
+                }
+            }
+
+            Message::ViewNameChanged(name) => {
+                self.view_name_input = name;
+            }
+
+            // saving under an existing name replaces that view
+            Message::SaveProcessView => {
+                let name = self.view_name_input.trim().to_string();
+                if !name.is_empty() {
+                    let view = SavedView {
+                        name,
+                        filter: SavedFilter {
+                            pattern: self.process_filter.pattern.clone(),
+                            is_regex: self.process_filter.is_regex,
+                        },
+                        sort: self.process_sort,
+                        sort_reversed: self.process_sort_reversed,
+                        show_command_line: self.config.show_command_line,
+                    };
+
+                    let views = &mut self.config.saved_views;
+                    match views.iter_mut().find(|saved| saved.name == view.name) {
+                        Some(saved) => *saved = view,
+                        None => views.push(view),
+                    }
+                    self.view_name_input.clear();
+                    self.save_config();
+                }
+            }
+
+            Message::ApplyProcessView(index) => {
+                if let Some(view) = self.config.saved_views.get(index).cloned() {
+                    self.process_filter
+                        .set(view.filter.pattern, view.filter.is_regex);
+                    self.process_sort = view.sort;
+                    self.process_sort_reversed = view.sort_reversed;
+                    self.process_page = 0;
+                    self.remember_process_order();
+                    if self.config.show_command_line != view.show_command_line {
+                        self.config.show_command_line = view.show_command_line;
+                        self.save_config();
+                    }
+                }
+            }
+
+            Message::DeleteProcessView(index) => {
+                if index < self.config.saved_views.len() {
+                    self.config.saved_views.remove(index);
+                    self.save_config();
+                }
+            }
+
+            Message::AlertNameChanged(x) => {
+                self.rule_form.name = x;
+            }
+
+            Message::AlertMetricSelected(metric) => {
+                self.rule_form.metric = metric;
+            }
+
+            Message::AlertThresholdChanged(x) => {
+                self.rule_form.threshold = x;
+            }
+
+            Message::AlertDurationChanged(x) => {
+                self.rule_form.duration_secs = x;
+            }
+
+            Message::AlertCooldownChanged(x) => {
+                self.rule_form.cooldown_secs = x;
+            }
+
+            Message::AlertActionSelected(action) => {
+                self.rule_form.action = action;
+            }
+
+            Message::AlertActionCommandChanged(x) => {
+                self.rule_form.action_command = x;
+            }
+
+            // an emptied field goes back to the default template
+            Message::AlertTemplateChanged(template) => {
+                self.config.alert_template = Some(template).filter(|t| !t.trim().is_empty());
+            }
+
+            Message::AlertTemplateSubmitted => {
+                self.save_config();
+            }
+
+            Message::AddAlertRule => match self.rule_form.to_rule() {
+                Ok(rule) if self.config.alert_rules.iter().any(|r| r.name == rule.name) => {
+                    self.rule_form.error =
+                        Some(format!("A rule named {} already exists", rule.name));
+                }
+                Ok(rule) => {
+                    self.config.alert_rules.push(rule);
+                    self.rule_form = RuleForm::default();
+                    self.save_config();
+                }
+                Err(e) => {
+                    self.rule_form.error = Some(e);
+                }
+            },
+
+            Message::AddStealAlertTemplate => {
+                let rule = AlertRule::steal_template();
+                if !self.config.alert_rules.iter().any(|r| r.name == rule.name) {
+                    self.config.alert_rules.push(rule);
+                    self.save_config();
+                }
+            }
+
+            Message::ExportAlertRules(format) => {
+                let extension = match format {
+                    ExportFormat::Csv => "csv",
+                    ExportFormat::Json => "json",
+                };
+                let path = format!(
+                    "alert_rules_{}.{}",
+                    Local::now().format("%Y%m%d-%H%M%S"),
+                    extension
+                );
+                self.rule_file_status = match export_rules(&self.config.alert_rules, &path) {
+                    Ok(()) => format!(
+                        "Exported {} rules to {}",
+                        self.config.alert_rules.len(),
+                        path
+                    ),
+                    Err(e) => e,
+                };
+            }
+
+            Message::RuleFileChanged(path) => {
+                self.rule_file_input = path;
+            }
+
+            // rules of the same name are replaced, so importing a newer set updates them
+            Message::ImportAlertRules => {
+                let path = self.rule_file_input.trim().to_string();
+                self.rule_file_status = match import_rules(&path) {
+                    Ok(mut imported) => {
+                        let stripped = strip_actions(&mut imported);
+                        let count = imported.len();
+                        let added = merge_rules(&mut self.config.alert_rules, imported);
+                        self.save_config();
+                        format!(
+                            "Imported {} rules ({} new) from {}{}",
+                            count,
+                            added,
+                            path,
+                            stripped_note(stripped)
+                        )
+                    }
+                    Err(e) => e,
+                };
+            }
+
+            Message::ToggleAlertRule(i, enabled) => {
+                if let Some(rule) = self.config.alert_rules.get_mut(i) {
+                    rule.enabled = enabled;
+                    self.save_config();
+                }
+            }
+
+            Message::DeleteAlertRule(i) => {
+                if i < self.config.alert_rules.len() {
+                    self.config.alert_rules.remove(i);
+                    self.save_config();
+                }
+            }
+
+            Message::DecimalPlacesSelected(decimals) => {
+                self.config.decimal_places = Some(decimals);
+                self.save_config();
+            }
+
+            Message::NumberLocaleSelected(locale) => {
+                self.config.number_locale = locale;
+                self.save_config();
+            }
+
+            Message::ToggleCoreHeatmap(show) => {
+                self.config.core_heatmap = show;
+                self.save_config();
+            }
+
+            Message::ToggleCommandColumn(show) => {
+                self.config.show_command_line = show;
+                self.save_config();
+            }
+
+            Message::ProcessSelected(pid, selected) => {
+                if selected {
+                    let start_time = self
+                        .filtered_processes()
+                        .iter()
+                        .find(|p| p.id == pid)
+                        .map(|p| p.start_time);
+                    if let Some(start_time) = start_time {
+                        self.selected_processes.insert(pid, start_time);
+                    }
+                } else {
+                    self.selected_processes.remove(&pid);
+                }
+                self.refresh_process_details();
+            }
 
-            // Add the disk info to the main display column
-            disk_display = disk_display.push(container(disk_info).padding(10));
-        }
+            Message::ToggleTitleStats(show) => {
+                self.config.title_stats = show;
+                self.save_config();
+            }
 
-        // return display column
-        disk_display
-    }
+            Message::ToggleFollowSelection(follow) => {
+                self.config.follow_selection = follow;
+                self.save_config();
+                if follow {
+                    self.follow_selection();
+                }
+            }
 
-    fn view_network_info(&self) -> Column<Message> {
-        column![
-            text("Network Usage\n")
-                .size(22)
-                .style(iced::theme::Text::Color(iced::Color::from_rgb(
-                    0.2, 0.6, 1.0,
-                ))),
-            text("---------------")
-                .size(22)
-                .style(iced::theme::Text::Color(iced::Color::from_rgb(
-                    0.1, 0.8, 0.2,
-                ))),
-            text(format!(
-                "- Sent: {:.2} KB\n\n- Received: {:.2} KB",
-                convert_from_bytes(self.network_sent, 1),
-                convert_from_bytes(self.network_received, 1)
-            ))
-            .size(16)
-            .style(iced::theme::Text::Color(iced::Color::from_rgb(
-                0.1, 0.8, 0.2,
-            )))
-        ]
-    }
+            Message::SelectVisibleProcesses => {
+                let processes: Vec<(u32, u64)> = self
+                    .visible_processes()
+                    .iter()
+                    .map(|p| (p.id, p.start_time))
+                    .collect();
+                self.selected_processes.extend(processes);
+                self.refresh_process_details();
+            }
 
-    fn view_per_core_usage(&self) -> Column<Message> {
-        self.processors_info
-            .iter()
-            .fold(Column::new(), |col, (name, usage)| {
-                col.push(text(format!("{}: {:.2}%", name, usage)).size(16).style(
-                    iced::theme::Text::Color(iced::Color::from_rgb(0.1, 0.8, 0.2)),
-                ))
-            })
-    }
+            Message::ClearProcessSelection => {
+                self.selected_processes.clear();
+                self.refresh_process_details();
+            }
 
-    fn view_process_info(&self) -> Column<Message> {
-        let mut process_display = column![
-            text("Running Processes")
-                .size(24)
-                .style(iced::theme::Text::Color(iced::Color::from_rgb(
-                    0.2, 0.6, 1.0
-                ))),
-            text("---------------")
-                .size(22)
-                .style(iced::theme::Text::Color(iced::Color::from_rgb(
-                    0.1, 0.8, 0.2,
-                ))),
-        ];
+            Message::KillSelectedProcesses => {
+                let processes: Vec<(u32, u64)> = self
+                    .selected_processes
+                    .iter()
+                    .map(|(pid, start_time)| (*pid, *start_time))
+                    .collect();
+                let killed = kill_processes(&self.system, &processes);
 
-        for each in self.processes.iter() {
-            // slicing the running process name if it's too long
-            let name = if each.name.len() > 40 {
-                &each.name[..38]
-            } else {
-                &each.name
-            };
+                self.process_action_status = format!(
+                    "Killed {} of {} selected processes",
+                    killed,
+                    processes.len()
+                );
+                self.selected_processes.clear();
+            }
 
-            process_display = process_display.push(
-                row![
-                    text(format!("ID: {} |", each.id)).style(iced::theme::Text::Color(
-                        iced::Color::from_rgb(0.1, 0.8, 0.2)
-                    ),),
-                    text(format!("Name: {} |", name)).style(iced::theme::Text::Color(
-                        iced::Color::from_rgb(0.1, 0.8, 0.2)
-                    ),),
-                    text(format!("CPU: {:.2}% |", each.cpu_usage_percent)).style(
-                        iced::theme::Text::Color(iced::Color::from_rgb(0.1, 0.8, 0.2)),
-                    ),
-                    text(format!("Memory: {:.2}%", each.memory_usage_percent)).style(
-                        iced::theme::Text::Color(iced::Color::from_rgb(0.1, 0.8, 0.2)),
+            // the start time comes from the process table, nvidia-smi doesn't report it
+            // only the process the list was read for: a pid reused since then has another
+            // start time, and kill_processes leaves it alone
+            Message::KillGpuProcess(pid, start_time) => {
+                let killed = kill_processes(&self.system, &[(pid, start_time)]) > 0;
+                self.gpu_action_status = if killed {
+                    format!("Killed process {}", pid)
+                } else {
+                    format!(
+                        "Failed to kill process {}, it may belong to another user or have exited",
+                        pid
                     )
-                ]
-                .spacing(10),
-            );
-        }
+                };
+            }
 
-        process_display
-    }
-}
+            Message::NiceValueChanged(x) => {
+                self.nice_value = x;
+            }
 
-impl Application for SystemMonitor {
-    type Executor = executor::Default;
-    type Message = Message;
-    type Theme = Theme;
-    type Flags = ();
+            Message::ReniceSelectedProcesses => {
+                let processes: Vec<(u32, u64)> = self
+                    .selected_processes
+                    .iter()
+                    .map(|(pid, start_time)| (*pid, *start_time))
+                    .collect();
 
-    fn theme(&self) -> iced::Theme {
-        iced::Theme::Dark
-    }
+                self.process_action_status = match self.nice_value.trim().parse::<i32>() {
+                    Ok(nice) => match renice_processes(&self.system, &processes, nice) {
+                        Ok(reniced) => format!(
+                            "Set nice value {} on {} of {} selected processes",
+                            nice,
+                            reniced,
+                            processes.len()
+                        ),
+                        Err(e) => format!("Failed to renice: {}", e),
+                    },
+                    Err(_) => String::from("Nice value must be a number between -20 and 19"),
+                };
+            }
 
-    fn new(_flags: ()) -> (Self, Command<Self::Message>) {
-        let mut system = System::new_all();
-        let disks = Disks::new_with_refreshed_list();
-        let networks = Networks::new_with_refreshed_list();
-        system.refresh_all();
+            Message::SetChargeLimit(battery, percent) => {
+                self.power_status = match set_charge_limit(&battery, percent) {
+                    Ok(()) => format!("Charging stops at {}%", percent),
+                    Err(e) => e,
+                };
+                self.battery = read_battery();
+            }
 
-        let total_memory = system.total_memory();
-        let used_memory = system.used_memory();
-        let total_swap_memory = system.total_swap();
-        let used_swap_memory = system.used_swap();
+            Message::QuotaCpuChanged(x) => {
+                self.quota_cpu_input = x;
+            }
 
-        let (used_disk, total_disk) = calculate_disk_usage(&disks);
+            Message::QuotaMemoryChanged(x) => {
+                self.quota_memory_input = x;
+            }
 
-        let disks_info: Vec<DisksInfo> = disks
-            .iter()
-            .map(|disk| DisksInfo {
-                name: String::from(disk.name().to_string_lossy()),
-                kind: disk.kind().to_string(),
-                mount: disk.mount_point().to_string_lossy().to_string(),
-                total_disk: disk.total_space(),
-                free_disk: disk.available_space(),
-                used_disk_percent: if total_disk > 0 {
-                    ((disk.total_space() as f64 - disk.available_space() as f64)
-                        / disk.total_space() as f64)
-                        * 100.
-                } else {
-                    0.0
-                },
-            })
-            .collect();
+            // empty fields lift the limit, so the same button both throttles and releases
+            Message::LimitProcess(pid) => {
+                let quota = || -> Result<ProcessQuota, &str> {
+                    let cpu_percent = match self.quota_cpu_input.trim() {
+                        "" => None,
+                        cpu => Some(
+                            cpu.parse::<f64>()
+                                .ok()
+                                .filter(|cpu| *cpu > 0.)
+                                .ok_or("CPU limit must be a percentage above 0")?,
+                        ),
+                    };
+                    let memory_mib = match self.quota_memory_input.trim() {
+                        "" => None,
+                        memory => Some(
+                            memory
+                                .parse::<u64>()
+                                .ok()
+                                .filter(|memory| *memory > 0)
+                                .ok_or("Memory limit must be a number of MiB above 0")?,
+                        ),
+                    };
+                    Ok(ProcessQuota {
+                        cpu_percent,
+                        memory_mib,
+                    })
+                };
 
-        let network_sent = networks.iter().fold(0, |acc, (_interface, network)| {
-            acc + network.total_transmitted()
-        });
+                // busctl may wait for a polkit prompt to be answered
+                match quota() {
+                    Ok(quota) => {
+                        self.process_action_status = format!("Limiting process {}...", pid);
+                        return Command::perform(
+                            off_thread("process-limit", move || limit_process(pid, quota)),
+                            move |result| Message::ProcessLimited(pid, result),
+                        );
+                    }
+                    Err(e) => self.process_action_status = e.to_string(),
+                }
+            }
 
-        let network_received = networks.iter().fold(0, |acc, (_interface, network)| {
-            acc + network.total_received()
-        });
+            Message::ProcessLimited(pid, result) => {
+                self.process_action_status = match result {
+                    Some(Ok(unit)) => format!("Set the limits of process {} on {}", pid, unit),
+                    Some(Err(e)) => format!("Failed to limit process {}: {}", pid, e),
+                    None => format!("Failed to limit process {}", pid),
+                };
+                self.refresh_process_details();
+            }
 
-        let no_of_processes: u32 = system.processes().len() as u32;
-        let cpu_usage = system.global_cpu_usage();
-        let physical_cores: u32 = match system.physical_core_count() {
-            Some(count) => count as u32,
-            None => {
-                eprintln!("Failed to retrieve physical core count. Using default value of 0.");
-                0
+            Message::SpaceScanMountSelected(mount) => {
+                self.space_scan_mount = Some(mount);
             }
-        };
-        let logical_processors = system.cpus().len() as u32;
 
-        let system_base_info = SystemBaseInfo {
-            system_name: System::name().unwrap_or_default(),
-            kernal_version: System::kernel_version().unwrap_or_default(),
-            os_version: System::os_version().unwrap_or_default(),
-            host_name: System::host_name().unwrap_or_default(),
-        };
+            Message::SpaceScanGroupingSelected(grouping) => {
+                self.space_scan_grouping = grouping;
+            }
 
-        let processors_info = system
-            .cpus()
-            .iter()
-            .map(|cpu| (cpu.name().to_string(), cpu.cpu_usage()))
-            .collect();
+            Message::StartSpaceScan => {
+                if let Some(mount) = self.space_scan_mount.clone() {
+                    self.space_scan = Some(SpaceScan::start(mount, self.space_scan_grouping));
+                }
+            }
 
-        // capture initial data to calculate CPU percentages accurately
-        let initial_cpu_time = system.global_cpu_usage();
+            // dropping the scan stops its thread
+            Message::CancelSpaceScan => {
+                self.space_scan = None;
+            }
 
-        // Optional delay for measuring changes over time
-        thread::sleep(Duration::from_secs(1));
+            Message::PollSpaceScan => {}
 
-        // Refresh system data after sleep for updated values
-        system.refresh_cpu_all();
-        system.refresh_memory();
+            Message::ResetSessionStats => {
+                self.session_stats = SessionStats::default();
+            }
 
-        // Calculate the change in CPU usage over time
-        let cpu_time_diff = system.global_cpu_usage() - initial_cpu_time;
+            Message::TabSelected(tab) => {
+                self.active_tab = tab;
+                self.remember_view();
+            }
 
-        let mut processes: Vec<Process> = Vec::new();
+            Message::TogglePanelPause(panel) => {
+                if self.paused_panels.remove(&panel).is_none() {
+                    self.paused_panels.insert(panel, self.panel_snapshot());
+                }
+            }
 
-        // Iterate through each process
-        for (pid, process) in system.processes() {
-            // Calculate CPU usage percent for the process
-            let cpu_usage_percent = if cpu_time_diff > 0.0 {
-                process.cpu_usage()
-            } else {
-                0.0
-            };
+            Message::TogglePanelMaximized(panel) => {
+                self.maximized_panel = match self.maximized_panel {
+                    Some(maximized) if maximized == panel => None,
+                    _ => Some(panel),
+                };
+                self.remember_view();
+            }
 
-            // Calculate memory usage percent relative to total system memory
-            let memory_usage_percent =
-                (process.memory() as f64 / system.total_memory() as f64) * 100.0;
+            Message::RestoreLayout => {
+                self.maximized_panel = None;
+                self.remember_view();
+            }
 
-            if memory_usage_percent >= 0.01 {
-                processes.push(Process {
-                    id: pid.as_u32(),
-                    name: process.name().to_string_lossy().to_string(),
-                    cpu_usage_percent: cpu_usage_percent as f64,
-                    memory_usage_percent: memory_usage_percent,
+            Message::DetachPanel(panel) => {
+                let (id, spawn) = window::spawn(window::Settings {
+                    size: Size::new(480., 360.),
+                    level: window::Level::AlwaysOnTop,
+                    ..window::Settings::default()
                 });
+                self.detached_panels.insert(id, panel);
+                return spawn;
+            }
+
+            Message::ToggleMiniMode => {
+                return match self.mini_mode.take() {
+                    Some(size) => Command::batch([
+                        window::toggle_decorations(window::Id::MAIN),
+                        window::change_level(window::Id::MAIN, window::Level::Normal),
+                        window::resize(window::Id::MAIN, size),
+                    ]),
+                    // remember the current size first, to restore it afterwards
+                    None => window::fetch_size(window::Id::MAIN, Message::MiniModeEntered),
+                };
+            }
+
+            Message::MiniModeEntered(size) => {
+                self.mini_mode = Some(size);
+                if self.config.quick_controls {
+                    self.brightness = read_level(QuickControl::Brightness);
+                    self.volume = read_level(QuickControl::Volume);
+                }
+                let mini_size = if self.brightness.is_some() || self.volume.is_some() {
+                    MINI_MODE_CONTROLS_SIZE
+                } else {
+                    MINI_MODE_SIZE
+                };
+                return Command::batch([
+                    window::toggle_decorations(window::Id::MAIN),
+                    window::change_level(window::Id::MAIN, window::Level::AlwaysOnTop),
+                    window::resize(window::Id::MAIN, mini_size),
+                ]);
             }
-        }
 
-        (
-            SystemMonitor {
-                system,
-                disks,
-                disks_info,
-                networks,
-                system_base_info,
-                cpu_usage,
-                no_of_processes,
-                processors_info,
-                physical_cores,
-                logical_processors,
-                memory_usage: (used_memory, total_memory),
-                swap_memory_usage: (used_swap_memory, total_swap_memory),
-                disk_usage: (used_disk, total_disk),
-                network_sent,
-                network_received,
-                processes,
-                is_monitoring: false,
-                save_to_file: false,
-                interval_in_secs: "".to_string(),
+            // the slider only moves while dragging, the level is set once it is let go
+            Message::QuickControlChanged(control, level) => match control {
+                QuickControl::Brightness => self.brightness = Some(level),
+                QuickControl::Volume => self.volume = Some(level),
             },
-            Command::none(),
-        )
-    }
 
-    fn title(&self) -> String {
-        String::from("Real-Time System Monitor")
-    }
+            Message::ApplyQuickControl(control) => {
+                let level = match control {
+                    QuickControl::Brightness => self.brightness,
+                    QuickControl::Volume => self.volume,
+                };
+                self.quick_control_status = match level.map(|level| set_level(control, level)) {
+                    Some(Err(e)) => format!("{}: {}", control, e),
+                    _ => String::new(),
+                };
+            }
 
-    fn update(&mut self, message: Self::Message) -> Command<Self::Message> {
-        match message {
-            Message::Tick => {
-                if self.is_monitoring {
-                    self.system.refresh_all();
-
-                    // update cpu info
-                    self.cpu_usage = self.system.global_cpu_usage();
-                    self.no_of_processes = self.system.processes().len() as u32;
-                    self.processors_info = self
-                        .system
-                        .cpus()
-                        .iter()
-                        .map(|cpu| (cpu.name().to_string(), cpu.cpu_usage()))
-                        .collect();
+            Message::TakeScreenshot => {
+                return window::screenshot(window::Id::MAIN, Message::ScreenshotTaken);
+            }
+
+            Message::ScreenshotTaken(screenshot) => {
+                self.screenshot_status = match save_png(&screenshot) {
+                    Ok(path) => format!("Saved {}", path),
+                    Err(e) => e,
+                };
+            }
+
+            Message::DragMiniWindow => {
+                return window::drag(window::Id::MAIN);
+            }
 
-                    self.physical_cores = self.system.physical_core_count().unwrap_or(0) as u32;
-                    self.logical_processors = self.system.cpus().len() as u32;
+            Message::ToggleRecording => {
+                self.recording_status = match self.recorder.take() {
+                    Some(recorder) => {
+                        let (path, frames) = (recorder.path.clone(), recorder.frames);
+                        match recorder.finish() {
+                            Ok(()) => format!("Saved {} ({} refreshes)", path, frames),
+                            Err(e) => format!("Failed to save {}: {}", path, e),
+                        }
+                    }
+                    None => match Recorder::create(self) {
+                        Ok(recorder) => {
+                            let status = format!("Recording to {}", recorder.path);
+                            self.recorder = Some(recorder);
+                            status
+                        }
+                        Err(e) => format!("Failed to start recording: {}", e),
+                    },
+                };
+            }
 
-                    // update memory info
-                    self.memory_usage = (self.system.used_memory(), self.system.total_memory());
-                    self.swap_memory_usage = (self.system.used_swap(), self.system.total_swap());
+            Message::ToggleCpuStrip => {
+                self.config.cpu_strip = self.cpu_strip.is_none();
+                self.save_config();
+                return match self.cpu_strip {
+                    Some(id) => window::close(id),
+                    None => self.open_cpu_strip(),
+                };
+            }
 
-                    // update disk info
-                    self.disk_usage = calculate_disk_usage(&self.disks);
+            Message::DragCpuStrip => {
+                if let Some(id) = self.cpu_strip {
+                    return window::drag(id);
+                }
+            }
 
-                    self.disks_info = self
-                        .disks
-                        .iter()
-                        .map(|disk| DisksInfo {
-                            name: String::from(disk.name().to_string_lossy()),
-                            kind: disk.kind().to_string(),
-                            mount: disk.mount_point().to_string_lossy().to_string(),
-                            total_disk: disk.total_space(),
-                            free_disk: disk.available_space(),
-                            used_disk_percent: ((disk.total_space() as f64
-                                - disk.available_space() as f64)
-                                / disk.total_space() as f64)
-                                * 100.,
-                        })
-                        .collect();
+            // remembered in the config, which is written when the strip or the app is closed
+            Message::WindowMoved(id, x, y) => {
+                if self.cpu_strip == Some(id) {
+                    self.config.cpu_strip_position = Some((x, y));
+                }
+            }
+
+            Message::WindowResized(id, width) => {
+                if self.cpu_strip == Some(id) {
+                    self.config.cpu_strip_width = Some(width);
+                }
+            }
+
+            Message::WindowClosed(id) => {
+                self.detached_panels.remove(&id);
+                if self.cpu_strip == Some(id) {
+                    self.cpu_strip = None;
+                    self.save_config();
+                }
 
-                    // update network info
-                    self.network_sent =
-                        self.networks.iter().fold(0, |acc, (_interface, network)| {
-                            acc + network.total_transmitted()
-                        });
-                    self.network_received =
-                        self.networks.iter().fold(0, |acc, (_interface, network)| {
-                            acc + network.total_received()
-                        });
-
-                    // update processes
-                    self.processes.clear();
-                    for (pid, process) in self.system.processes() {
-                        // Calculate memory usage percent relative to total system memory
-                        let memory_usage_percent =
-                            (process.memory() as f64 / self.system.total_memory() as f64) * 100.0;
-
-                        self.processes.push(Process {
-                            id: pid.as_u32(),
-                            name: process.name().to_string_lossy().to_string(),
-                            cpu_usage_percent: process.cpu_usage() as f64,
-                            memory_usage_percent,
-                        });
+                // closing the main window closes the detached ones too, which ends the app
+                if id == window::Id::MAIN {
+                    return Command::batch(
+                        self.detached_panels.keys().map(|id| window::close(*id)),
+                    );
+                }
+            }
+
+            // minimizing the window or switching away from it starts the digest period
+            Message::WindowUnfocused(id) => {
+                if id == window::Id::MAIN && self.is_monitoring && self.away_mark.is_none() {
+                    self.away_mark = Some(AwayMark::new(self));
+                }
+            }
+
+            Message::WindowFocused(id) => {
+                if id == window::Id::MAIN {
+                    if let Some(mark) = self.away_mark.take() {
+                        if let Some(digest) = build_digest(&mark, self) {
+                            self.digest = Some(digest);
+                        }
+                    }
+                }
+            }
+
+            Message::DismissDigest => {
+                self.digest = None;
+            }
+
+            Message::StartDiagnosis => {
+                self.diagnosis = None;
+                self.diagnosis_capture = Some(DiagnosisCapture::new(self));
+            }
+
+            Message::CancelDiagnosis => {
+                self.diagnosis_capture = None;
+            }
+
+            Message::DismissDiagnosis => {
+                self.diagnosis = None;
+            }
+
+            // the main window doesn't exit on its own, so the log can be finished first
+            Message::CloseRequested(id) => {
+                if id == window::Id::MAIN {
+                    return self.shut_down();
+                }
+                return window::close(id);
+            }
+
+            Message::CheckShutdown => {
+                if shutdown::requested() {
+                    return self.shut_down();
+                }
+            }
+
+            Message::HistoryRangeSelected(start, end) => {
+                self.history_selection = self
+                    .history
+                    .timestamp_at(start)
+                    .zip(self.history.timestamp_at(end))
+                    .map(|(start, end)| (start.to_string(), end.to_string()));
+            }
+
+            Message::AnnotationChanged(x) => {
+                self.annotation_input = x;
+            }
+
+            Message::AddAnnotation => {
+                let label = self.annotation_input.trim();
+                if !label.is_empty() {
+                    self.history
+                        .add_annotation(current_timestamp(), label.to_string());
+                    self.annotation_input.clear();
+                }
+            }
+
+            Message::ClearHistorySelection => {
+                self.history_selection = None;
+            }
+
+            Message::ToggleShadeIdle(shade) => {
+                self.config.shade_idle = shade;
+                self.save_config();
+            }
+
+            Message::HistoryComparisonSelected(period) => {
+                self.history_comparison = None;
+                self.comparison_loading = None;
+                self.comparison_status.clear();
+                if period != ComparisonPeriod::Off {
+                    self.comparison_status = String::from("Reading the log...");
+                    return self.load_comparison(period);
+                }
+            }
+
+            Message::ComparisonLoaded(period, result) => {
+                // another period was picked while this one was read
+                if self.comparison_loading != Some(period) {
+                    return Command::none();
+                }
+                self.comparison_loading = None;
+                match result {
+                    Some(Ok(comparison)) => {
+                        self.history_comparison = Some(comparison);
+                        self.comparison_status.clear();
+                    }
+                    Some(Err(e)) => {
+                        self.history_comparison = None;
+                        self.comparison_status = format!("Nothing to compare with: {}", e);
                     }
+                    None => {
+                        self.history_comparison = None;
+                        self.comparison_status = String::from("Failed to read the log");
+                    }
+                }
+            }
+
+            // the file is there for when nothing can be pasted, e.g. over SSH without a clipboard
+            Message::CopyReport => {
+                self.metrics_status = String::from("Copied the system report");
+                return clipboard::write(render_report(self));
+            }
+
+            Message::CopyMetrics => {
+                let metrics = render_metrics(self);
+                self.metrics_status = match save_metrics(&metrics) {
+                    Ok(path) => format!("Copied metrics, saved to {}", path),
+                    Err(e) => format!("Copied metrics, failed to save them: {}", e),
+                };
+                return clipboard::write(metrics);
+            }
+
+            // both act on the filtered list in its current order, across all pages
+            Message::CopyProcessTable => {
+                let processes = self.filtered_processes();
+                let table = process_table_text(&processes, self.config.number_format());
+                self.process_action_status =
+                    format!("Copied {} processes to the clipboard", processes.len());
+                return clipboard::write(table);
+            }
+
+            Message::ExportProcessesCsv => {
+                let processes = self.filtered_processes();
+                self.process_action_status =
+                    match export_processes_csv(&processes, self.config.number_locale) {
+                        Ok(path) => format!("Exported {} processes to {}", processes.len(), path),
+                        Err(e) => e,
+                    };
+            }
+
+            Message::ExportHistoryRange(format) => {
+                if let Some((start, end)) = &self.history_selection {
+                    let samples = self.history.range(start, end);
+                    let path = export_file_name(start, end, format);
 
-                    self.processes.sort_by(|a, b| {
-                        b.memory_usage_percent
-                            .partial_cmp(&a.memory_usage_percent)
-                            .unwrap_or(std::cmp::Ordering::Less)
-                    });
+                    self.export_status =
+                        match export_samples(&samples, &path, format, self.config.number_locale) {
+                            Ok(()) => format!("Exported {} samples to {}", samples.len(), path),
+                            Err(e) => format!("Failed to export history: {}", e),
+                        };
                 }
             }
 
             Message::LogToFile => {
-                log_metrics(&self);
+                if let Err(e) = log_metrics(self) {
+                    eprintln!("Failed to write log file: {}", e);
+                }
+            }
+
+            Message::SetupIntervalChanged(interval) => {
+                if let Some(wizard) = self.setup.as_mut() {
+                    wizard.interval = interval;
+                }
+            }
+
+            Message::SetupPanelToggled(panel, visible) => {
+                if let Some(wizard) = self.setup.as_mut() {
+                    wizard.set_panel(panel, visible);
+                }
+            }
+
+            Message::SetupSaveToFileToggled(save) => {
+                if let Some(wizard) = self.setup.as_mut() {
+                    wizard.config.save_to_file = save;
+                }
+            }
+
+            Message::SetupLogFormatSelected(format) => {
+                if let Some(wizard) = self.setup.as_mut() {
+                    wizard.config.log_format = format;
+                }
+            }
+
+            Message::SetupThemeSelected(theme) => {
+                if let Some(wizard) = self.setup.as_mut() {
+                    wizard.config.theme = theme;
+                }
+            }
+
+            Message::SetupBack => {
+                if let Some(wizard) = self.setup.as_mut() {
+                    wizard.back();
+                }
+            }
+
+            Message::SetupNext => {
+                if let Some(config) = self.setup.as_mut().and_then(|wizard| wizard.next()) {
+                    self.finish_setup(config);
+                }
+            }
+
+            Message::SetupSkip => {
+                self.finish_setup(Config::default());
+            }
+
+            Message::ReloadConfig => {
+                if self.reload_config() {
+                    if let Some(secs) = self.config.interval_secs {
+                        self.interval_in_secs = secs.to_string();
+                    }
+                }
+            }
+
+            Message::ToggleLogFsync(fsync) => {
+                self.config.log_fsync = fsync;
+                self.save_config();
             }
 
             Message::ToggleSaveToFile(x) => {
                 self.save_to_file = x;
+                if x {
+                    self.log_writer.skip_to(self.history.pushed());
+                } else {
+                    self.flush_log();
+                }
+            }
+
+            Message::ToggleLogAggregate(aggregate) => {
+                self.config.log_aggregate = aggregate;
+                self.log_writer.skip_to(self.history.pushed());
+                self.save_config();
+            }
+
+            Message::LogFormatSelected(format) => {
+                self.flush_log();
+                self.config.log_format = format;
+                self.save_config();
+            }
+
+            Message::LogCompressionSelected(compression) => {
+                self.flush_log();
+                self.config.log_compression = compression;
+                self.save_config();
             }
 
             Message::ToggleMonitoring => {
                 self.is_monitoring = !self.is_monitoring;
+                self.history.add_annotation(
+                    current_timestamp(),
+                    String::from(if self.is_monitoring {
+                        "Monitoring started"
+                    } else {
+                        "Monitoring stopped"
+                    }),
+                );
+                if !self.is_monitoring {
+                    self.sampler = None;
+                    self.flush_log();
+                }
             }
 
             Message::IntervalChanged(x) => {
                 self.interval_in_secs = x;
             }
+
+            Message::HighlightSecsChanged(input) => {
+                if let Ok(secs) = input.trim().parse::<u64>() {
+                    self.config.new_process_highlight_secs = Some(secs);
+                    self.save_config();
+                }
+                self.highlight_secs_input = input;
+            }
+
+            Message::DismissHint(kind) => {
+                self.dismissed_hints.insert(kind);
+            }
+
+            Message::ProcessRefreshSecsChanged(input) => {
+                if let Ok(secs) = input.trim().parse::<u64>() {
+                    self.config.process_refresh_secs = Some(secs);
+                    self.save_config();
+                }
+                self.process_refresh_input = input;
+            }
         }
 
         Command::none()
     }
 
-    fn view(&self) -> Element<Self::Message> {
+    fn view(&self, window: window::Id) -> Element<'_, Self::Message> {
+        if let Some(panel) = self.detached_panels.get(&window) {
+            return self.view_single_panel(*panel, 120.);
+        }
+        if self.cpu_strip == Some(window) {
+            return self.view_cpu_strip();
+        }
+
+        if self.mini_mode.is_some() {
+            return self.view_mini_mode();
+        }
+
+        if let Some(wizard) = &self.setup {
+            return container(wizard.view())
+                .width(Length::Fill)
+                .height(Length::Fill)
+                .center_x()
+                .center_y()
+                .into();
+        }
+
+        // a maximized panel takes the whole window until Esc or "Restore"
+        if let Some(panel) = self.maximized_panel {
+            if self.active_tab == Tab::Dashboard {
+                return self.view_single_panel(panel, 300.);
+            }
+        }
+
         let control_row = self.create_control_row();
+        let tab_row = self.create_tab_row();
 
         // system information row
         let sys_info_row = self
@@ -551,28 +4391,19 @@ impl Application for SystemMonitor {
             .align_items(Alignment::Center)
             .padding(10);
 
-        // Create a column for each info category: CPU, Memory, Disk, Network, Processes
-        let cpu_info = self.view_cpu_info().padding(5);
-        let memory_info = self.view_memory_info().padding(5);
-        let disk_info = self.view_disk_info().padding(10);
-        let network_info = self.view_network_info().padding(5);
-        let process_info = self.view_process_info().padding(5);
-
-        let scrollable_process = scrollable(process_info).height(Length::FillPortion(3));
-
-        // Arrange these categories in a row with proper spacing
-        let metrics_row = row![
-            cpu_info,
-            column!(memory_info, network_info).padding(5),
-            disk_info,
-            scrollable_process
-        ]
-        .spacing(15)
-        .padding(5)
-        .align_items(Alignment::Center);
+        let tab_content = match self.active_tab {
+            Tab::Dashboard => self.view_dashboard(),
+            Tab::History => self.view_history().into(),
+            Tab::Interrupts => scrollable(self.view_interrupts()).into(),
+            Tab::Alerts => scrollable(self.view_alerts()).into(),
+            Tab::Timeline => scrollable(self.view_process_timeline()).into(),
+        };
 
         // Combine the layout
-        let content = column![control_row, sys_info_row, metrics_row]
+        let content = column![control_row, tab_row, sys_info_row]
+            .push_maybe(self.view_digest())
+            .push_maybe(self.view_diagnosis())
+            .push(tab_content)
             .spacing(20)
             .align_items(Alignment::Center)
             .padding(10);
@@ -586,7 +4417,33 @@ impl Application for SystemMonitor {
     }
 
     fn subscription(&self) -> Subscription<Self::Message> {
-        if self.is_monitoring {
+        let window_events = event::listen_with(|event, _status| match event {
+            Event::Window(id, window::Event::Closed) => Some(Message::WindowClosed(id)),
+            Event::Window(id, window::Event::Focused) => Some(Message::WindowFocused(id)),
+            Event::Window(id, window::Event::Moved { x, y }) => {
+                Some(Message::WindowMoved(id, x, y))
+            }
+            Event::Window(id, window::Event::Resized { width, .. }) => {
+                Some(Message::WindowResized(id, width))
+            }
+            Event::Window(id, window::Event::Unfocused) => Some(Message::WindowUnfocused(id)),
+            Event::Window(id, window::Event::CloseRequested) => Some(Message::CloseRequested(id)),
+            _ => None,
+        });
+
+        let shortcuts = keyboard::on_key_press(|key, modifiers| match key.as_ref() {
+            keyboard::Key::Character("m") if modifiers.command() => Some(Message::ToggleMiniMode),
+            keyboard::Key::Named(keyboard::key::Named::Escape) => Some(Message::RestoreLayout),
+            _ => None,
+        });
+
+        // watch the config file for edits, also while not monitoring
+        let config_watch = time::every(Duration::from_secs(2)).map(|_| Message::ReloadConfig);
+
+        // SIGTERM/SIGINT only set a flag, pick it up from here
+        let signal_watch = time::every(Duration::from_millis(500)).map(|_| Message::CheckShutdown);
+
+        let monitoring = if self.is_monitoring {
             // Parse interval string to u64
             let interval_secs = match self.interval_in_secs.parse::<u64>() {
                 Ok(x) => x,
@@ -597,8 +4454,8 @@ impl Application for SystemMonitor {
                 }
             };
 
-            // Message::Tick updates system metrics on the screen every second
-            let tick_interval = time::every(Duration::from_secs(1)).map(|_| Message::Tick);
+            // Message::Tick updates system metrics on the screen, every second by default
+            let tick_interval = time::every(self.config.redraw_interval()).map(|_| Message::Tick);
             if self.interval_in_secs.is_empty() {
                 eprintln!("Input value for logging interval is empty!\nSystem Data will not be saved to the file.")
             }
@@ -608,13 +4465,33 @@ impl Application for SystemMonitor {
                 let log_interval =
                     time::every(Duration::from_secs(interval_secs)).map(|_| Message::LogToFile);
 
-                Subscription::batch([tick_interval, log_interval].into_iter())
+                Subscription::batch([tick_interval, log_interval])
             } else {
                 // Only monitor without saving
                 tick_interval
             }
         } else {
             Subscription::none()
-        }
+        };
+
+        // a running space scan redraws with its progress, also while not monitoring
+        let space_scan = if self
+            .space_scan
+            .as_ref()
+            .is_some_and(|scan| !scan.finished())
+        {
+            time::every(Duration::from_millis(500)).map(|_| Message::PollSpaceScan)
+        } else {
+            Subscription::none()
+        };
+
+        Subscription::batch([
+            window_events,
+            shortcuts,
+            config_watch,
+            signal_watch,
+            monitoring,
+            space_scan,
+        ])
     }
 }
@@ -0,0 +1,95 @@
+// Clock synchronization status, since a skewed clock makes logged timestamps misleading
+use serde::{Deserialize, Serialize};
+use std::process::Command;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TimeSync {
+    // tool the status was read from
+    pub source: String,
+    pub synchronized: bool,
+    // positive when the local clock is ahead, None when the tool doesn't report it
+    pub offset_ms: Option<f64>,
+}
+
+// stdout of a command that ran successfully
+fn command_output(program: &str, args: &[&str]) -> Option<String> {
+    let output = Command::new(program).args(args).output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    Some(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+// value of a "Key : value" line
+fn field<'a>(output: &'a str, key: &str) -> Option<&'a str> {
+    output.lines().find_map(|line| {
+        let (name, value) = line.split_once(':')?;
+        (name.trim() == key).then(|| value.trim())
+    })
+}
+
+// status from the first time service found, None when none could be queried
+pub fn read_time_sync() -> Option<TimeSync> {
+    if cfg!(windows) {
+        w32tm()
+    } else {
+        chrony().or_else(timedatectl)
+    }
+}
+
+fn chrony() -> Option<TimeSync> {
+    let output = command_output("chronyc", &["tracking"])?;
+    let synchronized = field(&output, "Leap status")? != "Not synchronised";
+
+    // e.g. "0.000012345 seconds fast of NTP time"
+    let offset_ms = field(&output, "System time").and_then(|value| {
+        let mut words = value.split_whitespace();
+        let seconds: f64 = words.next()?.parse().ok()?;
+        let sign = if words.nth(1)? == "slow" { -1. } else { 1. };
+        Some(sign * seconds * 1000.)
+    });
+
+    Some(TimeSync {
+        source: String::from("chrony"),
+        synchronized,
+        offset_ms,
+    })
+}
+
+fn timedatectl() -> Option<TimeSync> {
+    let output = command_output("timedatectl", &["show", "-p", "NTPSynchronized", "--value"])?;
+
+    // only systemd-timesyncd reports an offset, e.g. "Offset: -1.501ms"
+    let offset_ms = command_output("timedatectl", &["timesync-status"])
+        .and_then(|status| parse_duration_ms(field(&status, "Offset")?));
+
+    Some(TimeSync {
+        source: String::from("timedatectl"),
+        synchronized: output.trim() == "yes",
+        offset_ms,
+    })
+}
+
+fn w32tm() -> Option<TimeSync> {
+    let output = command_output("w32tm", &["/query", "/status", "/verbose"])?;
+    let source = field(&output, "Source")?;
+    let synchronized = !source.contains("Local CMOS Clock") && !source.contains("Free-running");
+
+    // e.g. "Phase Offset: 0.0012345s"
+    let offset_ms = field(&output, "Phase Offset").and_then(parse_duration_ms);
+
+    Some(TimeSync {
+        source: String::from("w32tm"),
+        synchronized,
+        offset_ms,
+    })
+}
+
+// "+263us", "-1.501ms" or "0.0012345s" in milliseconds
+fn parse_duration_ms(value: &str) -> Option<f64> {
+    let units = [("us", 0.001), ("μs", 0.001), ("ms", 1.), ("s", 1000.)];
+    units.iter().find_map(|(unit, scale)| {
+        let number: f64 = value.strip_suffix(unit)?.parse().ok()?;
+        Some(number * scale)
+    })
+}
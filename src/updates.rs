@@ -0,0 +1,80 @@
+// Pending OS updates and required reboots, where the OS makes them easy to detect
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UpdateStatus {
+    pub reboot_required: bool,
+    // None when the number of pending updates isn't known
+    pub pending_updates: Option<usize>,
+}
+
+// None on systems where neither can be detected
+pub fn read_update_status() -> Option<UpdateStatus> {
+    if cfg!(windows) {
+        windows_status()
+    } else if cfg!(target_os = "linux") {
+        linux_status()
+    } else {
+        None
+    }
+}
+
+fn linux_status() -> Option<UpdateStatus> {
+    // Debian/Ubuntu
+    if Path::new("/var/lib/update-notifier").exists()
+        || Path::new("/var/run/reboot-required").exists()
+    {
+        return Some(UpdateStatus {
+            reboot_required: Path::new("/var/run/reboot-required").exists(),
+            pending_updates: apt_pending_updates(),
+        });
+    }
+
+    // Fedora/RHEL, exits with 1 when a reboot is needed
+    let status = Command::new("needs-restarting")
+        .arg("-r")
+        .output()
+        .ok()?
+        .status;
+    Some(UpdateStatus {
+        reboot_required: status.code() == Some(1),
+        pending_updates: None,
+    })
+}
+
+// count left by update-notifier, e.g. "12 updates can be applied immediately."
+fn apt_pending_updates() -> Option<usize> {
+    let contents = fs::read_to_string("/var/lib/update-notifier/updates-available").ok()?;
+    if contents.trim().is_empty() {
+        return Some(0);
+    }
+    contents.lines().find_map(|line| {
+        let (count, rest) = line.trim().split_once(' ')?;
+        if !rest.starts_with("update") {
+            return None;
+        }
+        count.parse().ok()
+    })
+}
+
+fn windows_status() -> Option<UpdateStatus> {
+    // these registry keys only exist while a reboot is pending
+    let keys = [
+        r"HKLM\SOFTWARE\Microsoft\Windows\CurrentVersion\WindowsUpdate\Auto Update\RebootRequired",
+        r"HKLM\SOFTWARE\Microsoft\Windows\CurrentVersion\Component Based Servicing\RebootPending",
+    ];
+    let reboot_required = keys.iter().any(|key| {
+        Command::new("reg")
+            .args(["query", key])
+            .output()
+            .is_ok_and(|output| output.status.success())
+    });
+
+    Some(UpdateStatus {
+        reboot_required,
+        pending_updates: None,
+    })
+}
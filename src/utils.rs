@@ -1,12 +1,59 @@
-use crate::models::{SystemData, SystemMonitor};
-use chrono::Utc;
-use std::fs::OpenOptions;
-use std::io::Write;
+use crate::disks::DiskTopology;
+use crate::format::{LOCAL_TIMESTAMP_FORMAT, TIMESTAMP_FORMAT};
+use crate::models::{DeviceIops, DisksInfo, SystemData, SystemMonitor};
+use chrono::{Local, NaiveDateTime, Utc};
 use sysinfo::Disks;
+use sysinfo::Process;
+use sysinfo::System;
+use sysinfo::Users;
+#[cfg(feature = "gui")]
+use {crate::models::UserTotal, std::collections::HashMap};
 
-// B to KB, MB, or GB depending on the value
-pub fn convert_from_bytes(bytes: u64, value: i32) -> f64 {
-    bytes as f64 / f64::powf(1024., value as f64)
+// full command line of a process, falling back to its name for kernel threads
+pub fn command_line(process: &Process) -> String {
+    let command = process
+        .cmd()
+        .iter()
+        .map(|arg| arg.to_string_lossy())
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    if command.is_empty() {
+        format!("[{}]", process.name().to_string_lossy())
+    } else {
+        command
+    }
+}
+
+// name of the account a process runs as, falling back to its numeric id
+pub fn process_user(process: &Process, users: &Users) -> String {
+    match process.user_id() {
+        Some(uid) => users
+            .get_user_by_id(uid)
+            .map(|user| user.name().to_string())
+            .unwrap_or_else(|| uid.to_string()),
+        None => String::from("unknown"),
+    }
+}
+
+// cpu and memory per account, busiest first
+#[cfg(feature = "gui")]
+pub fn user_totals(processes: &[crate::models::Process]) -> Vec<UserTotal> {
+    let mut totals: HashMap<&str, UserTotal> = HashMap::new();
+    for process in processes {
+        let total = totals.entry(&process.user).or_insert_with(|| UserTotal {
+            user: process.user.clone(),
+            cpu_usage_percent: 0.,
+            memory_usage_percent: 0.,
+            processes: 0,
+        });
+        total.cpu_usage_percent += process.cpu_usage_percent;
+        total.memory_usage_percent += process.memory_usage_percent;
+        total.processes += 1;
+    }
+    let mut totals: Vec<UserTotal> = totals.into_values().collect();
+    totals.sort_by(|a, b| b.cpu_usage_percent.total_cmp(&a.cpu_usage_percent));
+    totals
 }
 
 pub fn calculate_disk_usage(disks: &Disks) -> (u64, u64) {
@@ -17,23 +64,93 @@ pub fn calculate_disk_usage(disks: &Disks) -> (u64, u64) {
     (used_disk, total_disk)
 }
 
-pub fn log_metrics(system_monitor: &SystemMonitor) {
-    let data = SystemData {
-        timestamp: Utc::now().format("%Y-%m-%d %H:%M:%S").to_string(),
+// memory the system can take back from the page cache and buffers when programs need it;
+// used memory already leaves it out, so it is what's available beyond the free memory
+pub fn memory_cache(system: &System) -> u64 {
+    system
+        .available_memory()
+        .saturating_sub(system.free_memory())
+}
+
+// size and use of each mounted partition, with the physical disk it is on
+pub fn disks_info(disks: &Disks, topology: &mut DiskTopology) -> Vec<DisksInfo> {
+    disks
+        .iter()
+        .map(|disk| {
+            let name = String::from(disk.name().to_string_lossy());
+            let mount = disk.mount_point().to_string_lossy().to_string();
+            let label = topology.label_of(&name, &mount);
+            // Windows volumes aren't traced back to their disk, each stands for itself
+            let device = if cfg!(windows) {
+                label.clone()
+            } else {
+                topology.device_of(&name)
+            };
+            DisksInfo {
+                name,
+                device,
+                label,
+                kind: disk.kind().to_string(),
+                mount,
+                total_disk: disk.total_space(),
+                free_disk: disk.available_space(),
+                used_disk_percent: if disk.total_space() > 0 {
+                    ((disk.total_space() as f64 - disk.available_space() as f64)
+                        / disk.total_space() as f64)
+                        * 100.
+                } else {
+                    0.0
+                },
+            }
+        })
+        .collect()
+}
+
+// timestamp format used by samples, logs and annotations
+pub fn current_timestamp() -> String {
+    Utc::now().format(TIMESTAMP_FORMAT).to_string()
+}
+
+// a sample timestamp in the local time zone, empty if it doesn't parse
+pub fn local_timestamp(timestamp: &str) -> String {
+    NaiveDateTime::parse_from_str(timestamp, TIMESTAMP_FORMAT)
+        .map(|utc| {
+            utc.and_utc()
+                .with_timezone(&Local)
+                .format(LOCAL_TIMESTAMP_FORMAT)
+                .to_string()
+        })
+        .unwrap_or_default()
+}
+
+// current metrics as a single sample
+pub fn collect_system_data(system_monitor: &SystemMonitor) -> SystemData {
+    let timestamp = system_monitor.sample_timestamp();
+    SystemData {
+        local_timestamp: local_timestamp(&timestamp),
+        timestamp,
+        // numbered by whatever writes it out
+        sample_index: 0,
+        // the latest refresh, the history counts the samples pushed to it
+        refresh_index: system_monitor.history.pushed().saturating_sub(1),
         cpu_usage_percent: system_monitor.cpu_usage,
         memory_usage_byte: system_monitor.memory_usage,
+        memory_cache_byte: system_monitor.memory_cache,
         swap_memory_usage_byte: system_monitor.swap_memory_usage,
         disk_usage_byte: system_monitor.disk_usage,
         network_sent_byte: system_monitor.network_sent,
         network_received_byte: system_monitor.network_received,
-    };
-
-    let serialized = serde_json::to_string(&data).expect("Failed to serialize system data");
-    let mut file = OpenOptions::new()
-        .create(true)
-        .append(true)
-        .open("system_log.json")
-        .expect("Failed to open log file");
-
-    writeln!(file, "{}", serialized).expect("Failed to write data to log file");
+        disk_iops: system_monitor
+            .disk_io
+            .iter()
+            .map(|disk| DeviceIops {
+                device: disk.name.clone(),
+                read_iops: disk.read_iops,
+                write_iops: disk.write_iops,
+            })
+            .collect(),
+        tcp_states: system_monitor.tcp_states.unwrap_or_default(),
+        user_idle: system_monitor.user_idle,
+        host: system_monitor.host_identity.clone(),
+    }
 }
@@ -0,0 +1,120 @@
+// Bytes written to each SSD, carried over between runs to estimate how much of the drive's
+// rated endurance (TBW) has been used
+use crate::procfs::{DiskCounters, SECTOR_SIZE};
+use crate::utils::current_timestamp;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::io;
+use std::path::Path;
+
+pub const WEAR_FILE: &str = "disk_wear.json";
+
+// Writes counted for one SSD
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DeviceWear {
+    pub model: String,
+    pub bytes_written: u64,
+    // when counting started, writes from before aren't included in the estimate
+    pub since: String,
+}
+
+impl DeviceWear {
+    // share of the rated endurance used, as a percentage
+    pub fn percent_of(&self, rated_tbw: f64) -> f64 {
+        self.bytes_written as f64 / (rated_tbw * 1e12) * 100.
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WearLedger {
+    // by block device name, e.g. nvme0n1
+    pub devices: BTreeMap<String, DeviceWear>,
+    // written to all physical disks and by all processes over the same time, their ratio is
+    // the write amplification between what programs write and what reaches the disks
+    pub disk_bytes_written: u64,
+    pub process_bytes_written: u64,
+    // devices already looked up in /sys this run, and whether they are SSDs
+    #[serde(skip)]
+    probed: HashMap<String, bool>,
+}
+
+impl WearLedger {
+    // missing or unreadable file starts counting from zero
+    pub fn load() -> WearLedger {
+        fs::read_to_string(WEAR_FILE)
+            .ok()
+            .and_then(|contents| serde_json::from_str(&contents).ok())
+            .unwrap_or_default()
+    }
+
+    // nothing to keep on machines without SSDs, or when the data doesn't come from the system
+    pub fn save(&self) -> io::Result<()> {
+        if self.devices.is_empty() {
+            return Ok(());
+        }
+        fs::write(WEAR_FILE, serde_json::to_string_pretty(self)?)
+    }
+
+    // count what was written since an earlier reading of /proc/diskstats; the counters
+    // start again at boot, so the ledger adds up the differences instead of keeping totals
+    pub fn add_disk_writes(&mut self, current: &DiskCounters, previous: &DiskCounters) {
+        for stat in &current.devices {
+            let Some(before) = previous.devices.iter().find(|s| s.name == stat.name) else {
+                continue;
+            };
+            let bytes = stat.sectors_written.saturating_sub(before.sectors_written) * SECTOR_SIZE;
+            self.disk_bytes_written += bytes;
+
+            if self.probe(&stat.name) {
+                if let Some(device) = self.devices.get_mut(&stat.name) {
+                    device.bytes_written += bytes;
+                }
+            }
+        }
+    }
+
+    pub fn add_process_writes(&mut self, bytes: u64) {
+        self.process_bytes_written += bytes;
+    }
+
+    // bytes reaching the disks per byte written by processes, None before anything was written
+    pub fn write_amplification(&self) -> Option<f64> {
+        (self.process_bytes_written > 0)
+            .then(|| self.disk_bytes_written as f64 / self.process_bytes_written as f64)
+    }
+
+    // whether the device is an SSD, starting its entry on first sight; a different model
+    // under the same name is a replaced drive and starts again from zero
+    fn probe(&mut self, name: &str) -> bool {
+        if let Some(solid_state) = self.probed.get(name) {
+            return *solid_state;
+        }
+        let model = read_ssd_model(name);
+        if let Some(model) = &model {
+            let device = self.devices.entry(name.to_string()).or_default();
+            if device.model != *model || device.since.is_empty() {
+                *device = DeviceWear {
+                    model: model.clone(),
+                    bytes_written: 0,
+                    since: current_timestamp(),
+                };
+            }
+        }
+        self.probed.insert(name.to_string(), model.is_some());
+        model.is_some()
+    }
+}
+
+// model of a non-rotational disk backed by hardware, None for hard disks and for
+// device-mapper, md and zram devices, whose writes land on another device
+fn read_ssd_model(name: &str) -> Option<String> {
+    let block = Path::new("/sys/block").join(name);
+    let rotational = fs::read_to_string(block.join("queue/rotational")).ok()?;
+    if rotational.trim() != "0" || !block.join("device").exists() {
+        return None;
+    }
+    let model = fs::read_to_string(block.join("device/model")).unwrap_or_default();
+    Some(model.trim().to_string())
+}
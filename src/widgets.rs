@@ -0,0 +1,169 @@
+// Monitor widgets that other iced applications can embed with their own message type
+use crate::chart::{view_chart, LineChart, Threshold};
+use crate::format::{cpu_time, truncate, NumberFormat};
+use crate::models::{Process, SystemData};
+use iced::widget::{canvas, checkbox, row, text, tooltip, Column};
+use iced::Color;
+use std::collections::HashMap;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+// CPU usage over the given samples, oldest first, with threshold lines such as an alert level
+pub fn cpu_chart<'a, Message: 'a>(
+    samples: impl IntoIterator<Item = &'a SystemData>,
+    thresholds: Vec<Threshold>,
+    shaded: Vec<(f32, f32)>,
+    height: f32,
+) -> canvas::Canvas<LineChart<Message>, Message> {
+    let cpu_series = samples.into_iter().map(|s| s.cpu_usage_percent).collect();
+    let chart = LineChart {
+        series: vec![(Color::from_rgb(0.2, 0.6, 1.0), cpu_series)],
+        ghosts: Vec::new(),
+        shaded,
+        selection: None,
+        markers: Vec::new(),
+        on_select: None,
+        thresholds,
+    };
+    view_chart(chart, height)
+}
+
+// The text of one process row, which can be kept and reused while the process doesn't change
+#[derive(Debug, Clone)]
+pub struct ProcessRow {
+    pub id: String,
+    pub name: String,
+    pub cpu: String,
+    pub time: String,
+    pub memory: String,
+    // cut to fit, and in full for the tooltip
+    pub command: String,
+    pub full_command: String,
+}
+
+impl ProcessRow {
+    pub fn new(process: &Process, numbers: NumberFormat) -> ProcessRow {
+        ProcessRow {
+            id: format!("ID: {} |", process.id),
+            // slicing the running process name if it's too long
+            name: format!("Name: {} |", truncate(&process.name, 38)),
+            cpu: format!("CPU: {} |", numbers.percent(process.cpu_usage_percent)),
+            time: format!("Time: {} |", cpu_time(process.cpu_time_secs)),
+            memory: format!("Memory: {}", numbers.percent(process.memory_usage_percent)),
+            command: format!("| {}", truncate(&process.command, 60)),
+            full_command: process.command.to_string(),
+        }
+    }
+}
+
+// Process list with one row per process, optionally with selection checkboxes
+pub struct ProcessTable<'p, Message> {
+    processes: &'p [&'p Process],
+    numbers: NumberFormat,
+    // rows formatted earlier by pid and start time, processes without one are formatted here
+    rows: Option<&'p HashMap<(u32, u64), ProcessRow>>,
+    // message sent when a checkbox is toggled, None shows no checkboxes
+    on_toggle: Option<fn(u32, bool) -> Message>,
+    selected: Vec<u32>,
+    show_command_line: bool,
+    // highlight processes younger than this many seconds, 0 turns it off
+    highlight_secs: u64,
+}
+
+impl<'p, Message> ProcessTable<'p, Message> {
+    pub fn new(processes: &'p [&'p Process], numbers: NumberFormat) -> Self {
+        ProcessTable {
+            processes,
+            numbers,
+            rows: None,
+            on_toggle: None,
+            selected: Vec::new(),
+            show_command_line: false,
+            highlight_secs: 0,
+        }
+    }
+
+    pub fn selectable(mut self, selected: Vec<u32>, on_toggle: fn(u32, bool) -> Message) -> Self {
+        self.on_toggle = Some(on_toggle);
+        self.selected = selected;
+        self
+    }
+
+    pub fn rows(mut self, rows: &'p HashMap<(u32, u64), ProcessRow>) -> Self {
+        self.rows = Some(rows);
+        self
+    }
+
+    pub fn show_command_line(mut self, show: bool) -> Self {
+        self.show_command_line = show;
+        self
+    }
+
+    pub fn highlight_new(mut self, secs: u64) -> Self {
+        self.highlight_secs = secs;
+        self
+    }
+
+    pub fn view<'a>(self) -> Column<'a, Message>
+    where
+        Message: 'a,
+    {
+        // processes started within the highlight window stand out, e.g. to spot spawn loops
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |elapsed| elapsed.as_secs());
+        let numbers = self.numbers;
+
+        let mut table = Column::new();
+        for each in self.processes {
+            let formatted;
+            let cached = self
+                .rows
+                .and_then(|rows| rows.get(&(each.id, each.start_time)));
+            let cells = match cached {
+                Some(row) => row,
+                None => {
+                    formatted = ProcessRow::new(each, numbers);
+                    &formatted
+                }
+            };
+            let color = if self.highlight_secs > 0
+                && now.saturating_sub(each.start_time) < self.highlight_secs
+            {
+                Color::from_rgb(1.0, 0.92, 0.0)
+            } else {
+                Color::from_rgb(0.1, 0.8, 0.2)
+            };
+
+            let mut process_row = row![].spacing(10);
+            if let Some(on_toggle) = self.on_toggle {
+                let id = each.id;
+                process_row = process_row.push(
+                    checkbox("", self.selected.contains(&id))
+                        .on_toggle(move |selected| on_toggle(id, selected)),
+                );
+            }
+            process_row = process_row
+                .push(text(&cells.id).style(iced::theme::Text::Color(color)))
+                .push(text(&cells.name).style(iced::theme::Text::Color(color)))
+                .push(text(&cells.cpu).style(iced::theme::Text::Color(color)))
+                .push(text(&cells.time).style(iced::theme::Text::Color(color)))
+                .push(text(&cells.memory).style(iced::theme::Text::Color(color)));
+
+            // full command line is shown in a tooltip when it doesn't fit
+            if self.show_command_line {
+                process_row = process_row.push(
+                    tooltip(
+                        text(&cells.command)
+                            .style(iced::theme::Text::Color(Color::from_rgb(0.6, 0.6, 0.6))),
+                        text(&cells.full_command).size(14),
+                        tooltip::Position::Bottom,
+                    )
+                    .style(iced::theme::Container::Box),
+                );
+            }
+
+            table = table.push(process_row);
+        }
+        table
+    }
+}
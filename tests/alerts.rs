@@ -0,0 +1,117 @@
+// Alert rule files written on one machine and read on another
+use rust_system_monitor::alerts::{
+    export_rules, import_rules, merge_rules, strip_actions, AlertAction, AlertMetric, AlertRule,
+};
+use std::fs;
+
+fn rules() -> Vec<AlertRule> {
+    vec![
+        AlertRule {
+            name: String::from("CPU high, sustained"),
+            metric: AlertMetric::CpuUsage,
+            threshold: 85.5,
+            duration_secs: 120,
+            enabled: true,
+            cooldown_secs: 600,
+            action: Some(AlertAction::Command(String::from(
+                "notify-send \"CPU high\" \"$ALERT_MESSAGE, still going\"",
+            ))),
+        },
+        AlertRule {
+            name: String::from("Too many \"CLOSE_WAIT\" sockets"),
+            metric: AlertMetric::TcpCloseWait,
+            threshold: 100.,
+            duration_secs: 0,
+            enabled: false,
+            cooldown_secs: 0,
+            action: None,
+        },
+    ]
+}
+
+fn round_trip(extension: &str) -> Vec<AlertRule> {
+    let path = std::env::temp_dir().join(format!(
+        "alert_rules_test_{}.{}",
+        std::process::id(),
+        extension
+    ));
+    let path = path.to_str().unwrap();
+    export_rules(&rules(), path).unwrap();
+    let imported = import_rules(path).unwrap();
+    fs::remove_file(path).unwrap();
+    imported
+}
+
+#[test]
+fn csv_rule_files_round_trip() {
+    assert_eq!(round_trip("csv"), rules());
+}
+
+#[test]
+fn json_rule_files_round_trip() {
+    assert_eq!(round_trip("json"), rules());
+}
+
+#[test]
+fn csv_rule_files_without_cooldowns_still_import() {
+    let path =
+        std::env::temp_dir().join(format!("alert_rules_old_test_{}.csv", std::process::id()));
+    let path = path.to_str().unwrap();
+    fs::write(
+        path,
+        "name,metric,threshold,duration_secs,enabled\nSwap,SwapUsage,50,30,true\n",
+    )
+    .unwrap();
+    let imported = import_rules(path).unwrap();
+    fs::remove_file(path).unwrap();
+
+    assert_eq!(imported.len(), 1);
+    assert_eq!(imported[0].metric, AlertMetric::SwapUsage);
+    assert_eq!(imported[0].cooldown_secs, 0);
+}
+
+#[test]
+fn csv_rule_files_without_actions_still_import() {
+    let path = std::env::temp_dir().join(format!(
+        "alert_rules_no_action_test_{}.csv",
+        std::process::id()
+    ));
+    let path = path.to_str().unwrap();
+    fs::write(
+        path,
+        "name,metric,threshold,duration_secs,enabled,cooldown_secs\nHot,Temperature,95,10,true,300\n",
+    )
+    .unwrap();
+    let imported = import_rules(path).unwrap();
+    fs::remove_file(path).unwrap();
+
+    assert_eq!(imported.len(), 1);
+    assert_eq!(imported[0].cooldown_secs, 300);
+    assert_eq!(imported[0].action, None);
+}
+
+#[test]
+fn actions_are_dropped_from_imported_rules() {
+    let mut imported = rules();
+    imported[1].action = Some(AlertAction::Hibernate);
+    imported.push(AlertRule {
+        action: Some(AlertAction::LockSession),
+        ..rules()[1].clone()
+    });
+    let stripped = strip_actions(&mut imported);
+
+    assert_eq!(stripped, 3);
+    assert!(imported.iter().all(|rule| rule.action.is_none()));
+}
+
+#[test]
+fn imported_rules_replace_those_of_the_same_name() {
+    let mut existing = vec![AlertRule {
+        threshold: 50.,
+        ..rules()[0].clone()
+    }];
+    let added = merge_rules(&mut existing, rules());
+
+    assert_eq!(added, 1);
+    assert_eq!(existing, rules());
+}
@@ -0,0 +1,114 @@
+// Property tests for the formatting helpers shared by the panels and the log output
+use proptest::prelude::*;
+use rust_system_monitor::format::{
+    memory_categories, truncate, usage_percent, NumberFormat, NumberLocale,
+};
+
+const UNITS: [&str; 5] = ["B", "KB", "MB", "GB", "TB"];
+
+fn locale() -> impl Strategy<Value = NumberLocale> {
+    prop_oneof![
+        Just(NumberLocale::Plain),
+        Just(NumberLocale::English),
+        Just(NumberLocale::European),
+        Just(NumberLocale::Spaced),
+    ]
+}
+
+// undo the locale's separators, so the text can be parsed again
+fn parse(formatted: &str, locale: NumberLocale) -> f64 {
+    let plain = match locale {
+        NumberLocale::English => formatted.replace(',', ""),
+        NumberLocale::European => formatted.replace('.', "").replace(',', "."),
+        NumberLocale::Spaced => formatted.replace(' ', "").replace(',', "."),
+        _ => formatted.to_string(),
+    };
+    plain.parse().unwrap()
+}
+
+proptest! {
+    #[test]
+    fn truncate_never_exceeds_the_limit(value in "\\PC{0,80}", max_chars in 3usize..60) {
+        let truncated = truncate(&value, max_chars);
+        prop_assert!(truncated.chars().count() <= max_chars);
+    }
+
+    #[test]
+    fn truncate_keeps_short_text(value in "\\PC{0,40}") {
+        let max_chars = value.chars().count();
+        prop_assert_eq!(truncate(&value, max_chars), value);
+    }
+
+    #[test]
+    fn truncate_marks_the_cut(value in "\\PC{10,80}", max_chars in 3usize..10) {
+        let truncated = truncate(&value, max_chars);
+        prop_assert!(truncated.ends_with("..."));
+        let kept = truncated.trim_end_matches("...");
+        prop_assert!(value.starts_with(kept));
+    }
+
+    #[test]
+    fn usage_percent_stays_in_range(total in 1u64.., fraction in 0.0f64..=1.0) {
+        let used = (total as f64 * fraction) as u64;
+        let percent = usage_percent((used.min(total), total));
+        prop_assert!((0.0..=100.0).contains(&percent));
+    }
+
+    #[test]
+    fn usage_percent_of_unknown_total_is_zero(used in any::<u64>()) {
+        prop_assert_eq!(usage_percent((used, 0)), 0.0);
+    }
+
+    #[test]
+    fn memory_categories_add_up_to_the_total(used in any::<u64>(), total in any::<u64>(), cache in any::<u64>()) {
+        let (apps, cache, free) = memory_categories((used, total), cache);
+        prop_assert_eq!(apps as u128 + cache as u128 + free as u128, total as u128);
+    }
+
+    #[test]
+    fn numbers_round_trip(value in -1e12f64..1e12, decimals in 0usize..4, locale in locale()) {
+        let numbers = NumberFormat::new(decimals, locale);
+        let parsed = parse(&numbers.number(value), locale);
+        // allow for rounding to `decimals` plus the float error of large values
+        let tolerance = 0.5 * 10f64.powi(-(decimals as i32)) + value.abs() * f64::EPSILON * 4.;
+        prop_assert!((parsed - value).abs() <= tolerance);
+    }
+
+    #[test]
+    fn integers_keep_every_digit(value in any::<u32>(), locale in locale()) {
+        let numbers = NumberFormat::new(2, locale);
+        prop_assert_eq!(parse(&numbers.integer(value as u64), locale), value as f64);
+    }
+
+    #[test]
+    fn percent_has_a_suffix(value in 0.0f64..=100.0, locale in locale()) {
+        let formatted = NumberFormat::new(1, locale).percent(value);
+        prop_assert!(formatted.ends_with('%'));
+    }
+
+    #[test]
+    fn bytes_use_the_largest_fitting_unit(bytes in 0u64..(1u64 << 50)) {
+        let formatted = NumberFormat::new(2, NumberLocale::Plain).bytes(bytes as f64);
+        let (value, unit) = formatted.split_once(' ').unwrap();
+        let value: f64 = value.parse().unwrap();
+        let power = UNITS.iter().position(|u| *u == unit).unwrap();
+
+        // rounding may show 1024.00 just below the next unit, and TB is never scaled further
+        prop_assert!(power == UNITS.len() - 1 || value < 1024.005);
+        prop_assert!(power == 0 || value >= 1.0);
+        let scaled = value * 1024f64.powi(power as i32);
+        prop_assert!((scaled - bytes as f64).abs() <= 0.005 * 1024f64.powi(power as i32) + 0.5);
+    }
+
+    #[test]
+    fn rates_are_bytes_per_second(bytes in 0.0f64..1e15) {
+        let numbers = NumberFormat::new(2, NumberLocale::English);
+        prop_assert_eq!(numbers.rate(bytes), format!("{}/s", numbers.bytes(bytes)));
+    }
+
+    #[test]
+    fn no_negative_zero(value in -0.004f64..0.0) {
+        let formatted = NumberFormat::new(2, NumberLocale::Plain).number(value);
+        prop_assert_eq!(formatted, "0.00");
+    }
+}
@@ -0,0 +1,121 @@
+// Sinks registered with the log writer get the samples written to the log
+use rust_system_monitor::config::Config;
+use rust_system_monitor::models::SystemData;
+use rust_system_monitor::sinks::{LogSettings, MetricsSink};
+use rust_system_monitor::LogWriter;
+use std::fs;
+use std::io;
+use std::sync::{Arc, Mutex};
+
+// keeps the timestamps and indexes of the samples it was given where the test can see them
+#[derive(Debug)]
+struct Collecting(Arc<Mutex<Vec<(String, u64)>>>);
+
+impl MetricsSink for Collecting {
+    fn write_sample(&mut self, _settings: &LogSettings, sample: &SystemData) -> io::Result<()> {
+        self.0
+            .lock()
+            .unwrap()
+            .push((sample.timestamp.clone(), sample.sample_index));
+        Ok(())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn rotate(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+fn sample(timestamp: &str, refresh_index: u64) -> SystemData {
+    SystemData {
+        timestamp: timestamp.to_string(),
+        local_timestamp: String::new(),
+        sample_index: 0,
+        refresh_index,
+        cpu_usage_percent: 12.5,
+        memory_usage_byte: (4, 16),
+        memory_cache_byte: 0,
+        swap_memory_usage_byte: (0, 2),
+        disk_usage_byte: (10, 100),
+        network_sent_byte: 0,
+        network_received_byte: 0,
+        disk_iops: Vec::new(),
+        tcp_states: Default::default(),
+        user_idle: None,
+        host: Default::default(),
+    }
+}
+
+// a log writer in a directory of its own, so tests running at once don't share the log file
+fn writer(name: &str) -> (LogWriter, std::path::PathBuf) {
+    let directory =
+        std::env::temp_dir().join(format!("sinks_test_{}_{}", std::process::id(), name));
+    fs::create_dir_all(&directory).unwrap();
+    let mut writer = LogWriter::default();
+    writer.set_directory(directory.clone());
+    (writer, directory)
+}
+
+fn timestamps(collected: &Mutex<Vec<(String, u64)>>) -> Vec<String> {
+    let collected = collected.lock().unwrap();
+    collected
+        .iter()
+        .map(|(timestamp, _)| timestamp.clone())
+        .collect()
+}
+
+#[test]
+fn added_sinks_receive_every_sample() {
+    let (mut writer, directory) = writer("added");
+
+    let received = Arc::new(Mutex::new(Vec::new()));
+    writer
+        .add_sink(Box::new(Collecting(received.clone())))
+        .unwrap();
+
+    let config = Config::default();
+    writer
+        .write(&config, &sample("2024-01-01 10:00:00", 0))
+        .unwrap();
+    // a sink added once the log thread runs gets the samples from then on
+    let late = Arc::new(Mutex::new(Vec::new()));
+    writer.add_sink(Box::new(Collecting(late.clone()))).unwrap();
+    writer
+        .write(&config, &sample("2024-01-01 10:00:01", 1))
+        .unwrap();
+    writer.finish().unwrap();
+    drop(writer);
+    fs::remove_dir_all(&directory).unwrap();
+
+    assert_eq!(
+        timestamps(&received),
+        ["2024-01-01 10:00:00", "2024-01-01 10:00:01"]
+    );
+    assert_eq!(timestamps(&late), ["2024-01-01 10:00:01"]);
+}
+
+#[test]
+fn logged_samples_are_numbered_by_the_log_not_the_refresh() {
+    let (mut writer, directory) = writer("numbered");
+    let received = Arc::new(Mutex::new(Vec::new()));
+    writer
+        .add_sink(Box::new(Collecting(received.clone())))
+        .unwrap();
+
+    // logged every fifth refresh
+    let config = Config::default();
+    for refresh in [0, 5, 10] {
+        writer
+            .write(&config, &sample("2024-01-01 10:00:00", refresh))
+            .unwrap();
+    }
+    writer.finish().unwrap();
+    drop(writer);
+    fs::remove_dir_all(&directory).unwrap();
+
+    let indexes: Vec<u64> = received.lock().unwrap().iter().map(|(_, i)| *i).collect();
+    assert_eq!(indexes, [0, 1, 2]);
+}
@@ -0,0 +1,391 @@
+// Drives the app's update() with scripted messages against the mock data source
+#![cfg(feature = "gui")]
+
+use iced::multi_window::Application;
+use rust_system_monitor::alerts::{AlertMetric, AlertRule};
+use rust_system_monitor::config::Config;
+use rust_system_monitor::mock::{MockSource, MockTick};
+use rust_system_monitor::models::{
+    Message, Panel, Process, ProcessOrder, ProcessSort, SystemMonitor, Tab,
+};
+use rust_system_monitor::recording::Replay;
+
+const GB: u64 = 1024 * 1024 * 1024;
+
+fn process(id: u32, start_time: u64, name: &str, cpu: f64, memory: f64) -> Process {
+    Process {
+        id,
+        start_time,
+        name: name.into(),
+        command: format!("/usr/bin/{}", name).into(),
+        user: "tester".into(),
+        cpu_usage_percent: cpu,
+        cpu_time_secs: 0.,
+        memory_usage_percent: memory,
+        disk_io_rate: 0.,
+    }
+}
+
+fn tick(cpu_usage: f32, processes: Vec<Process>) -> MockTick {
+    MockTick {
+        cpu_usage,
+        memory_usage: (4 * GB, 16 * GB),
+        swap_memory_usage: (0, 2 * GB),
+        processes,
+    }
+}
+
+// a monitor that is already monitoring, so every Tick refreshes from the script
+fn monitor(config: Config, ticks: Vec<MockTick>) -> SystemMonitor {
+    let mut monitor = SystemMonitor::with_mock(config, MockSource::new(ticks));
+    let _ = monitor.update(Message::ToggleMonitoring);
+    monitor
+}
+
+fn send(monitor: &mut SystemMonitor, messages: impl IntoIterator<Item = Message>) {
+    for message in messages {
+        let _ = monitor.update(message);
+    }
+}
+
+fn cpu_rule(threshold: f64) -> AlertRule {
+    AlertRule {
+        name: String::from("CPU high"),
+        metric: AlertMetric::CpuUsage,
+        threshold,
+        duration_secs: 0,
+        enabled: true,
+        cooldown_secs: 0,
+        action: None,
+    }
+}
+
+#[test]
+fn ticks_are_ignored_until_monitoring_starts() {
+    let mut monitor =
+        SystemMonitor::with_mock(Config::default(), MockSource::new([tick(50., vec![])]));
+    send(&mut monitor, [Message::Tick]);
+
+    assert!(monitor.history.samples().is_empty());
+    assert_eq!(monitor.cpu_usage, 0.);
+}
+
+#[test]
+fn each_tick_adds_a_history_sample() {
+    let mut monitor = monitor(
+        Config::default(),
+        vec![tick(10., vec![]), tick(20., vec![]), tick(30., vec![])],
+    );
+    send(&mut monitor, [Message::Tick, Message::Tick, Message::Tick]);
+
+    let cpu: Vec<f32> = monitor
+        .history
+        .samples()
+        .iter()
+        .map(|sample| sample.cpu_usage_percent)
+        .collect();
+    assert_eq!(cpu, vec![10., 20., 30.]);
+    assert_eq!(monitor.history.pushed(), 3);
+    assert!(monitor
+        .history
+        .annotations()
+        .iter()
+        .any(|annotation| annotation.label == "Monitoring started"));
+}
+
+#[test]
+fn history_samples_are_numbered_in_order() {
+    let mut monitor = monitor(
+        Config::default(),
+        vec![tick(10., vec![]), tick(20., vec![]), tick(30., vec![])],
+    );
+    send(&mut monitor, [Message::Tick, Message::Tick, Message::Tick]);
+
+    let samples = monitor.history.samples();
+    let indexes: Vec<u64> = samples.iter().map(|sample| sample.refresh_index).collect();
+    assert_eq!(indexes, vec![0, 1, 2]);
+    assert!(samples
+        .iter()
+        .all(|sample| !sample.local_timestamp.is_empty()));
+}
+
+#[test]
+fn diagnosis_names_the_busy_process_when_cpu_bound() {
+    let busy = vec![
+        process(10, 1, "encoder", 90., 2.),
+        process(11, 1, "shell", 1., 1.),
+    ];
+    let mut monitor = monitor(Config::default(), vec![tick(95., busy); 31]);
+    send(&mut monitor, [Message::Tick, Message::StartDiagnosis]);
+    send(&mut monitor, std::iter::repeat_n(Message::Tick, 29));
+    assert!(monitor.diagnosis.is_none());
+
+    send(&mut monitor, [Message::Tick]);
+    let diagnosis = monitor.diagnosis.as_ref().expect("capture finished");
+    assert_eq!(
+        diagnosis
+            .bottleneck
+            .map(|bottleneck| bottleneck.to_string()),
+        Some(String::from("CPU-bound"))
+    );
+    assert!(diagnosis.contributors[0].starts_with("encoder (10)"));
+    assert!(monitor.diagnosis_capture.is_none());
+}
+
+#[test]
+fn processes_are_sorted_by_memory() {
+    let mut monitor = monitor(
+        Config::default(),
+        vec![tick(
+            5.,
+            vec![
+                process(1, 100, "small", 1., 0.5),
+                process(2, 100, "large", 1., 20.),
+                process(3, 100, "medium", 1., 5.),
+            ],
+        )],
+    );
+    send(&mut monitor, [Message::Tick]);
+
+    let names: Vec<&str> = monitor.processes.iter().map(|p| &*p.name).collect();
+    assert_eq!(names, vec!["large", "medium", "small"]);
+    assert_eq!(monitor.no_of_processes, 3);
+}
+
+#[test]
+fn alert_fires_once_per_breach() {
+    let config = Config {
+        alert_rules: vec![cpu_rule(80.)],
+        ..Config::default()
+    };
+    let mut monitor = monitor(
+        config,
+        vec![
+            tick(95., vec![]),
+            tick(97., vec![]),
+            tick(10., vec![]),
+            tick(90., vec![]),
+        ],
+    );
+
+    send(&mut monitor, [Message::Tick, Message::Tick]);
+    assert_eq!(monitor.alert_history.len(), 1);
+    assert_eq!(monitor.alert_history[0].rule_name, "CPU high");
+    assert_eq!(monitor.alert_history[0].value, 95.);
+
+    // recovering re-arms the rule
+    send(&mut monitor, [Message::Tick, Message::Tick]);
+    assert_eq!(monitor.alert_history.len(), 2);
+    assert_eq!(monitor.alert_history[1].value, 90.);
+}
+
+#[test]
+fn flapping_metric_notifies_once_per_cooldown() {
+    let config = Config {
+        alert_rules: vec![AlertRule {
+            cooldown_secs: 3600,
+            ..cpu_rule(80.)
+        }],
+        ..Config::default()
+    };
+    let mut monitor = monitor(
+        config,
+        vec![
+            tick(95., vec![]),
+            tick(10., vec![]),
+            tick(96., vec![]),
+            tick(10., vec![]),
+            tick(97., vec![]),
+        ],
+    );
+    send(&mut monitor, vec![Message::Tick; 5]);
+
+    assert_eq!(monitor.alert_history.len(), 1);
+    assert_eq!(monitor.alert_history[0].value, 95.);
+    assert_eq!(monitor.alert_history[0].suppressed, 2);
+}
+
+#[test]
+fn disabled_rules_never_fire() {
+    let config = Config {
+        alert_rules: vec![AlertRule {
+            enabled: false,
+            ..cpu_rule(50.)
+        }],
+        ..Config::default()
+    };
+    let mut monitor = monitor(config, vec![tick(99., vec![])]);
+    send(&mut monitor, [Message::Tick]);
+
+    assert!(monitor.alert_history.is_empty());
+}
+
+#[test]
+fn selection_is_dropped_when_the_pid_is_reused() {
+    let mut monitor = monitor(
+        Config::default(),
+        vec![
+            tick(5., vec![process(7, 100, "worker", 1., 1.)]),
+            tick(5., vec![process(7, 100, "worker", 1., 1.)]),
+            // same pid, but a different process
+            tick(5., vec![process(7, 200, "other", 1., 1.)]),
+        ],
+    );
+    send(
+        &mut monitor,
+        [Message::Tick, Message::ProcessSelected(7, true)],
+    );
+    assert_eq!(monitor.selected_processes.get(&7), Some(&100));
+
+    send(&mut monitor, [Message::Tick]);
+    assert_eq!(monitor.selected_processes.get(&7), Some(&100));
+
+    send(&mut monitor, [Message::Tick]);
+    assert!(monitor.selected_processes.is_empty());
+}
+
+#[test]
+fn configured_default_sort_orders_the_list() {
+    let config = Config {
+        default_process_order: Some(ProcessOrder {
+            sort: ProcessSort::Cpu,
+            reversed: true,
+        }),
+        ..Config::default()
+    };
+    let mut monitor = monitor(
+        config,
+        vec![tick(
+            5.,
+            vec![
+                process(1, 100, "busy", 50., 1.),
+                process(2, 100, "idle", 0., 9.),
+                process(3, 100, "some", 10., 5.),
+            ],
+        )],
+    );
+    send(&mut monitor, [Message::Tick]);
+
+    let names: Vec<&str> = monitor.processes.iter().map(|p| &*p.name).collect();
+    assert_eq!(names, vec!["idle", "some", "busy"]);
+    assert_eq!(monitor.process_sort, ProcessSort::Cpu);
+    assert!(monitor.process_sort_reversed);
+
+    send(&mut monitor, [Message::ToggleProcessSortReversed(false)]);
+    assert_eq!(
+        monitor.config.process_order,
+        ProcessOrder {
+            sort: ProcessSort::Cpu,
+            reversed: false,
+        }
+    );
+}
+
+#[test]
+fn filter_and_sort_reset_the_page() {
+    let processes = (1..=120)
+        .map(|id| process(id, 100, &format!("proc{}", id), 0., id as f64 / 10.))
+        .collect();
+    let mut monitor = monitor(Config::default(), vec![tick(5., processes)]);
+    send(
+        &mut monitor,
+        [Message::Tick, Message::ProcessPageChanged(2)],
+    );
+    assert_eq!(monitor.process_page, 2);
+
+    send(
+        &mut monitor,
+        [Message::ProcessFilterChanged(String::from("proc1"))],
+    );
+    assert_eq!(monitor.process_page, 0);
+    assert_eq!(monitor.process_filter.pattern, "proc1");
+
+    send(
+        &mut monitor,
+        [
+            Message::ProcessPageChanged(1),
+            Message::ProcessSortSelected(ProcessSort::Cpu),
+        ],
+    );
+    assert_eq!(monitor.process_page, 0);
+    assert_eq!(monitor.process_sort, ProcessSort::Cpu);
+}
+
+#[test]
+fn maximized_panel_is_restored_by_escape_or_its_button() {
+    let mut monitor = monitor(Config::default(), vec![tick(5., vec![])]);
+    send(
+        &mut monitor,
+        [Message::TogglePanelMaximized(Panel::Processes)],
+    );
+    assert_eq!(monitor.maximized_panel, Some(Panel::Processes));
+
+    send(&mut monitor, [Message::RestoreLayout]);
+    assert_eq!(monitor.maximized_panel, None);
+
+    send(
+        &mut monitor,
+        [
+            Message::TogglePanelMaximized(Panel::Cpu),
+            Message::TogglePanelMaximized(Panel::Cpu),
+        ],
+    );
+    assert_eq!(monitor.maximized_panel, None);
+}
+
+#[test]
+fn window_opens_on_the_last_tab_unless_a_startup_panel_is_set() {
+    let mut monitor = monitor(Config::default(), vec![tick(5., vec![])]);
+    send(&mut monitor, [Message::TabSelected(Tab::History)]);
+    let reopened = SystemMonitor::with_mock(monitor.config.clone(), MockSource::new([]));
+    assert_eq!(reopened.active_tab, Tab::History);
+    assert_eq!(reopened.maximized_panel, None);
+
+    let config = Config {
+        startup_panel: Some(Panel::Processes),
+        ..monitor.config.clone()
+    };
+    let reopened = SystemMonitor::with_mock(config, MockSource::new([]));
+    assert_eq!(reopened.active_tab, Tab::Dashboard);
+    assert_eq!(reopened.maximized_panel, Some(Panel::Processes));
+}
+
+#[test]
+fn recorded_session_replays_the_same_samples() {
+    let mut monitor = monitor(
+        Config::default(),
+        vec![
+            tick(12., vec![process(1, 100, "recorded", 3., 4.)]),
+            tick(34., vec![process(2, 100, "replayed", 5., 6.)]),
+        ],
+    );
+    // readings the mock source leaves alone, taken as they are into the recording
+    monitor.on_battery = Some(true);
+    monitor.user_idle = Some(true);
+    send(
+        &mut monitor,
+        [Message::ToggleRecording, Message::Tick, Message::Tick],
+    );
+    let path = monitor.recorder.as_ref().unwrap().path.clone();
+    send(&mut monitor, [Message::ToggleRecording]);
+
+    let (header, replay) = Replay::load(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(replay.total, 2);
+
+    let mut replayed = SystemMonitor::with_replay(Config::default(), header, replay);
+    send(&mut replayed, [Message::Tick, Message::Tick]);
+
+    let samples = |monitor: &SystemMonitor| -> Vec<(String, f32)> {
+        monitor
+            .history
+            .samples()
+            .iter()
+            .map(|sample| (sample.timestamp.clone(), sample.cpu_usage_percent))
+            .collect()
+    };
+    assert_eq!(samples(&replayed), samples(&monitor));
+    assert_eq!(&*replayed.processes[0].name, "replayed");
+    assert_eq!(replayed.on_battery, Some(true));
+    assert_eq!(replayed.user_idle, Some(true));
+}